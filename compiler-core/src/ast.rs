@@ -152,11 +152,35 @@ impl<A> Arg<A> {
     pub fn get_variable_name(&self) -> Option<&EcoString> {
         self.names.get_variable_name()
     }
+
+    /// The name of the variable the argument assigns and where the name
+    /// starts in the code of its module, as the location of an argument also
+    /// includes its label and annotation.
+    pub fn variable_name_start(&self, code: &str) -> Option<(&EcoString, u32)> {
+        match &self.names {
+            ArgNames::Named { name } => Some((name, self.location.start)),
+            ArgNames::NamedLabelled { name, label } => {
+                let after_label = self.location.start + label.len() as u32;
+                let rest = code
+                    .get(after_label as usize..self.location.end as usize)
+                    .unwrap_or_default();
+                let spaces = rest.len() - rest.trim_start().len();
+                Some((name, after_label + spaces as u32))
+            }
+            ArgNames::Discard { .. } | ArgNames::LabelledDiscard { .. } => None,
+        }
+    }
 }
 
 impl TypedArg {
     pub fn find_node(&self, byte_index: u32) -> Option<Located<'_>> {
-        if self.location.contains(byte_index) {
+        // The argument added for a `use` pattern that is not a single
+        // variable has the location of the pattern, which is then assigned
+        // at the start of the callback, so the pattern is found there.
+        let is_use_assignment = self
+            .get_variable_name()
+            .is_some_and(|name| name.starts_with(USE_ASSIGNMENT_VARIABLE));
+        if self.location.contains(byte_index) && !is_use_assignment {
             Some(Located::Arg(self))
         } else {
             None
@@ -380,7 +404,7 @@ pub type TypedFunction = Function<Arc<Type>, TypedExpr>;
 pub type UntypedFunction = Function<(), UntypedExpr>;
 
 impl<T, E> Function<T, E> {
-    fn full_location(&self) -> SrcSpan {
+    pub(crate) fn full_location(&self) -> SrcSpan {
         SrcSpan::new(self.location.start, self.end_position)
     }
 }
@@ -562,6 +586,14 @@ impl TypedDefinition {
             }
 
             Definition::CustomType(custom) => {
+                if let Some(constructor) = custom
+                    .constructors
+                    .iter()
+                    .find(|constructor| constructor.location.contains(byte_index))
+                {
+                    return Some(Located::VariantConstructorDefinition(constructor));
+                }
+
                 // Note that the custom type `.location` covers the function
                 // head, not the entire statement.
                 if custom.full_location().contains(byte_index) {
@@ -1338,9 +1370,13 @@ impl TypedPattern {
             | Pattern::VarUsage { .. }
             | Pattern::Assign { .. }
             | Pattern::Discard { .. }
-            | Pattern::BitArray { .. }
             | Pattern::StringPrefix { .. } => Some(Located::Pattern(self)),
 
+            Pattern::BitArray { segments, .. } => segments
+                .iter()
+                .find_map(|segment| segment.find_node(byte_index))
+                .or(Some(Located::Pattern(self))),
+
             Pattern::Constructor { arguments, .. } => {
                 arguments.iter().find_map(|arg| arg.find_node(byte_index))
             }
@@ -1404,9 +1440,32 @@ pub struct BitArraySegment<Value, Type> {
     pub type_: Type,
 }
 
-impl TypedExprBitArraySegment {
+/// A typed node that can be searched for the node at a byte index, such as
+/// the value of a bit array segment.
+pub trait FindNode {
+    fn find_node(&self, byte_index: u32) -> Option<Located<'_>>;
+}
+
+impl FindNode for TypedExpr {
+    fn find_node(&self, byte_index: u32) -> Option<Located<'_>> {
+        TypedExpr::find_node(self, byte_index)
+    }
+}
+
+impl FindNode for TypedPattern {
+    fn find_node(&self, byte_index: u32) -> Option<Located<'_>> {
+        TypedPattern::find_node(self, byte_index)
+    }
+}
+
+impl<Value: FindNode, Type> BitArraySegment<Value, Type> {
     pub fn find_node(&self, byte_index: u32) -> Option<Located<'_>> {
-        self.value.find_node(byte_index)
+        self.value.find_node(byte_index).or_else(|| {
+            self.options
+                .iter()
+                .filter_map(|option| option.value())
+                .find_map(|value| value.find_node(byte_index))
+        })
     }
 }
 
@@ -12,13 +12,13 @@ mod telemetry;
 mod tests;
 
 pub use self::package_compiler::PackageCompiler;
-pub use self::package_loader::StaleTracker;
+pub use self::package_loader::{SourceFilter, StaleTracker};
 pub use self::project_compiler::{Built, Options, ProjectCompiler};
 pub use self::telemetry::{NullTelemetry, Telemetry};
 
 use crate::ast::{
     CustomType, DefinitionLocation, TypedArg, TypedDefinition, TypedExpr, TypedFunction,
-    TypedPattern, TypedStatement,
+    TypedPattern, TypedRecordConstructor, TypedStatement,
 };
 use crate::{
     ast::{Definition, SrcSpan, TypedModule},
@@ -204,7 +204,7 @@ impl Package {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Module {
     pub name: EcoString,
     pub code: EcoString,
@@ -293,9 +293,23 @@ pub enum Located<'a> {
     ModuleStatement(&'a TypedDefinition),
     FunctionBody(&'a TypedFunction),
     Arg(&'a TypedArg),
+    /// One of the variants in the definition of a custom type.
+    VariantConstructorDefinition(&'a TypedRecordConstructor),
 }
 
 impl<'a> Located<'a> {
+    pub fn location(&self) -> SrcSpan {
+        match self {
+            Self::Pattern(pattern) => pattern.location(),
+            Self::Statement(statement) => statement.location(),
+            Self::Expression(expression) => expression.location(),
+            Self::ModuleStatement(definition) => definition.location(),
+            Self::FunctionBody(function) => function.full_location(),
+            Self::Arg(arg) => arg.location,
+            Self::VariantConstructorDefinition(constructor) => constructor.location,
+        }
+    }
+
     pub fn definition_location(&self) -> Option<DefinitionLocation<'_>> {
         match self {
             Self::Pattern(pattern) => pattern.definition_location(),
@@ -307,6 +321,10 @@ impl<'a> Located<'a> {
                 span: statement.location(),
             }),
             Self::Arg(_) => None,
+            Self::VariantConstructorDefinition(constructor) => Some(DefinitionLocation {
+                module: None,
+                span: constructor.location,
+            }),
         }
     }
 }
@@ -6,7 +6,7 @@ use crate::{
         elixir_libraries::ElixirLibraries,
         module_loader::SourceFingerprint,
         native_file_copier::NativeFileCopier,
-        package_loader::{CodegenRequired, PackageLoader, StaleTracker},
+        package_loader::{CodegenRequired, PackageLoader, SourceFilter, StaleTracker},
         Mode, Module, Origin, Package, Target,
     },
     codegen::{Erlang, ErlangApp, JavaScript, TypeScriptDeclarations},
@@ -46,6 +46,10 @@ pub struct PackageCompiler<'a, IO> {
     pub compile_beam_bytecode: bool,
     pub subprocess_stdio: Stdio,
     pub target_support: TargetSupport,
+    pub source_filter: SourceFilter,
+    /// Directories of `src` modules in addition to `src` itself, relative to
+    /// the package root.
+    pub extra_source_directories: Vec<Utf8PathBuf>,
 }
 
 impl<'a, IO> PackageCompiler<'a, IO>
@@ -78,6 +82,8 @@ where
             compile_beam_bytecode: true,
             subprocess_stdio: Stdio::Inherit,
             target_support: TargetSupport::NotEnforced,
+            source_filter: SourceFilter::default(),
+            extra_source_directories: vec![],
         }
     }
 
@@ -86,12 +92,34 @@ where
     /// from the cache will not be returned.
     // TODO: return the cached modules.
     pub fn compile(
+        self,
+        warnings: &WarningEmitter,
+        existing_modules: &mut im::HashMap<EcoString, type_::ModuleInterface>,
+        already_defined_modules: &mut im::HashMap<EcoString, Utf8PathBuf>,
+        stale_modules: &mut StaleTracker,
+        telemetry: &dyn Telemetry,
+    ) -> Result<Vec<Module>, Error> {
+        self.compile_streaming(
+            warnings,
+            existing_modules,
+            already_defined_modules,
+            stale_modules,
+            telemetry,
+            &mut (),
+        )
+    }
+
+    /// Compile the package, telling the observer about each module as soon as
+    /// it has been parsed and type checked rather than waiting for the whole
+    /// package. Returns the same modules as `compile`.
+    pub fn compile_streaming(
         mut self,
         warnings: &WarningEmitter,
         existing_modules: &mut im::HashMap<EcoString, type_::ModuleInterface>,
         already_defined_modules: &mut im::HashMap<EcoString, Utf8PathBuf>,
         stale_modules: &mut StaleTracker,
         telemetry: &dyn Telemetry,
+        observer: &mut dyn ModuleObserver,
     ) -> Result<Vec<Module>, Error> {
         let span = tracing::info_span!("compile", package = %self.config.name.as_str());
         let _enter = span.enter();
@@ -117,6 +145,8 @@ where
             &self.config.name,
             stale_modules,
             already_defined_modules,
+            &self.source_filter,
+            &self.extra_source_directories,
         )
         .run()?;
 
@@ -134,6 +164,10 @@ where
             }
         }
 
+        for module in &loaded.to_compile {
+            observer.parsed(module);
+        }
+
         // Type check the modules that are new or have changed
         tracing::info!(count=%loaded.to_compile.len(), "analysing_modules");
         let modules = analyse(
@@ -145,6 +179,7 @@ where
             existing_modules,
             warnings,
             self.target_support,
+            observer,
         )?;
 
         tracing::debug!("performing_code_generation");
@@ -385,6 +420,22 @@ where
     }
 }
 
+/// Observes the modules of a package as they are compiled, for example so the
+/// language server can report on each one without waiting for the others.
+pub trait ModuleObserver {
+    /// Called with each module that is to be compiled once it has been
+    /// parsed, before type checking begins.
+    fn parsed(&mut self, _module: &UncompiledModule) {}
+
+    /// Called with the name of each module just before it is type checked.
+    fn type_checking(&mut self, _module: &str) {}
+
+    /// Called with each module once it has been type checked.
+    fn compiled(&mut self, _module: &Module) {}
+}
+
+impl ModuleObserver for () {}
+
 fn analyse(
     package_config: &PackageConfig,
     target: Target,
@@ -394,6 +445,7 @@ fn analyse(
     module_types: &mut im::HashMap<EcoString, type_::ModuleInterface>,
     warnings: &WarningEmitter,
     target_support: TargetSupport,
+    observer: &mut dyn ModuleObserver,
 ) -> Result<Vec<Module>, Error> {
     let mut modules = Vec::with_capacity(parsed_modules.len() + 1);
     let direct_dependencies = package_config.dependencies_for(mode).expect("Package deps");
@@ -418,6 +470,7 @@ fn analyse(
     } in parsed_modules
     {
         tracing::debug!(module = ?name, "Type checking");
+        observer.type_checking(&name);
 
         let ast = crate::analyse::infer_module(
             target,
@@ -442,7 +495,7 @@ fn analyse(
 
         // Register the successfully type checked module data so that it can be
         // used for code generation
-        modules.push(Module {
+        let module = Module {
             dependencies,
             origin,
             extra,
@@ -451,7 +504,9 @@ fn analyse(
             code,
             ast,
             input_path: path,
-        });
+        };
+        observer.compiled(&module);
+        modules.push(module);
     }
 
     Ok(modules)
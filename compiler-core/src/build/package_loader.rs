@@ -7,6 +7,7 @@ use std::{
 };
 
 use camino::{Utf8Path, Utf8PathBuf};
+use globset::GlobSet;
 
 // TODO: emit warnings for cached modules even if they are not compiled again.
 
@@ -47,6 +48,32 @@ impl CodegenRequired {
     }
 }
 
+/// Which of the source files of a package are to be loaded.
+#[derive(Debug, Clone)]
+pub struct SourceFilter {
+    /// Whether to load the `test` directory when the mode includes tests.
+    pub include_tests: bool,
+    /// Source files to skip, matched against their path relative to the
+    /// package root, e.g. `src/generated/**`.
+    pub exclude: GlobSet,
+}
+
+impl Default for SourceFilter {
+    fn default() -> Self {
+        Self {
+            include_tests: true,
+            exclude: GlobSet::empty(),
+        }
+    }
+}
+
+impl SourceFilter {
+    fn includes(&self, root: &Utf8Path, path: &Utf8Path) -> bool {
+        let relative = path.strip_prefix(root).unwrap_or(path);
+        !self.exclude.is_match(relative.as_str())
+    }
+}
+
 #[derive(Debug)]
 pub struct PackageLoader<'a, IO> {
     io: IO,
@@ -60,6 +87,8 @@ pub struct PackageLoader<'a, IO> {
     target: Target,
     stale_modules: &'a mut StaleTracker,
     already_defined_modules: &'a mut im::HashMap<EcoString, Utf8PathBuf>,
+    source_filter: &'a SourceFilter,
+    extra_source_directories: &'a [Utf8PathBuf],
 }
 
 impl<'a, IO> PackageLoader<'a, IO>
@@ -78,6 +107,8 @@ where
         package_name: &'a EcoString,
         stale_modules: &'a mut StaleTracker,
         already_defined_modules: &'a mut im::HashMap<EcoString, Utf8PathBuf>,
+        source_filter: &'a SourceFilter,
+        extra_source_directories: &'a [Utf8PathBuf],
     ) -> Self {
         Self {
             io,
@@ -91,6 +122,8 @@ where
             artefact_directory,
             stale_modules,
             already_defined_modules,
+            source_filter,
+            extra_source_directories,
         }
     }
 
@@ -200,23 +233,38 @@ where
             origin: Origin::Src,
         };
 
-        // Src
-        for path in self.io.gleam_source_files(&src) {
-            if !self.is_gleam_path(&path, &src) {
-                self.warnings.emit(crate::Warning::InvalidSource { path });
-                continue;
+        // Src, along with any other directories of `src` modules the package
+        // has been configured with
+        let extra_directories = self
+            .extra_source_directories
+            .iter()
+            .map(|directory| self.root.join(directory))
+            .collect_vec();
+        for directory in std::iter::once(&src).chain(&extra_directories) {
+            loader.source_directory = directory;
+            for path in self.io.gleam_source_files(directory) {
+                if !self.source_filter.includes(self.root, &path) {
+                    continue;
+                }
+                if !self.is_gleam_path(&path, directory) {
+                    self.warnings.emit(crate::Warning::InvalidSource { path });
+                    continue;
+                }
+                let input = loader.load(path)?;
+                inputs.insert(input)?;
             }
-            let input = loader.load(path)?;
-            inputs.insert(input)?;
         }
 
         // Test
-        if self.mode.includes_tests() {
+        if self.mode.includes_tests() && self.source_filter.include_tests {
             let test = self.root.join("test");
             loader.origin = Origin::Test;
             loader.source_directory = &test;
 
             for path in self.io.gleam_source_files(&test) {
+                if !self.source_filter.includes(self.root, &path) {
+                    continue;
+                }
                 let input = loader.load(path)?;
                 inputs.insert(input)?;
             }
@@ -74,6 +74,8 @@ fn run_loader(fs: InMemoryFileSystem, root: &Utf8Path, artefact: &Utf8Path) -> L
         target: Target::JavaScript,
         stale_modules: &mut StaleTracker::default(),
         already_defined_modules: &mut defined,
+        source_filter: &SourceFilter::default(),
+        extra_source_directories: &[],
     };
     let loaded = loader.run().unwrap();
 
@@ -1,8 +1,12 @@
 use crate::{
     analyse::TargetSupport,
     build::{
-        package_compiler, package_compiler::PackageCompiler, package_loader::StaleTracker,
-        project_compiler, telemetry::Telemetry, Mode, Module, Origin, Package, Target,
+        package_compiler,
+        package_compiler::{ModuleObserver, PackageCompiler},
+        package_loader::{SourceFilter, StaleTracker},
+        project_compiler,
+        telemetry::Telemetry,
+        Mode, Module, Origin, Package, Target,
     },
     codegen::{self, ErlangApp},
     config::PackageConfig,
@@ -87,12 +91,18 @@ pub struct ProjectCompiler<IO> {
     warnings: WarningEmitter,
     telemetry: Box<dyn Telemetry>,
     options: Options,
-    paths: ProjectPaths,
-    ids: UniqueIdGenerator,
+    pub(crate) paths: ProjectPaths,
+    pub(crate) ids: UniqueIdGenerator,
     pub(crate) io: IO,
     /// We may want to silence subprocess stdout if we are running in LSP mode.
     /// The language server talks over stdio so printing would break that.
     pub subprocess_stdio: Stdio,
+    /// Which of the root package's source files are compiled. Dependencies
+    /// are always compiled in full.
+    pub root_source_filter: SourceFilter,
+    /// Directories of the root package that contain `src` modules in addition
+    /// to `src` itself, relative to the package root.
+    pub root_source_directories: Vec<Utf8PathBuf>,
 }
 
 // TODO: test that tests cannot be imported into src
@@ -123,6 +133,8 @@ where
             ids: UniqueIdGenerator::new(),
             warnings: WarningEmitter::new(warning_emitter),
             subprocess_stdio: Stdio::Inherit,
+            root_source_filter: SourceFilter::default(),
+            root_source_directories: vec![],
             telemetry,
             packages,
             options,
@@ -136,6 +148,15 @@ where
         &self.importable_modules
     }
 
+    /// Replace the interfaces of the modules that have been compiled, such as
+    /// with those from an earlier compilation.
+    pub fn set_importable_modules(
+        &mut self,
+        modules: im::HashMap<EcoString, type_::ModuleInterface>,
+    ) {
+        self.importable_modules = modules;
+    }
+
     pub fn mode(&self) -> Mode {
         self.options.mode
     }
@@ -144,6 +165,12 @@ where
         self.options.target.unwrap_or(self.config.target)
     }
 
+    /// Override the target given in the package config. `None` uses the
+    /// config's target again.
+    pub fn set_target(&mut self, target: Option<Target>) {
+        self.options.target = target;
+    }
+
     /// Compiles all packages in the project and returns the compiled
     /// information from the root package
     pub fn compile(mut self) -> Result<Built> {
@@ -184,8 +211,18 @@ where
     }
 
     pub fn compile_root_package(&mut self) -> Result<Package, Error> {
+        self.compile_root_package_streaming(&mut ())
+    }
+
+    /// Compiles the root package, telling the observer about each module as
+    /// soon as it has been parsed and type checked.
+    pub fn compile_root_package_streaming(
+        &mut self,
+        observer: &mut dyn ModuleObserver,
+    ) -> Result<Package, Error> {
         let config = self.config.clone();
-        let modules = self.compile_gleam_package(&config, true, self.paths.root().to_path_buf())?;
+        let root = self.paths.root().to_path_buf();
+        let modules = self.compile_gleam_package(&config, true, root, observer)?;
         Ok(Package { config, modules })
     }
 
@@ -222,12 +259,25 @@ where
     }
 
     pub fn compile_dependencies(&mut self) -> Result<Vec<Module>, Error> {
+        self.compile_dependencies_with_progress(|_, _| ())
+    }
+
+    /// Compile the dependency packages, calling `on_progress` with the number
+    /// of packages that have been compiled or loaded from the cache and the
+    /// total number of packages, before the first package and after each one.
+    pub fn compile_dependencies_with_progress(
+        &mut self,
+        on_progress: impl Fn(usize, usize),
+    ) -> Result<Vec<Module>, Error> {
         let sequence = order_packages(&self.packages)?;
+        let total = sequence.len();
         let mut modules = vec![];
 
-        for name in sequence {
-            let compiled = self.load_cache_or_compile_package(&name)?;
+        on_progress(0, total);
+        for (index, name) in sequence.iter().enumerate() {
+            let compiled = self.load_cache_or_compile_package(name)?;
             modules.extend(compiled);
+            on_progress(index + 1, total);
         }
 
         Ok(modules)
@@ -469,7 +519,15 @@ where
         package: &ManifestPackage,
     ) -> Result<Vec<Module>, Error> {
         // TODO: Test
-        let package_root = match &package.source {
+        let package_root = self.package_root(package)?;
+        let config_path = package_root.join("gleam.toml");
+        let config = PackageConfig::read(config_path, &self.io)?;
+        self.compile_gleam_package(&config, false, package_root, &mut ())
+    }
+
+    /// The directory containing the source code of a dependency package.
+    pub(crate) fn package_root(&self, package: &ManifestPackage) -> Result<Utf8PathBuf, Error> {
+        Ok(match &package.source {
             // If the path is relative it is relative to the root of the
             // project, not to the current working directory. The language server
             // could have the working directory and the project root in different
@@ -486,10 +544,7 @@ where
             ManifestPackageSource::Git { .. } | ManifestPackageSource::Hex { .. } => {
                 self.paths.build_packages_package(&package.name)
             }
-        };
-        let config_path = package_root.join("gleam.toml");
-        let config = PackageConfig::read(config_path, &self.io)?;
-        self.compile_gleam_package(&config, false, package_root)
+        })
     }
 
     fn load_cached_package(
@@ -514,6 +569,7 @@ where
         config: &PackageConfig,
         is_root: bool,
         root_path: Utf8PathBuf,
+        observer: &mut dyn ModuleObserver,
     ) -> Result<Vec<Module>, Error> {
         let out_path =
             self.paths
@@ -562,6 +618,10 @@ where
         compiler.perform_codegen = self.options.codegen.should_codegen(is_root);
         compiler.compile_beam_bytecode = self.options.codegen.should_codegen(is_root);
         compiler.subprocess_stdio = self.subprocess_stdio;
+        if is_root {
+            compiler.source_filter = self.root_source_filter.clone();
+            compiler.extra_source_directories = self.root_source_directories.clone();
+        }
         compiler.target_support = if is_root {
             // When compiling the root package it is context specific as to whether we need to
             // enforce that all functions have an implementation for the current target.
@@ -578,12 +638,13 @@ where
         };
 
         // Compile project to Erlang or JavaScript source code
-        let compiled = compiler.compile(
+        let compiled = compiler.compile_streaming(
             &mut self.warnings,
             &mut self.importable_modules,
             &mut self.defined_modules,
             &mut self.stale_modules,
             self.telemetry.as_ref(),
+            observer,
         )?;
 
         Ok(compiled)
@@ -13,6 +13,16 @@ pub enum Level {
     Warning,
 }
 
+/// Extra information about a diagnostic that editors can use when
+/// displaying it, such as rendering unused code greyed out.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Tag {
+    /// The diagnosed code is unused or can never be run.
+    Unnecessary,
+    /// The diagnosed code uses something that has been deprecated.
+    Deprecated,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Label {
     pub text: Option<String>,
@@ -39,6 +49,10 @@ pub struct Diagnostic {
     pub title: String,
     pub text: String,
     pub level: Level,
+    pub tags: Vec<Tag>,
+    /// A short name for the kind of problem, such as `todo`, which editors
+    /// can display and filter diagnostics by.
+    pub code: Option<&'static str>,
     pub location: Option<Location>,
     pub hint: Option<String>,
 }
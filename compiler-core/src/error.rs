@@ -467,6 +467,8 @@ of the Gleam dependency modules."
                     title: "Failed to decode module metadata".into(),
                     text,
                     level: Level::Error,
+                    tags: vec![],
+                    code: None,
                     location: None,
                     hint: None,
                 }
@@ -502,6 +504,8 @@ This prefix is intended for official Gleam packages only.",
                     text,
                     hint: None,
                     level: Level::Error,
+                    tags: vec![],
+                    code: None,
                     location: None,
                 }
             }
@@ -514,6 +518,8 @@ Module names can only contain lowercase letters, underscore, and
 forward slash and must not end with a slash."
                 ),
                 level: Level::Error,
+                tags: vec![],
+                code: None,
                 location: None,
                 hint: None,
             },
@@ -527,6 +533,8 @@ forward slash and must not end with a slash."
                     title: "Module does not exist".into(),
                     text: format!("Module `{module}` was not found."),
                     level: Level::Error,
+                    tags: vec![],
+                    code: None,
                     location: None,
                     hint: Some(hint),
                 }
@@ -538,6 +546,8 @@ forward slash and must not end with a slash."
                     "`{module}` does not have a main function so the module can not be run."
                 ),
                 level: Level::Error,
+                tags: vec![],
+                code: None,
                 location: None,
                 hint: Some(format!(
                     "Add a public `main` function to \
@@ -552,6 +562,8 @@ to `src/{module}.gleam`."
 target, so it cannot be run."
                 ),
                 level: Level::Error,
+                tags: vec![],
+                code: None,
                 location: None,
                 hint: None,
             },
@@ -562,6 +574,8 @@ target, so it cannot be run."
                     "`{module}:main` should have an arity of 0 to be run but its arity is {arity}."
                 ),
                 level: Level::Error,
+                tags: vec![],
+                code: None,
                 location: None,
                 hint: Some("Change the function signature of main to `pub fn main() {}`.".into()),
             },
@@ -570,6 +584,8 @@ target, so it cannot be run."
                 title: "Project folder already exists".into(),
                 text: format!("Project folder root:\n\n  {path}"),
                 level: Level::Error,
+                tags: vec![],
+                code: None,
                 hint: None,
                 location: None,
             },
@@ -594,6 +610,8 @@ If you want to overwrite these files, delete them and run the command again.
                         .join("\n")
                 ),
                 level: Level::Error,
+                tags: vec![],
+                code: None,
                 hint: None,
                 location: None,
             },
@@ -613,6 +631,8 @@ Please remove them and try again.
                         .join("\n")
                 ),
                 level: Level::Error,
+                tags: vec![],
+                code: None,
                 hint: None,
                 location: None,
             },
@@ -622,6 +642,8 @@ Please remove them and try again.
                 text: format!("We were unable to find the project root:\n\n  {path}"),
                 hint: None,
                 level: Level::Error,
+                tags: vec![],
+                code: None,
                 location: None,
             },
 
@@ -635,6 +657,8 @@ your app.src file \"{app_ver}\"."
                     hint: None,
                     text,
                     level: Level::Error,
+                    tags: vec![],
+                    code: None,
                     location: None,
                 }
             }
@@ -673,6 +697,8 @@ You can also install rebar3 via homebrew using \"brew install rebar3\"",
                     text,
                     hint: None,
                     level: Level::Error,
+                    tags: vec![],
+                    code: None,
                     location: None,
                 }
             }
@@ -688,6 +714,8 @@ You can also install rebar3 via homebrew using \"brew install rebar3\"",
                     text,
                     hint: None,
                     level: Level::Error,
+                    tags: vec![],
+                    code: None,
                     location: None,
                 }
             }
@@ -710,6 +738,8 @@ The error from the shell command library was:
                     text,
                     hint: None,
                     level: Level::Error,
+                    tags: vec![],
+                    code: None,
                     location: None,
                 }
             }
@@ -727,6 +757,8 @@ This was error from the gzip library:
                     text,
                     hint: None,
                     level: Level::Error,
+                    tags: vec![],
+                    code: None,
                     location: None,
                 }
             }
@@ -745,6 +777,8 @@ This was error from the tar library:
                     text,
                     hint: None,
                     level: Level::Error,
+                    tags: vec![],
+                    code: None,
                     location: None,
                 }
             }
@@ -762,6 +796,8 @@ This was error from the tar library:
                     text,
                     hint: None,
                     level: Level::Error,
+                    tags: vec![],
+                    code: None,
                     location: None,
                 }
             }
@@ -779,6 +815,8 @@ This was error from the tar library:
                     text,
                     hint: None,
                     level: Level::Error,
+                    tags: vec![],
+                    code: None,
                     location: None,
                 }
             }
@@ -796,6 +834,8 @@ This was error from the Hex client library:
                     text,
                     hint: None,
                     level: Level::Error,
+                    tags: vec![],
+                    code: None,
                     location: None,
                 }
             }
@@ -817,6 +857,8 @@ Second: {second}"
                     text,
                     hint: None,
                     level: Level::Error,
+                    tags: vec![],
+                    code: None,
                     location: None,
                 }
             }
@@ -826,6 +868,8 @@ Second: {second}"
                 text: format!("The file `{file}` is defined multiple times."),
                 hint: None,
                 level: Level::Error,
+                tags: vec![],
+                code: None,
                 location: None,
             },
 
@@ -856,6 +900,8 @@ Second: {second}"
                     text,
                     hint: None,
                     level: Level::Error,
+                    tags: vec![],
+                    code: None,
                     location: None,
                 }
             }
@@ -867,6 +913,8 @@ Second: {second}"
                     title: "Non UTF-8 Path Encountered".into(),
                     text,
                     level: Level::Error,
+                    tags: vec![],
+                    code: None,
                     location: None,
                     hint: None,
                 }
@@ -883,6 +931,8 @@ Second: {second}"
                     text,
                     hint: None,
                     level: Level::Error,
+                    tags: vec![],
+                    code: None,
                     location: None,
                 }
             }
@@ -905,6 +955,8 @@ modules cannot import them. Perhaps move the `{test_module}` module to the src d
                         text,
                         hint: None,
                         level: Level::Error,
+                        tags: vec![],
+                        code: None,
                         location: Some(Location {
                             label: Label {
                                 text: Some("Imported here".into()),
@@ -964,6 +1016,8 @@ constructor accepts."
                         text,
                         hint: None,
                         level: Level::Error,
+                        tags: vec![],
+                        code: None,
                         location: Some(Location {
                             label,
                             path: path.clone(),
@@ -983,6 +1037,8 @@ not expect any. Please remove the label `{label}`."
                         text,
                         hint: None,
                         level: Level::Error,
+                        tags: vec![],
+                        code: None,
                         location: Some(Location {
                             label: Label {
                                 text: None,
@@ -1006,6 +1062,8 @@ also be labelled."
                         text,
                         hint: None,
                         level: Level::Error,
+                        tags: vec![],
+                        code: None,
                         location: Some(Location {
                             label: Label {
                                 text: None,
@@ -1032,6 +1090,8 @@ Names in a Gleam module must be unique so one will need to be renamed."
                         text,
                         hint: None,
                         level: Level::Error,
+                        tags: vec![],
+                        code: None,
                         location: Some(Location {
                             label: Label {
                                 text: Some("Reimported here".into()),
@@ -1067,6 +1127,8 @@ Names in a Gleam module must be unique so one will need to be renamed."
                         text,
                         hint: None,
                         level: Level::Error,
+                        tags: vec![],
+                        code: None,
                         location: Some(Location {
                             label: Label {
                                 text: Some("Redefined here".into()),
@@ -1097,6 +1159,8 @@ Names in a Gleam module must be unique so one will need to be renamed."
                         text,
                         hint: None,
                         level: Level::Error,
+                        tags: vec![],
+                        code: None,
                         location: Some(Location {
                             label: Label {
                                 text: Some("Redefined here".into()),
@@ -1120,6 +1184,8 @@ Names in a Gleam module must be unique so one will need to be renamed."
                         text,
                         hint: None,
                         level: Level::Error,
+                        tags: vec![],
+                        code: None,
                         location: Some(Location {
                             label: Label {
                                 text: None,
@@ -1140,6 +1206,8 @@ Names in a Gleam module must be unique so one will need to be renamed."
                         text,
                         hint: None,
                         level: Level::Error,
+                        tags: vec![],
+                        code: None,
                         location: Some(Location {
                             label: Label {
                                 text: None,
@@ -1163,6 +1231,8 @@ Hint: Add some type annotations and try again."
                         text,
                         hint: None,
                         level: Level::Error,
+                        tags: vec![],
+                        code: None,
                         location: Some(Location {
                             label: Label {
                                 text: None,
@@ -1186,6 +1256,8 @@ Hint: Add some type annotations and try again."
                         text,
                         hint: None,
                         level: Level::Error,
+                        tags: vec![],
+                        code: None,
                         location: Some(Location {
                             label: Label {
                                 text: None,
@@ -1248,6 +1320,8 @@ to call a method on this value you may want to use the function syntax instead."
                         text,
                         hint: None,
                         level: Level::Error,
+                        tags: vec![],
+                        code: None,
                         location: Some(Location {
                             label: Label {
                                 text: Some(label),
@@ -1291,6 +1365,8 @@ But this argument has this type:
                         text,
                         hint: None,
                         level: Level::Error,
+                        tags: vec![],
+                        code: None,
                         location: Some(Location {
                             label: Label {
                                 text: None,
@@ -1342,6 +1418,8 @@ But function expects:
                         text,
                         hint: None,
                         level: Level::Error,
+                        tags: vec![],
+                        code: None,
                         location: Some(Location {
                             label: Label {
                                 text: Some("This function does not accept the piped type".into()),
@@ -1381,6 +1459,8 @@ But function expects:
                         text,
                         hint: None,
                         level: Level::Error,
+                        tags: vec![],
+                        code: None,
                         location: Some(Location {
                             label: Label {
                                 text: None,
@@ -1412,6 +1492,8 @@ number of arguments."
                         text,
                         hint: None,
                         level: Level::Error,
+                        tags: vec![],
+                        code: None,
                         location: Some(Location {
                             label: Label {
                                 text: Some(format!("Expected {expected}, got {given}")),
@@ -1453,6 +1535,8 @@ number of arguments."
                         text,
                         hint: None,
                         level: Level::Error,
+                        tags: vec![],
+                        code: None,
                         location: Some(Location {
                             label: Label {
                                 text: Some(label),
@@ -1475,6 +1559,8 @@ assigned variables to all of them."
                         text,
                         hint: None,
                         level: Level::Error,
+                        tags: vec![],
+                        code: None,
                         location: Some(Location {
                             label: Label {
                                 text: None,
@@ -1501,6 +1587,8 @@ constructing a new record with its values."
                         text,
                         hint: None,
                         level: Level::Error,
+                        tags: vec![],
+                        code: None,
                         location: Some(Location {
                             label: Label {
                                 text: Some(
@@ -1545,6 +1633,8 @@ constructing a new record with its values."
                         text,
                         hint: None,
                         level: Level::Error,
+                        tags: vec![],
+                        code: None,
                         location: Some(Location {
                             label: Label {
                                 text: label_text,
@@ -1566,6 +1656,8 @@ constructing a new record with its values."
                     text: wrap_format!("The name `{name}` is not in scope here."),
                     hint: None,
                     level: Level::Error,
+                    tags: vec![],
+                    code: None,
                     location: Some(Location {
                         label: Label {
                             text: did_you_mean(name, variables),
@@ -1598,6 +1690,8 @@ Private types can only be used within the module that defines them.",
                         text,
                         hint: None,
                         level: Level::Error,
+                        tags: vec![],
+                        code: None,
                         location: Some(Location {
                             label: Label {
                                 text: None,
@@ -1619,6 +1713,8 @@ Private types can only be used within the module that defines them.",
                     text: format!("No module has been found with the name `{name}`."),
                     hint: None,
                     level: Level::Error,
+                    tags: vec![],
+                    code: None,
                     location: Some(Location {
                         label: Label {
                             text: did_you_mean(name, imported_modules),
@@ -1643,6 +1739,8 @@ Private types can only be used within the module that defines them.",
                         text,
                         hint: None,
                         level: Level::Error,
+                        tags: vec![],
+                        code: None,
                         location: Some(Location {
                             label: Label {
                                 text: did_you_mean(name, type_constructors),
@@ -1668,6 +1766,8 @@ Private types can only be used within the module that defines them.",
                         text,
                         hint: None,
                         level: Level::Error,
+                        tags: vec![],
+                        code: None,
                         location: Some(Location {
                             label: Label {
                                 text: did_you_mean(name, value_constructors),
@@ -1699,6 +1799,8 @@ Private types can only be used within the module that defines them.",
                         text,
                         hint: None,
                         level: Level::Error,
+                        tags: vec![],
+                        code: None,
                         location: Some(Location {
                             label: Label {
                                 text: did_you_mean(name, &options),
@@ -1725,6 +1827,8 @@ Each clause must have a pattern for every subject value.",
                         text,
                         hint: None,
                         level: Level::Error,
+                        tags: vec![],
+                        code: None,
                         location: Some(Location {
                             label: Label {
                                 text: Some(format!("Expected {expected} patterns, got {given}")),
@@ -1747,6 +1851,8 @@ function, or be an argument to the function. The variable `{name}` is not define
                         text,
                         hint: None,
                         level: Level::Error,
+                        tags: vec![],
+                        code: None,
                         location: Some(Location {
                             label: Label {
                                 text: Some("Is not locally defined".into()),
@@ -1769,6 +1875,8 @@ This variable `{name}` has not been previously defined.",
                         text,
                         hint: None,
                         level: Level::Error,
+                        tags: vec![],
+                        code: None,
                         location: Some(Location {
                             label: Label {
                                 text: Some("Has not been previously defined".into()),
@@ -1791,6 +1899,8 @@ as the initial pattern, but the `{name}` variable is missing.",
                         text,
                         hint: None,
                         level: Level::Error,
+                        tags: vec![],
+                        code: None,
                         location: Some(Location {
                             label: Label {
                                 text: Some("This does not define all required variables".into()),
@@ -1816,6 +1926,8 @@ e.g. (x, y) if x == y -> ...",
                         text,
                         hint: None,
                         level: Level::Error,
+                        tags: vec![],
+                        code: None,
                         location: Some(Location {
                             label: Label {
                                 text: Some("This has already been used".into()),
@@ -1835,6 +1947,8 @@ e.g. (x, y) if x == y -> ...",
                     text: "This tuple has no elements so it cannot be indexed at all.".into(),
                     hint: None,
                     level: Level::Error,
+                    tags: vec![],
+                    code: None,
                     location: Some(Location {
                         label: Label {
                             text: None,
@@ -1863,6 +1977,8 @@ tuple has {} elements so the highest valid index is {}.",
                         text,
                         hint: None,
                         level: Level::Error,
+                        tags: vec![],
+                        code: None,
                         location: Some(Location {
                             label: Label {
                                 text: Some("This index is too large".into()),
@@ -1888,6 +2004,8 @@ tuple has {} elements so the highest valid index is {}.",
                         text,
                         hint: None,
                         level: Level::Error,
+                        tags: vec![],
+                        code: None,
                         location: Some(Location {
                             label: Label {
                                 text: Some("This is not a tuple".into()),
@@ -1910,6 +2028,8 @@ we can continue."
                         text,
                         hint: None,
                         level: Level::Error,
+                        tags: vec![],
+                        code: None,
                         location: Some(Location {
                             label: Label {
                                 text: Some("What type is this?".into()),
@@ -1932,6 +2052,8 @@ function and try again."
                         text,
                         hint: None,
                         level: Level::Error,
+                        tags: vec![],
+                        code: None,
                         location: Some(Location {
                             label: Label {
                                 text: Some("I don't know what type this is".into()),
@@ -2029,6 +2151,8 @@ allowed at the end of a bin pattern.")],
                         text,
                         hint: None,
                         level: Level::Error,
+                        tags: vec![],
+                        code: None,
                         location: Some(Location {
                             label: Label {
                                 text: Some(label.into()),
@@ -2046,6 +2170,8 @@ allowed at the end of a bin pattern.")],
                     text: "Only record constructors can be used with the update syntax.".into(),
                     hint: None,
                     level: Level::Error,
+                    tags: vec![],
+                    code: None,
                     location: Some(Location {
                         label: Label {
                             text: Some("This is not a record constructor".into()),
@@ -2063,6 +2189,8 @@ allowed at the end of a bin pattern.")],
                         .into(),
                     hint: None,
                     level: Level::Error,
+                    tags: vec![],
+                    code: None,
                     location: Some(Location {
                         label: Label {
                             text: Some("I need to know what this is".into()),
@@ -2085,6 +2213,8 @@ Try a different name for this module."
                         hint: None,
                         location: None,
                         level: Level::Error,
+                        tags: vec![],
+                        code: None,
                     }
                 }
 
@@ -2100,6 +2230,8 @@ Try a different name for this module."
                         hint: None,
                         location: None,
                         level: Level::Error,
+                        tags: vec![],
+                        code: None,
                     }
                 }
 
@@ -2133,6 +2265,8 @@ value, or use `let assert` rather than `let`."
                         text,
                         hint: None,
                         level: Level::Error,
+                        tags: vec![],
+                        code: None,
                         location: Some(Location {
                             label: Label {
                                 text: None,
@@ -2150,6 +2284,8 @@ value, or use `let assert` rather than `let`."
                     text: format!("Two `{name}` arguments have been defined for this function."),
                     hint: None,
                     level: Level::Error,
+                    tags: vec![],
+                    code: None,
                     location: Some(Location {
                         label: Label {
                             text: None,
@@ -2166,6 +2302,8 @@ value, or use `let assert` rather than `let`."
                     text: wrap("All unlabelled arguments must come before any labelled arguments."),
                     hint: None,
                     level: Level::Error,
+                    tags: vec![],
+                    code: None,
                     location: Some(Location {
                         label: Label {
                             text: None,
@@ -2189,6 +2327,8 @@ forever in a loop, and we'd never get the final type.",
                         text,
                         hint: None,
                         level: Level::Error,
+                        tags: vec![],
+                        code: None,
                         location: Some(Location {
                             label: Label {
                                 text: None,
@@ -2217,6 +2357,8 @@ so we can tell what type of values they accept and return.",
                         text,
                         hint: None,
                         level: Level::Error,
+                        tags: vec![],
+                        code: None,
                         location: Some(Location {
                             label: Label {
                                 text: None,
@@ -2239,6 +2381,8 @@ using the `@external` attribute."
                         text,
                         hint: None,
                         level: Level::Error,
+                        tags: vec![],
+                        code: None,
                         location: Some(Location {
                             label: Label {
                                 text: None,
@@ -2265,6 +2409,8 @@ implementation but the module path `{module}` is not valid."
                         text,
                         hint: None,
                         level: Level::Error,
+                        tags: vec![],
+                        code: None,
                         location: Some(Location {
                             label: Label {
                                 text: None,
@@ -2291,6 +2437,8 @@ implementation but the function name `{function}` is not valid."
                         text,
                         hint: None,
                         level: Level::Error,
+                        tags: vec![],
+                        code: None,
                         location: Some(Location {
                             label: Label {
                                 text: None,
@@ -2324,6 +2472,8 @@ The missing patterns are:\n"
                             "Use a more general pattern or use `let assert` instead.".into(),
                         ),
                         level: Level::Error,
+                        tags: vec![],
+                        code: None,
                         location: Some(Location {
                             src: src.clone(),
                             path: path.to_path_buf(),
@@ -2352,6 +2502,8 @@ The missing patterns are:\n"
                         text,
                         hint: None,
                         level: Level::Error,
+                        tags: vec![],
+                        code: None,
                         location: Some(Location {
                             src: src.clone(),
                             path: path.to_path_buf(),
@@ -2381,6 +2533,8 @@ and there is no implementation for the {} target.",
                         text,
                         hint: None,
                         level: Level::Error,
+                        tags: vec![],
+                        code: None,
                         location: Some(Location {
                             path: path.clone(),
                             src: src.clone(),
@@ -2412,6 +2566,8 @@ must be able to compile for a module to be valid."
                         text,
                         hint: None,
                         level: Level::Error,
+                        tags: vec![],
+                        code: None,
                         location: Some(Location {
                             path: path.clone(),
                             src: src.clone(),
@@ -2433,6 +2589,8 @@ must be able to compile for a module to be valid."
                         text,
                         hint: None,
                         level: Level::Error,
+                        tags: vec![],
+                        code: None,
                         location: Some(Location {
                             path: path.clone(),
                             src: src.clone(),
@@ -2455,6 +2613,8 @@ Rename or remove one of them.",
                         text,
                         hint: None,
                         level: Level::Error,
+                        tags: vec![],
+                        code: None,
                         location: Some(Location {
                             path: path.clone(),
                             src: src.clone(),
@@ -2486,6 +2646,8 @@ Rename or remove one of them.",
                     text,
                     hint: None,
                     level: Level::Error,
+                    tags: vec![],
+                    code: None,
                     location: Some(Location {
                         label: Label {
                             text: Some(label.to_string()),
@@ -2512,6 +2674,8 @@ cycle to continue.",
                     text,
                     hint: None,
                     level: Level::Error,
+                    tags: vec![],
+                    code: None,
                     location: None,
                 }
             }
@@ -2530,6 +2694,8 @@ cycle to continue.",
                     text,
                     hint: None,
                     level: Level::Error,
+                    tags: vec![],
+                    code: None,
                     location: None,
                 }
             }
@@ -2551,6 +2717,8 @@ but it cannot be found."
                     text,
                     hint: None,
                     level: Level::Error,
+                    tags: vec![],
+                    code: None,
                     location: Some(Location {
                         label: Label {
                             text: did_you_mean(import, modules),
@@ -2583,6 +2751,8 @@ but it cannot be found."
                     hint: None,
                     location: None,
                     level: Level::Error,
+                    tags: vec![],
+                    code: None,
                 }
             }
 
@@ -2601,6 +2771,8 @@ but it cannot be found."
                     hint: None,
                     location: None,
                     level: Level::Error,
+                    tags: vec![],
+                    code: None,
                 }
             }
 
@@ -2618,6 +2790,8 @@ Fix the warnings and try again."
                     hint: None,
                     location: None,
                     level: Level::Error,
+                    tags: vec![],
+                    code: None,
                 }
             }
 
@@ -2627,6 +2801,8 @@ Fix the warnings and try again."
                     text: format!("{feature} is not supported for JavaScript compilation."),
                     hint: None,
                     level: Level::Error,
+                    tags: vec![],
+                    code: None,
                     location: Some(Location {
                         label: Label {
                             text: None,
@@ -2656,6 +2832,8 @@ The error from the package manager client was:
                     hint: None,
                     location: None,
                     level: Level::Error,
+                    tags: vec![],
+                    code: None,
                 }
             }
 
@@ -2672,6 +2850,8 @@ The error from the HTTP client was:
                     hint: None,
                     location: None,
                     level: Level::Error,
+                    tags: vec![],
+                    code: None,
                 }
             }
 
@@ -2688,6 +2868,8 @@ The error from the parser was:
                     hint: None,
                     location: None,
                     level: Level::Error,
+                    tags: vec![],
+                    code: None,
                 }
             }
 
@@ -2700,6 +2882,8 @@ The error from the parser was:
                     hint: None,
                     location: None,
                     level: Level::Error,
+                    tags: vec![],
+                    code: None,
                 }
             }
 
@@ -2718,6 +2902,8 @@ The error from the version resolver library was:
                     hint: None,
                     location: None,
                     level: Level::Error,
+                    tags: vec![],
+                    code: None,
                 }
             }
 
@@ -2727,6 +2913,8 @@ The error from the version resolver library was:
                 hint: None,
                 location: None,
                 level: Level::Error,
+                tags: vec![],
+                code: None,
             },
 
             Error::WrongDependencyProvided {
@@ -2744,6 +2932,8 @@ The error from the version resolver library was:
                     hint: None,
                     location: None,
                     level: Level::Error,
+                    tags: vec![],
+                    code: None,
                 }
             }
 
@@ -2762,6 +2952,8 @@ The error from the version resolver library was:
                     hint: None,
                     location: None,
                     level: Level::Error,
+                    tags: vec![],
+                    code: None,
                 }
             }
 
@@ -2776,6 +2968,8 @@ dev-dependencies sections of the gleam.toml file."
                     hint: None,
                     location: None,
                     level: Level::Error,
+                    tags: vec![],
+                    code: None,
                 }
             }
 
@@ -2807,6 +3001,8 @@ licences = ["Apache-2.0"]"#
                     hint: None,
                     location: None,
                     level: Level::Error,
+                    tags: vec![],
+                    code: None,
                 }
             }
 
@@ -2819,6 +3015,8 @@ because dependency `{package}` is not a Hex dependency.",
                 hint: None,
                 location: None,
                 level: Level::Error,
+                tags: vec![],
+                code: None,
             },
 
             Error::UnsupportedBuildTool {
@@ -2840,6 +3038,8 @@ issue in our tracker: https://github.com/gleam-lang/gleam/issues",
                     hint: None,
                     location: None,
                     level: Level::Error,
+                    tags: vec![],
+                    code: None,
                 }
             }
 
@@ -2856,6 +3056,8 @@ issue in our tracker: https://github.com/gleam-lang/gleam/issues",
                     text,
                     hint: None,
                     level: Level::Error,
+                    tags: vec![],
+                    code: None,
                     location: None,
                 }
             }
@@ -2875,6 +3077,8 @@ but you are using v{gleam_version}.",
                     hint: None,
                     location: None,
                     level: Level::Error,
+                    tags: vec![],
+                    code: None,
                 }
             }
 
@@ -2900,6 +3104,8 @@ but you are using v{gleam_version}.",
                     hint,
                     location: None,
                     level: Level::Error,
+                    tags: vec![],
+                    code: None,
                 }
             }
 
@@ -2908,6 +3114,8 @@ but you are using v{gleam_version}.",
                 text: "The --javascript-prelude flag must be given when compiling to JavaScript."
                     .into(),
                 level: Level::Error,
+                tags: vec![],
+                code: None,
                 location: None,
                 hint: None,
             },
@@ -84,7 +84,7 @@ impl InMemoryFileSystem {
 impl FileSystemWriter for InMemoryFileSystem {
     fn delete_directory(&self, path: &Utf8Path) -> Result<(), Error> {
         let mut files = self.files.deref().borrow_mut();
-        let _ = files.remove(path);
+        files.retain(|file, _| !file.starts_with(path));
         Ok(())
     }
 
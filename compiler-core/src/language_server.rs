@@ -1,22 +1,77 @@
+mod boolean_case;
+mod call_labels;
+mod closures;
 mod code_action;
 mod compiler;
+mod completion;
+#[cfg(feature = "lsp-debug")]
+mod debug;
+mod dependencies;
+mod dependents;
+mod desugar;
+mod documentation;
 mod engine;
+mod external;
 mod feedback;
 mod files;
+mod formatting;
+mod highlights;
+mod identifier;
+mod import_style;
+mod imports;
+mod linked_editing;
+mod lsp_warning;
+mod mismatch;
+mod outline;
+mod package_config;
+mod patterns;
+mod pipe;
 mod progress;
+mod publicity;
+mod qualified_name;
+mod reexports;
+mod references;
+mod rename;
 mod router;
+mod scaffold;
+mod selection;
 mod server;
+mod settings;
+mod shadowing;
+mod state;
+mod trace_level;
+mod unreachable;
+mod unused;
+mod visit;
+mod warning_level;
+mod wrap;
 
 #[cfg(test)]
 mod tests;
 
+pub use compiler::LspProjectCompiler;
+pub use completion::{CompletionContext, CompletionTier};
+pub use dependencies::PackageSummary;
+pub use external::{DefinitionWithExternal, DefinitionWithExternalRequest, ExternalTarget};
+pub use feedback::ProjectHealth;
+pub use formatting::format_range;
+pub use highlights::HighlightKind;
+pub use mismatch::TypeMismatch;
+pub use outline::{ConstructorInfo, DefinitionId, FieldInfo, TypeDefInfo, TypeParameterInfo};
+pub use package_config::validate_config;
+pub use references::{Reference, ReferenceScope, Workspace};
 pub use server::LanguageServer;
+pub use settings::LspSettings;
+pub use state::CompilerState;
+pub use trace_level::TraceLevel;
+pub use warning_level::{WarningKind, WarningLevel};
 
 use crate::{
     ast::SrcSpan, build::Target, line_numbers::LineNumbers, manifest::Manifest,
     paths::ProjectPaths, Result,
 };
-use lsp_types::{Position, Range};
+use camino::Utf8PathBuf;
+use lsp_types::{Position, Range, Url};
 use std::any::Any;
 
 #[derive(Debug)]
@@ -43,3 +98,9 @@ pub fn src_span_to_lsp_range(location: SrcSpan, line_numbers: &LineNumbers) -> R
         Position::new(end.line - 1, end.column - 1),
     )
 }
+
+pub(crate) fn path_to_uri(path: Utf8PathBuf) -> Url {
+    let mut file: String = "file://".into();
+    file.push_str(&path.as_os_str().to_string_lossy());
+    Url::parse(&file).expect("path_to_uri URL parse")
+}
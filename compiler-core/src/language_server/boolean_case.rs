@@ -0,0 +1,220 @@
+use crate::{
+    ast::{Pattern, SrcSpan, TypedClause, TypedExpr},
+    language_server::{
+        compiler::LspProjectCompiler,
+        desugar::line_indent,
+        pipe::full_span,
+        src_span_to_lsp_range,
+        visit::{walk_module, Visit},
+    },
+    line_numbers::LineNumbers,
+    type_::{ValueConstructorVariant, PRELUDE_MODULE_NAME},
+};
+use lsp_types::TextEdit;
+
+impl<IO> LspProjectCompiler<IO> {
+    /// Rewrite the innermost `case` over a single `Bool` that contains the
+    /// byte index into its simplest form.
+    ///
+    /// A case that gives the value it matches on, or its opposite, becomes
+    /// the value or its negation. Otherwise the case has a `True` clause and
+    /// then a `False` clause, with a `_` standing for whichever of the two
+    /// it is reached for, and any clause after them removed as it can never
+    /// match. A clause that is itself a case over a local `Bool` variable is
+    /// written as the two clauses it stands for, the first with the variable
+    /// as its guard.
+    ///
+    /// ```gleam
+    /// case is_admin {
+    ///   False -> "guest"
+    ///   _ -> case verified {
+    ///     True -> "admin"
+    ///     False -> "pending"
+    ///   }
+    /// }
+    /// ```
+    ///
+    /// becomes
+    ///
+    /// ```gleam
+    /// case is_admin {
+    ///   True if verified -> "admin"
+    ///   True -> "pending"
+    ///   False -> "guest"
+    /// }
+    /// ```
+    ///
+    /// Only cases whose clauses match `True`, `False` or `_`, without their
+    /// own guards, are rewritten, and `None` is returned if the case is
+    /// already in this form. The module is searched as it was in the last
+    /// compilation in which it compiled.
+    pub fn simplify_boolean_case(&self, module: &str, byte_index: u32) -> Option<TextEdit> {
+        let module = self.modules.get(module)?;
+        let code = module.code.as_str();
+        let mut finder = BooleanCaseFinder {
+            byte_index,
+            found: None,
+        };
+        walk_module(&module.ast, &mut finder);
+        let (location, subject, (if_true, if_false)) = finder.found?;
+
+        let subject_text = text(code, full_span(subject));
+        let new_text = match (bool_value(if_true), bool_value(if_false)) {
+            (Some(true), Some(false)) => subject_text.to_string(),
+            (Some(false), Some(true)) => negate(subject, subject_text),
+            _ => {
+                let indent = line_indent(code, location.start);
+                let clauses = [("True", if_true), ("False", if_false)]
+                    .into_iter()
+                    .flat_map(|(pattern, then)| clauses(code, pattern, then))
+                    .map(|clause| format!("{indent}  {clause}\n"))
+                    .collect::<String>();
+                format!("case {subject_text} {{\n{clauses}{indent}}}")
+            }
+        };
+
+        if text(code, location) == new_text {
+            return None;
+        }
+        let line_numbers = LineNumbers::new(code);
+        Some(TextEdit {
+            range: src_span_to_lsp_range(location, &line_numbers),
+            new_text,
+        })
+    }
+}
+
+/// The clauses for matching the pattern, with the code they run. If the code
+/// is a case over a local variable it is replaced by a clause guarded by the
+/// variable and a clause without a guard.
+fn clauses(code: &str, pattern: &str, then: &TypedExpr) -> Vec<String> {
+    if let TypedExpr::Case {
+        subjects, clauses, ..
+    } = then
+    {
+        if let (
+            [subject @ TypedExpr::Var {
+                name, constructor, ..
+            }],
+            Some((if_true, if_false)),
+        ) = (subjects.as_slice(), boolean_branches(clauses))
+        {
+            if subject.type_().is_bool()
+                && matches!(
+                    constructor.variant,
+                    ValueConstructorVariant::LocalVariable { .. }
+                )
+            {
+                return vec![
+                    format!("{pattern} if {name} -> {}", text(code, full_span(if_true))),
+                    format!("{pattern} -> {}", text(code, full_span(if_false))),
+                ];
+            }
+        }
+    }
+    vec![format!("{pattern} -> {}", text(code, full_span(then)))]
+}
+
+/// The code run when the subject of a case over a `Bool` is `True` and when
+/// it is `False`. Cases with guards, alternative patterns, or patterns other
+/// than `True`, `False` and `_` are not considered, and nor are cases with a
+/// single clause for both values, as they have nothing to simplify.
+fn boolean_branches(clauses: &[TypedClause]) -> Option<(&TypedExpr, &TypedExpr)> {
+    let mut if_true = None;
+    let mut if_false = None;
+    for (index, clause) in clauses.iter().enumerate() {
+        if clause.guard.is_some() || !clause.alternative_patterns.is_empty() {
+            return None;
+        }
+        let (matches_true, matches_false) = match clause.pattern.as_slice() {
+            [Pattern::Discard { .. }] => (true, true),
+            [Pattern::Constructor {
+                name, arguments, ..
+            }] if arguments.is_empty() => match name.as_str() {
+                "True" => (true, false),
+                "False" => (false, true),
+                _ => return None,
+            },
+            _ => return None,
+        };
+        if matches_true && if_true.is_none() {
+            if_true = Some((index, &clause.then));
+        }
+        if matches_false && if_false.is_none() {
+            if_false = Some((index, &clause.then));
+        }
+    }
+    match (if_true, if_false) {
+        (Some((true_index, if_true)), Some((false_index, if_false))) => {
+            (true_index != false_index).then_some((if_true, if_false))
+        }
+        _ => None,
+    }
+}
+
+/// Whether the expression is the `True` or `False` of the prelude.
+fn bool_value(expr: &TypedExpr) -> Option<bool> {
+    let TypedExpr::Var { constructor, .. } = expr else {
+        return None;
+    };
+    match &constructor.variant {
+        ValueConstructorVariant::Record { name, module, .. } if module == PRELUDE_MODULE_NAME => {
+            match name.as_str() {
+                "True" => Some(true),
+                "False" => Some(false),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+/// The code of the negation of the expression, in a block if `!` would
+/// otherwise apply only to the start of it.
+fn negate(expr: &TypedExpr, expr_text: &str) -> String {
+    match expr {
+        TypedExpr::Var { .. }
+        | TypedExpr::Call { .. }
+        | TypedExpr::RecordAccess { .. }
+        | TypedExpr::ModuleSelect { .. }
+        | TypedExpr::TupleIndex { .. }
+        | TypedExpr::Block { .. }
+        | TypedExpr::NegateBool { .. } => format!("!{expr_text}"),
+        _ => format!("!{{ {expr_text} }}"),
+    }
+}
+
+fn text(code: &str, span: SrcSpan) -> &str {
+    code.get(span.start as usize..span.end as usize)
+        .unwrap_or_default()
+}
+
+/// Finds the innermost case over a single `Bool` containing the byte index,
+/// with the code it runs for `True` and for `False`.
+struct BooleanCaseFinder<'ast> {
+    byte_index: u32,
+    found: Option<(SrcSpan, &'ast TypedExpr, (&'ast TypedExpr, &'ast TypedExpr))>,
+}
+
+impl<'ast> Visit<'ast> for BooleanCaseFinder<'ast> {
+    fn visit_expr(&mut self, expr: &'ast TypedExpr) {
+        let TypedExpr::Case {
+            location,
+            subjects,
+            clauses,
+            ..
+        } = expr
+        else {
+            return;
+        };
+        // Cases are visited before the cases within them.
+        if !location.contains(self.byte_index) {
+            return;
+        }
+        if let ([subject], Some(branches)) = (subjects.as_slice(), boolean_branches(clauses)) {
+            if subject.type_().is_bool() {
+                self.found = Some((*location, subject, branches));
+            }
+        }
+    }
+}
@@ -0,0 +1,451 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::{
+    ast::{
+        CallArg, Definition, SrcSpan, Statement, TypedExpr, UntypedExpr, UntypedModule,
+        UntypedStatement,
+    },
+    io::{CommandExecutor, FileSystemReader, FileSystemWriter},
+    language_server::{
+        compiler::{LspProjectCompiler, ModuleTypeError},
+        src_span_to_lsp_range,
+        visit::{walk_module, Visit},
+    },
+    line_numbers::LineNumbers,
+    type_::{self, FieldMap, ModuleValueConstructor, TypedCallArg},
+};
+use itertools::Itertools;
+use lsp_types::TextEdit;
+
+impl<IO> LspProjectCompiler<IO>
+where
+    IO: CommandExecutor + FileSystemWriter + FileSystemReader + Clone,
+{
+    /// Rewrite the arguments of the innermost call containing the byte index
+    /// so that each argument the function has a label for is given with it,
+    /// in the order the function declares them.
+    ///
+    /// Arguments are evaluated in the order they are written, so they are
+    /// only reordered if at most one of them could have an effect, such as a
+    /// call. Otherwise they are labelled but kept in the order they are. A
+    /// call is not rewritten if it is already in this form, if it has
+    /// arguments added by a pipe or `use`, or if there are comments between
+    /// its arguments, as they would be lost.
+    ///
+    /// Only modules that compiled in the most recent compilation can be
+    /// rewritten.
+    pub fn normalize_call_labels(&self, module: &str, byte_index: u32) -> Option<TextEdit> {
+        let compiled = self.current_module(module)?;
+        let mut finder = CallFinder {
+            byte_index,
+            found: None,
+        };
+        walk_module(&compiled.ast, &mut finder);
+        let (fun, args) = finder.found?;
+        if args.is_empty() || args.iter().any(|arg| arg.implicit) {
+            return None;
+        }
+        let labels: HashMap<_, _> = self
+            .call_field_map(fun)?
+            .fields
+            .iter()
+            .map(|(label, index)| (*index as usize, label))
+            .collect();
+
+        // The typed arguments are in the order the function declares them.
+        let code = compiled.code.as_str();
+        let arguments = args
+            .iter()
+            .enumerate()
+            .map(|(index, arg)| {
+                let value = argument_value(code, arg)?;
+                let written = match labels.get(&index) {
+                    Some(label) => format!("{label}: {value}"),
+                    None => value.to_string(),
+                };
+                Some((arg, written))
+            })
+            .collect::<Option<Vec<_>>>()?;
+        let in_source_order = arguments
+            .iter()
+            .sorted_by_key(|(arg, _)| arg.location.start)
+            .collect_vec();
+
+        let (first, last) = (in_source_order.first()?.0, in_source_order.last()?.0);
+        let between_arguments = in_source_order.iter().tuple_windows().all(|(one, other)| {
+            code.get(one.0.location.end as usize..other.0.location.start as usize)
+                .is_some_and(|between| between.trim() == ",")
+        });
+        if !between_arguments {
+            return None;
+        }
+
+        let with_effects = arguments
+            .iter()
+            .filter(|(arg, _)| !is_free_of_effects(&arg.value))
+            .count();
+        let new_text = if with_effects <= 1 {
+            arguments.iter().map(|(_, written)| written).join(", ")
+        } else {
+            in_source_order
+                .iter()
+                .map(|(_, written)| written)
+                .join(", ")
+        };
+
+        let span = SrcSpan::new(first.location.start, last.location.end);
+        if code.get(span.start as usize..span.end as usize) == Some(new_text.as_str()) {
+            return None;
+        }
+        let line_numbers = LineNumbers::new(code);
+        Some(TextEdit {
+            range: src_span_to_lsp_range(span, &line_numbers),
+            new_text,
+        })
+    }
+
+    /// Add a `todo` for each argument missing from the call at the byte index
+    /// that stopped the module compiling, written with its label if the
+    /// function has one for it. Arguments without labels are added after the
+    /// other arguments without labels, and labelled ones at the end of the
+    /// call. A `todo` cannot be given a type, but it takes the type of the
+    /// argument it is given as.
+    ///
+    /// The labels of functions are known for those of this module, as they
+    /// were in the last compilation in which it compiled, and for those it
+    /// imports. Calls given their last argument by `use` are not changed.
+    pub fn add_missing_argument_fix(&self, module: &str, byte_index: u32) -> Option<TextEdit> {
+        let Some(ModuleTypeError {
+            src,
+            error:
+                type_::Error::IncorrectArity {
+                    location,
+                    expected,
+                    given,
+                    ..
+                },
+            ..
+        }) = self.type_error(module)
+        else {
+            return None;
+        };
+        if given >= expected || !location.contains(byte_index) {
+            return None;
+        }
+        let untyped = self.untyped_module(module)?;
+        let (fun, arguments, piped) = untyped
+            .iter_statements(self.project_compiler.target())
+            .find_map(|definition| match definition {
+                Definition::Function(function) => function
+                    .body
+                    .iter()
+                    .find_map(|statement| find_call_in_statement(statement, *location)),
+                _ => None,
+            })?;
+        if arguments.len() != *given {
+            return None;
+        }
+
+        let fields = self
+            .untyped_field_map(module, untyped, fun)
+            .map(|field_map| &field_map.fields);
+        let mut given_by_label = HashSet::new();
+        for label in arguments.iter().filter_map(|arg| arg.label.as_ref()) {
+            _ = given_by_label.insert(*fields?.get(label)?);
+        }
+        let labels: HashMap<_, _> = fields
+            .into_iter()
+            .flatten()
+            .map(|(label, index)| (*index, label))
+            .collect();
+        // Arguments without labels are given in order to the parameters that
+        // are not given by label, starting with the one given by a pipe.
+        let positional =
+            arguments.iter().filter(|arg| arg.label.is_none()).count() + piped as usize;
+        let (labelled, unlabelled): (Vec<_>, Vec<_>) = (0..*expected as u32)
+            .filter(|index| !given_by_label.contains(index))
+            .skip(positional)
+            .partition(|index| labels.contains_key(index));
+        let unlabelled = unlabelled.iter().map(|_| "todo").join(", ");
+        let labelled = labelled
+            .iter()
+            .filter_map(|index| Some(format!("{}: todo", labels.get(index)?)))
+            .join(", ");
+
+        let mut insertions = vec![];
+        match arguments.last() {
+            None => {
+                let end = fun.location().end;
+                let open = end + src.get(end as usize..)?.find('(')? as u32;
+                let all = [unlabelled, labelled]
+                    .into_iter()
+                    .filter(|text| !text.is_empty())
+                    .join(", ");
+                insertions.push((open + 1, all));
+            }
+            Some(last) => {
+                if !unlabelled.is_empty() {
+                    insertions.push(match arguments.iter().find(|arg| arg.label.is_some()) {
+                        Some(first_labelled) => {
+                            (first_labelled.location.start, format!("{unlabelled}, "))
+                        }
+                        None => (last.location.end, format!(", {unlabelled}")),
+                    });
+                }
+                if !labelled.is_empty() {
+                    insertions.push((last.location.end, format!(", {labelled}")));
+                }
+            }
+        }
+
+        // The arguments between the insertions are kept as they are written.
+        let (start, first) = insertions.first()?;
+        let (end, last) = insertions.last()?;
+        let new_text = if insertions.len() == 1 {
+            first.clone()
+        } else {
+            format!("{first}{}{last}", src.get(*start as usize..*end as usize)?)
+        };
+        let line_numbers = LineNumbers::new(src);
+        Some(TextEdit {
+            range: src_span_to_lsp_range(SrcSpan::new(*start, *end), &line_numbers),
+            new_text,
+        })
+    }
+
+    /// The labels of the function being called in an untyped module, if it is
+    /// a function or record constructor of the module or one it imports.
+    fn untyped_field_map(
+        &self,
+        module: &str,
+        untyped: &UntypedModule,
+        fun: &UntypedExpr,
+    ) -> Option<&FieldMap> {
+        let imports = untyped
+            .iter_statements(self.project_compiler.target())
+            .filter_map(|definition| match definition {
+                Definition::Import(import) => Some(import),
+                _ => None,
+            });
+        let value = match fun {
+            UntypedExpr::Var { name, .. } => {
+                match self
+                    .modules
+                    .get(module)
+                    .and_then(|compiled| compiled.ast.type_info.values.get(name))
+                {
+                    Some(value) => value,
+                    None => imports
+                        .flat_map(|import| {
+                            import
+                                .unqualified_values
+                                .iter()
+                                .map(move |value| (import, value))
+                        })
+                        .find(|(_, value)| value.used_name() == name)
+                        .and_then(|(import, value)| {
+                            self.get_module_inferface(&import.module)?
+                                .values
+                                .get(&value.name)
+                        })?,
+                }
+            }
+            UntypedExpr::FieldAccess {
+                label, container, ..
+            } => {
+                let UntypedExpr::Var { name, .. } = container.as_ref() else {
+                    return None;
+                };
+                let import = imports
+                    .into_iter()
+                    .find(|import| import.used_name().as_ref() == Some(name))?;
+                self.get_module_inferface(&import.module)?
+                    .values
+                    .get(label)?
+            }
+            _ => return None,
+        };
+        value.field_map()
+    }
+
+    /// The labels of the arguments of the function being called, if it is a
+    /// module function or a record constructor.
+    fn call_field_map<'a>(&'a self, fun: &'a TypedExpr) -> Option<&'a FieldMap> {
+        match fun {
+            TypedExpr::Var { constructor, .. } => constructor.field_map(),
+            TypedExpr::ModuleSelect {
+                constructor: ModuleValueConstructor::Record { field_map, .. },
+                ..
+            } => field_map.as_ref(),
+            TypedExpr::ModuleSelect {
+                module_name,
+                label,
+                constructor: ModuleValueConstructor::Fn { .. },
+                ..
+            } => self
+                .get_module_inferface(module_name)?
+                .values
+                .get(label)?
+                .field_map(),
+            _ => None,
+        }
+    }
+}
+
+/// The value of an argument as it is written, without its label.
+fn argument_value<'a>(code: &'a str, arg: &TypedCallArg) -> Option<&'a str> {
+    let text = code.get(arg.location.start as usize..arg.location.end as usize)?;
+    match &arg.label {
+        Some(label) => Some(
+            text.strip_prefix(label.as_str())?
+                .trim_start()
+                .strip_prefix(':')?
+                .trim_start(),
+        ),
+        None => Some(text),
+    }
+}
+
+/// Whether evaluating the expression cannot do anything other than give its
+/// value, so it can be moved before or after other arguments.
+fn is_free_of_effects(expr: &TypedExpr) -> bool {
+    match expr {
+        TypedExpr::Int { .. }
+        | TypedExpr::Float { .. }
+        | TypedExpr::String { .. }
+        | TypedExpr::Var { .. }
+        | TypedExpr::Fn { .. }
+        | TypedExpr::ModuleSelect { .. } => true,
+        TypedExpr::Tuple { elems, .. } => elems.iter().all(is_free_of_effects),
+        TypedExpr::List { elements, tail, .. } => {
+            elements.iter().all(is_free_of_effects)
+                && tail.as_deref().into_iter().all(is_free_of_effects)
+        }
+        _ => false,
+    }
+}
+
+/// Finds the innermost call containing the byte index.
+struct CallFinder<'ast> {
+    byte_index: u32,
+    found: Option<(&'ast TypedExpr, &'ast [TypedCallArg])>,
+}
+
+impl<'ast> Visit<'ast> for CallFinder<'ast> {
+    fn visit_expr(&mut self, expr: &'ast TypedExpr) {
+        if let TypedExpr::Call {
+            location,
+            fun,
+            args,
+            ..
+        } = expr
+        {
+            // Calls are visited before the calls within them.
+            if location.contains(self.byte_index) {
+                self.found = Some((fun, args));
+            }
+        }
+    }
+}
+
+/// The function and arguments of a call, and whether a pipe gives it its
+/// first argument.
+type UntypedCall<'a> = (&'a UntypedExpr, &'a [CallArg<UntypedExpr>], bool);
+
+fn find_call_in_statement(
+    statement: &UntypedStatement,
+    location: SrcSpan,
+) -> Option<UntypedCall<'_>> {
+    match statement {
+        Statement::Expression(expr) => find_call(expr, location),
+        Statement::Assignment(assignment) => find_call(&assignment.value, location),
+        Statement::Use(use_) => find_call(&use_.call, location),
+    }
+}
+
+fn find_call_in<'a>(
+    exprs: impl IntoIterator<Item = &'a UntypedExpr>,
+    location: SrcSpan,
+) -> Option<UntypedCall<'a>> {
+    exprs.into_iter().find_map(|expr| find_call(expr, location))
+}
+
+/// Finds the call with the location within the expression.
+fn find_call(expr: &UntypedExpr, location: SrcSpan) -> Option<UntypedCall<'_>> {
+    if !expr.location().contains(location.start) {
+        return None;
+    }
+    match expr {
+        UntypedExpr::Call {
+            location: call_location,
+            fun,
+            arguments,
+        } if *call_location == location => Some((fun, arguments, false)),
+        UntypedExpr::Call { fun, arguments, .. } => find_call_in(
+            std::iter::once(fun.as_ref()).chain(arguments.iter().map(|arg| &arg.value)),
+            location,
+        ),
+        UntypedExpr::Block { statements, .. } => statements
+            .iter()
+            .find_map(|statement| find_call_in_statement(statement, location)),
+        UntypedExpr::Fn { body, .. } => body
+            .iter()
+            .find_map(|statement| find_call_in_statement(statement, location)),
+        UntypedExpr::List { elements, tail, .. } => {
+            find_call_in(elements.iter().chain(tail.as_deref()), location)
+        }
+        UntypedExpr::BinOp { left, right, .. } => find_call_in(
+            [left, right].into_iter().map(|expr| expr.as_ref()),
+            location,
+        ),
+        UntypedExpr::PipeLine { expressions } => expressions
+            .iter()
+            .skip(1)
+            .find_map(|expr| match expr {
+                UntypedExpr::Call {
+                    location: call_location,
+                    fun,
+                    arguments,
+                } if *call_location == location => Some((fun.as_ref(), arguments.as_slice(), true)),
+                _ => None,
+            })
+            .or_else(|| find_call_in(expressions.iter(), location)),
+        UntypedExpr::Case {
+            subjects, clauses, ..
+        } => find_call_in(
+            subjects
+                .iter()
+                .chain(clauses.iter().map(|clause| &clause.then)),
+            location,
+        ),
+        UntypedExpr::FieldAccess { container, .. } => find_call(container, location),
+        UntypedExpr::Tuple { elems, .. } => find_call_in(elems.iter(), location),
+        UntypedExpr::TupleIndex { tuple, .. } => find_call(tuple, location),
+        UntypedExpr::Todo { message, .. } | UntypedExpr::Panic { message, .. } => {
+            find_call_in(message.as_deref(), location)
+        }
+        UntypedExpr::BitArray { segments, .. } => find_call_in(
+            segments.iter().map(|segment| segment.value.as_ref()),
+            location,
+        ),
+        UntypedExpr::RecordUpdate {
+            constructor,
+            spread,
+            arguments,
+            ..
+        } => find_call_in(
+            [constructor.as_ref(), spread.base.as_ref()]
+                .into_iter()
+                .chain(arguments.iter().map(|arg| &arg.value)),
+            location,
+        ),
+        UntypedExpr::NegateBool { value, .. } | UntypedExpr::NegateInt { value, .. } => {
+            find_call(value, location)
+        }
+        UntypedExpr::Int { .. }
+        | UntypedExpr::Float { .. }
+        | UntypedExpr::String { .. }
+        | UntypedExpr::Var { .. }
+        | UntypedExpr::Placeholder { .. } => None,
+    }
+}
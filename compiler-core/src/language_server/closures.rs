@@ -0,0 +1,191 @@
+use crate::{
+    ast::{ClauseGuard, Pattern, SrcSpan, TypedClauseGuard, TypedExpr, TypedPattern},
+    language_server::{
+        compiler::LspProjectCompiler,
+        visit::{walk_module, Visit},
+    },
+    type_::{pretty::Printer, Type, ValueConstructor, ValueConstructorVariant},
+};
+use ecow::EcoString;
+use std::sync::Arc;
+
+impl<IO> LspProjectCompiler<IO> {
+    /// The location and type of each anonymous function written with `fn` in
+    /// the module, in the order they appear, with the type printed as it
+    /// would be written in Gleam. Function captures such as `add(_, 1)` and
+    /// the callbacks of `use` expressions are not included, as they are not
+    /// written as functions.
+    ///
+    /// The module is searched as it was in the last compilation in which it
+    /// compiled.
+    pub fn anonymous_functions(&self, module: &str) -> Vec<(SrcSpan, String)> {
+        let Some(module) = self.modules.get(module) else {
+            return vec![];
+        };
+        let mut finder = AnonymousFunctionFinder {
+            code: &module.code,
+            found: vec![],
+        };
+        walk_module(&module.ast, &mut finder);
+        finder.found.sort_by_key(|(location, _)| location.start);
+        finder.found
+    }
+
+    /// The local variables used within the span of the module that are
+    /// defined outside of it, in the order they are first used, each with
+    /// its type printed as it would be written in Gleam. These are what a
+    /// function made from the code in the span would need to be given as
+    /// arguments.
+    ///
+    /// Variables defined within the span are not included, and nor are the
+    /// functions, constants and record constructors of this or any other
+    /// module, as a new function can refer to them itself.
+    ///
+    /// The module is searched as it was in the last compilation in which it
+    /// compiled.
+    pub fn captured_variables(&self, module: &str, range: SrcSpan) -> Vec<(EcoString, String)> {
+        let Some(module) = self.modules.get(module) else {
+            return vec![];
+        };
+        let mut finder = CapturedVariableFinder {
+            range,
+            bound: vec![],
+            used: vec![],
+        };
+        walk_module(&module.ast, &mut finder);
+
+        let mut printer = Printer::new();
+        let mut captured: Vec<(EcoString, String)> = vec![];
+        finder.used.sort_by_key(|usage| usage.location.start);
+        for usage in finder.used {
+            let bound_within = match usage.definition {
+                Some(definition) => range.contains(definition.start),
+                // Variables in guards do not record where they are defined,
+                // but they can only be defined by the pattern of their clause
+                // or before the case.
+                None => finder.bound.iter().any(|(name, location)| {
+                    *name == usage.name && location.start < usage.location.start
+                }),
+            };
+            if !bound_within && !captured.iter().any(|(name, _)| *name == *usage.name) {
+                let type_ = printer.pretty_print(&usage.type_, 0);
+                captured.push((usage.name.clone(), type_));
+            }
+        }
+        captured
+    }
+}
+
+struct AnonymousFunctionFinder<'a> {
+    code: &'a str,
+    found: Vec<(SrcSpan, String)>,
+}
+
+impl<'ast> Visit<'ast> for AnonymousFunctionFinder<'_> {
+    fn visit_expr(&mut self, expr: &'ast TypedExpr) {
+        if let TypedExpr::Fn {
+            location,
+            typ,
+            is_capture: false,
+            ..
+        } = expr
+        {
+            // The callback of a `use` has the location of the `use`.
+            let code = self.code.get(location.start as usize..).unwrap_or_default();
+            if code.starts_with("fn") {
+                let type_ = Printer::new().pretty_print(typ, 0);
+                self.found.push((*location, type_));
+            }
+        }
+    }
+}
+
+struct LocalUsage<'ast> {
+    name: &'ast EcoString,
+    location: SrcSpan,
+    definition: Option<SrcSpan>,
+    type_: Arc<Type>,
+}
+
+/// Finds the local variables used within a span and those defined within it.
+struct CapturedVariableFinder<'ast> {
+    range: SrcSpan,
+    bound: Vec<(&'ast EcoString, SrcSpan)>,
+    used: Vec<LocalUsage<'ast>>,
+}
+
+impl<'ast> CapturedVariableFinder<'ast> {
+    fn contains(&self, location: SrcSpan) -> bool {
+        self.range.start <= location.start && location.end <= self.range.end
+    }
+
+    fn use_variable(
+        &mut self,
+        name: &'ast EcoString,
+        location: SrcSpan,
+        constructor: &ValueConstructor,
+    ) {
+        if !self.contains(location) {
+            return;
+        }
+        if let ValueConstructorVariant::LocalVariable {
+            location: definition,
+        } = &constructor.variant
+        {
+            self.used.push(LocalUsage {
+                name,
+                location,
+                definition: Some(*definition),
+                type_: constructor.type_.clone(),
+            });
+        }
+    }
+}
+
+impl<'ast> Visit<'ast> for CapturedVariableFinder<'ast> {
+    fn visit_expr(&mut self, expr: &'ast TypedExpr) {
+        if let TypedExpr::Var {
+            location,
+            constructor,
+            name,
+        } = expr
+        {
+            self.use_variable(name, *location, constructor);
+        }
+    }
+
+    fn visit_pattern(&mut self, pattern: &'ast TypedPattern) {
+        match pattern {
+            Pattern::Variable { name, location, .. } | Pattern::Assign { name, location, .. }
+                if self.contains(*location) =>
+            {
+                self.bound.push((name, *location));
+            }
+            Pattern::VarUsage {
+                name,
+                location,
+                constructor: Some(constructor),
+                ..
+            } => self.use_variable(name, *location, constructor),
+            _ => (),
+        }
+    }
+
+    fn visit_clause_guard(&mut self, guard: &'ast TypedClauseGuard) {
+        if let ClauseGuard::Var {
+            location,
+            type_,
+            name,
+        } = guard
+        {
+            if self.contains(*location) {
+                self.used.push(LocalUsage {
+                    name,
+                    location: *location,
+                    definition: None,
+                    type_: type_.clone(),
+                });
+            }
+        }
+    }
+}
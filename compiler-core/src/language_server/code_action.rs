@@ -36,6 +36,11 @@ impl CodeActionBuilder {
         self
     }
 
+    pub fn command(mut self, command: lsp_types::Command) -> Self {
+        self.action.command = Some(command);
+        self
+    }
+
     pub fn preferred(mut self, is_preferred: bool) -> Self {
         self.action.is_preferred = Some(is_preferred);
         self
@@ -4,20 +4,38 @@ use itertools::Itertools;
 
 use crate::{
     analyse::TargetSupport,
-    build::{self, Mode, Module, NullTelemetry, ProjectCompiler},
+    ast::{Definition, SrcSpan, TypedExpr, UntypedModule},
+    build::{
+        self,
+        package_compiler::{module_name, ModuleObserver, UncompiledModule},
+        Mode, Module, NullTelemetry, Origin, ProjectCompiler,
+    },
     config::PackageConfig,
     io::{CommandExecutor, FileSystemReader, FileSystemWriter, Stdio},
-    language_server::Locker,
+    language_server::{
+        lsp_warning::{LspWarning, LspWarningKind},
+        settings::LspSettings,
+        shadowing::emit_shadowing_warnings,
+        visit::{walk_module, Visit},
+        Locker, TraceLevel, WarningKind, WarningLevel,
+    },
     line_numbers::LineNumbers,
     manifest::Manifest,
     paths::ProjectPaths,
-    type_::ModuleInterface,
+    type_::{self, pretty::Printer, ModuleInterface, Type, TypeVar},
     warning::VectorWarningEmitterIO,
     Error, Result, Warning,
 };
-use std::{collections::HashMap, sync::Arc};
+use std::{
+    cell::RefCell,
+    collections::{HashMap, HashSet},
+    ops::Deref,
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
-use camino::Utf8PathBuf;
+use camino::{Utf8Path, Utf8PathBuf};
+use lsp_types::CodeLens;
 
 /// A wrapper around the project compiler which makes it possible to repeatedly
 /// recompile the top level package, reusing the information about the already
@@ -31,24 +49,83 @@ pub struct LspProjectCompiler<IO> {
     pub modules: HashMap<EcoString, Module>,
     pub sources: HashMap<EcoString, ModuleSourceInformation>,
 
+    /// The most recent parse of each module, kept even if the module then
+    /// failed to type check.
+    pub untyped_modules: HashMap<EcoString, UntypedModule>,
+
+    /// The modules that did not compile in the most recent compilation.
+    pub(super) failed_modules: Vec<EcoString>,
+
+    /// The root package modules that were type checked in the most recent
+    /// compilation, in the order they were type checked.
+    pub(super) compile_order: Vec<EcoString>,
+
+    /// The warnings for case clauses that can never match, for each module
+    /// of the root package that has been type checked.
+    pub(super) redundant_clauses: HashMap<EcoString, Vec<Warning>>,
+
+    /// The type error of the module that failed to type check in the most
+    /// recent compilation.
+    pub(super) type_errors: HashMap<EcoString, ModuleTypeError>,
+
+    /// Dependency modules that have been type checked again so that their
+    /// source can be navigated.
+    pub dependency_modules: HashMap<EcoString, Module>,
+
     /// The storage for the warning emitter.
     pub warnings: Arc<VectorWarningEmitterIO>,
 
+    /// The warnings that only the language server reports, found since they
+    /// were last taken.
+    pub(super) lsp_warnings: Vec<LspWarning>,
+
     /// A lock to ensure that multiple instances of the LSP don't try and use
     /// build directory at the same time.
     pub locker: DebugIgnore<Box<dyn Locker>>,
+
+    /// The user's settings, which may change while the server is running.
+    pub settings: LspSettings,
+
+    /// How much is logged about each compilation.
+    trace_level: TraceLevel,
+
+    /// How long each root package module took to type check in the most
+    /// recent compilation, recorded only at the verbose trace level.
+    module_timings: HashMap<EcoString, Duration>,
+
+    /// Incremented each time the modules can change, so that information
+    /// cached about them can be discarded.
+    compile_generation: u64,
+
+    /// The public names of each module that have been asked for, with the
+    /// generation they were found in.
+    export_names: RefCell<HashMap<EcoString, (u64, Vec<EcoString>)>>,
+
+    /// The reference count lenses of each module that have been asked for,
+    /// with the generation they were counted in.
+    pub(super) reference_lenses: RefCell<HashMap<EcoString, (u64, Vec<CodeLens>)>>,
+
+    /// Whether the most recent compilation reused the previously compiled
+    /// modules of every dependency package.
+    dependencies_were_cached: bool,
 }
 
 impl<IO> LspProjectCompiler<IO>
 where
     IO: CommandExecutor + FileSystemWriter + FileSystemReader + Clone,
 {
+    /// `source_directories` are directories of the project, relative to its
+    /// root, that contain modules in addition to the `src` directory, such as
+    /// a directory of generated code. The warning level is used until the
+    /// settings sent by the client replace it.
     pub fn new(
         manifest: Manifest,
         config: PackageConfig,
         paths: ProjectPaths,
         io: IO,
         locker: Box<dyn Locker>,
+        source_directories: Vec<Utf8PathBuf>,
+        warning_level: WarningLevel,
     ) -> Result<Self> {
         let telemetry = NullTelemetry;
         let target = config.target;
@@ -85,26 +162,103 @@ where
         // To avoid the Erlang compiler printing to stdout (and thus
         // violating LSP which is currently using stdout) we silence it.
         project_compiler.subprocess_stdio = Stdio::Null;
+        project_compiler.root_source_directories = source_directories;
 
         Ok(Self {
             locker: locker.into(),
             warnings,
+            lsp_warnings: Vec::new(),
             project_compiler,
             modules: HashMap::new(),
             sources: HashMap::new(),
+            untyped_modules: HashMap::new(),
+            failed_modules: vec![],
+            compile_order: vec![],
+            type_errors: HashMap::new(),
+            redundant_clauses: HashMap::new(),
+            dependency_modules: HashMap::new(),
+            settings: LspSettings {
+                warning_level,
+                ..Default::default()
+            },
+            trace_level: TraceLevel::Off,
+            module_timings: HashMap::new(),
+            compile_generation: 0,
+            dependencies_were_cached: false,
+            export_names: RefCell::new(HashMap::new()),
+            reference_lenses: RefCell::new(HashMap::new()),
         })
     }
 
+    /// Change how much is logged about each compilation. This takes effect
+    /// from the next compilation, without compiling again.
+    pub fn set_trace_level(&mut self, level: TraceLevel) {
+        self.trace_level = level;
+    }
+
+    /// How much is logged about each compilation.
+    pub fn trace_level(&self) -> TraceLevel {
+        self.trace_level
+    }
+
+    /// How long each root package module took to type check in the most
+    /// recent compilation. Timings are only recorded while the trace level is
+    /// verbose, and modules that were not type checked again because they
+    /// had not changed have no timing.
+    pub fn last_module_timings(&self) -> HashMap<EcoString, Duration> {
+        self.module_timings.clone()
+    }
+
+    /// Whether the most recent compilation reused the modules of the
+    /// dependency packages from an earlier compilation, rather than having to
+    /// type check any of them. This is `false` before the first compilation
+    /// and if the dependencies could not be compiled.
+    pub fn dependencies_were_cached(&self) -> bool {
+        self.dependencies_were_cached
+    }
+
+    /// Compile the dependency packages ahead of the first compilation of the
+    /// root package, such as while the editor is starting, calling
+    /// `on_progress` with the number of packages compiled so far and the
+    /// total so that the progress can be shown. The next compilation then
+    /// loads the dependencies from the cache.
+    ///
+    /// The warnings of the dependencies are discarded, as they are not
+    /// something the programmer can fix.
+    pub fn warm_dependencies(&mut self, on_progress: impl Fn(usize, usize)) -> Result<()> {
+        let _lock_guard = self.locker.lock_for_build();
+        self.project_compiler.check_gleam_version()?;
+        let result = self
+            .project_compiler
+            .compile_dependencies_with_progress(on_progress);
+        let _ = self.take_warnings();
+        result.map(|_| ())
+    }
+
     pub fn compile(&mut self) -> Result<Vec<Utf8PathBuf>, Error> {
+        self.compile_streaming(|_| ())
+    }
+
+    /// Compile the project, calling `on_module` with each root package module
+    /// as soon as it has finished compiling so that its feedback can be
+    /// published without waiting for the rest of the package.
+    pub fn compile_streaming(
+        &mut self,
+        on_module: impl FnMut(&Module),
+    ) -> Result<Vec<Utf8PathBuf>, Error> {
         // Lock the build directory to ensure to ensure we are the only one compiling
         let _lock_guard = self.locker.lock_for_build();
+        self.failed_modules.clear();
+        self.advance_generation();
 
         // Verify that the build directory was created using the same version of
         // Gleam as we are running. If it is not then we discard the build
         // directory as the cache files may be in a different format.
         self.project_compiler.check_gleam_version()?;
 
+        self.dependencies_were_cached = false;
         let compiled_dependencies = self.project_compiler.compile_dependencies()?;
+        self.dependencies_were_cached = compiled_dependencies.is_empty();
 
         // Warnings from dependencies are not fixable by the programmer so
         // we don't bother them with diagnostics for them.
@@ -113,7 +267,65 @@ where
         // Do that there compilation. We don't use `?` to return early in the
         // event of an error because we _always_ want to do the restoration of
         // state afterwards.
-        let result = self.project_compiler.compile_root_package();
+        let mut observer = Observer {
+            on_module,
+            untyped_modules: &mut self.untyped_modules,
+            unfinished: vec![],
+            compiled: vec![],
+            record_timings: self.trace_level >= TraceLevel::Verbose,
+            type_checking_started: None,
+            timings: HashMap::new(),
+        };
+        let result = self
+            .project_compiler
+            .compile_root_package_streaming(&mut observer);
+
+        // Any module that was parsed but not type checked failed, either
+        // itself or because compilation stopped before reaching it.
+        let mut failed = observer.unfinished;
+        self.compile_order = observer.compiled;
+        self.module_timings = observer.timings;
+        if let Err(Error::Parse { path, .. }) = &result {
+            failed.extend(self.module_name_for_path(path));
+        }
+        failed.sort();
+        self.failed_modules = failed;
+        if self.trace_level >= TraceLevel::Messages {
+            tracing::info!(
+                type_checked = self.compile_order.len(),
+                failed = ?self.failed_modules,
+                "compiled_root_package"
+            );
+        }
+        if self.trace_level >= TraceLevel::Verbose {
+            for module in &self.compile_order {
+                let duration = self.module_timings.get(module);
+                tracing::info!(%module, ?duration, "type_checked_module");
+            }
+        }
+        self.type_errors.clear();
+        if let Err(Error::Type { path, src, error }) = &result {
+            if let Some(name) = self.module_name_for_path(path) {
+                let error = ModuleTypeError {
+                    path: path.clone(),
+                    src: src.clone(),
+                    error: error.clone(),
+                };
+                _ = self.type_errors.insert(name, error);
+            }
+        }
+        self.record_redundant_clauses();
+        if self.settings.warning_level != WarningLevel::All {
+            self.warnings
+                .warnings
+                .write()
+                .expect("Vector lock poisoned")
+                .retain(|warning| {
+                    self.settings
+                        .warning_level
+                        .includes(WarningKind::of(warning))
+                });
+        }
 
         // Return any error
         let package = result?;
@@ -125,21 +337,249 @@ where
             .collect_vec();
 
         // Store the compiled module information
+        let mut compiled_names = vec![];
         for module in package.modules {
+            if self.settings.panic_warnings
+                && self
+                    .settings
+                    .warning_level
+                    .includes(WarningKind::Unfinished)
+            {
+                emit_panic_warnings(&module, &mut self.lsp_warnings);
+            }
+            if self.settings.shadowing_warnings
+                && self.settings.warning_level.includes(WarningKind::Other)
+            {
+                emit_shadowing_warnings(&module, &mut self.lsp_warnings);
+            }
             let path = module.input_path.as_os_str().to_string_lossy().to_string();
             let line_numbers = LineNumbers::new(&module.code);
             let source = ModuleSourceInformation { path, line_numbers };
             compiled_modules.push(module.input_path.clone());
+            compiled_names.push(module.name.clone());
             _ = self.sources.insert(module.name.clone(), source);
             _ = self.modules.insert(module.name.clone(), module);
         }
 
+        if self.settings.private_function_hints
+            && self.settings.warning_level.includes(WarningKind::Unused)
+        {
+            self.emit_private_function_hints(&compiled_names);
+        }
+
+        self.check_warnings_as_errors()?;
+
         Ok(compiled_modules)
     }
 
     pub fn get_module_inferface(&self, name: &str) -> Option<&ModuleInterface> {
         self.project_compiler.get_importable_modules().get(name)
     }
+
+    /// Which of the warnings found by each compilation are reported.
+    pub fn warning_level(&self) -> &WarningLevel {
+        &self.settings.warning_level
+    }
+
+    /// A number that changes whenever the compiled modules can have changed,
+    /// so that information derived from them can be cached with the
+    /// generation it was found in and discarded once it no longer matches.
+    ///
+    /// It advances with every compilation, including those that fail, as the
+    /// modules of dependencies and the errors of the root package can change
+    /// even then, and when a snapshot of the compiler state is loaded.
+    pub fn compile_generation(&self) -> u64 {
+        self.compile_generation
+    }
+
+    /// Start a new generation, as the compiled modules are about to change.
+    pub(super) fn advance_generation(&mut self) {
+        self.compile_generation += 1;
+    }
+
+    /// The names of the public values and types of a module, sorted and
+    /// without duplicates. These are cached until the next compilation, so
+    /// asking for them repeatedly, such as while filtering completions, does
+    /// not walk the interface of the module each time.
+    pub fn module_export_names(&self, module: &str) -> Option<Vec<EcoString>> {
+        let mut cache = self.export_names.borrow_mut();
+        if let Some((generation, names)) = cache.get(module) {
+            if *generation == self.compile_generation {
+                return Some(names.clone());
+            }
+        }
+
+        let interface = self.get_module_inferface(module)?;
+        let values = interface
+            .values
+            .iter()
+            .filter(|(_, value)| value.public)
+            .map(|(name, _)| name);
+        let types = interface
+            .types
+            .iter()
+            .filter(|(_, type_)| type_.public)
+            .map(|(name, _)| name);
+        let names = values.chain(types).sorted().dedup().cloned().collect_vec();
+        _ = cache.insert(module.into(), (self.compile_generation, names.clone()));
+        Some(names)
+    }
+
+    /// The type of the positional argument at the index of a function or
+    /// record constructor defined in the module, printed as it would be
+    /// written in Gleam.
+    pub fn argument_type(&self, module: &str, func: &str, index: usize) -> Option<String> {
+        let value = self.get_module_inferface(module)?.values.get(func)?;
+        let (arguments, _) = value.type_.fn_types()?;
+        let argument = arguments.get(index)?;
+        Some(Printer::new().pretty_print(argument, 0))
+    }
+
+    /// The names of the type parameters of a function or type in the module,
+    /// in the order they first appear. The type variables of a function are
+    /// named as they are when its type is printed, so the first is `a`, the
+    /// second `b`, and so on. The parameters of a type defined in the root
+    /// package keep the names written in its definition.
+    ///
+    /// Root package modules are read from the last compilation in which they
+    /// compiled, and other modules from their interface.
+    pub fn type_parameters(&self, module: &str, name: &str) -> Vec<EcoString> {
+        if let Some(module) = self.modules.get(module) {
+            return module
+                .ast
+                .definitions
+                .iter()
+                .find_map(|definition| match definition {
+                    Definition::Function(function) if function.name == name => {
+                        let arguments = function.arguments.iter().map(|arg| arg.type_.clone());
+                        let type_ = type_::fn_(arguments.collect(), function.return_type.clone());
+                        Some(generic_names(&[type_]))
+                    }
+                    Definition::CustomType(custom_type) if custom_type.name == name => {
+                        Some(custom_type.parameters.clone())
+                    }
+                    Definition::TypeAlias(alias) if alias.alias == name => {
+                        Some(alias.parameters.clone())
+                    }
+                    _ => None,
+                })
+                .unwrap_or_default();
+        }
+
+        let Some(interface) = self.get_module_inferface(module) else {
+            return vec![];
+        };
+        if let Some(value) = interface.values.get(name) {
+            generic_names(std::slice::from_ref(&value.type_))
+        } else if let Some(type_) = interface.types.get(name) {
+            generic_names(&type_.parameters)
+        } else {
+            vec![]
+        }
+    }
+
+    /// Whether a module with the given origin in the root package can import
+    /// the given module.
+    ///
+    /// Test modules can import anything, but `src` modules cannot import the
+    /// root package's test modules or modules from packages that are only
+    /// required by the dev-dependencies, as that code would not be available
+    /// in production.
+    pub fn is_importable_from(&self, origin: Origin, module: &ModuleInterface) -> bool {
+        if !origin.is_src() {
+            return true;
+        }
+        if module.package == self.project_compiler.config.name {
+            return module.origin.is_src();
+        }
+        !self
+            .dev_dependency_packages()
+            .contains(module.package.as_str())
+    }
+
+    /// The names of the packages that are only needed by the root package's
+    /// dev-dependencies, and not by any of its regular dependencies.
+    pub fn dev_dependency_packages(&self) -> HashSet<EcoString> {
+        let packages = &self.project_compiler.packages;
+        let config = &self.project_compiler.config;
+
+        // Everything reachable from the regular dependencies is needed in
+        // production.
+        let mut production = HashSet::new();
+        let mut to_visit = config.dependencies.keys().cloned().collect_vec();
+        while let Some(name) = to_visit.pop() {
+            if !production.insert(name.clone()) {
+                continue;
+            }
+            if let Some(package) = packages.get(name.as_str()) {
+                to_visit.extend(package.requirements.iter().cloned());
+            }
+        }
+
+        packages
+            .keys()
+            .map(|name| EcoString::from(name.as_str()))
+            .chain(config.dev_dependencies.keys().cloned())
+            .filter(|name| !production.contains(name))
+            .collect()
+    }
+
+    /// The names of the modules of the root package, found by listing the
+    /// `.gleam` files of its `src` and `test` directories and of any other
+    /// directories of modules it has, sorted by name. Unlike the compiled
+    /// modules this includes those that failed to compile or have not been
+    /// compiled yet.
+    pub fn root_package_modules(&self) -> Vec<EcoString> {
+        let paths = &self.project_compiler.paths;
+        let extra_directories = self
+            .project_compiler
+            .root_source_directories
+            .iter()
+            .map(|directory| paths.root().join(directory));
+        [paths.src_directory(), paths.test_directory()]
+            .into_iter()
+            .chain(extra_directories)
+            .flat_map(|directory| {
+                self.project_compiler
+                    .io
+                    .gleam_source_files(&directory)
+                    .into_iter()
+                    .map(move |path| module_name(&directory, &path))
+            })
+            .sorted()
+            .dedup()
+            .collect()
+    }
+
+    /// Whether the importer importing the imported module would create an
+    /// import cycle, as the imported module already depends on the importer
+    /// either directly or through other modules of the root package.
+    ///
+    /// The imports are those of the most recent parse of each module, so the
+    /// answer is up to date with modules that do not compile.
+    pub fn would_create_cycle(&self, importer: &str, imported: &str) -> bool {
+        let target = self.project_compiler.target();
+        let mut visited = HashSet::new();
+        let mut to_visit = vec![imported];
+        while let Some(module) = to_visit.pop() {
+            if module == importer {
+                return true;
+            }
+            if !visited.insert(module) {
+                continue;
+            }
+            // Modules of other packages cannot import the root package.
+            if let Some(untyped) = self.untyped_modules.get(module) {
+                to_visit.extend(untyped.iter_statements(target).filter_map(|definition| {
+                    match definition {
+                        Definition::Import(import) => Some(import.module.as_str()),
+                        _ => None,
+                    }
+                }));
+            }
+        }
+        false
+    }
 }
 
 impl<IO> LspProjectCompiler<IO> {
@@ -147,12 +587,238 @@ impl<IO> LspProjectCompiler<IO> {
         self.warnings.take()
     }
 
+    pub fn take_lsp_warnings(&mut self) -> Vec<LspWarning> {
+        std::mem::take(&mut self.lsp_warnings)
+    }
+
     pub fn get_source(&self, module: &str) -> Option<&ModuleSourceInformation> {
         self.sources.get(module)
     }
+
+    /// The names of the root package modules that failed to compile in the
+    /// most recent compilation, including those that could not be type
+    /// checked because compilation stopped at an error in another module.
+    pub fn failed_modules(&self) -> Vec<EcoString> {
+        self.failed_modules.clone()
+    }
+
+    /// The names of the root package modules in the order they were type
+    /// checked in the most recent compilation. Modules are checked after the
+    /// modules they import. Modules that failed or were not reached are left
+    /// out, as are those loaded from the build cache because neither they nor
+    /// the modules they import have changed.
+    pub fn last_compile_order(&self) -> &[EcoString] {
+        &self.compile_order
+    }
+
+    /// The type error of the module if it failed to type check in the most
+    /// recent compilation.
+    pub fn type_error(&self, module: &str) -> Option<&ModuleTypeError> {
+        self.type_errors.get(module)
+    }
+
+    /// The name a source file of the root package has as a module, if it is
+    /// in the `src` or `test` directory or one of the configured source
+    /// directories. Files elsewhere are not part of the project and have no
+    /// module name.
+    pub fn module_name_for_path(&self, path: &Utf8Path) -> Option<EcoString> {
+        let paths = &self.project_compiler.paths;
+        let extra_directories = self
+            .project_compiler
+            .root_source_directories
+            .iter()
+            .map(|directory| paths.root().join(directory));
+        [paths.src_directory(), paths.test_directory()]
+            .into_iter()
+            .chain(extra_directories)
+            .find(|directory| path.starts_with(directory))
+            .map(|directory| module_name(&directory, path))
+    }
+
+    /// Whether a file is one of the source files of this project, as opposed
+    /// to a file of a dependency or of another project open in the editor.
+    pub fn owns_file(&self, path: &Utf8Path) -> bool {
+        self.module_name_for_path(path).is_some()
+    }
+
+    /// The typed AST of a root package module from the most recent
+    /// compilation, or `None` if the module failed to compile in it.
+    pub fn current_module(&self, module: &str) -> Option<&Module> {
+        if self.failed_modules.iter().any(|failed| failed == module) {
+            return None;
+        }
+        self.modules.get(module)
+    }
+
+    /// The typed AST of a root package module from the last compilation in
+    /// which it compiled successfully. If the module has failed to compile
+    /// since then this is out of date with its source.
+    pub fn last_good_module(&self, module: &str) -> Option<&Module> {
+        self.modules.get(module)
+    }
+
+    /// The other definitions of the module level name defined at the
+    /// location, if the most recent compilation of the module failed because
+    /// the name is defined more than once.
+    pub fn duplicate_definitions(&self, module: &str, location: SrcSpan) -> Vec<SrcSpan> {
+        let (one, other) = match self.type_errors.get(module) {
+            Some(ModuleTypeError {
+                error:
+                    type_::Error::DuplicateName {
+                        location_a,
+                        location_b,
+                        ..
+                    },
+                ..
+            }) => (*location_a, *location_b),
+            Some(ModuleTypeError {
+                error:
+                    type_::Error::DuplicateTypeName {
+                        location,
+                        previous_location,
+                        ..
+                    },
+                ..
+            }) => (*location, *previous_location),
+            _ => return vec![],
+        };
+        if one == location {
+            vec![other]
+        } else if other == location {
+            vec![one]
+        } else {
+            vec![]
+        }
+    }
+
+    /// The untyped AST of a root package module, for features that only need
+    /// the syntax and so should keep working when there are type errors.
+    pub fn untyped_module(&self, module: &str) -> Option<&UntypedModule> {
+        self.untyped_modules.get(module)
+    }
 }
 
-#[derive(Debug)]
+/// The names the printer gives the generic type variables of the types, in
+/// the order they first appear.
+fn generic_names(types: &[Arc<Type>]) -> Vec<EcoString> {
+    fn collect(type_: &Type, ids: &mut Vec<u64>) {
+        match type_ {
+            Type::Var { type_ } => match type_.borrow().deref() {
+                TypeVar::Generic { id } => {
+                    if !ids.contains(id) {
+                        ids.push(*id);
+                    }
+                }
+                TypeVar::Link { type_ } => collect(type_, ids),
+                TypeVar::Unbound { .. } => (),
+            },
+            Type::Named { args, .. } => args.iter().for_each(|arg| collect(arg, ids)),
+            Type::Fn { args, retrn } => {
+                args.iter().for_each(|arg| collect(arg, ids));
+                collect(retrn, ids);
+            }
+            Type::Tuple { elems } => elems.iter().for_each(|elem| collect(elem, ids)),
+        }
+    }
+
+    let mut ids = vec![];
+    for type_ in types {
+        collect(type_, &mut ids);
+    }
+    let mut printer = Printer::new();
+    ids.into_iter()
+        .map(|id| printer.generic_type_var(id).to_pretty_string(80).into())
+        .collect()
+}
+
+/// Report each use of `panic` in a module as a warning.
+fn emit_panic_warnings(module: &Module, warnings: &mut Vec<LspWarning>) {
+    let mut finder = PanicFinder { locations: vec![] };
+    walk_module(&module.ast, &mut finder);
+    for location in finder.locations {
+        warnings.push(LspWarning {
+            path: module.input_path.clone(),
+            src: module.code.clone(),
+            kind: LspWarningKind::Panic { location },
+        });
+    }
+}
+
+struct PanicFinder {
+    locations: Vec<SrcSpan>,
+}
+
+impl<'ast> Visit<'ast> for PanicFinder {
+    fn visit_expr(&mut self, expr: &'ast TypedExpr) {
+        if let TypedExpr::Panic { location, .. } = expr {
+            self.locations.push(*location);
+        }
+    }
+}
+
+/// Forwards type checked modules to the language server's callback, and
+/// keeps the untyped AST of each module that is parsed. If asked it also times
+/// how long each module takes to type check.
+struct Observer<'a, F> {
+    on_module: F,
+    untyped_modules: &'a mut HashMap<EcoString, UntypedModule>,
+    /// Modules which have been parsed but not yet type checked.
+    unfinished: Vec<EcoString>,
+    /// Modules which have been type checked, in order.
+    compiled: Vec<EcoString>,
+    record_timings: bool,
+    /// When the module being type checked started, if timings are recorded.
+    type_checking_started: Option<Instant>,
+    timings: HashMap<EcoString, Duration>,
+}
+
+impl<F> ModuleObserver for Observer<'_, F>
+where
+    F: FnMut(&Module),
+{
+    fn parsed(&mut self, module: &UncompiledModule) {
+        _ = self
+            .untyped_modules
+            .insert(module.name.clone(), module.ast.clone());
+        self.unfinished.push(module.name.clone());
+    }
+
+    fn type_checking(&mut self, _module: &str) {
+        if self.record_timings {
+            self.type_checking_started = Some(Instant::now());
+        }
+    }
+
+    fn compiled(&mut self, module: &Module) {
+        if let Some(started) = self.type_checking_started.take() {
+            _ = self.timings.insert(module.name.clone(), started.elapsed());
+        }
+        self.unfinished.retain(|name| name != &module.name);
+        self.compiled.push(module.name.clone());
+        (self.on_module)(module)
+    }
+}
+
+/// The type error a module of the root package failed to type check with,
+/// along with the path and source of the module as it was checked.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ModuleTypeError {
+    pub path: Utf8PathBuf,
+    pub src: EcoString,
+    pub error: type_::Error,
+}
+
+impl ModuleTypeError {
+    pub fn to_error(&self) -> Error {
+        Error::Type {
+            path: self.path.clone(),
+            src: self.src.clone(),
+            error: self.error.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
 pub struct ModuleSourceInformation {
     /// The path to the source file from within the project root
     pub path: String,
@@ -0,0 +1,903 @@
+use crate::{
+    ast::{Definition, Statement},
+    build::{Located, Module},
+    io::FileSystemReader,
+    language_server::compiler::LspProjectCompiler,
+    parse::{
+        error::{LexicalError, LexicalErrorType},
+        lexer::{make_tokenizer, Spanned},
+        token::Token,
+    },
+};
+use ecow::EcoString;
+use itertools::Itertools;
+use lsp_types as lsp;
+use serde::{Deserialize, Serialize};
+
+/// How relevant a completion is based on where its value or type comes from.
+/// Completions are sorted by their tier first, in the order the variants are
+/// defined here.
+///
+/// The tier is stored in the `data` field of each completion item so that
+/// clients can sort the completions differently if they wish.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum CompletionTier {
+    /// Defined in the module being edited.
+    Local,
+    /// From a module that has been imported.
+    Imported,
+    /// From a module that could be imported but has not been yet.
+    Importable,
+    /// Built into the language.
+    Prelude,
+}
+
+impl CompletionTier {
+    /// The tier of a completion item produced by the language server.
+    pub fn of(item: &lsp::CompletionItem) -> Option<Self> {
+        serde_json::from_value(item.data.clone()?).ok()
+    }
+}
+
+/// How well the label of a completion matches what has been typed so far.
+/// Within a tier completions are sorted by this, best match first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum PrefixMatch {
+    Exact,
+    Prefix,
+    CaseInsensitivePrefix,
+    Contains,
+    None,
+}
+
+impl PrefixMatch {
+    fn new(name: &str, prefix: &str) -> Self {
+        if name == prefix {
+            Self::Exact
+        } else if name.starts_with(prefix) {
+            Self::Prefix
+        } else if name.to_lowercase().starts_with(&prefix.to_lowercase()) {
+            Self::CaseInsensitivePrefix
+        } else if name.contains(prefix) {
+            Self::Contains
+        } else {
+            Self::None
+        }
+    }
+
+    /// Qualified labels such as `list.map` also match on the unqualified
+    /// name, so typing `ma` ranks `list.map` as a prefix match.
+    fn for_label(label: &str, prefix: &str) -> Self {
+        let unqualified = match label.rsplit_once('.') {
+            Some((_, name)) => Self::new(name, prefix),
+            None => Self::None,
+        };
+        Self::new(label, prefix).min(unqualified)
+    }
+}
+
+/// The partially typed name that ends at the given byte index, which is
+/// what completions are matched against.
+pub(crate) fn typed_prefix(code: &str, byte_index: u32) -> &str {
+    let end = (byte_index as usize).min(code.len());
+    let before = code.get(..end).unwrap_or_default();
+    let start = before
+        .char_indices()
+        .rev()
+        .find(|(_, char)| !(char.is_alphanumeric() || *char == '_' || *char == '.'))
+        .map(|(index, char)| index + char.len_utf8())
+        .unwrap_or(0);
+    &before[start..]
+}
+
+/// Set the `sort_text` and `data` of a completion item so that clients list
+/// the most relevant completions first.
+pub(crate) fn rank(
+    mut item: lsp::CompletionItem,
+    tier: CompletionTier,
+    prefix: &str,
+) -> lsp::CompletionItem {
+    let prefix_match = PrefixMatch::for_label(&item.label, prefix);
+    item.sort_text = Some(format!(
+        "{}{}_{}",
+        tier as u8, prefix_match as u8, item.label
+    ));
+    item.data = serde_json::to_value(tier).ok();
+    item
+}
+
+/// The operators that can be used in the guard of a case clause.
+pub(crate) const GUARD_OPERATORS: [&str; 12] = [
+    "==", "!=", "<", ">", "<=", ">=", "<.", ">.", "<=.", ">=.", "&&", "||",
+];
+
+/// The most completions offered from modules that are not imported yet, as
+/// a project and its dependencies can define far more than can be listed.
+pub(crate) const MAX_IMPORTABLE_COMPLETIONS: usize = 50;
+
+/// Keep only the best of the completions from modules that are not imported
+/// yet. They are only offered once a name has been started, and only if
+/// their label or unqualified name starts with it.
+pub(crate) fn best_importable(
+    completions: Vec<lsp::CompletionItem>,
+    prefix: &str,
+) -> Vec<lsp::CompletionItem> {
+    if prefix.is_empty() {
+        return vec![];
+    }
+    completions
+        .into_iter()
+        .filter(|item| {
+            PrefixMatch::for_label(&item.label, prefix) <= PrefixMatch::CaseInsensitivePrefix
+        })
+        .sorted_by(|a, b| a.sort_text.cmp(&b.sort_text))
+        .take(MAX_IMPORTABLE_COMPLETIONS)
+        .collect()
+}
+
+/// Whether [`best_importable`] could keep any of the completions for the
+/// public names of a module that is not imported yet, so that modules none
+/// of which could be kept are skipped without making their completions.
+pub(crate) fn could_complete_importable(
+    qualifier: &str,
+    names: &[EcoString],
+    prefix: &str,
+) -> bool {
+    !prefix.is_empty()
+        && names.iter().any(|name| {
+            PrefixMatch::for_label(&format!("{qualifier}.{name}"), prefix)
+                <= PrefixMatch::CaseInsensitivePrefix
+        })
+}
+
+/// The edit that imports a module into the code of another, added on the
+/// line after its last leading import. A module without imports has it added
+/// after the module comments at its top.
+pub(crate) fn import_edit(code: &str, module: &str) -> lsp::TextEdit {
+    let mut line = 0;
+    let mut in_import = false;
+    for (index, text) in code.lines().enumerate() {
+        let text = text.trim();
+        if in_import {
+            in_import = !text.contains('}');
+            line = index + 1;
+        } else if text.starts_with("import ") {
+            in_import = text.contains('{') && !text.contains('}');
+            line = index + 1;
+        } else if text.starts_with("////") {
+            line = index + 1;
+        } else if !(text.is_empty() || text.starts_with("//")) {
+            break;
+        }
+    }
+
+    // The last line may not have a line ending to insert after.
+    let lines = code.lines().count();
+    let (position, new_text) = if line == lines && !code.is_empty() && !code.ends_with('\n') {
+        let last = code.lines().last().unwrap_or_default();
+        let character = last.encode_utf16().count() as u32;
+        let position = lsp::Position::new(line.saturating_sub(1) as u32, character);
+        (position, format!("\nimport {module}"))
+    } else {
+        (
+            lsp::Position::new(line as u32, 0),
+            format!("import {module}\n"),
+        )
+    };
+    lsp::TextEdit {
+        range: lsp::Range::new(position, position),
+        new_text,
+    }
+}
+
+/// What is being written at the position completions were requested for,
+/// which determines what can be suggested there.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CompletionContext {
+    /// After the name of an imported module and a `.`, where only the public
+    /// definitions of that module can be used.
+    QualifiedAccess {
+        /// The full name of the module, such as `gleam/list`.
+        module: EcoString,
+        /// Whether a type is expected rather than a value.
+        in_type_annotation: bool,
+    },
+    /// An import statement, including its unqualified imports.
+    Import,
+    /// A pattern, such as a function parameter or the left hand side of an
+    /// assignment or case clause.
+    Pattern,
+    /// A type, such as the annotation of a function parameter or a field of a
+    /// custom type.
+    TypeAnnotation,
+    /// An expression, such as a statement in the body of a function.
+    Expression,
+    /// The guard of a case clause, after its `if`, where functions cannot be
+    /// called and only variables, constants, comparisons and boolean
+    /// operators can be used.
+    Guard {
+        /// The variables bound by the patterns of the clause, followed by
+        /// those assigned before the case expression. Variables bound by the
+        /// patterns of enclosing case clauses are not included.
+        variables: Vec<EcoString>,
+        /// Whether the guard so far ends with a variable or value, so that an
+        /// operator comes next.
+        after_operand: bool,
+    },
+    /// The start of an argument of a call, where an expression or the label
+    /// of one of the labelled arguments of the function can be written.
+    CallArgument {
+        /// The function as it is written at the call, such as `list.map`.
+        function: EcoString,
+        /// The labels of the arguments given before the position.
+        labels: Vec<EcoString>,
+    },
+    /// Somewhere nothing can be completed, such as in a comment or a string or
+    /// where a new definition is being named.
+    None,
+}
+
+impl<IO> LspProjectCompiler<IO>
+where
+    IO: FileSystemReader,
+{
+    /// Classify the code at the byte index of a module of the root package.
+    ///
+    /// The typed AST of the module is only used if it has not been edited
+    /// since it was last compiled. While code is being typed the module
+    /// usually fails to compile, so instead the context is worked out from
+    /// the tokens that come before the byte index in the current source.
+    pub fn completion_context(&self, module: &str, byte_index: u32) -> CompletionContext {
+        let Some(compiled) = self.modules.get(module) else {
+            return CompletionContext::None;
+        };
+        let code = self.current_code(compiled);
+        let Some(tokens) = Tokens::before(&code, byte_index) else {
+            return CompletionContext::None;
+        };
+
+        if let Some(context) = tokens
+            .qualified_access()
+            .or_else(|| tokens.import())
+            .or_else(|| tokens.guard())
+            .or_else(|| tokens.call_argument())
+        {
+            return context;
+        }
+        if code != compiled.code {
+            return tokens.context(tokens.tokens.len());
+        }
+        match compiled.find_node(byte_index) {
+            Some(Located::Pattern(_)) => CompletionContext::Pattern,
+            // An argument is both a pattern and its annotation.
+            Some(Located::Arg(_)) => tokens.context(tokens.tokens.len()),
+            // The annotation of an assignment is within the statement.
+            Some(Located::Statement(Statement::Assignment(assignment)))
+                if assignment.annotation.as_ref().is_some_and(|annotation| {
+                    let location = annotation.location();
+                    location.start <= byte_index && byte_index <= location.end
+                }) =>
+            {
+                CompletionContext::TypeAnnotation
+            }
+            Some(
+                Located::Expression(_)
+                | Located::Statement(_)
+                | Located::FunctionBody(_)
+                | Located::ModuleStatement(Definition::ModuleConstant(_)),
+            ) => CompletionContext::Expression,
+            Some(
+                Located::ModuleStatement(
+                    Definition::Function(_) | Definition::TypeAlias(_) | Definition::CustomType(_),
+                )
+                | Located::VariantConstructorDefinition(_),
+            ) => CompletionContext::TypeAnnotation,
+            Some(Located::ModuleStatement(Definition::Import(_))) => CompletionContext::Import,
+            None => CompletionContext::None,
+        }
+    }
+
+    /// The source of a module as it is now, which may have been edited since
+    /// the module was compiled.
+    pub(crate) fn current_code(&self, module: &Module) -> EcoString {
+        self.project_compiler
+            .io
+            .read(&module.input_path)
+            .map(EcoString::from)
+            .unwrap_or_else(|_| module.code.clone())
+    }
+}
+
+/// The kind of code enclosed by a pair of brackets.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Group {
+    /// The parameters of a named or anonymous function.
+    FunctionHead,
+    FunctionBody,
+    /// The variants of a custom type.
+    TypeBody,
+    /// The fields of a variant of a custom type.
+    Variant,
+    /// The clauses of a case expression.
+    CaseBody,
+    Other,
+}
+
+/// The tokens of the source that come before the position completions were
+/// requested for, without comments and without the name that is being typed
+/// at that position.
+struct Tokens<'a> {
+    code: &'a str,
+    byte_index: u32,
+    tokens: Vec<Spanned>,
+}
+
+impl<'a> Tokens<'a> {
+    /// Returns `None` if the byte index is within a comment or a string.
+    fn before(code: &'a str, byte_index: u32) -> Option<Self> {
+        let byte_index = byte_index.min(code.len() as u32);
+        let mut tokens = vec![];
+        for result in make_tokenizer(code.get(..byte_index as usize)?) {
+            match result {
+                Ok((
+                    _,
+                    Token::CommentNormal | Token::CommentDoc { .. } | Token::CommentModule,
+                    end,
+                )) => {
+                    // Comments run to the end of the line.
+                    if end >= byte_index {
+                        return None;
+                    }
+                }
+                Ok((_, Token::EmptyLine | Token::EndOfFile, _)) => (),
+                Ok(token) => tokens.push(token),
+                Err(LexicalError {
+                    error: LexicalErrorType::UnexpectedStringEnd,
+                    ..
+                }) => return None,
+                // Nothing after an invalid token can be understood.
+                Err(_) => break,
+            }
+        }
+
+        if let Some((
+            _,
+            Token::Name { .. } | Token::UpName { .. } | Token::DiscardName { .. },
+            end,
+        )) = tokens.last()
+        {
+            if *end == byte_index {
+                _ = tokens.pop();
+            }
+        }
+        Some(Self {
+            code,
+            byte_index,
+            tokens,
+        })
+    }
+
+    fn token(&self, index: usize) -> &Token {
+        self.tokens
+            .get(index)
+            .map_or(&Token::EndOfFile, |(_, token, _)| token)
+    }
+
+    /// `module.` where `module` is the name an import is used by.
+    fn qualified_access(&self) -> Option<CompletionContext> {
+        let [.., (_, Token::Name { name }, name_end), (dot_start, Token::Dot, _)] =
+            self.tokens.as_slice()
+        else {
+            return None;
+        };
+        if name_end != dot_start {
+            return None;
+        }
+        let module = self.imported_module(name)?;
+        let in_type_annotation =
+            self.context(self.tokens.len() - 2) == CompletionContext::TypeAnnotation;
+        Some(CompletionContext::QualifiedAccess {
+            module,
+            in_type_annotation,
+        })
+    }
+
+    /// The full name of the module imported with the given used name.
+    fn imported_module(&self, used_name: &str) -> Option<EcoString> {
+        let mut found = None;
+        let mut index = 0;
+        while index < self.tokens.len() {
+            index += 1;
+            if *self.token(index - 1) != Token::Import {
+                continue;
+            }
+
+            let mut segments = vec![];
+            while let Some((_, Token::Name { name }, _)) = self.tokens.get(index) {
+                segments.push(name.as_str());
+                index += 1;
+                match self.tokens.get(index) {
+                    Some((_, Token::Slash, _)) => index += 1,
+                    _ => break,
+                }
+            }
+
+            // Skip over any unqualified imports.
+            if let Some((_, Token::Dot, _)) = self.tokens.get(index) {
+                while index < self.tokens.len() && *self.token(index) != Token::RightBrace {
+                    index += 1;
+                }
+                index += 1;
+            }
+
+            let name = match (self.tokens.get(index), self.tokens.get(index + 1)) {
+                (Some((_, Token::As, _)), Some((_, Token::Name { name }, _))) => {
+                    Some(name.as_str())
+                }
+                (Some((_, Token::As, _)), _) => None,
+                _ => segments.last().copied(),
+            };
+            if name == Some(used_name) {
+                found = Some(segments.join("/").into());
+            }
+        }
+        found
+    }
+
+    /// The start of an argument of a call to a named function, as in `f(` or
+    /// `module.f(a, `.
+    fn call_argument(&self) -> Option<CompletionContext> {
+        let end = self.tokens.len();
+        let opener = self.enclosing(end)?;
+        if *self.token(opener) != Token::LeftParen
+            || self.group(opener) != Group::Other
+            || !(opener + 1 == end || *self.token(end - 1) == Token::Comma)
+            || self.start_of_group(Some(opener)) != CompletionContext::Expression
+        {
+            return None;
+        }
+
+        let name = match self.token(opener.checked_sub(1)?) {
+            Token::Name { name } | Token::UpName { name } => name,
+            _ => return None,
+        };
+        let function = match opener
+            .checked_sub(3)
+            .map(|index| (index, self.token(index)))
+        {
+            Some((index, Token::Name { name: module })) if *self.token(index + 1) == Token::Dot => {
+                format!("{module}.{name}").into()
+            }
+            _ => name.clone(),
+        };
+        let labels = self
+            .level(end)
+            .filter(|index| *self.token(index + 1) == Token::Colon)
+            .filter_map(|index| match self.token(index) {
+                Token::Name { name } => Some(name.clone()),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        Some(CompletionContext::CallArgument {
+            function,
+            labels: labels.into_iter().rev().collect(),
+        })
+    }
+
+    /// After the `if` of a case clause, as in `Ok(x) if x > `, including
+    /// within brackets in the guard.
+    fn guard(&self) -> Option<CompletionContext> {
+        let mut end = self.tokens.len();
+        let case_body = loop {
+            let opener = self.enclosing(end)?;
+            match self.group(opener) {
+                Group::CaseBody => break opener,
+                Group::Other => end = opener,
+                _ => return None,
+            }
+        };
+        let guard = self
+            .level(end)
+            .find(|index| matches!(self.token(*index), Token::If | Token::RArrow))
+            .filter(|index| *self.token(*index) == Token::If)?;
+
+        // The patterns start on the line after the body of the previous
+        // clause, if there is one.
+        let previous_arrow = self
+            .level(guard)
+            .find(|index| *self.token(*index) == Token::RArrow);
+        let patterns_start = match previous_arrow {
+            Some(arrow) => self
+                .level(guard)
+                .filter(|index| *index > arrow)
+                .filter(|index| self.starts_new_line(*index))
+                .last()
+                .unwrap_or(guard),
+            None => case_body + 1,
+        };
+        let variables = (patterns_start..guard)
+            .filter(|index| {
+                let previous = index.checked_sub(1).map(|index| self.token(index));
+                previous != Some(&Token::Dot)
+                    && !matches!(
+                        self.token(index + 1),
+                        Token::Dot | Token::Colon | Token::LeftParen
+                    )
+            })
+            .filter_map(|index| match self.token(index) {
+                Token::Name { name } => Some(name.clone()),
+                _ => None,
+            })
+            .chain(self.variables_in_scope(case_body))
+            .unique()
+            .collect();
+        let after_operand = matches!(
+            self.tokens.last(),
+            Some((
+                _,
+                Token::Name { .. }
+                    | Token::UpName { .. }
+                    | Token::Int { .. }
+                    | Token::Float { .. }
+                    | Token::String { .. }
+                    | Token::RightParen
+                    | Token::RightSquare
+                    | Token::RightBrace,
+                _
+            ))
+        );
+        Some(CompletionContext::Guard {
+            variables,
+            after_operand,
+        })
+    }
+
+    /// The variables that can be used after the first `end` tokens that were
+    /// assigned before them: the parameters of the functions they are within
+    /// and the variables of the `let` and `use` statements before them in the
+    /// bodies of those functions.
+    fn variables_in_scope(&self, end: usize) -> Vec<EcoString> {
+        let mut variables = vec![];
+        let mut end = end;
+        while let Some(opener) = self.enclosing(end) {
+            if self.group(opener) == Group::FunctionBody {
+                let statements = self.level(end).collect_vec();
+                for (position, index) in statements.iter().enumerate() {
+                    // The statement is only finished if another starts after
+                    // it, as otherwise the tokens are within its value.
+                    let finished = statements
+                        .get(..position)
+                        .unwrap_or_default()
+                        .iter()
+                        .any(|later| self.starts_new_line(*later));
+                    if finished && matches!(self.token(*index), Token::Let | Token::Use) {
+                        variables.extend(self.bound_names(index + 1, end));
+                    }
+                }
+                let head = self
+                    .level(opener)
+                    .find(|index| *self.token(*index) == Token::Fn)
+                    .and_then(|keyword| {
+                        (keyword + 1..opener).find(|index| *self.token(*index) == Token::LeftParen)
+                    });
+                if let Some(head) = head {
+                    variables.extend(self.bound_names(head + 1, opener));
+                }
+            }
+            end = opener;
+        }
+        variables
+    }
+
+    /// The names of the variables bound by the patterns starting at `start`,
+    /// which end at an `=` or `<-` or at the end of the brackets they are
+    /// within, as with the parameters of a function. Labels, annotations and
+    /// the options of bit array segments are skipped.
+    fn bound_names(&self, start: usize, end: usize) -> Vec<EcoString> {
+        let mut names = vec![];
+        // For each bracket the names are within, whether it is a pair of
+        // parentheses that can contain labels, whether the bracket is within
+        // an annotation, and whether an annotation has been started in it.
+        let mut levels = vec![(false, false, false)];
+        for index in start..end {
+            let token = self.token(index);
+            let (parentheses, within_annotation, annotated) =
+                levels.last().copied().unwrap_or_default();
+            match token {
+                Token::Equal | Token::LArrow if levels.len() == 1 => break,
+                token if is_closer(token) => {
+                    _ = levels.pop();
+                    if levels.is_empty() {
+                        break;
+                    }
+                }
+                token if is_opener(token) => {
+                    levels.push((*token == Token::LeftParen, annotated, annotated));
+                }
+                Token::Colon | Token::Comma => {
+                    if let Some(level) = levels.last_mut() {
+                        level.2 = within_annotation || (*token == Token::Colon && !parentheses);
+                    }
+                }
+                Token::Name { name } if !annotated => {
+                    let previous = index.checked_sub(1).map(|index| self.token(index));
+                    let next = self.token(index + 1);
+                    let label = matches!(next, Token::Name { .. })
+                        || (parentheses && *next == Token::Colon);
+                    if !label
+                        && previous != Some(&Token::Dot)
+                        && !matches!(next, Token::Dot | Token::LeftParen)
+                    {
+                        names.push(name.clone());
+                    }
+                }
+                _ => (),
+            }
+        }
+        names
+    }
+
+    /// An import is written on a single line, unless its unqualified imports
+    /// are spread over several.
+    fn import(&self) -> Option<CompletionContext> {
+        let mut braces_closed = false;
+        let mut in_braces = false;
+        for (start, token, _) in self.tokens.iter().rev() {
+            match token {
+                Token::Import if in_braces || self.on_last_line(*start) => {
+                    return Some(CompletionContext::Import);
+                }
+                Token::RightBrace => braces_closed = true,
+                Token::LeftBrace => in_braces = !braces_closed,
+                Token::Name { .. }
+                | Token::UpName { .. }
+                | Token::DiscardName { .. }
+                | Token::Slash
+                | Token::Dot
+                | Token::Comma
+                | Token::Type
+                | Token::As => (),
+                _ => return None,
+            }
+        }
+        None
+    }
+
+    /// The context after the first `end` tokens.
+    fn context(&self, end: usize) -> CompletionContext {
+        let opener = self.enclosing(end);
+        let marker = self.level(end).find(|index| is_marker(self.token(*index)));
+
+        match marker.map(|index| (index, self.token(index))) {
+            None | Some((_, Token::Comma)) => self.start_of_group(opener),
+
+            Some((index, Token::Colon)) => {
+                let group = opener.map(|opener| self.group(opener));
+                if matches!(group, Some(Group::FunctionHead | Group::Variant))
+                    || self.is_declaration(index)
+                {
+                    CompletionContext::TypeAnnotation
+                } else {
+                    self.start_of_group(opener)
+                }
+            }
+
+            Some((index, Token::RArrow)) => {
+                let group = opener.map(|opener| self.group(opener));
+                if self.follows_function_head(index) {
+                    CompletionContext::TypeAnnotation
+                } else if group == Some(Group::CaseBody) && self.starts_new_line(end) {
+                    // The body of the previous clause has been written.
+                    CompletionContext::Pattern
+                } else {
+                    CompletionContext::Expression
+                }
+            }
+
+            Some((index, Token::Equal)) => {
+                let keyword = self
+                    .level(index)
+                    .map(|index| self.token(index))
+                    .find(|token| {
+                        matches!(
+                            token,
+                            Token::Let | Token::Assert | Token::Const | Token::Type
+                        )
+                    });
+                if keyword == Some(&Token::Type) {
+                    CompletionContext::TypeAnnotation
+                } else {
+                    CompletionContext::Expression
+                }
+            }
+
+            Some((_, Token::Let | Token::Assert | Token::Use)) => CompletionContext::Pattern,
+
+            Some((_, Token::Fn | Token::Type | Token::Const | Token::Pub | Token::Import)) => {
+                CompletionContext::None
+            }
+
+            Some(_) => CompletionContext::Expression,
+        }
+    }
+
+    /// The context at the start of the group opened by the given bracket, or
+    /// at the top level of the module.
+    fn start_of_group(&self, opener: Option<usize>) -> CompletionContext {
+        let Some(opener) = opener else {
+            return CompletionContext::None;
+        };
+        match self.group(opener) {
+            // The parameters of a function type are types.
+            Group::FunctionHead
+                if *self.token(opener - 1) == Token::Fn
+                    && self.context(opener - 1) == CompletionContext::TypeAnnotation =>
+            {
+                CompletionContext::TypeAnnotation
+            }
+            Group::FunctionHead | Group::CaseBody => CompletionContext::Pattern,
+            Group::Variant => CompletionContext::TypeAnnotation,
+            Group::TypeBody => CompletionContext::None,
+            Group::FunctionBody => CompletionContext::Expression,
+            // Tuples, lists, calls and the like contain the same kind of
+            // code as their surroundings.
+            Group::Other => match self.context(opener) {
+                context @ (CompletionContext::Pattern | CompletionContext::TypeAnnotation) => {
+                    context
+                }
+                _ => CompletionContext::Expression,
+            },
+        }
+    }
+
+    fn group(&self, opener: usize) -> Group {
+        let previous = opener.checked_sub(1).map(|index| self.token(index));
+        match self.token(opener) {
+            Token::LeftParen => match previous {
+                Some(Token::Fn) => Group::FunctionHead,
+                Some(Token::Name { .. }) if opener >= 2 && *self.token(opener - 2) == Token::Fn => {
+                    Group::FunctionHead
+                }
+                Some(Token::UpName { .. })
+                    if self.enclosing(opener).map(|outer| self.group(outer))
+                        == Some(Group::TypeBody) =>
+                {
+                    Group::Variant
+                }
+                _ => Group::Other,
+            },
+            Token::LeftBrace => {
+                let keyword = self
+                    .level(opener)
+                    .map(|index| self.token(index))
+                    .find(|token| matches!(token, Token::Fn | Token::Case | Token::Type));
+                match keyword {
+                    Some(Token::Fn) => Group::FunctionBody,
+                    Some(Token::Case) => Group::CaseBody,
+                    Some(Token::Type) => Group::TypeBody,
+                    _ => Group::Other,
+                }
+            }
+            _ => Group::Other,
+        }
+    }
+
+    /// Whether the colon at the given index annotates a `let`, `assert` or
+    /// `const`.
+    fn is_declaration(&self, colon: usize) -> bool {
+        self.level(colon)
+            .map(|index| self.token(index))
+            .find(|token| is_marker(token) && **token != Token::Comma)
+            .is_some_and(|token| matches!(token, Token::Let | Token::Assert | Token::Const))
+    }
+
+    /// Whether the arrow at the given index comes after the parameters of a
+    /// function, making what follows its return type.
+    fn follows_function_head(&self, arrow: usize) -> bool {
+        arrow
+            .checked_sub(1)
+            .filter(|index| *self.token(*index) == Token::RightParen)
+            .and_then(|index| self.enclosing(index))
+            .is_some_and(|opener| self.group(opener) == Group::FunctionHead)
+    }
+
+    /// Whether the first `end` tokens end with a complete expression or
+    /// pattern on an earlier line than the position after them.
+    fn starts_new_line(&self, end: usize) -> bool {
+        let Some((_, token, last_end)) =
+            end.checked_sub(1).and_then(|index| self.tokens.get(index))
+        else {
+            return false;
+        };
+        let complete = matches!(
+            token,
+            Token::Name { .. }
+                | Token::UpName { .. }
+                | Token::DiscardName { .. }
+                | Token::Int { .. }
+                | Token::Float { .. }
+                | Token::String { .. }
+                | Token::RightParen
+                | Token::RightSquare
+                | Token::RightBrace
+                | Token::GtGt
+        );
+        let position = self
+            .tokens
+            .get(end)
+            .map_or(self.byte_index, |(start, _, _)| *start);
+        complete && self.code[*last_end as usize..position as usize].contains('\n')
+    }
+
+    fn on_last_line(&self, position: u32) -> bool {
+        !self.code[position as usize..self.byte_index as usize].contains('\n')
+    }
+
+    /// The index of the unclosed bracket that the first `end` tokens are
+    /// within, if any.
+    fn enclosing(&self, end: usize) -> Option<usize> {
+        let mut depth = 0;
+        for index in (0..end).rev() {
+            match self.token(index) {
+                token if is_closer(token) => depth += 1,
+                token if is_opener(token) && depth == 0 => return Some(index),
+                token if is_opener(token) => depth -= 1,
+                _ => (),
+            }
+        }
+        None
+    }
+
+    /// The indices of the tokens before `end` that are not within brackets
+    /// closed before `end`, nearest first, stopping at the enclosing bracket.
+    fn level(&self, end: usize) -> impl Iterator<Item = usize> + '_ {
+        let mut depth = 0;
+        (0..end)
+            .rev()
+            .map_while(move |index| match self.token(index) {
+                token if is_closer(token) => {
+                    depth += 1;
+                    Some(None)
+                }
+                token if is_opener(token) && depth == 0 => None,
+                token if is_opener(token) => {
+                    depth -= 1;
+                    Some(None)
+                }
+                _ if depth == 0 => Some(Some(index)),
+                _ => Some(None),
+            })
+            .flatten()
+    }
+}
+
+fn is_opener(token: &Token) -> bool {
+    matches!(
+        token,
+        Token::LeftParen | Token::LeftSquare | Token::LeftBrace | Token::LtLt
+    )
+}
+
+fn is_closer(token: &Token) -> bool {
+    matches!(
+        token,
+        Token::RightParen | Token::RightSquare | Token::RightBrace | Token::GtGt
+    )
+}
+
+/// Tokens that determine what kind of code follows them.
+fn is_marker(token: &Token) -> bool {
+    !matches!(
+        token,
+        Token::Name { .. }
+            | Token::UpName { .. }
+            | Token::DiscardName { .. }
+            | Token::Int { .. }
+            | Token::Float { .. }
+            | Token::String { .. }
+            | Token::Dot
+            | Token::Hash
+            | Token::Vbar
+    )
+}
@@ -0,0 +1,129 @@
+use crate::{
+    ast::{AssignmentKind, Definition, Pattern, SrcSpan, Statement, TypedExpr, TypedPattern},
+    build::Located,
+    language_server::{
+        compiler::LspProjectCompiler,
+        visit::{walk_module, Visit},
+    },
+};
+
+impl<IO> LspProjectCompiler<IO> {
+    /// What kind of node the innermost node of the typed AST at the byte
+    /// index is, such as `Call`, `Var` or `Let`, for finding out why a
+    /// feature that works on positions does not do what is expected with a
+    /// particular piece of code. The description is for people rather than
+    /// programs and may change.
+    ///
+    /// The module is searched as it was in the last compilation in which it
+    /// compiled.
+    pub fn node_at(&self, module: &str, byte_index: u32) -> Option<String> {
+        let module = self.modules.get(module)?;
+        let description = match module.find_node(byte_index)? {
+            Located::Expression(expression) => expression_kind(expression).into(),
+            Located::Statement(statement) => statement_kind(statement).into(),
+            Located::ModuleStatement(definition) => definition_kind(definition).into(),
+            Located::FunctionBody(_) => "Function body".into(),
+            Located::Arg(_) => "Function argument".into(),
+            Located::VariantConstructorDefinition(_) => "Record constructor definition".into(),
+            Located::Pattern(pattern) => {
+                let mut finder = ClausePatterns { locations: vec![] };
+                walk_module(&module.ast, &mut finder);
+                let location = pattern.location();
+                let in_clause = finder
+                    .locations
+                    .iter()
+                    .any(|clause| clause.start <= location.start && location.end <= clause.end);
+                if in_clause {
+                    format!("Case clause pattern ({})", pattern_kind(pattern))
+                } else {
+                    format!("Pattern ({})", pattern_kind(pattern))
+                }
+            }
+        };
+        Some(description)
+    }
+}
+
+fn expression_kind(expression: &TypedExpr) -> &'static str {
+    match expression {
+        TypedExpr::Int { .. } => "Int",
+        TypedExpr::Float { .. } => "Float",
+        TypedExpr::String { .. } => "String",
+        TypedExpr::Block { .. } => "Block",
+        TypedExpr::Pipeline { .. } => "Pipeline",
+        TypedExpr::Var { .. } => "Var",
+        TypedExpr::Fn { .. } => "Anonymous function",
+        TypedExpr::List { .. } => "List",
+        TypedExpr::Call { .. } => "Call",
+        TypedExpr::BinOp { .. } => "Binary operator",
+        TypedExpr::Case { .. } => "Case",
+        TypedExpr::RecordAccess { .. } => "Record access",
+        TypedExpr::ModuleSelect { .. } => "Module select",
+        TypedExpr::Tuple { .. } => "Tuple",
+        TypedExpr::TupleIndex { .. } => "Tuple index",
+        TypedExpr::Todo { .. } => "Todo",
+        TypedExpr::Panic { .. } => "Panic",
+        TypedExpr::BitArray { .. } => "Bit array",
+        TypedExpr::RecordUpdate { .. } => "Record update",
+        TypedExpr::NegateBool { .. } => "Negate bool",
+        TypedExpr::NegateInt { .. } => "Negate int",
+    }
+}
+
+fn statement_kind<T, E>(statement: &Statement<T, E>) -> &'static str {
+    match statement {
+        Statement::Expression(_) => "Expression statement",
+        Statement::Assignment(assignment) => match assignment.kind {
+            AssignmentKind::Let => "Let",
+            AssignmentKind::Assert => "Let assert",
+        },
+        Statement::Use(_) => "Use",
+    }
+}
+
+fn definition_kind<T, E, C, P>(definition: &Definition<T, E, C, P>) -> &'static str {
+    match definition {
+        Definition::Function(_) => "Function",
+        Definition::TypeAlias(_) => "Type alias",
+        Definition::CustomType(_) => "Custom type",
+        Definition::Import(_) => "Import",
+        Definition::ModuleConstant(_) => "Module constant",
+    }
+}
+
+fn pattern_kind(pattern: &TypedPattern) -> &'static str {
+    match pattern {
+        Pattern::Int { .. } => "Int",
+        Pattern::Float { .. } => "Float",
+        Pattern::String { .. } => "String",
+        Pattern::Variable { .. } => "Variable",
+        Pattern::VarUsage { .. } => "Variable usage",
+        Pattern::Assign { .. } => "Assign",
+        Pattern::Discard { .. } => "Discard",
+        Pattern::List { .. } => "List",
+        Pattern::Constructor { .. } => "Constructor",
+        Pattern::Tuple { .. } => "Tuple",
+        Pattern::BitArray { .. } => "Bit array",
+        Pattern::StringPrefix { .. } => "String prefix",
+    }
+}
+
+/// The locations of the patterns of every case clause.
+struct ClausePatterns {
+    locations: Vec<SrcSpan>,
+}
+
+impl<'ast> Visit<'ast> for ClausePatterns {
+    fn visit_expr(&mut self, expr: &'ast TypedExpr) {
+        if let TypedExpr::Case { clauses, .. } = expr {
+            let patterns = clauses
+                .iter()
+                .flat_map(|clause| {
+                    std::iter::once(&clause.pattern).chain(&clause.alternative_patterns)
+                })
+                .flatten();
+            self.locations
+                .extend(patterns.map(|pattern| pattern.location()));
+        }
+    }
+}
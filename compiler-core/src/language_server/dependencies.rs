@@ -0,0 +1,142 @@
+use std::collections::HashMap;
+
+use crate::{
+    analyse::{infer_module, TargetSupport},
+    build::{Module, Origin},
+    io::{CommandExecutor, FileSystemReader, FileSystemWriter},
+    language_server::compiler::{LspProjectCompiler, ModuleSourceInformation},
+    line_numbers::LineNumbers,
+    parse::parse_module,
+    warning::TypeWarningEmitter,
+    STDLIB_PACKAGE_NAME,
+};
+use camino::Utf8PathBuf;
+use ecow::EcoString;
+use hexpm::version::Version;
+use itertools::Itertools;
+
+/// A package in the project's manifest.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PackageSummary {
+    pub name: EcoString,
+    pub version: Version,
+    /// Whether the package is only needed by the root package's
+    /// dev-dependencies.
+    pub dev_dependency: bool,
+}
+
+impl<IO> LspProjectCompiler<IO>
+where
+    IO: CommandExecutor + FileSystemWriter + FileSystemReader + Clone,
+{
+    /// Every package in the manifest, the direct and indirect dependencies
+    /// of the root package, sorted by name.
+    pub fn dependency_packages(&self) -> Vec<PackageSummary> {
+        let dev_dependencies = self.dev_dependency_packages();
+        self.project_compiler
+            .packages
+            .values()
+            .map(|package| PackageSummary {
+                name: package.name.clone(),
+                version: package.version.clone(),
+                dev_dependency: dev_dependencies.contains(&package.name),
+            })
+            .sorted_by(|a, b| a.name.cmp(&b.name))
+            .collect()
+    }
+
+    /// The name and version of the package that a module importable by the
+    /// root package belongs to, for showing where a dependency's code comes
+    /// from. The modules of the root package give the root package.
+    pub fn module_package(&self, module: &str) -> Option<(EcoString, EcoString)> {
+        let package = &self.get_module_inferface(module)?.package;
+        let config = &self.project_compiler.config;
+        let (name, version) = if *package == config.name {
+            (&config.name, &config.version)
+        } else {
+            let package = self.project_compiler.packages.get(package.as_str())?;
+            (&package.name, &package.version)
+        };
+        Some((name.clone(), version.to_string().into()))
+    }
+
+    /// The directory containing the source modules of the `gleam_stdlib`
+    /// package, which is in the build directory unless the package is a path
+    /// dependency. Returns `None` if the standard library is not a dependency
+    /// of the root package.
+    pub fn stdlib_source_root(&self) -> Option<Utf8PathBuf> {
+        let compiler = &self.project_compiler;
+        let package = compiler.packages.get(STDLIB_PACKAGE_NAME)?;
+        compiler
+            .package_root(package)
+            .ok()
+            .map(|root| root.join("src"))
+    }
+
+    /// The typed AST of a module from one of the root package's
+    /// dependencies, for navigating within library code.
+    ///
+    /// Dependencies are compiled without keeping their typed AST, so the
+    /// first time a module is requested it is type checked again against the
+    /// already compiled interfaces of the modules it imports. Returns `None`
+    /// if the module is not from a dependency or could not be compiled.
+    pub fn load_dependency_module(&mut self, module: &str) -> Option<&Module> {
+        if !self.dependency_modules.contains_key(module) {
+            let compiled = self.compile_dependency_module(module)?;
+            let path = compiled.input_path.to_string();
+            let line_numbers = LineNumbers::new(&compiled.code);
+            let source = ModuleSourceInformation { path, line_numbers };
+            _ = self.sources.insert(compiled.name.clone(), source);
+            _ = self
+                .dependency_modules
+                .insert(compiled.name.clone(), compiled);
+        }
+        self.dependency_modules.get(module)
+    }
+
+    fn compile_dependency_module(&self, module: &str) -> Option<Module> {
+        let compiler = &self.project_compiler;
+        let package = &self.get_module_inferface(module)?.package;
+        let manifest_package = compiler.packages.get(package.as_str())?;
+        let path = compiler
+            .package_root(manifest_package)
+            .ok()?
+            .join("src")
+            .join(module)
+            .with_extension("gleam");
+        let code: EcoString = compiler.io.read(&path).ok()?.into();
+        let mtime = compiler.io.modification_time(&path).ok()?;
+
+        let parsed = parse_module(&code).ok()?;
+        let mut ast = parsed.module;
+        ast.name = module.into();
+        let target = compiler.target();
+        let dependencies = ast.dependencies(target);
+
+        // Warnings from dependencies are not fixable by the programmer so
+        // they are discarded.
+        let ast = infer_module::<()>(
+            target,
+            &compiler.ids,
+            ast,
+            Origin::Src,
+            package,
+            compiler.get_importable_modules(),
+            &TypeWarningEmitter::null(),
+            &HashMap::new(),
+            TargetSupport::NotEnforced,
+        )
+        .ok()?;
+
+        Some(Module {
+            name: module.into(),
+            code,
+            mtime,
+            input_path: path,
+            origin: Origin::Src,
+            ast,
+            extra: parsed.extra,
+            dependencies,
+        })
+    }
+}
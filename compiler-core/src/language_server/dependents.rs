@@ -0,0 +1,238 @@
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+};
+
+use crate::{
+    analyse::{infer_module, TargetSupport},
+    ast::Definition,
+    build::{Mode, Origin},
+    diagnostic::Diagnostic,
+    io::{CommandExecutor, FileSystemReader, FileSystemWriter},
+    language_server::{compiler::LspProjectCompiler, WarningKind},
+    parse::parse_module,
+    type_::ModuleInterface,
+    warning::{TypeWarningEmitter, VectorWarningEmitterIO, WarningEmitter},
+    Error,
+};
+use camino::Utf8PathBuf;
+use ecow::EcoString;
+use itertools::Itertools;
+
+impl<IO> LspProjectCompiler<IO>
+where
+    IO: CommandExecutor + FileSystemWriter + FileSystemReader + Clone,
+{
+    /// Type check the modules of the root package that import the changed
+    /// module, directly or through other modules, against the interface the
+    /// changed module has now, giving the diagnostics of each of them. This
+    /// is quicker than compiling the whole root package to find out what a
+    /// change to the public API of a module breaks.
+    ///
+    /// The changed module and its dependents are parsed from their current
+    /// source and checked in the order they import each other, each against
+    /// the interfaces of the modules checked before it and those of the most
+    /// recent compilation for the rest. Which modules import which is taken
+    /// from the most recent compilation. Nothing is kept, so the modules are
+    /// only updated by the next full compilation. A dependent without any
+    /// problems is given with no diagnostics.
+    pub fn recompile_dependents(
+        &mut self,
+        changed: &EcoString,
+    ) -> Vec<(EcoString, Vec<Diagnostic>)> {
+        let mut interfaces = self.project_compiler.get_importable_modules().clone();
+        let mut results = vec![];
+        for module in self.dependents_in_order(changed) {
+            let diagnostics = self.check_module(&module, &mut interfaces);
+            if module != *changed {
+                results.push((module, diagnostics));
+            }
+        }
+        results
+    }
+
+    /// Type check one module of the root package from the given source, such
+    /// as the unsaved contents of the file being edited, giving its
+    /// diagnostics. The module is checked against the interfaces of the other
+    /// modules from the most recent compilation, and the modules that import
+    /// it are not checked, so this is much quicker than a full compilation but
+    /// cannot find the problems a change causes elsewhere.
+    ///
+    /// A module without a source file in the root package is checked as if it
+    /// were in `src`. Nothing is kept, so the module is only updated by the
+    /// next full compilation.
+    pub fn check_active_module(&mut self, module: &str, source: &str) -> Vec<Diagnostic> {
+        let module = EcoString::from(module);
+        let (path, origin) = self.root_module_path(&module).unwrap_or_else(|| {
+            let path = self
+                .project_compiler
+                .paths
+                .src_directory()
+                .join(module.as_str());
+            (path.with_extension("gleam"), Origin::Src)
+        });
+        let mut interfaces = self.project_compiler.get_importable_modules().clone();
+        self.check_source(&module, path, source.into(), origin, &mut interfaces)
+    }
+
+    /// The changed module and every root package module that depends on it,
+    /// with each module after those it imports.
+    fn dependents_in_order(&self, changed: &EcoString) -> Vec<EcoString> {
+        let target = self.project_compiler.target();
+        let imports: HashMap<&EcoString, Vec<&EcoString>> = self
+            .untyped_modules
+            .iter()
+            .map(|(name, module)| {
+                let imported = module
+                    .iter_statements(target)
+                    .filter_map(|definition| match definition {
+                        Definition::Import(import) => Some(&import.module),
+                        _ => None,
+                    })
+                    .collect();
+                (name, imported)
+            })
+            .collect();
+
+        let mut affected = HashSet::from([changed]);
+        let mut to_visit = vec![changed];
+        while let Some(module) = to_visit.pop() {
+            for (importer, imported) in &imports {
+                if imported.contains(&module) && affected.insert(*importer) {
+                    to_visit.push(*importer);
+                }
+            }
+        }
+
+        fn visit<'a>(
+            module: &'a EcoString,
+            imports: &HashMap<&EcoString, Vec<&'a EcoString>>,
+            affected: &HashSet<&EcoString>,
+            visited: &mut HashSet<&'a EcoString>,
+            order: &mut Vec<EcoString>,
+        ) {
+            if !visited.insert(module) {
+                return;
+            }
+            for imported in imports.get(module).into_iter().flatten() {
+                if affected.contains(imported) {
+                    visit(imported, imports, affected, visited, order);
+                }
+            }
+            order.push(module.clone());
+        }
+
+        let mut visited = HashSet::new();
+        let mut order = vec![];
+        visit(changed, &imports, &affected, &mut visited, &mut order);
+        for module in affected.iter().sorted() {
+            visit(module, &imports, &affected, &mut visited, &mut order);
+        }
+        order
+    }
+
+    /// Type check a module of the root package from its current source,
+    /// adding its interface to the interfaces if it type checks.
+    fn check_module(
+        &self,
+        module: &EcoString,
+        interfaces: &mut im::HashMap<EcoString, ModuleInterface>,
+    ) -> Vec<Diagnostic> {
+        let Some((path, origin)) = self.root_module_path(module) else {
+            return vec![];
+        };
+        let Ok(code) = self.project_compiler.io.read(&path) else {
+            return vec![];
+        };
+        self.check_source(module, path, code.into(), origin, interfaces)
+    }
+
+    /// Type check the code of a module against the interfaces, adding its
+    /// interface to them if it type checks.
+    fn check_source(
+        &self,
+        module: &EcoString,
+        path: Utf8PathBuf,
+        code: EcoString,
+        origin: Origin,
+        interfaces: &mut im::HashMap<EcoString, ModuleInterface>,
+    ) -> Vec<Diagnostic> {
+        let mut ast = match parse_module(&code) {
+            Ok(parsed) => parsed.module,
+            Err(error) => {
+                let error = Error::Parse {
+                    path,
+                    src: code,
+                    error,
+                };
+                return vec![error.to_diagnostic()];
+            }
+        };
+        ast.name = module.clone();
+
+        let compiler = &self.project_compiler;
+        let warnings = Arc::new(VectorWarningEmitterIO::new());
+        let emitter = TypeWarningEmitter::new(
+            path.clone(),
+            code.clone(),
+            WarningEmitter::new(warnings.clone()),
+        );
+        let direct_dependencies = compiler
+            .config
+            .dependencies_for(Mode::Lsp)
+            .unwrap_or_default();
+        let result = infer_module(
+            compiler.target(),
+            &compiler.ids,
+            ast,
+            origin,
+            &compiler.config.name,
+            interfaces,
+            &emitter,
+            &direct_dependencies,
+            TargetSupport::Enforced,
+        );
+
+        let mut diagnostics = warnings
+            .take()
+            .into_iter()
+            .filter(|warning| {
+                self.settings
+                    .warning_level
+                    .includes(WarningKind::of(warning))
+            })
+            .map(|warning| warning.to_diagnostic())
+            .collect_vec();
+        match result {
+            Ok(typed) => _ = interfaces.insert(module.clone(), typed.type_info),
+            Err(error) => {
+                let error = Error::Type {
+                    path,
+                    src: code,
+                    error,
+                };
+                diagnostics.push(error.to_diagnostic());
+            }
+        }
+        diagnostics
+    }
+
+    /// The path and origin of the source file of a module of the root
+    /// package.
+    pub(super) fn root_module_path(&self, module: &str) -> Option<(Utf8PathBuf, Origin)> {
+        let compiler = &self.project_compiler;
+        let paths = &compiler.paths;
+        let extra_directories = compiler
+            .root_source_directories
+            .iter()
+            .map(|directory| (paths.root().join(directory), Origin::Src));
+        [
+            (paths.src_directory(), Origin::Src),
+            (paths.test_directory(), Origin::Test),
+        ]
+        .into_iter()
+        .chain(extra_directories)
+        .map(|(directory, origin)| (directory.join(module).with_extension("gleam"), origin))
+        .find(|(path, _)| compiler.io.is_file(path))
+    }
+}
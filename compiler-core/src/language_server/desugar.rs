@@ -0,0 +1,267 @@
+use crate::{
+    ast::{SrcSpan, Statement, TypedArg, TypedExpr, TypedStatement, USE_ASSIGNMENT_VARIABLE},
+    language_server::{
+        compiler::LspProjectCompiler,
+        src_span_to_lsp_range,
+        visit::{walk_module, Visit},
+    },
+    line_numbers::LineNumbers,
+};
+use itertools::Itertools;
+use lsp_types::TextEdit;
+
+impl<IO> LspProjectCompiler<IO> {
+    /// Rewrite the `use` expression whose line contains the byte index as a
+    /// call to its function, passing the rest of the block as a callback.
+    ///
+    /// ```gleam
+    /// use x <- result.try(get())
+    /// Ok(x + 1)
+    /// ```
+    ///
+    /// becomes
+    ///
+    /// ```gleam
+    /// result.try(get(), fn(x) {
+    ///   Ok(x + 1)
+    /// })
+    /// ```
+    ///
+    /// This is the call Gleam desugars `use` into, so the types and behaviour
+    /// of the code are unchanged.
+    pub fn desugar_use(&self, module: &str, byte_index: u32) -> Option<TextEdit> {
+        let module = self.modules.get(module)?;
+        let code = module.code.as_str();
+        let mut finder = UseFinder {
+            code,
+            byte_index,
+            found: None,
+        };
+        walk_module(&module.ast, &mut finder);
+        let use_ = finder.found?;
+
+        let indent = line_indent(code, use_.location.start);
+        let inner_indent = format!("{indent}  ");
+
+        // Assignments of patterns other than variables are made at the start
+        // of the callback, now with an argument that can be referenced.
+        let mut parameters = vec![];
+        let mut bindings = String::new();
+        for index in 0..use_.arguments.len() {
+            let pattern = assignment_text(code, &use_, index);
+            if use_.destructured.contains(&index) {
+                let name = format!("use_{index}");
+                bindings.push_str(&format!("{inner_indent}let {pattern} = {name}\n"));
+                parameters.push(name);
+            } else {
+                parameters.push(pattern.to_string());
+            }
+        }
+
+        let body = text(code, use_.header_end, use_.location.end);
+        let callback = format!(
+            "fn({}) {{\n{bindings}{}\n{indent}}}",
+            parameters.join(", "),
+            reindent(body, &inner_indent),
+        );
+
+        let call = text(code, use_.function.end, use_.header_end).trim_end();
+        let function = text(code, use_.location.start, use_.function.end);
+        let function = function
+            .rsplit_once("<-")
+            .map_or(function, |(_, function)| function)
+            .trim_start();
+        let arguments = call.strip_prefix('(').and_then(|c| c.strip_suffix(')'));
+        let new_text = match (arguments, use_.first_label) {
+            // The callback goes after the positional arguments, which must all
+            // come before the first labelled argument.
+            (Some(_), Some(label)) => {
+                let before = text(code, use_.function.end, label).trim_end();
+                let after = text(code, label, use_.header_end).trim_end();
+                let separator = if before.ends_with('(') { "" } else { " " };
+                format!("{function}{before}{separator}{callback}, {after}")
+            }
+            (Some(arguments), None) => {
+                let arguments = arguments.trim_end().trim_end_matches(',');
+                if arguments.trim().is_empty() {
+                    format!("{function}({callback})")
+                } else {
+                    format!("{function}({arguments}, {callback})")
+                }
+            }
+            // `use <- f` calls the function with only the callback.
+            (None, _) => format!("{function}{call}({callback})"),
+        };
+
+        let line_numbers = LineNumbers::new(code);
+        Some(TextEdit {
+            range: src_span_to_lsp_range(use_.location, &line_numbers),
+            new_text,
+        })
+    }
+}
+
+/// The parts of the source of a `use` expression.
+struct FoundUse<'ast> {
+    /// From the `use` keyword to the end of the block.
+    location: SrcSpan,
+    /// The start and end of the function that the callback is given to.
+    function: SrcSpan,
+    /// The end of the `use` line, before the expressions that become the body
+    /// of the callback.
+    header_end: u32,
+    /// The start of the first labelled argument given to the function.
+    first_label: Option<u32>,
+    arguments: &'ast [TypedArg],
+    /// The indexes of the arguments that are patterns rather than variables.
+    destructured: Vec<usize>,
+}
+
+struct UseFinder<'a, 'ast> {
+    code: &'a str,
+    byte_index: u32,
+    found: Option<FoundUse<'ast>>,
+}
+
+impl<'a, 'ast> Visit<'ast> for UseFinder<'a, 'ast> {
+    fn visit_expr(&mut self, expr: &'ast TypedExpr) {
+        let TypedExpr::Call {
+            location,
+            fun,
+            args,
+            ..
+        } = expr
+        else {
+            return;
+        };
+        let Some(TypedExpr::Fn {
+            args: arguments,
+            body,
+            ..
+        }) = args.iter().find(|arg| arg.implicit).map(|arg| &arg.value)
+        else {
+            return;
+        };
+
+        let (destructured, statements): (Vec<_>, Vec<_>) =
+            body.iter().partition(|statement| is_use_binding(statement));
+        let destructured = destructured
+            .iter()
+            .filter_map(|statement| {
+                let location = statement.location();
+                arguments
+                    .iter()
+                    .position(|argument| argument.location == location)
+            })
+            .collect_vec();
+
+        // The `use` line ends before its first following statement. A `use`
+        // with no following statements is given a `todo` at its own location.
+        let Some(first) = statements.first() else {
+            return;
+        };
+        if first.location().start == location.start {
+            return;
+        }
+        let header_end = header_end(self.code, location.start, first.location().start);
+        if !(location.start..=header_end).contains(&self.byte_index) {
+            return;
+        }
+
+        let function = fun.location();
+        let first_label = args
+            .iter()
+            .filter(|arg| !arg.implicit && arg.label.is_some())
+            .map(|arg| arg.location.start)
+            .min();
+        self.found = Some(FoundUse {
+            location: *location,
+            function,
+            header_end,
+            first_label,
+            arguments,
+            destructured,
+        });
+    }
+}
+
+/// The source of an assignment of a `use`. The location of an assignment may
+/// not include all of its annotation, so it runs up to the next assignment or
+/// the `<-`.
+fn assignment_text<'a>(code: &'a str, use_: &FoundUse<'_>, index: usize) -> &'a str {
+    let Some(argument) = use_.arguments.get(index) else {
+        return "";
+    };
+    let end = match use_.arguments.get(index + 1) {
+        Some(next) => next.location.start,
+        None => {
+            let rest = text(code, argument.location.end, use_.function.start);
+            argument.location.end + rest.find("<-").unwrap_or(rest.len()) as u32
+        }
+    };
+    text(code, argument.location.start, end)
+        .trim_end()
+        .trim_end_matches(',')
+        .trim_end()
+}
+
+/// The assignments that Gleam adds to the start of a callback for the
+/// patterns of a `use`.
+fn is_use_binding(statement: &TypedStatement) -> bool {
+    match statement {
+        Statement::Assignment(assignment) => matches!(
+            assignment.value.as_ref(),
+            TypedExpr::Var { name, .. } if name.starts_with(USE_ASSIGNMENT_VARIABLE)
+        ),
+        _ => false,
+    }
+}
+
+/// The end of the `use` line, leaving out any whitespace or comments between
+/// it and the first following statement.
+fn header_end(code: &str, start: u32, next: u32) -> u32 {
+    let mut header = text(code, start, next).trim_end();
+    while let Some((before, last_line)) = header.rsplit_once('\n') {
+        if !last_line.trim_start().starts_with("//") {
+            break;
+        }
+        header = before.trim_end();
+    }
+    start + header.len() as u32
+}
+
+/// The whitespace at the start of the line containing the byte index.
+pub(super) fn line_indent(code: &str, byte_index: u32) -> &str {
+    let line_start = text(code, 0, byte_index)
+        .rfind('\n')
+        .map_or(0, |index| index + 1);
+    let line = text(code, line_start as u32, byte_index);
+    let indent = line.len() - line.trim_start().len();
+    text(code, line_start as u32, (line_start + indent) as u32)
+}
+
+/// Indent the statements following a `use` by one more level, as they are
+/// now the body of the callback.
+fn reindent(body: &str, indent: &str) -> String {
+    let body = body.trim_end();
+    let leading = body.len() - body.trim_start().len();
+    let body = match body.get(..leading).and_then(|start| start.rfind('\n')) {
+        Some(newline) => body.get(newline + 1..).unwrap_or(body),
+        None => body.trim_start(),
+    };
+    let current = body.len() - body.trim_start().len();
+    body.lines()
+        .map(|line| {
+            if line.trim().is_empty() {
+                String::new()
+            } else {
+                let line = line.get(current.min(line.len() - line.trim_start().len())..);
+                format!("{indent}{}", line.unwrap_or_default())
+            }
+        })
+        .join("\n")
+}
+
+fn text(code: &str, start: u32, end: u32) -> &str {
+    code.get(start as usize..end as usize).unwrap_or_default()
+}
@@ -0,0 +1,83 @@
+use crate::{
+    ast::{CustomType, Definition, Function, Import, ModuleConstant, TypeAlias, TypedExpr},
+    build::Located,
+    language_server::compiler::LspProjectCompiler,
+    parse::extra::Comment,
+    type_::ValueConstructorVariant,
+};
+use ecow::EcoString;
+use itertools::Itertools;
+
+impl<IO> LspProjectCompiler<IO> {
+    /// The documentation comment of the value or type at the byte index,
+    /// whether it is where it is defined or where it is used, however it was
+    /// imported. Over the module path of an import this is the module's own
+    /// documentation.
+    ///
+    /// Local variables and definitions without a documentation comment have
+    /// none, as do the modules of dependencies that have not been loaded for
+    /// navigation. The module is searched as it was in the last compilation
+    /// in which it compiled.
+    pub fn documentation_at(&self, module: &str, byte_index: u32) -> Option<String> {
+        let module = self.modules.get(module)?;
+        let documentation = match module.find_node(byte_index)? {
+            Located::Expression(TypedExpr::Var { constructor, .. })
+                if matches!(
+                    constructor.variant,
+                    ValueConstructorVariant::LocalVariable { .. }
+                        | ValueConstructorVariant::LocalConstant { .. }
+                ) =>
+            {
+                None
+            }
+            Located::Expression(expression) => expression.get_documentation(),
+            Located::Pattern(pattern) => pattern.get_documentation(),
+            Located::ModuleStatement(Definition::Import(import)) => {
+                return self.import_documentation(&module.code, import, byte_index);
+            }
+            Located::ModuleStatement(
+                Definition::Function(Function { documentation, .. })
+                | Definition::ModuleConstant(ModuleConstant { documentation, .. })
+                | Definition::CustomType(CustomType { documentation, .. })
+                | Definition::TypeAlias(TypeAlias { documentation, .. }),
+            ) => documentation.as_deref(),
+            Located::VariantConstructorDefinition(constructor) => {
+                constructor.documentation.as_deref()
+            }
+            Located::Statement(_) | Located::FunctionBody(_) | Located::Arg(_) => None,
+        };
+        documentation.map(String::from)
+    }
+
+    /// The documentation of the imported module, if the byte index is within
+    /// the module path of the import.
+    fn import_documentation(
+        &self,
+        code: &str,
+        import: &Import<EcoString>,
+        byte_index: u32,
+    ) -> Option<String> {
+        let start = import.location.start;
+        let path_start = start + code.get(start as usize..)?.find(import.module.as_str())? as u32;
+        let path_end = path_start + import.module.len() as u32;
+        if byte_index < path_start || path_end < byte_index {
+            return None;
+        }
+        let imported = self
+            .modules
+            .get(&import.module)
+            .or_else(|| self.dependency_modules.get(&import.module))?;
+        // Module comments are only attached to the AST when generating
+        // documentation, so they are read from the source.
+        let comments = &imported.extra.module_comments;
+        if comments.is_empty() {
+            return None;
+        }
+        Some(
+            comments
+                .iter()
+                .map(|span| Comment::from((span, &imported.code)).content)
+                .join("\n"),
+        )
+    }
+}
@@ -1,33 +1,53 @@
 use crate::{
     ast::{
-        Arg, Definition, Function, Import, ModuleConstant, TypedDefinition, TypedExpr, TypedPattern,
+        Arg, ArgNames, BitArrayOption, BitArraySegment, Definition, Function, Import,
+        ModuleConstant, Pattern, SrcSpan, TypedDefinition, TypedExpr, TypedPattern,
     },
-    build::{Located, Module},
+    build::{Located, Module, Target},
     config::PackageConfig,
     io::{CommandExecutor, FileSystemReader, FileSystemWriter},
     language_server::{
-        compiler::LspProjectCompiler, files::FileSystemProxy, progress::ProgressReporter,
+        compiler::{LspProjectCompiler, ModuleTypeError},
+        completion::{
+            best_importable, could_complete_importable, import_edit, rank, typed_prefix,
+            CompletionContext, CompletionTier, GUARD_OPERATORS,
+        },
+        external::DefinitionWithExternal,
+        files::FileSystemProxy,
+        highlights::HighlightKind,
+        import_style::import_at,
+        lsp_warning::LspWarning,
+        progress::ProgressReporter,
+        settings::LspSettings,
+        WarningLevel,
     },
     line_numbers::LineNumbers,
     paths::ProjectPaths,
-    type_::{pretty::Printer, PreludeType, Type, ValueConstructorVariant},
+    type_::{
+        pretty::Printer, ModuleInterface, PreludeType, Type, ValueConstructor,
+        ValueConstructorVariant, PRELUDE_MODULE_NAME,
+    },
     Error, Result, Warning,
 };
 use camino::Utf8PathBuf;
 use ecow::EcoString;
+use itertools::Itertools;
 use lsp::CodeAction;
 use lsp_types::{self as lsp, Hover, HoverContents, MarkedString, Url};
 use std::sync::Arc;
 use strum::IntoEnumIterator;
 
 use super::{
-    code_action::CodeActionBuilder, src_span_to_lsp_range, DownloadDependencies, MakeLocker,
+    code_action::CodeActionBuilder, path_to_uri, src_span_to_lsp_range, DownloadDependencies,
+    MakeLocker,
 };
 
 #[derive(Debug, PartialEq, Eq)]
 pub struct Response<T> {
     pub result: Result<T, Error>,
     pub warnings: Vec<Warning>,
+    /// The warnings that only the language server reports.
+    pub lsp_warnings: Vec<LspWarning>,
     pub compilation: Compilation,
 }
 
@@ -39,10 +59,20 @@ pub enum Compilation {
     No,
 }
 
+/// How the contents of a hover are written, which depends on what the client
+/// is able to display.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum HoverFormat {
+    #[default]
+    Markdown,
+    /// Markdown without its markup, for clients that cannot render it. Code
+    /// blocks are written without their fences and links as their text
+    /// followed by their address.
+    PlainText,
+}
+
 #[derive(Debug)]
 pub struct LanguageServerEngine<IO, Reporter> {
-    pub(crate) paths: ProjectPaths,
-
     /// A compiler for the project that supports repeat compilation of the root
     /// package.
     /// In the event the the project config changes this will need to be
@@ -73,12 +103,19 @@ where
     // IO to be supplied from inside of gleam-core
     Reporter: ProgressReporter + Clone + 'a,
 {
+    /// The project is built in `build_directory` if one is given, rather
+    /// than in the project's own build directory.
     pub fn new(
         config: PackageConfig,
         progress_reporter: Reporter,
         io: FileSystemProxy<IO>,
         paths: ProjectPaths,
+        build_directory: Option<Utf8PathBuf>,
     ) -> Result<Self> {
+        let paths = match build_directory {
+            Some(directory) => paths.with_lsp_build_directory(directory),
+            None => paths,
+        };
         let locker = io.inner().make_locker(&paths, config.target)?;
 
         // Download dependencies to ensure they are up-to-date for this new
@@ -90,8 +127,15 @@ where
         // NOTE: This must come after the progress reporter has finished!
         let manifest = manifest?;
 
-        let compiler =
-            LspProjectCompiler::new(manifest, config, paths.clone(), io.clone(), locker)?;
+        let compiler = LspProjectCompiler::new(
+            manifest,
+            config,
+            paths,
+            io.clone(),
+            locker,
+            vec![],
+            WarningLevel::All,
+        )?;
 
         let hex_deps = compiler
             .project_compiler
@@ -111,7 +155,6 @@ where
             compiled_since_last_feedback: false,
             progress_reporter,
             compiler,
-            paths,
             hex_deps,
         })
     }
@@ -134,6 +177,17 @@ where
         Ok(())
     }
 
+    /// Use the user's new settings, recompiling the project only if a setting
+    /// that affects compilation has changed.
+    pub fn apply_settings(&mut self, settings: LspSettings) -> Response<()> {
+        self.respond(|this| {
+            if this.compiler.set_settings(settings)? {
+                this.compile()?;
+            }
+            Ok(())
+        })
+    }
+
     fn take_warnings(&mut self) -> Vec<Warning> {
         self.compiler.take_warnings()
     }
@@ -158,81 +212,335 @@ where
     pub fn goto_definition(
         &mut self,
         params: lsp::GotoDefinitionParams,
-    ) -> Response<Option<lsp::Location>> {
+    ) -> Response<Option<lsp::GotoDefinitionResponse>> {
         self.respond(|this| {
             let params = params.text_document_position_params;
-            let (line_numbers, node) = match this.node_at_position(&params) {
-                Some(location) => location,
-                None => return Ok(None),
+            let mut locations = this
+                .definitions(&params)
+                .into_iter()
+                .filter_map(|(module, span)| this.definition_location(&params, &module, span))
+                .collect_vec();
+            Ok(if locations.len() > 1 {
+                Some(lsp::GotoDefinitionResponse::Array(locations))
+            } else {
+                locations.pop().map(lsp::GotoDefinitionResponse::Scalar)
+            })
+        })
+    }
+
+    /// The definition of the value at the position, as with `goto_definition`,
+    /// along with the function that implements it if it is an external
+    /// function. If there is more than one definition the first is given.
+    pub fn definition_with_external(
+        &mut self,
+        params: lsp::GotoDefinitionParams,
+    ) -> Response<Option<DefinitionWithExternal>> {
+        self.respond(|this| {
+            let params = params.text_document_position_params;
+            let Some((module, span)) = this.definitions(&params).into_iter().next() else {
+                return Ok(None);
             };
+            let Some(location) = this.definition_location(&params, &module, span) else {
+                return Ok(None);
+            };
+            let span = this.compiled_span(&params.text_document.uri, &module, span);
+            let external = this.compiler.external_target(&module, span);
+            Ok(Some(DefinitionWithExternal { location, external }))
+        })
+    }
 
-            let location = match node.definition_location() {
-                Some(location) => location,
-                None => return Ok(None),
+    /// The module and location of each definition of the node at the
+    /// position. There is usually one, but a module that failed to compile
+    /// because a name is defined more than once has a definition for each.
+    /// The module qualifier of a qualified name is defined at the start of
+    /// the module it refers to.
+    ///
+    /// Locations in the module of the position are in its code as the client
+    /// has it now, which may have changed since the module last compiled.
+    pub fn definitions(
+        &self,
+        params: &lsp::TextDocumentPositionParams,
+    ) -> Vec<(EcoString, SrcSpan)> {
+        if let Some(module) = self.module_qualifier_at_position(params) {
+            return vec![(module, SrcSpan::new(0, 0))];
+        }
+        let Some((_, node)) = self.node_at_position(params) else {
+            return vec![];
+        };
+        let Some(location) = node.definition_location() else {
+            return vec![];
+        };
+        let own_module = self.module_name_for_uri(&params.text_document.uri);
+        let module = match location.module {
+            Some(module) => module.into(),
+            None => match &own_module {
+                Some(module) => module.clone(),
+                None => return vec![],
+            },
+        };
+        let mut span = location.span;
+        if own_module.as_ref() == Some(&module) {
+            if let Some(compiled) = self.navigable_module_for_uri(&params.text_document.uri) {
+                let code = self.compiler.current_code(compiled);
+                span = SrcSpan::new(
+                    moved_offset(&compiled.code, &code, span.start),
+                    moved_offset(&compiled.code, &code, span.end),
+                );
+            }
+        }
+
+        let mut definitions = vec![(module.clone(), span)];
+        for duplicate in self.compiler.duplicate_definitions(&module, span) {
+            definitions.push((module.clone(), duplicate));
+        }
+        definitions
+    }
+
+    /// The imported module that the module qualifier at the position refers
+    /// to.
+    fn module_qualifier_at_position(
+        &self,
+        params: &lsp::TextDocumentPositionParams,
+    ) -> Option<EcoString> {
+        let module = self.navigable_module_for_uri(&params.text_document.uri)?;
+        let line_numbers = LineNumbers::new(&module.code);
+        let position = params.position;
+        let byte_index = line_numbers.byte_index(position.line, position.character);
+        self.compiler.module_qualifier_at(&module.name, byte_index)
+    }
+
+    /// The location to navigate to for a definition in the module.
+    fn definition_location(
+        &mut self,
+        params: &lsp::TextDocumentPositionParams,
+        module: &EcoString,
+        span: SrcSpan,
+    ) -> Option<lsp::Location> {
+        let uri = &params.text_document.uri;
+        if self.module_name_for_uri(uri).as_ref() == Some(module) {
+            // The location is in the code that the client has now.
+            let code = self
+                .compiler
+                .current_code(self.navigable_module_for_uri(uri)?);
+            let range = src_span_to_lsp_range(span, &LineNumbers::new(&code));
+            return Some(lsp::Location {
+                uri: uri.clone(),
+                range,
+            });
+        }
+
+        // Modules from dependencies are only compiled for navigation when
+        // something within them is requested.
+        if self.compiler.get_source(module).is_none() {
+            _ = self.compiler.load_dependency_module(module);
+        }
+        let module = self.compiler.get_source(module)?;
+        let uri =
+            Url::parse(&format!("file:///{}", &module.path)).expect("goto definition URL parse");
+        let range = src_span_to_lsp_range(span, &module.line_numbers);
+        Some(lsp::Location { uri, range })
+    }
+
+    /// A span given by `definitions` for the module of the document, moved
+    /// back into the code that the module last compiled with. Spans in
+    /// other modules are returned as they are.
+    fn compiled_span(&self, uri: &Url, module: &EcoString, span: SrcSpan) -> SrcSpan {
+        let compiled = match self.navigable_module_for_uri(uri) {
+            Some(compiled) if &compiled.name == module => compiled,
+            _ => return span,
+        };
+        let code = self.compiler.current_code(compiled);
+        SrcSpan::new(
+            moved_offset(&code, &compiled.code, span.start),
+            moved_offset(&code, &compiled.code, span.end),
+        )
+    }
+
+    /// The number of references to each module level value of the module,
+    /// shown above its definition.
+    pub fn code_lens(
+        &mut self,
+        params: lsp::CodeLensParams,
+    ) -> Response<Option<Vec<lsp::CodeLens>>> {
+        self.respond(|this| {
+            let Some(module) = this.module_name_for_uri(&params.text_document.uri) else {
+                return Ok(None);
             };
+            Ok(Some(this.compiler.reference_count_lenses(&module)))
+        })
+    }
 
-            let (uri, line_numbers) = match location.module {
-                None => (params.text_document.uri, &line_numbers),
-                Some(name) => {
-                    let module = match this.compiler.get_source(name) {
-                        Some(module) => module,
-                        // TODO: support goto definition for functions defined in
-                        // different packages. Currently it is not possible as the
-                        // required LineNumbers and source file path information is
-                        // not stored in the module metadata.
-                        None => return Ok(None),
-                    };
-                    let url = Url::parse(&format!("file:///{}", &module.path))
-                        .expect("goto definition URL parse");
-                    (url, &module.line_numbers)
-                }
+    /// A code lens given by [`Self::code_lens`], updated for the module as it
+    /// was last compiled.
+    pub fn code_lens_resolve(&mut self, lens: lsp::CodeLens) -> Response<lsp::CodeLens> {
+        self.respond(|this| Ok(this.compiler.resolve_reference_count_lens(lens)))
+    }
+
+    /// The ranges of the local variable at the position, which the client
+    /// can edit together to rename it.
+    pub fn linked_editing_range(
+        &mut self,
+        params: lsp::LinkedEditingRangeParams,
+    ) -> Response<Option<lsp::LinkedEditingRanges>> {
+        self.respond(|this| {
+            let params = params.text_document_position_params;
+            let module = match this.module_for_uri(&params.text_document.uri) {
+                Some(module) => module,
+                None => return Ok(None),
             };
-            let range = src_span_to_lsp_range(location.span, line_numbers);
+            let line_numbers = LineNumbers::new(&module.code);
+            let byte_index =
+                line_numbers.byte_index(params.position.line, params.position.character);
+            let ranges = match this
+                .compiler
+                .linked_editing_ranges(&module.name, byte_index)
+            {
+                Some(ranges) => ranges,
+                None => return Ok(None),
+            };
+            Ok(Some(lsp::LinkedEditingRanges {
+                ranges: ranges
+                    .into_iter()
+                    .map(|span| src_span_to_lsp_range(span, &line_numbers))
+                    .collect(),
+                word_pattern: None,
+            }))
+        })
+    }
 
-            Ok(Some(lsp::Location { uri, range }))
+    /// The occurrences within the document of the symbol at the position,
+    /// with where it is defined as a write and where it is used as a read.
+    pub fn document_highlight(
+        &mut self,
+        params: lsp::DocumentHighlightParams,
+    ) -> Response<Option<Vec<lsp::DocumentHighlight>>> {
+        self.respond(|this| {
+            let params = params.text_document_position_params;
+            let module = match this.module_for_uri(&params.text_document.uri) {
+                Some(module) => module,
+                None => return Ok(None),
+            };
+            let line_numbers = LineNumbers::new(&module.code);
+            let byte_index =
+                line_numbers.byte_index(params.position.line, params.position.character);
+            let highlights = this
+                .compiler
+                .document_highlights(&module.name, byte_index)
+                .into_iter()
+                .map(|(span, kind)| lsp::DocumentHighlight {
+                    range: src_span_to_lsp_range(span, &line_numbers),
+                    kind: Some(match kind {
+                        HighlightKind::Read => lsp::DocumentHighlightKind::READ,
+                        HighlightKind::Write => lsp::DocumentHighlightKind::WRITE,
+                    }),
+                })
+                .collect();
+            Ok(Some(highlights))
         })
     }
 
+    /// Completions for the position. When `snippets` is true, which it should
+    /// only be if the client supports snippets, functions are completed as a
+    /// call with a placeholder for each argument.
     pub fn completion(
         &mut self,
         params: lsp::TextDocumentPositionParams,
+        snippets: bool,
     ) -> Response<Option<Vec<lsp::CompletionItem>>> {
         self.respond(|this| {
-            let module = match this.module_for_uri(&params.text_document.uri) {
+            // Completions are most needed while a module is being edited and so
+            // does not compile, so the last version that did is used unless
+            // the settings say otherwise.
+            let module = match this.navigable_module_for_uri(&params.text_document.uri) {
                 Some(m) => m,
                 None => return Ok(None),
             };
 
-            let line_numbers = LineNumbers::new(&module.code);
+            // The module may have been edited since it last compiled, in
+            // which case the position is within the current source.
+            let code = this.compiler.current_code(module);
+            let line_numbers = LineNumbers::new(&code);
             let byte_index =
                 line_numbers.byte_index(params.position.line, params.position.character);
+            let prefix = typed_prefix(&code, byte_index);
 
-            let Some(found) = module.find_node(byte_index) else {
-                return Ok(None);
-            };
-
-            let completions = match found {
-                Located::Pattern(_pattern) => None,
-
-                Located::Statement(_) | Located::Expression(_) => {
-                    Some(this.completion_values(module))
+            let completions = match this.compiler.completion_context(&module.name, byte_index) {
+                CompletionContext::Expression => {
+                    Some(this.completion_values(module, prefix, snippets))
                 }
 
-                Located::ModuleStatement(Definition::Function(_)) => {
-                    Some(this.completion_types(module))
+                CompletionContext::CallArgument { function, labels } => {
+                    let mut completions =
+                        this.completion_labels(module, &function, &labels, prefix);
+                    completions.extend(this.completion_values(module, prefix, snippets));
+                    Some(completions)
                 }
 
-                Located::FunctionBody(_) => Some(this.completion_values(module)),
+                CompletionContext::Guard {
+                    after_operand: true,
+                    ..
+                } => {
+                    let completions = GUARD_OPERATORS
+                        .iter()
+                        .map(|operator| {
+                            let completion = lsp::CompletionItem {
+                                label: operator.to_string(),
+                                kind: Some(lsp::CompletionItemKind::OPERATOR),
+                                ..Default::default()
+                            };
+                            rank(completion, CompletionTier::Local, prefix)
+                        })
+                        .collect();
+                    Some(completions)
+                }
 
-                Located::ModuleStatement(Definition::TypeAlias(_) | Definition::CustomType(_)) => {
-                    Some(this.completion_types(module))
+                CompletionContext::Guard { variables, .. } => {
+                    let mut completions = variables
+                        .iter()
+                        .map(|name| {
+                            let completion = lsp::CompletionItem {
+                                label: name.to_string(),
+                                kind: Some(lsp::CompletionItemKind::VARIABLE),
+                                ..Default::default()
+                            };
+                            rank(completion, CompletionTier::Local, prefix)
+                        })
+                        .collect_vec();
+                    // Functions cannot be called in a guard.
+                    completions.extend(
+                        this.completion_values(module, prefix, false)
+                            .into_iter()
+                            .filter(|completion| {
+                                completion.kind != Some(lsp::CompletionItemKind::FUNCTION)
+                            }),
+                    );
+                    Some(completions)
                 }
 
-                Located::ModuleStatement(Definition::Import(_) | Definition::ModuleConstant(_)) => {
-                    None
+                CompletionContext::TypeAnnotation => Some(this.completion_types(module, prefix)),
+
+                CompletionContext::QualifiedAccess {
+                    in_type_annotation, ..
+                } => {
+                    let completions = if in_type_annotation {
+                        this.completion_types(module, prefix)
+                    } else {
+                        this.completion_values(module, prefix, snippets)
+                    };
+                    let qualifier = match prefix.rsplit_once('.') {
+                        Some((qualifier, _)) => format!("{qualifier}."),
+                        None => String::new(),
+                    };
+                    let completions = completions
+                        .into_iter()
+                        .filter(|completion| completion.label.starts_with(&qualifier))
+                        .collect();
+                    Some(completions)
                 }
 
-                Located::Arg(_) => None,
+                CompletionContext::Pattern
+                | CompletionContext::Import
+                | CompletionContext::None => None,
             };
 
             Ok(completions)
@@ -242,11 +550,49 @@ where
     pub fn action(&mut self, params: lsp::CodeActionParams) -> Response<Option<Vec<CodeAction>>> {
         self.respond(|this| {
             let mut actions = vec![];
-            let Some(module) = this.module_for_uri(&params.text_document.uri) else {
-                return Ok(None);
+            this.code_action_wrap_expression(&params, &mut actions);
+            this.code_action_make_public(&params, &mut actions);
+            this.code_action_implement_for_target(&params, &mut actions);
+            this.code_action_add_missing_arguments(&params, &mut actions);
+            this.code_action_ambiguous_import(&params, &mut actions);
+            let Some(module) = this.navigable_module_for_uri(&params.text_document.uri) else {
+                return Ok(if actions.is_empty() {
+                    None
+                } else {
+                    Some(actions)
+                });
             };
 
+            // A module that does not compile has the actions of the last
+            // version of it that did, for the code before the first change
+            // made since then, as the spans of that version are still right
+            // for that code.
+            let unchanged = unchanged_until(&module.code, &this.compiler.current_code(module));
+            if params.range.end > unchanged {
+                return Ok(if actions.is_empty() {
+                    None
+                } else {
+                    Some(actions)
+                });
+            }
+            let compiled_actions = actions.len();
+
             code_action_unused_imports(module, &params, &mut actions);
+            this.code_action_unused_variables(module, &params, &mut actions);
+            this.code_action_pipe(module, &params, &mut actions);
+            this.code_action_desugar_use(module, &params, &mut actions);
+            this.code_action_run_test(module, &params, &mut actions);
+            this.code_action_generate_main(module, &params, &mut actions);
+            this.code_action_annotate_return_type(module, &params, &mut actions);
+            this.code_action_label_arguments(module, &params, &mut actions);
+            this.code_action_simplify_boolean_case(module, &params, &mut actions);
+            this.code_action_toggle_import_style(module, &params, &mut actions);
+            let last_good_actions = actions.split_off(compiled_actions);
+            actions.extend(
+                last_good_actions
+                    .into_iter()
+                    .filter(|action| edits_end(action).is_none_or(|end| end <= unchanged)),
+            );
 
             Ok(if actions.is_empty() {
                 None
@@ -259,6 +605,7 @@ where
     fn respond<T>(&mut self, handler: impl FnOnce(&mut Self) -> Result<T>) -> Response<T> {
         let result = handler(self);
         let warnings = self.take_warnings();
+        let lsp_warnings = self.compiler.take_lsp_warnings();
         // TODO: test. Ensure hover doesn't report as compiled
         let compilation = if self.compiled_since_last_feedback {
             let modules = std::mem::take(&mut self.modules_compiled_since_last_feedback);
@@ -270,11 +617,16 @@ where
         Response {
             result,
             warnings,
+            lsp_warnings,
             compilation,
         }
     }
 
-    pub fn hover(&mut self, params: lsp::HoverParams) -> Response<Option<Hover>> {
+    pub fn hover(
+        &mut self,
+        params: lsp::HoverParams,
+        format: HoverFormat,
+    ) -> Response<Option<Hover>> {
         self.respond(|this| {
             let params = params.text_document_position_params;
 
@@ -283,7 +635,28 @@ where
                 None => return Ok(None),
             };
 
-            Ok(match found {
+            // Bit array options are not nodes of their own, so they are
+            // found as the bit array they are in.
+            let byte_index = lines.byte_index(params.position.line, params.position.character);
+            let option_hover = match found {
+                Located::Expression(TypedExpr::BitArray { segments, .. }) => {
+                    bit_array_option_at(segments, byte_index)
+                        .map(|option| hover_for_bit_array_option(option, &lines))
+                }
+                Located::Pattern(Pattern::BitArray { segments, .. }) => {
+                    bit_array_option_at(segments, byte_index)
+                        .map(|option| hover_for_bit_array_option(option, &lines))
+                }
+                _ => None,
+            };
+            if let Some(hover) = option_hover {
+                return Ok(match format {
+                    HoverFormat::Markdown => Some(hover),
+                    HoverFormat::PlainText => Some(plain_text_hover(hover)),
+                });
+            }
+
+            let hover = match found {
                 Located::Statement(_) => None, // TODO: hover for statement
                 Located::ModuleStatement(Definition::Function(fun)) => {
                     Some(hover_for_function_head(fun, lines))
@@ -294,7 +667,7 @@ where
                 Located::ModuleStatement(_) => None,
                 Located::Pattern(pattern) => Some(hover_for_pattern(pattern, lines)),
                 Located::Expression(expression) => {
-                    let module = this.module_for_uri(&params.text_document.uri);
+                    let module = this.navigable_module_for_uri(&params.text_document.uri);
 
                     Some(hover_for_expression(
                         expression,
@@ -305,6 +678,11 @@ where
                 }
                 Located::Arg(arg) => Some(hover_for_function_argument(arg, lines)),
                 Located::FunctionBody(_) => None,
+                Located::VariantConstructorDefinition(_) => None,
+            };
+            Ok(match format {
+                HoverFormat::Markdown => hover,
+                HoverFormat::PlainText => hover.map(plain_text_hover),
             })
         })
     }
@@ -314,10 +692,31 @@ where
         params: &lsp::TextDocumentPositionParams,
         module: &'a Module,
     ) -> Option<(LineNumbers, Located<'a>)> {
+        // The position is in the code the client has now, which may have been
+        // changed since the module was compiled.
+        let code = self.compiler.current_code(module);
+        let byte_index =
+            LineNumbers::new(&code).byte_index(params.position.line, params.position.character);
+        let byte_index = moved_offset(&code, &module.code, byte_index);
         let line_numbers = LineNumbers::new(&module.code);
-        let byte_index = line_numbers.byte_index(params.position.line, params.position.character);
-        let node = module.find_node(byte_index);
-        let node = node?;
+
+        // The span of a node contains the bytes from its start up to but not
+        // including its end, so at the boundary between two nodes the one
+        // that starts at the position is found, and a node with an empty span
+        // is never found. If the position is instead just after a node, such
+        // as at the end of a name that has just been typed, and no node
+        // nested within it starts there, the node before the position is
+        // used.
+        let at = module.find_node(byte_index);
+        let before = byte_index
+            .checked_sub(1)
+            .and_then(|index| module.find_node(index))
+            .filter(|before| before.location().end == byte_index);
+        let node = match (at, before) {
+            (Some(at), Some(before)) if encloses(at.location(), before.location()) => before,
+            (None, Some(before)) => before,
+            (at, _) => at?,
+        };
         Some((line_numbers, node))
     }
 
@@ -325,53 +724,421 @@ where
         &self,
         params: &lsp::TextDocumentPositionParams,
     ) -> Option<(LineNumbers, Located<'_>)> {
-        let module = self.module_for_uri(&params.text_document.uri)?;
+        let module = self.navigable_module_for_uri(&params.text_document.uri)?;
         self.module_node_at_position(params, module)
     }
 
+    /// The module as of the most recent compilation, if it compiled then.
     fn module_for_uri(&self, uri: &Url) -> Option<&Module> {
-        use itertools::Itertools;
+        let name = self.module_name_for_uri(uri)?;
+        self.compiler.current_module(&name)
+    }
 
+    /// The module for navigating around, which is the last version of it that
+    /// compiled if it currently does not and the settings allow it.
+    fn navigable_module_for_uri(&self, uri: &Url) -> Option<&Module> {
+        let name = self.module_name_for_uri(uri)?;
+        self.compiler.current_module(&name).or_else(|| {
+            if self.compiler.settings.use_last_good_modules {
+                self.compiler.last_good_module(&name)
+            } else {
+                None
+            }
+        })
+    }
+
+    fn module_name_for_uri(&self, uri: &Url) -> Option<EcoString> {
         // The to_file_path method is available on these platforms
         #[cfg(any(unix, windows, target_os = "redox", target_os = "wasi"))]
-        let path = uri.to_file_path().expect("URL file");
+        let path = Utf8PathBuf::from_path_buf(uri.to_file_path().expect("URL file"))
+            .expect("Non Utf8 Path");
 
         #[cfg(not(any(unix, windows, target_os = "redox", target_os = "wasi")))]
         let path: Utf8PathBuf = uri.path().into();
 
-        let components = path
-            .strip_prefix(self.paths.root())
-            .ok()?
-            .components()
-            .skip(1)
-            .map(|c| c.as_os_str().to_string_lossy());
-        let module_name: EcoString = Itertools::intersperse(components, "/".into())
-            .collect::<String>()
-            .strip_suffix(".gleam")?
-            .into();
+        if path.extension() != Some("gleam") {
+            return None;
+        }
+        self.compiler.module_name_for_path(&path)
+    }
 
-        self.compiler.modules.get(&module_name)
+    fn code_action_pipe(
+        &self,
+        module: &Module,
+        params: &lsp::CodeActionParams,
+        actions: &mut Vec<CodeAction>,
+    ) {
+        let line_numbers = LineNumbers::new(&module.code);
+        let start = params.range.start;
+        let byte_index = line_numbers.byte_index(start.line, start.character);
+        let Some((title, edit)) = self.compiler.pipe_refactoring(&module.name, byte_index) else {
+            return;
+        };
+        CodeActionBuilder::new(title)
+            .kind(lsp_types::CodeActionKind::REFACTOR_REWRITE)
+            .changes(params.text_document.uri.clone(), vec![edit])
+            .push_to(actions);
     }
 
-    fn completion_types<'b>(&'b self, module: &'b Module) -> Vec<lsp::CompletionItem> {
+    fn code_action_desugar_use(
+        &self,
+        module: &Module,
+        params: &lsp::CodeActionParams,
+        actions: &mut Vec<CodeAction>,
+    ) {
+        let line_numbers = LineNumbers::new(&module.code);
+        let start = params.range.start;
+        let byte_index = line_numbers.byte_index(start.line, start.character);
+        let Some(edit) = self.compiler.desugar_use(&module.name, byte_index) else {
+            return;
+        };
+        CodeActionBuilder::new("Desugar use expression")
+            .kind(lsp_types::CodeActionKind::REFACTOR_REWRITE)
+            .changes(params.text_document.uri.clone(), vec![edit])
+            .push_to(actions);
+    }
+
+    /// Run the test function containing the cursor. The client is expected to
+    /// implement the `gleam.runTest` command, which is given the name of the
+    /// module and the function.
+    fn code_action_run_test(
+        &self,
+        module: &Module,
+        params: &lsp::CodeActionParams,
+        actions: &mut Vec<CodeAction>,
+    ) {
+        let line_numbers = LineNumbers::new(&module.code);
+        let start = params.range.start;
+        let byte_index = line_numbers.byte_index(start.line, start.character);
+        let Some((module, function)) = self.compiler.enclosing_test(&module.name, byte_index)
+        else {
+            return;
+        };
+        CodeActionBuilder::new("Run test at cursor")
+            .command(lsp::Command {
+                title: format!("Run {function}"),
+                command: "gleam.runTest".into(),
+                arguments: Some(vec![module.as_str().into(), function.as_str().into()]),
+            })
+            .push_to(actions);
+    }
+
+    /// Add a `main` function to a module without one. This is a source
+    /// action so that it is not suggested everywhere in library modules.
+    fn code_action_generate_main(
+        &self,
+        module: &Module,
+        params: &lsp::CodeActionParams,
+        actions: &mut Vec<CodeAction>,
+    ) {
+        let Some(edit) = self.compiler.generate_main_stub(&module.name) else {
+            return;
+        };
+        CodeActionBuilder::new("Generate `main` function")
+            .kind(lsp_types::CodeActionKind::SOURCE)
+            .changes(params.text_document.uri.clone(), vec![edit])
+            .push_to(actions);
+    }
+
+    /// Write the inferred return type of the function at the cursor as its
+    /// return annotation.
+    fn code_action_annotate_return_type(
+        &self,
+        module: &Module,
+        params: &lsp::CodeActionParams,
+        actions: &mut Vec<CodeAction>,
+    ) {
+        let line_numbers = LineNumbers::new(&module.code);
+        let start = params.range.start;
+        let byte_index = line_numbers.byte_index(start.line, start.character);
+        let Some(edit) = self.compiler.annotate_return_type(&module.name, byte_index) else {
+            return;
+        };
+        CodeActionBuilder::new("Annotate return type")
+            .kind(lsp_types::CodeActionKind::REFACTOR_REWRITE)
+            .changes(params.text_document.uri.clone(), vec![edit])
+            .push_to(actions);
+    }
+
+    /// Label the arguments of the call at the cursor and put them in the
+    /// order the function declares them.
+    fn code_action_label_arguments(
+        &self,
+        module: &Module,
+        params: &lsp::CodeActionParams,
+        actions: &mut Vec<CodeAction>,
+    ) {
+        let line_numbers = LineNumbers::new(&module.code);
+        let start = params.range.start;
+        let byte_index = line_numbers.byte_index(start.line, start.character);
+        let Some(edit) = self
+            .compiler
+            .normalize_call_labels(&module.name, byte_index)
+        else {
+            return;
+        };
+        CodeActionBuilder::new("Label arguments")
+            .kind(lsp_types::CodeActionKind::REFACTOR_REWRITE)
+            .changes(params.text_document.uri.clone(), vec![edit])
+            .push_to(actions);
+    }
+
+    /// Rewrite the case over a `Bool` at the cursor in its simplest form.
+    fn code_action_simplify_boolean_case(
+        &self,
+        module: &Module,
+        params: &lsp::CodeActionParams,
+        actions: &mut Vec<CodeAction>,
+    ) {
+        let line_numbers = LineNumbers::new(&module.code);
+        let start = params.range.start;
+        let byte_index = line_numbers.byte_index(start.line, start.character);
+        let Some(edit) = self
+            .compiler
+            .simplify_boolean_case(&module.name, byte_index)
+        else {
+            return;
+        };
+        CodeActionBuilder::new("Simplify boolean case")
+            .kind(lsp_types::CodeActionKind::REFACTOR_REWRITE)
+            .changes(params.text_document.uri.clone(), vec![edit])
+            .push_to(actions);
+    }
+
+    /// Switch the import at the cursor between qualified and unqualified
+    /// uses of its values.
+    fn code_action_toggle_import_style(
+        &self,
+        module: &Module,
+        params: &lsp::CodeActionParams,
+        actions: &mut Vec<CodeAction>,
+    ) {
+        let line_numbers = LineNumbers::new(&module.code);
+        let start = params.range.start;
+        let byte_index = line_numbers.byte_index(start.line, start.character);
+        let Some(import) = import_at(module, byte_index) else {
+            return;
+        };
+        let Some(edit) = self.compiler.toggle_import_style(&module.name, byte_index) else {
+            return;
+        };
+        let title = if import.unqualified_values.is_empty() {
+            format!(
+                "Import the values used from `{}` unqualified",
+                import.module
+            )
+        } else {
+            format!("Qualify the values imported from `{}`", import.module)
+        };
+        let mut action =
+            CodeActionBuilder::new(&title).kind(lsp_types::CodeActionKind::REFACTOR_REWRITE);
+        for (uri, edits) in edit.changes.unwrap_or_default() {
+            action = action.changes(uri, edits);
+        }
+        action.push_to(actions);
+    }
+
+    /// Fixes for the unused variable warnings the client has given.
+    fn code_action_unused_variables(
+        &self,
+        module: &Module,
+        params: &lsp::CodeActionParams,
+        actions: &mut Vec<CodeAction>,
+    ) {
+        let line_numbers = LineNumbers::new(&module.code);
+        let byte_index =
+            |position: lsp::Position| line_numbers.byte_index(position.line, position.character);
+        let code = lsp::NumberOrString::String("unused_variable".into());
+        for diagnostic in &params.context.diagnostics {
+            if diagnostic.code.as_ref() != Some(&code) {
+                continue;
+            }
+            let span = SrcSpan::new(
+                byte_index(diagnostic.range.start),
+                byte_index(diagnostic.range.end),
+            );
+            for (title, edit) in self.compiler.unused_variable_fixes(&module.name, span) {
+                CodeActionBuilder::new(&title)
+                    .kind(lsp_types::CodeActionKind::QUICKFIX)
+                    .changes(params.text_document.uri.clone(), vec![edit])
+                    .push_to(actions);
+            }
+        }
+    }
+
+    /// Fixes for a type error, which are offered while the module does not
+    /// compile.
+    fn code_action_wrap_expression(
+        &self,
+        params: &lsp::CodeActionParams,
+        actions: &mut Vec<CodeAction>,
+    ) {
+        let Some(name) = self.module_name_for_uri(&params.text_document.uri) else {
+            return;
+        };
+        let Some(ModuleTypeError { src, .. }) = self.compiler.type_error(&name) else {
+            return;
+        };
+        let line_numbers = LineNumbers::new(src);
+        let start = params.range.start;
+        let byte_index = line_numbers.byte_index(start.line, start.character);
+        for edit in self.compiler.wrap_expression_fix(&name, byte_index) {
+            let title = match edit.new_text.split_once('(') {
+                Some((constructor, _)) => format!("Wrap in `{constructor}`"),
+                None => format!("Replace with `{}`", edit.new_text),
+            };
+            CodeActionBuilder::new(&title)
+                .kind(lsp_types::CodeActionKind::QUICKFIX)
+                .changes(params.text_document.uri.clone(), vec![edit])
+                .push_to(actions);
+        }
+    }
+
+    /// Make public the definition of another module that the module failed
+    /// to compile because it used it while it was private.
+    fn code_action_make_public(
+        &self,
+        params: &lsp::CodeActionParams,
+        actions: &mut Vec<CodeAction>,
+    ) {
+        let Some(name) = self.module_name_for_uri(&params.text_document.uri) else {
+            return;
+        };
+        let Some(ModuleTypeError { src, .. }) = self.compiler.type_error(&name) else {
+            return;
+        };
+        let line_numbers = LineNumbers::new(src);
+        let start = params.range.start;
+        let byte_index = line_numbers.byte_index(start.line, start.character);
+        let Some((owner, edit)) = self.compiler.make_public_fix(&name, byte_index) else {
+            return;
+        };
+        let Some(owner_module) = self.compiler.modules.get(&owner) else {
+            return;
+        };
+        CodeActionBuilder::new(&format!("Make the definition in `{owner}` public"))
+            .kind(lsp_types::CodeActionKind::QUICKFIX)
+            .changes(path_to_uri(owner_module.input_path.clone()), vec![edit])
+            .preferred(true)
+            .push_to(actions);
+    }
+
+    /// Add an `@external` attribute for the target of the project to the
+    /// function at the cursor if it has no implementation for the target.
+    fn code_action_implement_for_target(
+        &self,
+        params: &lsp::CodeActionParams,
+        actions: &mut Vec<CodeAction>,
+    ) {
+        let Some(name) = self.module_name_for_uri(&params.text_document.uri) else {
+            return;
+        };
+        // The module may have failed to compile because of the function, in
+        // which case its latest code is the code the error is for.
+        let src = match (
+            self.compiler.current_module(&name),
+            self.compiler.type_error(&name),
+        ) {
+            (Some(module), _) => &module.code,
+            (None, Some(ModuleTypeError { src, .. })) => src,
+            (None, _) => return,
+        };
+        let line_numbers = LineNumbers::new(src);
+        let start = params.range.start;
+        let byte_index = line_numbers.byte_index(start.line, start.character);
+        let Some(edit) = self.compiler.implement_for_target_fix(&name, byte_index) else {
+            return;
+        };
+        let target = match self.compiler.project_compiler.target() {
+            Target::Erlang => "Erlang",
+            Target::JavaScript => "JavaScript",
+        };
+        CodeActionBuilder::new(&format!("Add an external implementation for {target}"))
+            .kind(lsp_types::CodeActionKind::QUICKFIX)
+            .changes(params.text_document.uri.clone(), vec![edit])
+            .push_to(actions);
+    }
+
+    fn code_action_add_missing_arguments(
+        &self,
+        params: &lsp::CodeActionParams,
+        actions: &mut Vec<CodeAction>,
+    ) {
+        let Some(name) = self.module_name_for_uri(&params.text_document.uri) else {
+            return;
+        };
+        let Some(ModuleTypeError { src, .. }) = self.compiler.type_error(&name) else {
+            return;
+        };
+        let line_numbers = LineNumbers::new(src);
+        let start = params.range.start;
+        let byte_index = line_numbers.byte_index(start.line, start.character);
+        let Some(edit) = self.compiler.add_missing_argument_fix(&name, byte_index) else {
+            return;
+        };
+        CodeActionBuilder::new("Add missing arguments")
+            .kind(lsp_types::CodeActionKind::QUICKFIX)
+            .changes(params.text_document.uri.clone(), vec![edit])
+            .preferred(true)
+            .push_to(actions);
+    }
+
+    fn code_action_ambiguous_import(
+        &self,
+        params: &lsp::CodeActionParams,
+        actions: &mut Vec<CodeAction>,
+    ) {
+        let Some(name) = self.module_name_for_uri(&params.text_document.uri) else {
+            return;
+        };
+        let src = match (
+            self.compiler.current_module(&name),
+            self.compiler.type_error(&name),
+        ) {
+            (Some(module), _) => &module.code,
+            (None, Some(ModuleTypeError { src, .. })) => src,
+            (None, _) => return,
+        };
+        let line_numbers = LineNumbers::new(src);
+        let start = params.range.start;
+        let byte_index = line_numbers.byte_index(start.line, start.character);
+        let Some(edit) = self.compiler.ambiguous_import_fix(&name, byte_index) else {
+            return;
+        };
+        CodeActionBuilder::new("Give the import another name")
+            .kind(lsp_types::CodeActionKind::QUICKFIX)
+            .changes(params.text_document.uri.clone(), vec![edit])
+            .preferred(true)
+            .push_to(actions);
+    }
+
+    fn completion_types<'b>(
+        &'b self,
+        module: &'b Module,
+        prefix: &str,
+    ) -> Vec<lsp::CompletionItem> {
         let mut completions = vec![];
 
         // Prelude types
         for type_ in PreludeType::iter() {
-            completions.push(lsp::CompletionItem {
+            let completion = lsp::CompletionItem {
                 label: type_.name().into(),
                 detail: Some("Type".into()),
                 kind: Some(lsp::CompletionItemKind::CLASS),
                 ..Default::default()
-            });
+            };
+            completions.push(rank(completion, CompletionTier::Prelude, prefix));
         }
 
         // Module types
         for (name, type_) in &module.ast.type_info.types {
-            completions.push(type_completion(None, name, type_));
+            let alias = is_type_alias(&module.ast.type_info, name);
+            let completion = type_completion(None, name, type_, alias);
+            completions.push(rank(completion, CompletionTier::Local, prefix));
         }
 
         // Imported modules
+        let origin = module.origin;
         for import in module.ast.definitions.iter().filter_map(get_import) {
             // The module may not be known of yet if it has not previously
             // compiled yet in this editor session.
@@ -380,15 +1147,22 @@ where
                 continue;
             };
 
+            // Modules from dev-dependencies are only available to test modules.
+            if !self.compiler.is_importable_from(origin, module) {
+                continue;
+            }
+
             // Qualified types
             for (name, type_) in &module.types {
                 if !type_.public {
                     continue;
                 }
 
+                let alias = is_type_alias(module, name);
                 let module = import.used_name();
                 if module.is_some() {
-                    completions.push(type_completion(module.as_ref(), name, type_));
+                    let completion = type_completion(module.as_ref(), name, type_, alias);
+                    completions.push(rank(completion, CompletionTier::Imported, prefix));
                 }
             }
 
@@ -397,22 +1171,64 @@ where
                 let Some(type_) = module.get_public_type(&unqualified.name) else {
                     continue;
                 };
-                completions.push(type_completion(None, unqualified.used_name(), type_));
+                let alias = is_type_alias(module, &unqualified.name);
+                let completion = type_completion(None, unqualified.used_name(), type_, alias);
+                completions.push(rank(completion, CompletionTier::Imported, prefix));
             }
         }
 
+        // Modules that could be imported
+        let mut importable_completions = vec![];
+        for (importable, import) in self.importable_modules(module) {
+            let qualifier = import_qualifier(&importable.name);
+            let names = self
+                .compiler
+                .module_export_names(&importable.name)
+                .unwrap_or_default();
+            if !could_complete_importable(&qualifier, &names, prefix) {
+                continue;
+            }
+            for (name, type_) in &importable.types {
+                if !type_.public {
+                    continue;
+                }
+                let alias = is_type_alias(importable, name);
+                let completion = lsp::CompletionItem {
+                    additional_text_edits: Some(vec![import.clone()]),
+                    ..type_completion(Some(&qualifier), name, type_, alias)
+                };
+                importable_completions.push(rank(completion, CompletionTier::Importable, prefix));
+            }
+        }
+        completions.extend(best_importable(importable_completions, prefix));
+
         completions
     }
 
-    fn completion_values<'b>(&'b self, module: &'b Module) -> Vec<lsp::CompletionItem> {
+    fn completion_values<'b>(
+        &'b self,
+        module: &'b Module,
+        prefix: &str,
+        snippets: bool,
+    ) -> Vec<lsp::CompletionItem> {
         let mut completions = vec![];
+        let value_completion = |module: Option<&str>, name: &str, value: &ValueConstructor| {
+            let completion = value_completion(module, name, value);
+            if snippets {
+                self.with_call_snippet(completion, value)
+            } else {
+                completion
+            }
+        };
 
         // Module functions
         for (name, value) in &module.ast.type_info.values {
-            completions.push(value_completion(None, name, value));
+            let completion = value_completion(None, name, value);
+            completions.push(rank(completion, CompletionTier::Local, prefix));
         }
 
         // Imported modules
+        let origin = module.origin;
         for import in module.ast.definitions.iter().filter_map(get_import) {
             // The module may not be known of yet if it has not previously
             // compiled yet in this editor session.
@@ -421,6 +1237,11 @@ where
                 continue;
             };
 
+            // Modules from dev-dependencies are only available to test modules.
+            if !self.compiler.is_importable_from(origin, module) {
+                continue;
+            }
+
             // Qualified values
             for (name, value) in &module.values {
                 if !value.public {
@@ -429,7 +1250,8 @@ where
 
                 let module = import.used_name();
                 if module.is_some() {
-                    completions.push(value_completion(module.as_deref(), name, value));
+                    let completion = value_completion(module.as_deref(), name, value);
+                    completions.push(rank(completion, CompletionTier::Imported, prefix));
                 }
             }
 
@@ -438,18 +1260,254 @@ where
                 let Some(value) = module.get_public_value(&unqualified.name) else {
                     continue;
                 };
-                completions.push(value_completion(None, unqualified.used_name(), value));
+                let completion = value_completion(None, unqualified.used_name(), value);
+                completions.push(rank(completion, CompletionTier::Imported, prefix));
             }
         }
 
+        // Modules that could be imported
+        let mut importable_completions = vec![];
+        for (importable, import) in self.importable_modules(module) {
+            let qualifier = import_qualifier(&importable.name);
+            let names = self
+                .compiler
+                .module_export_names(&importable.name)
+                .unwrap_or_default();
+            if !could_complete_importable(&qualifier, &names, prefix) {
+                continue;
+            }
+            for (name, value) in &importable.values {
+                if !value.public {
+                    continue;
+                }
+                let completion = lsp::CompletionItem {
+                    additional_text_edits: Some(vec![import.clone()]),
+                    ..value_completion(Some(&qualifier), name, value)
+                };
+                importable_completions.push(rank(completion, CompletionTier::Importable, prefix));
+            }
+        }
+        completions.extend(best_importable(importable_completions, prefix));
+
         completions
     }
+
+    /// The labels of the labelled arguments of a function that have not been
+    /// given yet at a call to it, in the order the function defines them.
+    fn completion_labels(
+        &self,
+        module: &Module,
+        function: &str,
+        given: &[EcoString],
+        prefix: &str,
+    ) -> Vec<lsp::CompletionItem> {
+        let Some(value) = self.called_value(module, function) else {
+            return vec![];
+        };
+        let Some(field_map) = value.field_map() else {
+            return vec![];
+        };
+        let arguments = value.type_.fn_types().map(|(arguments, _)| arguments);
+        // The labels belong to the function, which may be from another module.
+        let tier = if !function.contains('.') && module.ast.type_info.values.contains_key(function)
+        {
+            CompletionTier::Local
+        } else {
+            CompletionTier::Imported
+        };
+        field_map
+            .fields
+            .iter()
+            .filter(|(label, _)| !given.contains(label))
+            .sorted_by_key(|(_, index)| **index)
+            .map(|(label, index)| {
+                let type_ = arguments
+                    .as_ref()
+                    .and_then(|arguments| arguments.get(*index as usize))
+                    .map(|type_| Printer::new().pretty_print(type_, 0));
+                let completion = lsp::CompletionItem {
+                    label: format!("{label}:"),
+                    kind: Some(lsp::CompletionItemKind::FIELD),
+                    detail: type_,
+                    insert_text: Some(format!("{label}: ")),
+                    ..Default::default()
+                };
+                rank(completion, tier, prefix)
+            })
+            .collect()
+    }
+
+    /// The value a function is referred to by at a call in a module, such as
+    /// `wibble`, `list.map` or an unqualified import.
+    fn called_value<'b>(
+        &'b self,
+        module: &'b Module,
+        function: &str,
+    ) -> Option<&'b ValueConstructor> {
+        let imports = || module.ast.definitions.iter().filter_map(get_import);
+        if let Some((qualifier, name)) = function.split_once('.') {
+            let import =
+                imports().find(|import| import.used_name().as_deref() == Some(qualifier))?;
+            return self
+                .compiler
+                .get_module_inferface(&import.module)?
+                .get_public_value(name);
+        }
+        if let Some(value) = module.ast.type_info.values.get(function) {
+            return Some(value);
+        }
+        imports().find_map(|import| {
+            let unqualified = import
+                .unqualified_values
+                .iter()
+                .find(|unqualified| unqualified.used_name() == function)?;
+            self.compiler
+                .get_module_inferface(&import.module)?
+                .get_public_value(&unqualified.name)
+        })
+    }
+
+    /// Complete a function as a call to it, with a placeholder for each of its
+    /// arguments. Labelled arguments are given with their label.
+    fn with_call_snippet(
+        &self,
+        completion: lsp::CompletionItem,
+        value: &ValueConstructor,
+    ) -> lsp::CompletionItem {
+        let field_map = match &value.variant {
+            ValueConstructorVariant::ModuleFn { field_map, .. } => field_map,
+            ValueConstructorVariant::Record {
+                arity, field_map, ..
+            } if *arity > 0 => field_map,
+            _ => return completion,
+        };
+        let Some((arguments, _)) = value.type_.fn_types() else {
+            return completion;
+        };
+
+        let names = self.argument_names(value);
+        let mut printer = Printer::new();
+        let placeholders = arguments
+            .iter()
+            .enumerate()
+            .map(|(index, type_)| {
+                let tab_stop = index + 1;
+                let label = field_map.as_ref().and_then(|field_map| {
+                    field_map
+                        .fields
+                        .iter()
+                        .find(|(_, position)| **position as usize == index)
+                        .map(|(label, _)| label)
+                });
+                match label {
+                    Some(label) => format!("{label}: ${{{tab_stop}}}"),
+                    None => {
+                        let name = match names.get(index).and_then(Option::as_ref) {
+                            Some(name) => name.to_string(),
+                            None => printer.pretty_print(type_, 0),
+                        };
+                        format!("${{{tab_stop}:{name}}}")
+                    }
+                }
+            })
+            .collect_vec();
+        let insert_text = format!("{}({})", completion.label, placeholders.join(", "));
+
+        lsp::CompletionItem {
+            insert_text: Some(insert_text),
+            insert_text_format: Some(lsp::InsertTextFormat::SNIPPET),
+            ..completion
+        }
+    }
+
+    /// The names of the arguments of a function, if its source is available.
+    /// Discarded arguments are named without their leading underscore.
+    fn argument_names(&self, value: &ValueConstructor) -> Vec<Option<EcoString>> {
+        let ValueConstructorVariant::ModuleFn { module, name, .. } = &value.variant else {
+            return vec![];
+        };
+        let Some(compiled) = self
+            .compiler
+            .modules
+            .get(module)
+            .or_else(|| self.compiler.dependency_modules.get(module))
+        else {
+            return vec![];
+        };
+        compiled
+            .ast
+            .definitions
+            .iter()
+            .find_map(|definition| match definition {
+                Definition::Function(function) if function.name == *name => Some(function),
+                _ => None,
+            })
+            .map(|function| {
+                function
+                    .arguments
+                    .iter()
+                    .map(|argument| match &argument.names {
+                        ArgNames::Discard { name } | ArgNames::LabelledDiscard { name, .. } => {
+                            Some(name.trim_start_matches('_').into())
+                        }
+                        ArgNames::Named { name } | ArgNames::NamedLabelled { name, .. } => {
+                            Some(name.clone())
+                        }
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// The modules that the given module could import but has not, each with
+    /// the edit that adds the import after the imports the module has.
+    /// Modules that would create an import cycle are left out.
+    fn importable_modules<'b>(
+        &'b self,
+        module: &'b Module,
+    ) -> impl Iterator<Item = (&'b ModuleInterface, lsp::TextEdit)> {
+        let code = self.compiler.current_code(module);
+        let imported: std::collections::HashSet<_> = module
+            .ast
+            .definitions
+            .iter()
+            .filter_map(get_import)
+            .map(|import| &import.module)
+            .collect();
+
+        self.compiler
+            .project_compiler
+            .get_importable_modules()
+            .values()
+            .filter(move |importable| {
+                importable.name != module.name
+                    && importable.name != PRELUDE_MODULE_NAME
+                    && !imported.contains(&importable.name)
+                    && self.compiler.is_importable_from(module.origin, importable)
+                    && !self
+                        .compiler
+                        .would_create_cycle(&module.name, &importable.name)
+            })
+            .map(move |importable| (importable, import_edit(&code, &importable.name)))
+    }
+}
+
+/// The name a module is referred to by when imported without an alias.
+fn import_qualifier(module: &str) -> EcoString {
+    module.rsplit('/').next().unwrap_or(module).into()
+}
+
+/// Whether a type of a module is an alias for another type rather than a
+/// custom type, as only custom types have their constructors recorded.
+fn is_type_alias(module: &ModuleInterface, name: &str) -> bool {
+    !module.types_value_constructors.contains_key(name)
 }
 
 fn type_completion(
     module: Option<&EcoString>,
     name: &str,
     type_: &crate::type_::TypeConstructor,
+    alias: bool,
 ) -> lsp::CompletionItem {
     let label = match module {
         Some(module) => format!("{module}.{name}"),
@@ -465,7 +1523,7 @@ fn type_completion(
     lsp::CompletionItem {
         label,
         kind,
-        detail: Some("Type".into()),
+        detail: Some(if alias { "Type alias" } else { "Type" }.into()),
         ..Default::default()
     }
 }
@@ -473,7 +1531,7 @@ fn type_completion(
 fn value_completion(
     module: Option<&str>,
     name: &str,
-    value: &crate::type_::ValueConstructor,
+    value: &ValueConstructor,
 ) -> lsp::CompletionItem {
     let label = match module {
         Some(module) => format!("{module}.{name}"),
@@ -531,6 +1589,74 @@ fn hover_for_pattern(pattern: &TypedPattern, line_numbers: LineNumbers) -> Hover
     }
 }
 
+/// The option of one of the segments of a bit array that the byte index is
+/// on. Any value given to the option has already been searched.
+fn bit_array_option_at<Value, Type>(
+    segments: &[BitArraySegment<Value, Type>],
+    byte_index: u32,
+) -> Option<&BitArrayOption<Value>> {
+    segments
+        .iter()
+        .flat_map(|segment| &segment.options)
+        .find(|option| option.location().contains(byte_index))
+}
+
+fn hover_for_bit_array_option<Value>(
+    option: &BitArrayOption<Value>,
+    line_numbers: &LineNumbers,
+) -> Hover {
+    let documentation = match option {
+        BitArrayOption::Bytes { .. } => "The value is a `BitArray` of a whole number of bytes.",
+        BitArrayOption::Bits { .. } => "The value is a `BitArray` of any number of bits.",
+        BitArrayOption::Int { .. } => "The value is an `Int`. This is the default for a segment.",
+        BitArrayOption::Float { .. } => {
+            "The value is a `Float`, 64 bits in size unless another size is given."
+        }
+        BitArrayOption::Utf8 { .. } => "The value is a `String`, encoded as UTF-8.",
+        BitArrayOption::Utf16 { .. } => "The value is a `String`, encoded as UTF-16.",
+        BitArrayOption::Utf32 { .. } => "The value is a `String`, encoded as UTF-32.",
+        BitArrayOption::Utf8Codepoint { .. } => "The value is a `UtfCodepoint`, encoded as UTF-8.",
+        BitArrayOption::Utf16Codepoint { .. } => {
+            "The value is a `UtfCodepoint`, encoded as UTF-16."
+        }
+        BitArrayOption::Utf32Codepoint { .. } => {
+            "The value is a `UtfCodepoint`, encoded as UTF-32."
+        }
+        BitArrayOption::Signed { .. } => "The value is a signed integer.",
+        BitArrayOption::Unsigned { .. } => {
+            "The value is an unsigned integer. This is the default for a segment."
+        }
+        BitArrayOption::Big { .. } => {
+            "The value is big-endian, with its most significant byte first. This is the \
+default for a segment."
+        }
+        BitArrayOption::Little { .. } => {
+            "The value is little-endian, with its least significant byte first."
+        }
+        BitArrayOption::Native { .. } => {
+            "The value has the endianness of the processor the code runs on."
+        }
+        BitArrayOption::Size { .. } => {
+            "The number of units in the segment. A unit is a bit, or a byte for `bytes` \
+segments, unless another is given with `unit`."
+        }
+        BitArrayOption::Unit { .. } => {
+            "The number of bits in each unit of the size of the segment, from 1 to 256."
+        }
+    };
+    let label = option.label();
+    let contents = format!(
+        "```gleam
+{label}
+```
+{documentation}"
+    );
+    Hover {
+        contents: HoverContents::Scalar(MarkedString::String(contents)),
+        range: Some(src_span_to_lsp_range(option.location(), line_numbers)),
+    }
+}
+
 fn hover_for_function_head(
     fun: &Function<Arc<Type>, TypedExpr>,
     line_numbers: LineNumbers,
@@ -583,7 +1709,27 @@ fn hover_for_expression(
     module: Option<&Module>,
     hex_deps: &std::collections::HashSet<EcoString>,
 ) -> Hover {
-    let documentation = expression.get_documentation().unwrap_or_default();
+    // Only the field of a record access is hovered, so that each field of a
+    // chain of accesses can be told apart from the record before it.
+    let (location, documentation) = match expression {
+        TypedExpr::RecordAccess {
+            location,
+            label,
+            record,
+            ..
+        } => {
+            let record_type = Printer::new().pretty_print(record.type_().as_ref(), 0);
+            let start = location.end - label.len() as u32;
+            (
+                SrcSpan::new(start, location.end),
+                format!("The `{label}` field of a `{record_type}`."),
+            )
+        }
+        _ => (
+            expression.location(),
+            expression.get_documentation().unwrap_or_default().into(),
+        ),
+    };
 
     let link_section = module
         .and_then(|m: &Module| {
@@ -602,10 +1748,124 @@ fn hover_for_expression(
     );
     Hover {
         contents: HoverContents::Scalar(MarkedString::String(contents)),
-        range: Some(src_span_to_lsp_range(expression.location(), &line_numbers)),
+        range: Some(src_span_to_lsp_range(location, &line_numbers)),
     }
 }
 
+/// The hover with its markdown contents written as plain text.
+fn plain_text_hover(hover: Hover) -> Hover {
+    let contents = match hover.contents {
+        HoverContents::Scalar(MarkedString::String(markdown)) => {
+            HoverContents::Markup(lsp::MarkupContent {
+                kind: lsp::MarkupKind::PlainText,
+                value: markdown_to_plain_text(&markdown),
+            })
+        }
+        contents => contents,
+    };
+    Hover { contents, ..hover }
+}
+
+/// Remove the fences of code blocks, the backticks of inline code, and write
+/// each link as its text followed by its address in brackets. Other markup,
+/// such as emphasis, is kept as it is often readable as it is.
+fn markdown_to_plain_text(markdown: &str) -> String {
+    let mut in_code_block = false;
+    markdown
+        .lines()
+        .filter_map(|line| {
+            if line.trim_start().starts_with("```") {
+                in_code_block = !in_code_block;
+                None
+            } else if in_code_block {
+                Some(line.to_string())
+            } else {
+                Some(plain_text_line(line))
+            }
+        })
+        .join("\n")
+}
+
+fn plain_text_line(line: &str) -> String {
+    let mut plain = String::with_capacity(line.len());
+    let mut rest = line;
+    while let Some(start) = rest.find(['[', '`']) {
+        let (before, from) = rest.split_at(start);
+        plain.push_str(before);
+        let link = from.strip_prefix('[').and_then(|from| {
+            let (text, from) = from.split_once("](")?;
+            let (address, from) = from.split_once(')')?;
+            Some((text, address, from))
+        });
+        rest = match (link, from.strip_prefix('`')) {
+            (Some((text, address, after)), _) => {
+                plain.push_str(&format!("{text} ({address})"));
+                after
+            }
+            (None, Some(after)) => after,
+            (None, None) => {
+                plain.push('[');
+                from.strip_prefix('[').unwrap_or_default()
+            }
+        };
+    }
+    plain.push_str(rest);
+    plain
+}
+
+/// Whether the inner span is within the outer span.
+fn encloses(outer: SrcSpan, inner: SrcSpan) -> bool {
+    outer.start <= inner.start && inner.end <= outer.end
+}
+
+/// Where an offset into one version of the source of a module is in another
+/// version of it. Code after the part that differs, found from the longest
+/// end that the two have in common, is moved by as much as the difference
+/// added or removed. Code before it and within it keeps its offsets.
+fn moved_offset(from: &str, to: &str, offset: u32) -> u32 {
+    let same_end = from
+        .bytes()
+        .rev()
+        .zip(to.bytes().rev())
+        .take_while(|(from, to)| from == to)
+        .count();
+    if (offset as usize) < from.len() - same_end {
+        return offset;
+    }
+    (offset as usize + to.len() - from.len()) as u32
+}
+
+/// The position in the current source of a module up to which it is the same
+/// as the source it was compiled from.
+fn unchanged_until(compiled: &str, current: &str) -> lsp::Position {
+    let mut same = compiled
+        .bytes()
+        .zip(current.bytes())
+        .take_while(|(compiled, current)| compiled == current)
+        .count();
+    if same == compiled.len() && same == current.len() {
+        return lsp::Position::new(u32::MAX, u32::MAX);
+    }
+    while !compiled.is_char_boundary(same) {
+        same -= 1;
+    }
+    let line_numbers = LineNumbers::new(compiled);
+    src_span_to_lsp_range(SrcSpan::new(same as u32, same as u32), &line_numbers).start
+}
+
+/// The end of the last of the edits a code action makes, if it makes any.
+fn edits_end(action: &CodeAction) -> Option<lsp::Position> {
+    action
+        .edit
+        .as_ref()?
+        .changes
+        .as_ref()?
+        .values()
+        .flatten()
+        .map(|edit| edit.range.end)
+        .max()
+}
+
 // Check if the inner range is included in the outer range.
 fn range_includes(outer: &lsp_types::Range, inner: &lsp_types::Range) -> bool {
     (outer.start >= inner.start && outer.start <= inner.end)
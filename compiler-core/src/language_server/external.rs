@@ -0,0 +1,202 @@
+use crate::{
+    analyse::TargetSupport,
+    ast::{Definition, SrcSpan},
+    build::Target,
+    io::{CommandExecutor, FileSystemReader, FileSystemWriter},
+    language_server::{
+        compiler::{LspProjectCompiler, ModuleTypeError},
+        src_span_to_lsp_range,
+    },
+    line_numbers::LineNumbers,
+    type_,
+};
+use ecow::EcoString;
+use itertools::Itertools;
+use lsp_types::{GotoDefinitionParams, TextEdit};
+use serde::{Deserialize, Serialize};
+
+/// The function that implements an external Gleam function for the target
+/// of the project, such as the Erlang module and function, or the JavaScript
+/// file and the name it exports.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExternalTarget {
+    pub module: EcoString,
+    pub function: EcoString,
+}
+
+/// Where a value is defined, along with the function that implements it if it
+/// is an external function, so that the client can offer to reveal it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DefinitionWithExternal {
+    pub location: lsp_types::Location,
+    pub external: Option<ExternalTarget>,
+}
+
+/// The `gleam/definitionWithExternal` request, which the client can make in
+/// place of `textDocument/definition`.
+#[derive(Debug, Clone, Copy)]
+pub enum DefinitionWithExternalRequest {}
+
+impl lsp_types::request::Request for DefinitionWithExternalRequest {
+    type Params = GotoDefinitionParams;
+    type Result = Option<DefinitionWithExternal>;
+    const METHOD: &'static str = "gleam/definitionWithExternal";
+}
+
+impl<IO> LspProjectCompiler<IO>
+where
+    IO: CommandExecutor + FileSystemWriter + FileSystemReader + Clone,
+{
+    /// The implementation of the function defined at the location in the
+    /// module, if it is an external function for the target of the project.
+    ///
+    /// A dependency module is only searched if it has been loaded with
+    /// `load_dependency_module`.
+    pub fn external_target(&self, module: &str, location: SrcSpan) -> Option<ExternalTarget> {
+        let compiled = self
+            .modules
+            .get(module)
+            .or_else(|| self.dependency_modules.get(module))?;
+        let function = compiled
+            .ast
+            .definitions
+            .iter()
+            .find_map(|definition| match definition {
+                Definition::Function(function) if function.location == location => Some(function),
+                _ => None,
+            })?;
+        let (module, function) = match self.project_compiler.target() {
+            Target::Erlang => function.external_erlang.as_ref(),
+            Target::JavaScript => function.external_javascript.as_ref(),
+        }?;
+        Some(ExternalTarget {
+            module: module.clone(),
+            function: function.clone(),
+        })
+    }
+
+    /// An `@external` attribute for the target of the project to add to the
+    /// function at the byte index, if the function has no implementation for
+    /// the target. The attribute names a function with the same name in an
+    /// FFI module next to the Gleam module, such as `app_ffi` for Erlang or
+    /// `./app_ffi.mjs` for JavaScript, which the programmer then writes.
+    ///
+    /// This is offered for a function of a module that compiled, and for a
+    /// function that stopped its module compiling because it, or something
+    /// it uses, does not support the target.
+    pub fn implement_for_target_fix(&self, module: &str, byte_index: u32) -> Option<TextEdit> {
+        let target = self.project_compiler.target();
+        let (code, unsupported) = match self.current_module(module) {
+            Some(compiled) => {
+                let unsupported = compiled
+                    .ast
+                    .definitions
+                    .iter()
+                    .find_map(|definition| match definition {
+                        Definition::Function(function)
+                            if function.full_location().contains(byte_index) =>
+                        {
+                            Some(function)
+                        }
+                        _ => None,
+                    })
+                    .filter(|function| !function.implementations.supports(target))
+                    .map(|function| function.location);
+                (compiled.code.as_str(), unsupported?)
+            }
+            None => match self.type_error(module)? {
+                ModuleTypeError {
+                    src,
+                    error:
+                        type_::Error::UnsupportedPublicFunctionTarget { location, .. }
+                        | type_::Error::UnsupportedExpressionTarget { location, .. },
+                    ..
+                } => (src.as_str(), *location),
+                _ => return None,
+            },
+        };
+
+        let function = self
+            .untyped_module(module)?
+            .iter_statements(target)
+            .find_map(|definition| match definition {
+                Definition::Function(function)
+                    if function.full_location().contains(byte_index)
+                        && function.full_location().contains(unsupported.start) =>
+                {
+                    Some(function)
+                }
+                _ => None,
+            })?;
+        let attribute = match target {
+            Target::Erlang if function.external_erlang.is_none() => format!(
+                "@external(erlang, \"{}_ffi\", \"{}\")",
+                module.replace('/', "@"),
+                function.name
+            ),
+            Target::JavaScript if function.external_javascript.is_none() => format!(
+                "@external(javascript, \"./{}_ffi.mjs\", \"{}\")",
+                module.rsplit('/').next().unwrap_or(module),
+                function.name
+            ),
+            Target::Erlang | Target::JavaScript => return None,
+        };
+
+        let line_numbers = LineNumbers::new(code);
+        let start = SrcSpan::new(function.location.start, function.location.start);
+        Some(TextEdit {
+            range: src_span_to_lsp_range(start, &line_numbers),
+            new_text: format!("{attribute}\n"),
+        })
+    }
+
+    /// Whether every function of the module has an implementation for the
+    /// target of the project, either in Gleam or as an external for the
+    /// target. If so the module can be used anywhere on the target and
+    /// target support is `Enforced`. Otherwise it is `NotEnforced`, and the
+    /// functions without an implementation are given by
+    /// [`Self::functions_without_target_support`].
+    pub fn module_target_support(&self, module: &str) -> TargetSupport {
+        if self.functions_without_target_support(module).is_empty() {
+            TargetSupport::Enforced
+        } else {
+            TargetSupport::NotEnforced
+        }
+    }
+
+    /// The name and location of each function of the module that has no
+    /// implementation for the target of the project, sorted by location.
+    ///
+    /// A root package module only compiles if its public functions, and the
+    /// functions that use the others, support the target, so for a module
+    /// that compiled these are private functions that nothing supporting the
+    /// target uses. For a module that failed to compile because one of its
+    /// public functions does not support the target, that function is given.
+    /// A module that failed to compile for another reason is taken to support
+    /// the target, as nothing is known about its functions.
+    pub fn functions_without_target_support(&self, module: &str) -> Vec<(EcoString, SrcSpan)> {
+        if let Some(ModuleTypeError {
+            error: type_::Error::UnsupportedPublicFunctionTarget { name, location, .. },
+            ..
+        }) = self.type_error(module)
+        {
+            return vec![(name.clone(), *location)];
+        }
+        let Some(compiled) = self.current_module(module) else {
+            return vec![];
+        };
+        let target = self.project_compiler.target();
+        compiled
+            .ast
+            .definitions
+            .iter()
+            .filter_map(|definition| match definition {
+                Definition::Function(function) if !function.implementations.supports(target) => {
+                    Some((function.name.clone(), function.location))
+                }
+                _ => None,
+            })
+            .sorted_by_key(|(_, location)| location.start)
+            .collect()
+    }
+}
@@ -1,9 +1,16 @@
-use crate::{diagnostic::Diagnostic, Error, Warning};
-use std::collections::{HashMap, HashSet};
+use crate::{
+    diagnostic::{Diagnostic, Level},
+    Error, Warning,
+};
+use std::collections::{BTreeMap, HashMap, HashSet};
 
-use camino::Utf8PathBuf;
+use camino::{Utf8Path, Utf8PathBuf};
 
-use super::engine::Compilation;
+use super::{
+    engine::Compilation,
+    lsp_warning::{is_hint, LspWarning},
+    settings::LspSettings,
+};
 
 #[derive(Debug, Default, PartialEq, Eq)]
 pub struct Feedback {
@@ -25,6 +32,23 @@ impl Feedback {
     fn append_message(&mut self, diagnostic: Diagnostic) {
         self.messages.push(diagnostic);
     }
+
+    /// Add the feedback of another project. The projects must not share any
+    /// files.
+    pub fn append_feedback(&mut self, feedback: Feedback) {
+        self.diagnostics.extend(feedback.diagnostics);
+        self.messages.extend(feedback.messages);
+    }
+}
+
+/// A summary of the diagnostics the client has for the files of a project,
+/// such as for showing in the status bar.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProjectHealth {
+    Clean,
+    /// There are warnings but no errors.
+    Warnings(usize),
+    Errors(usize),
 }
 
 /// When an operation succeeds or fails we want to send diagnostics and
@@ -38,13 +62,159 @@ impl Feedback {
 /// To do this we keep track of which files have diagnostics and only overwrite
 /// them if the file has been recompiled.
 ///
-#[derive(Debug, Default)]
+#[derive(Debug)]
 pub struct FeedbackBookKeeper {
     files_with_warnings: HashSet<Utf8PathBuf>,
     files_with_errors: HashSet<Utf8PathBuf>,
+    /// Whether the diagnostics for `todo` and `panic` are shown as errors,
+    /// as set by the user's `unfinished_code_as_errors` setting.
+    pub unfinished_code_as_errors: bool,
+    /// The codes of the diagnostics that are not reported.
+    ignored_codes: HashSet<String>,
+    /// Whether a `// gleam-ignore` comment stops the warnings with the codes
+    /// it lists being reported for the line after it.
+    pub suppression_comments: bool,
+    /// The diagnostics that the client has been sent for each file and not
+    /// yet had replaced.
+    published: BTreeMap<Utf8PathBuf, Vec<Diagnostic>>,
+}
+
+impl Default for FeedbackBookKeeper {
+    /// Diagnostics are reported as the default settings have them reported.
+    fn default() -> Self {
+        let settings = LspSettings::default();
+        Self {
+            files_with_warnings: HashSet::new(),
+            files_with_errors: HashSet::new(),
+            unfinished_code_as_errors: settings.unfinished_code_as_errors,
+            ignored_codes: settings.ignored_diagnostic_codes,
+            suppression_comments: settings.suppression_comments,
+            published: BTreeMap::new(),
+        }
+    }
 }
 
 impl FeedbackBookKeeper {
+    /// Stop reporting diagnostics with any of these codes, such as `todo`.
+    /// This only changes what is reported, so whether compilation succeeds is
+    /// unaffected.
+    pub fn set_ignored_diagnostic_codes(&mut self, codes: HashSet<String>) {
+        self.ignored_codes = codes;
+    }
+
+    /// Let `// gleam-ignore` comments stop warnings being reported, as with
+    /// `// gleam-ignore unused_import todo` on the line before the warnings.
+    /// Codes may be written with `-` in place of `_`.
+    pub fn with_suppression_comments(mut self, enabled: bool) -> Self {
+        self.suppression_comments = enabled;
+        self
+    }
+
+    fn is_ignored(&self, diagnostic: &Diagnostic) -> bool {
+        diagnostic
+            .code
+            .is_some_and(|code| self.ignored_codes.contains(code))
+    }
+
+    /// Whether a `// gleam-ignore` comment on the line before the start of
+    /// the diagnostic lists its code.
+    fn is_suppressed(&self, diagnostic: &Diagnostic) -> bool {
+        let (true, Some(code), Some(location)) = (
+            self.suppression_comments,
+            diagnostic.code,
+            diagnostic.location.as_ref(),
+        ) else {
+            return false;
+        };
+        let before = location
+            .src
+            .get(..location.label.span.start as usize)
+            .unwrap_or_default();
+        let Some(line_start) = before.rfind('\n') else {
+            return false;
+        };
+        let previous_line = before
+            .get(..line_start)
+            .unwrap_or_default()
+            .rsplit('\n')
+            .next()
+            .unwrap_or_default();
+        let Some(codes) = previous_line.trim().strip_prefix("// gleam-ignore ") else {
+            return false;
+        };
+        codes
+            .split_whitespace()
+            .any(|ignored| ignored.replace('-', "_") == code)
+    }
+
+    /// Every diagnostic the client currently has for the files of the
+    /// project, sorted by file and then by where it is in the file. Each
+    /// response replaces the diagnostics of the files it has diagnostics for,
+    /// and diagnostics at the same position keep the order they were
+    /// reported in, so the order is the same from one compilation to the
+    /// next. Messages without a file are not included.
+    pub fn all_diagnostics_sorted(&mut self) -> Vec<(Utf8PathBuf, Diagnostic)> {
+        let mut all = vec![];
+        for (path, diagnostics) in &mut self.published {
+            sort_by_position(diagnostics);
+            all.extend(
+                diagnostics
+                    .iter()
+                    .map(|diagnostic| (path.clone(), diagnostic.clone())),
+            );
+        }
+        all
+    }
+
+    /// The diagnostics the client currently has for the file, sorted by
+    /// where they are in the file in the same way as
+    /// [`Self::all_diagnostics_sorted`].
+    pub fn file_diagnostics_sorted(&mut self, path: &Utf8Path) -> Vec<Diagnostic> {
+        match self.published.get_mut(path) {
+            Some(diagnostics) => {
+                sort_by_position(diagnostics);
+                diagnostics.clone()
+            }
+            None => vec![],
+        }
+    }
+
+    /// The number of errors the client currently has for the files of the
+    /// project, or if there are none the number of warnings. Diagnostics for
+    /// `todo` and `panic` shown as errors count as errors. Errors without a
+    /// file, such as for a problem with the project's config, are sent as
+    /// messages rather than diagnostics and are not counted, and nor are
+    /// hints.
+    pub fn project_health(&mut self) -> ProjectHealth {
+        let count = |level| {
+            self.published
+                .values()
+                .flatten()
+                .filter(|diagnostic| diagnostic.level == level && !is_hint(diagnostic))
+                .count()
+        };
+        let (errors, warnings) = (count(Level::Error), count(Level::Warning));
+        if errors > 0 {
+            ProjectHealth::Errors(errors)
+        } else if warnings > 0 {
+            ProjectHealth::Warnings(warnings)
+        } else {
+            ProjectHealth::Clean
+        }
+    }
+
+    /// Keep the diagnostics of the files in the feedback, which replace those
+    /// the client had for them.
+    fn record(&mut self, feedback: &Feedback) {
+        for (path, diagnostics) in &feedback.diagnostics {
+            if diagnostics.is_empty() {
+                _ = self.published.remove(path);
+            } else {
+                _ = self.published.insert(path.clone(), diagnostics.clone());
+            }
+        }
+    }
+
     /// Send diagnostics for any warnings and remove any diagnostics for files
     /// that have compiled without warnings.
     ///
@@ -71,12 +241,22 @@ impl FeedbackBookKeeper {
         }
 
         for warning in warnings {
-            self.insert_warning(&mut feedback, warning);
+            self.insert_warning(&mut feedback, warning.to_diagnostic());
         }
 
+        self.record(&feedback);
         feedback
     }
 
+    /// Add diagnostics for the warnings that only the language server reports
+    /// to the feedback of the response they were found in.
+    pub fn append_lsp_warnings(&mut self, feedback: &mut Feedback, warnings: Vec<LspWarning>) {
+        for warning in warnings {
+            self.insert_warning(feedback, warning.to_diagnostic());
+        }
+        self.record(feedback);
+    }
+
     fn unset_errors(&mut self, feedback: &mut Feedback) {
         // TODO: avoid clobbering warnings. They should be preserved rather than
         // removed with the errors here. We will need to store the warnings and
@@ -103,18 +283,20 @@ impl FeedbackBookKeeper {
 
         // A new error means that any existing errors are no longer valid. Unset them.
         self.unset_errors(&mut feedback);
+        if !self.is_ignored(&diagnostic) {
+            match diagnostic.location.as_ref().map(|l| l.path.clone()) {
+                Some(path) => {
+                    _ = self.files_with_errors.insert(path.clone());
+                    feedback.append_diagnostic(path, diagnostic);
+                }
 
-        match diagnostic.location.as_ref().map(|l| l.path.clone()) {
-            Some(path) => {
-                _ = self.files_with_errors.insert(path.clone());
-                feedback.append_diagnostic(path, diagnostic);
-            }
-
-            None => {
-                feedback.append_message(diagnostic);
+                None => {
+                    feedback.append_message(diagnostic);
+                }
             }
         }
 
+        self.record(&feedback);
         feedback
     }
 
@@ -122,8 +304,13 @@ impl FeedbackBookKeeper {
         self.build_with_error(error, Compilation::No, vec![])
     }
 
-    fn insert_warning(&mut self, feedback: &mut Feedback, warning: Warning) {
-        let diagnostic = warning.to_diagnostic();
+    fn insert_warning(&mut self, feedback: &mut Feedback, mut diagnostic: Diagnostic) {
+        if self.is_ignored(&diagnostic) || self.is_suppressed(&diagnostic) {
+            return;
+        }
+        if self.unfinished_code_as_errors && matches!(diagnostic.code, Some("todo" | "panic")) {
+            diagnostic.level = Level::Error;
+        }
         if let Some(path) = diagnostic.location.as_ref().map(|l| l.path.clone()) {
             _ = self.files_with_warnings.insert(path.clone());
             feedback.append_diagnostic(path, diagnostic);
@@ -131,6 +318,16 @@ impl FeedbackBookKeeper {
     }
 }
 
+/// Diagnostics at the same position keep the order they were reported in.
+fn sort_by_position(diagnostics: &mut [Diagnostic]) {
+    diagnostics.sort_by_key(|diagnostic| {
+        diagnostic
+            .location
+            .as_ref()
+            .map(|location| location.label.span.start)
+    });
+}
+
 #[cfg(test)]
 mod tests {
 
@@ -140,6 +337,7 @@ mod tests {
         parse::error::{ParseError, ParseErrorType},
         type_,
     };
+    use ecow::EcoString;
 
     #[test]
     fn feedback() {
@@ -427,4 +625,206 @@ mod tests {
             feedback
         );
     }
+
+    #[test]
+    fn unfinished_code_as_errors() {
+        let mut book_keeper = FeedbackBookKeeper {
+            unfinished_code_as_errors: true,
+            ..Default::default()
+        };
+        let file = Utf8PathBuf::from("src/file.gleam");
+
+        let todo = Warning::Type {
+            path: file.clone(),
+            src: "todo".into(),
+            warning: type_::Warning::Todo {
+                kind: crate::ast::TodoKind::Keyword,
+                location: SrcSpan::new(0, 4),
+                typ: type_::generic_var(0),
+            },
+        };
+        let other = Warning::Type {
+            path: file.clone(),
+            src: "todo".into(),
+            warning: type_::Warning::NoFieldsRecordUpdate {
+                location: SrcSpan::new(0, 4),
+            },
+        };
+
+        let feedback = book_keeper.response(Compilation::Yes(vec![]), vec![todo, other]);
+        let levels = feedback.diagnostics[&file]
+            .iter()
+            .map(|diagnostic| diagnostic.level)
+            .collect::<Vec<_>>();
+        assert_eq!(levels, vec![Level::Error, Level::Warning]);
+    }
+
+    #[test]
+    fn ignored_diagnostic_codes() {
+        let mut book_keeper = FeedbackBookKeeper::default();
+        book_keeper.set_ignored_diagnostic_codes(HashSet::from(["todo".into()]));
+        let file = Utf8PathBuf::from("src/file.gleam");
+
+        let todo = Warning::Type {
+            path: file.clone(),
+            src: "todo".into(),
+            warning: type_::Warning::Todo {
+                kind: crate::ast::TodoKind::Keyword,
+                location: SrcSpan::new(0, 4),
+                typ: type_::generic_var(0),
+            },
+        };
+        let other = Warning::Type {
+            path: file.clone(),
+            src: "todo".into(),
+            warning: type_::Warning::NoFieldsRecordUpdate {
+                location: SrcSpan::new(0, 4),
+            },
+        };
+
+        let feedback = book_keeper.response(Compilation::Yes(vec![]), vec![todo.clone(), other]);
+        let codes = feedback
+            .diagnostics
+            .get(&file)
+            .into_iter()
+            .flatten()
+            .map(|diagnostic| diagnostic.code)
+            .collect::<Vec<_>>();
+        assert_eq!(codes, vec![None]);
+
+        // When the file is compiled again with only ignored diagnostics the
+        // diagnostics it had are removed, and none are reported in their
+        // place.
+        let feedback = book_keeper.response(Compilation::Yes(vec![file.clone()]), vec![todo]);
+        assert_eq!(
+            feedback,
+            Feedback {
+                diagnostics: HashMap::from([(file, vec![])]),
+                messages: vec![],
+            }
+        );
+    }
+
+    #[test]
+    fn suppression_comments() {
+        // Comments suppress warnings by default, as with the default settings.
+        assert!(FeedbackBookKeeper::default().suppression_comments);
+        let mut book_keeper = FeedbackBookKeeper::default().with_suppression_comments(true);
+        let file = Utf8PathBuf::from("src/file.gleam");
+        let src: EcoString = "// gleam-ignore unused-import todo
+import one
+import two
+// gleam-ignore todo
+import three
+"
+        .into();
+        let unused = |name: &str| Warning::Type {
+            path: file.clone(),
+            src: src.clone(),
+            warning: type_::Warning::UnusedImportedModule {
+                name: name.into(),
+                location: {
+                    let start = src.find(&format!("import {name}")).unwrap() as u32;
+                    SrcSpan::new(start, start + 7 + name.len() as u32)
+                },
+            },
+        };
+        let warnings = vec![unused("one"), unused("two"), unused("three")];
+
+        let starts = |feedback: Feedback| {
+            feedback
+                .diagnostics
+                .get(&file)
+                .into_iter()
+                .flatten()
+                .map(|diagnostic| diagnostic.location.as_ref().unwrap().label.span.start)
+                .collect::<Vec<_>>()
+        };
+        let feedback = book_keeper.response(Compilation::Yes(vec![]), warnings.clone());
+        assert_eq!(starts(feedback), vec![46, 78]);
+
+        book_keeper.suppression_comments = false;
+        let feedback = book_keeper.response(Compilation::Yes(vec![]), warnings);
+        assert_eq!(starts(feedback), vec![35, 46, 78]);
+    }
+
+    #[test]
+    fn project_health() {
+        let mut book_keeper = FeedbackBookKeeper::default();
+        let file = Utf8PathBuf::from("src/file.gleam");
+        let warning = |start| Warning::Type {
+            path: file.clone(),
+            src: "src".into(),
+            warning: type_::Warning::NoFieldsRecordUpdate {
+                location: SrcSpan::new(start, start + 1),
+            },
+        };
+        let error = Error::Parse {
+            path: file.clone(),
+            src: "blah".into(),
+            error: ParseError {
+                error: ParseErrorType::ConcatPatternVariableLeftHandSide,
+                location: SrcSpan::new(2, 3),
+            },
+        };
+        assert_eq!(book_keeper.project_health(), ProjectHealth::Clean);
+
+        let _ = book_keeper.build_with_error(
+            error,
+            Compilation::Yes(vec![]),
+            vec![warning(0), warning(1)],
+        );
+        assert_eq!(book_keeper.project_health(), ProjectHealth::Errors(1));
+
+        let _ = book_keeper.response(Compilation::Yes(vec![file.clone()]), vec![warning(0)]);
+        assert_eq!(book_keeper.project_health(), ProjectHealth::Warnings(1));
+
+        let _ = book_keeper.response(Compilation::Yes(vec![file.clone()]), vec![]);
+        assert_eq!(book_keeper.project_health(), ProjectHealth::Clean);
+    }
+
+    #[test]
+    fn all_diagnostics_sorted() {
+        let mut book_keeper = FeedbackBookKeeper::default();
+        let file1 = Utf8PathBuf::from("src/file1.gleam");
+        let file2 = Utf8PathBuf::from("src/file2.gleam");
+        let warning = |path: &Utf8PathBuf, start| Warning::Type {
+            path: path.clone(),
+            src: "src".into(),
+            warning: type_::Warning::NoFieldsRecordUpdate {
+                location: SrcSpan::new(start, start + 1),
+            },
+        };
+        let error = Error::Parse {
+            path: file1.clone(),
+            src: "blah".into(),
+            error: ParseError {
+                error: ParseErrorType::ConcatPatternVariableLeftHandSide,
+                location: SrcSpan::new(2, 3),
+            },
+        };
+
+        let _ = book_keeper.build_with_error(
+            error.clone(),
+            Compilation::Yes(vec![]),
+            vec![warning(&file2, 1), warning(&file1, 5), warning(&file1, 0)],
+        );
+        assert_eq!(
+            book_keeper.all_diagnostics_sorted(),
+            vec![
+                (file1.clone(), warning(&file1, 0).to_diagnostic()),
+                (file1.clone(), error.to_diagnostic()),
+                (file1.clone(), warning(&file1, 5).to_diagnostic()),
+                (file2.clone(), warning(&file2, 1).to_diagnostic()),
+            ]
+        );
+
+        // The diagnostics of files that compile without any are removed, and
+        // those of other files are kept.
+        let _ = book_keeper.response(Compilation::Yes(vec![file1]), vec![]);
+        assert_eq!(
+            book_keeper.all_diagnostics_sorted(),
+            vec![(file2.clone(), warning(&file2, 1).to_diagnostic())]
+        );
+    }
 }
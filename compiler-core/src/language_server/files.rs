@@ -1,12 +1,14 @@
-use std::time::SystemTime;
+use std::{cell::RefCell, collections::HashMap, rc::Rc, time::SystemTime};
 
 use debug_ignore::DebugIgnore;
 
 use crate::{
+    ast::SrcSpan,
     io::{
         memory::InMemoryFileSystem, CommandExecutor, FileSystemReader, FileSystemWriter, ReadDir,
         Stdio, WrappedReader,
     },
+    language_server::compiler::LspProjectCompiler,
     Result,
 };
 
@@ -24,6 +26,9 @@ use camino::{Utf8Path, Utf8PathBuf};
 pub struct FileSystemProxy<IO> {
     io: DebugIgnore<IO>,
     edit_cache: InMemoryFileSystem,
+    /// The span of the new content of each file that its most recent edit
+    /// changed, shared by every clone of the proxy as the cache is.
+    edit_ranges: Rc<RefCell<HashMap<Utf8PathBuf, SrcSpan>>>,
 }
 
 impl<IO> FileSystemProxy<IO>
@@ -34,6 +39,7 @@ where
         Self {
             io: io.into(),
             edit_cache: InMemoryFileSystem::new(),
+            edit_ranges: Rc::default(),
         }
     }
 
@@ -42,6 +48,11 @@ where
     }
 
     pub fn write_mem_cache(&mut self, path: &Utf8Path, content: &str) -> Result<()> {
+        let previous = self.read(path).unwrap_or_default();
+        _ = self
+            .edit_ranges
+            .borrow_mut()
+            .insert(path.to_path_buf(), changed_span(&previous, content));
         let write_result = self.edit_cache.write(path, content);
         self.edit_cache
             .try_set_modification_time(path, SystemTime::now())?;
@@ -49,8 +60,55 @@ where
     }
 
     pub fn delete_mem_cache(&self, path: &Utf8Path) -> Result<()> {
+        _ = self.edit_ranges.borrow_mut().remove(path);
         self.edit_cache.delete_directory(path)
     }
+
+    /// The span of the content of the file that was changed by the most
+    /// recent edit written to the cache. This is empty, at where the content
+    /// was removed, if the edit only removed content.
+    pub fn last_edit_range(&self, path: &Utf8Path) -> Option<SrcSpan> {
+        self.edit_ranges.borrow().get(path).copied()
+    }
+}
+
+impl<IO> LspProjectCompiler<FileSystemProxy<IO>>
+where
+    IO: CommandExecutor + FileSystemWriter + FileSystemReader + Clone,
+{
+    /// The span of the source of a root package module that was changed by
+    /// the most recent edit made to it in the editor, so that work can be
+    /// limited to the code that has changed. The span is of the source as it
+    /// is now, which may not have been compiled yet.
+    pub fn last_edit_range(&self, module: &str) -> Option<SrcSpan> {
+        let (path, _) = self.root_module_path(module)?;
+        self.project_compiler.io.last_edit_range(&path)
+    }
+}
+
+/// The span of the new content that differs from the old, found from the
+/// longest start and end the two have in common.
+fn changed_span(old: &str, new: &str) -> SrcSpan {
+    let mut prefix = old
+        .bytes()
+        .zip(new.bytes())
+        .take_while(|(old, new)| old == new)
+        .count();
+    while !new.is_char_boundary(prefix) {
+        prefix -= 1;
+    }
+    let longest_suffix = old.len().min(new.len()) - prefix;
+    let mut suffix = old
+        .bytes()
+        .rev()
+        .zip(new.bytes().rev())
+        .take(longest_suffix)
+        .take_while(|(old, new)| old == new)
+        .count();
+    while !new.is_char_boundary(new.len() - suffix) {
+        suffix -= 1;
+    }
+    SrcSpan::new(prefix as u32, (new.len() - suffix) as u32)
 }
 
 // All write operations goes to disk (for mem-cache use the dedicated `_mem_cache` methods)
@@ -0,0 +1,116 @@
+use crate::{
+    ast::{Definition, SrcSpan, UntypedDefinition},
+    language_server::src_span_to_lsp_range,
+    line_numbers::LineNumbers,
+    Error, Result,
+};
+use camino::Utf8Path;
+use ecow::EcoString;
+use lsp_types::TextEdit;
+
+/// Format the top level definitions of a module that overlap the range,
+/// leaving the rest of the module as it was written.
+///
+/// The module is split into one chunk for each definition, running from the
+/// end of the previous definition to the end of this one, so that comments
+/// and attributes are formatted along with the definition they come before.
+/// Each run of chunks overlapping the range is replaced with the same chunks
+/// of the formatted module.
+pub fn format_range(path: &Utf8Path, src: &EcoString, range: SrcSpan) -> Result<Vec<TextEdit>> {
+    let mut formatted = String::new();
+    crate::format::pretty(&mut formatted, src, path)?;
+
+    let original = chunk_ends(src, path)?;
+    let new = chunk_ends(&formatted.as_str().into(), path)?;
+    let line_numbers = LineNumbers::new(src);
+
+    // The formatter never adds or removes definitions, so this only fails if
+    // it has a bug. Replacing the whole module is still correct.
+    if original.len() != new.len() {
+        let span = SrcSpan::new(0, src.len() as u32);
+        return Ok(vec![TextEdit {
+            range: src_span_to_lsp_range(span, &line_numbers),
+            new_text: formatted,
+        }]);
+    }
+
+    let mut edits = vec![];
+    let mut run: Option<(SrcSpan, SrcSpan)> = None;
+    let chunks = original
+        .iter()
+        .zip(&new)
+        .scan((0, 0), |(start, new_start), (&end, &new_end)| {
+            let chunk = (SrcSpan::new(*start, end), SrcSpan::new(*new_start, new_end));
+            *start = end;
+            *new_start = new_end;
+            Some(chunk)
+        });
+    for (chunk, new_chunk) in chunks {
+        // An empty range, such as that of a cursor, overlaps the chunks it
+        // touches.
+        let overlaps =
+            range.start <= chunk.end && (range.end > chunk.start || range.start == chunk.start);
+        run = match run {
+            Some((span, new_span)) if overlaps => Some((
+                SrcSpan::new(span.start, chunk.end),
+                SrcSpan::new(new_span.start, new_chunk.end),
+            )),
+            None if overlaps => Some((chunk, new_chunk)),
+            _ => {
+                edits.extend(run.and_then(|(span, new_span)| {
+                    edit(src, &formatted, span, new_span, &line_numbers)
+                }));
+                None
+            }
+        };
+    }
+    edits.extend(
+        run.and_then(|(span, new_span)| edit(src, &formatted, span, new_span, &line_numbers)),
+    );
+    Ok(edits)
+}
+
+/// The end of each top level definition in the module, followed by the end of
+/// the module itself.
+fn chunk_ends(src: &EcoString, path: &Utf8Path) -> Result<Vec<u32>> {
+    let parsed = crate::parse::parse_module(src).map_err(|error| Error::Parse {
+        path: path.to_path_buf(),
+        src: src.clone(),
+        error,
+    })?;
+    let mut ends = parsed
+        .module
+        .definitions
+        .iter()
+        .map(|definition| definition_end(&definition.definition))
+        .collect::<Vec<_>>();
+    ends.push(src.len() as u32);
+    Ok(ends)
+}
+
+fn definition_end(definition: &UntypedDefinition) -> u32 {
+    match definition {
+        Definition::Function(function) => function.end_position,
+        Definition::CustomType(type_) => type_.end_position,
+        Definition::ModuleConstant(constant) => constant.value.location().end,
+        Definition::TypeAlias(_) | Definition::Import(_) => definition.location().end,
+    }
+}
+
+fn edit(
+    src: &str,
+    formatted: &str,
+    span: SrcSpan,
+    new_span: SrcSpan,
+    line_numbers: &LineNumbers,
+) -> Option<TextEdit> {
+    let old_text = src.get(span.start as usize..span.end as usize)?;
+    let new_text = formatted.get(new_span.start as usize..new_span.end as usize)?;
+    if old_text == new_text {
+        return None;
+    }
+    Some(TextEdit {
+        range: src_span_to_lsp_range(span, line_numbers),
+        new_text: new_text.to_string(),
+    })
+}
@@ -0,0 +1,140 @@
+use crate::{
+    ast::{Definition, SrcSpan},
+    build::Module,
+    io::{CommandExecutor, FileSystemReader, FileSystemWriter},
+    language_server::{
+        compiler::LspProjectCompiler,
+        linked_editing::local_variable_occurrences,
+        references::{ReferenceFinder, Target},
+        visit::walk_module,
+    },
+    type_::PRELUDE_MODULE_NAME,
+};
+use ecow::EcoString;
+
+/// Whether an occurrence of a symbol gives it its value or uses it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HighlightKind {
+    Read,
+    Write,
+}
+
+impl<IO> LspProjectCompiler<IO>
+where
+    IO: CommandExecutor + FileSystemWriter + FileSystemReader + Clone,
+{
+    /// Every occurrence within the module of the symbol at the byte index,
+    /// sorted by where they are. Where a symbol is defined is a write and
+    /// where it is used is a read.
+    ///
+    /// For a local variable these are its definitions and usages within the
+    /// function defining it. For a module level value, whether it is defined
+    /// in this module, imported from another, or from the prelude, they are
+    /// its usages anywhere in the module and, if it is defined in this
+    /// module, the name of its definition. Only modules that compiled in the
+    /// most recent compilation can be searched.
+    pub fn document_highlights(
+        &self,
+        module: &str,
+        byte_index: u32,
+    ) -> Vec<(SrcSpan, HighlightKind)> {
+        let Some(compiled) = self.current_module(module) else {
+            return vec![];
+        };
+        local_variable_occurrences(compiled, byte_index)
+            .unwrap_or_else(|| self.module_value_highlights(compiled, byte_index))
+    }
+
+    fn module_value_highlights(
+        &self,
+        compiled: &Module,
+        byte_index: u32,
+    ) -> Vec<(SrcSpan, HighlightKind)> {
+        // Every value that the module could use, so that they can all be
+        // found in one walk of the module.
+        let this_module = std::iter::once((compiled.name.as_str(), &compiled.ast.type_info));
+        let other_modules = compiled
+            .dependencies
+            .iter()
+            .map(|(name, _)| name.as_str())
+            .chain([PRELUDE_MODULE_NAME])
+            .filter(|name| *name != compiled.name)
+            .filter_map(|name| Some((name, self.get_module_inferface(name)?)));
+        let (keys, targets): (Vec<_>, Vec<_>) = this_module
+            .chain(other_modules)
+            .flat_map(|(module, interface)| {
+                interface.values.iter().filter_map(move |(name, value)| {
+                    Some(((module, name), Target::new(module, value)?))
+                })
+            })
+            .unzip();
+
+        let mut finder = ReferenceFinder {
+            targets: &targets,
+            locations: vec![],
+        };
+        walk_module(&compiled.ast, &mut finder);
+        let usages = finder
+            .locations
+            .into_iter()
+            .map(|(index, location)| (index, location, HighlightKind::Read));
+        let definitions = definition_names(compiled).filter_map(|(name, location)| {
+            let index = keys
+                .iter()
+                .position(|(module, key)| *module == compiled.name && *key == name)?;
+            Some((index, location, HighlightKind::Write))
+        });
+        let occurrences: Vec<_> = usages.chain(definitions).collect();
+
+        let Some(target) = occurrences
+            .iter()
+            .find(|(_, location, _)| (location.start..=location.end).contains(&byte_index))
+            .map(|(index, _, _)| *index)
+        else {
+            return vec![];
+        };
+        let mut highlights: Vec<_> = occurrences
+            .into_iter()
+            .filter(|(index, _, _)| *index == target)
+            .map(|(_, location, kind)| (location, kind))
+            .collect();
+        highlights.sort_by_key(|(location, _)| location.start);
+        highlights.dedup_by_key(|(location, _)| *location);
+        highlights
+    }
+}
+
+/// The name and the location of the name of each function, constant and
+/// record constructor defined by the module.
+fn definition_names(module: &Module) -> impl Iterator<Item = (&EcoString, SrcSpan)> {
+    let code = module.code.as_str();
+    module
+        .ast
+        .definitions
+        .iter()
+        .flat_map(move |definition| match definition {
+            Definition::Function(function) => function_name_start(code, function.location.start)
+                .map(|start| (&function.name, start))
+                .into_iter()
+                .collect(),
+            Definition::ModuleConstant(constant) => {
+                vec![(&constant.name, constant.location.start)]
+            }
+            Definition::CustomType(type_) => type_
+                .constructors
+                .iter()
+                .map(|constructor| (&constructor.name, constructor.location.start))
+                .collect(),
+            Definition::TypeAlias(_) | Definition::Import(_) => vec![],
+        })
+        .map(|(name, start)| (name, SrcSpan::new(start, start + name.len() as u32)))
+}
+
+/// The location of a function starts with `pub` if it is public, followed by
+/// `fn` and then its name.
+fn function_name_start(code: &str, start: u32) -> Option<u32> {
+    let text = code.get(start as usize..)?;
+    let after_pub = text.strip_prefix("pub").unwrap_or(text).trim_start();
+    let after_fn = after_pub.strip_prefix("fn")?.trim_start();
+    Some(start + (text.len() - after_fn.len()) as u32)
+}
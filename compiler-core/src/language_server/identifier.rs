@@ -0,0 +1,114 @@
+use crate::{
+    ast::{Definition, SrcSpan, TypedExpr},
+    build::Located,
+    language_server::compiler::LspProjectCompiler,
+    parse::{lexer::make_tokenizer, token::Token},
+};
+use ecow::EcoString;
+
+impl<IO> LspProjectCompiler<IO> {
+    /// The name or operator at the byte index, and its location, in the code
+    /// of the module from the last compilation in which it compiled. When the
+    /// byte index is between two tokens the one that starts there is used,
+    /// so that the end of a name is still within it.
+    ///
+    /// Each part of a qualified name, such as `list` and `map` in `list.map`,
+    /// is its own identifier. There is no identifier within whitespace, a
+    /// comment, a literal, a keyword or other punctuation, or after code that
+    /// could not be lexed.
+    pub fn identifier_at(&self, module: &str, byte_index: u32) -> Option<(EcoString, SrcSpan)> {
+        let module = self.modules.get(module)?;
+        let mut before = None;
+        for result in make_tokenizer(&module.code) {
+            let Ok((start, token, end)) = result else {
+                break;
+            };
+            if start > byte_index {
+                break;
+            }
+            let identifier = identifier(&token).map(|name| (name, SrcSpan::new(start, end)));
+            if start == byte_index {
+                return identifier.or(before);
+            }
+            before = if byte_index <= end { identifier } else { None };
+        }
+        before
+    }
+
+    /// The imported module that the name at the byte index refers to, if it
+    /// is the module qualifier of a qualified name such as the `list` in
+    /// `list.map`. An alias is resolved to the module it was given to, so the
+    /// `l` of `l.map` is `gleam/list` after `import gleam/list as l`.
+    ///
+    /// A local variable with the same name as a module, such as in the record
+    /// access `list.length`, is not a module qualifier.
+    pub fn module_qualifier_at(&self, module: &str, byte_index: u32) -> Option<EcoString> {
+        let module = self.modules.get(module)?;
+        let tokens = make_tokenizer(&module.code)
+            .map_while(Result::ok)
+            .collect::<Vec<_>>();
+        let index = tokens.iter().position(|(start, token, end)| {
+            matches!(token, Token::Name { .. }) && *start <= byte_index && byte_index <= *end
+        })?;
+        let (_, Token::Name { name }, _) = tokens.get(index)? else {
+            return None;
+        };
+        let before = index.checked_sub(1).and_then(|index| tokens.get(index));
+        let after = tokens.get(index + 1);
+        if matches!(before, Some((_, Token::Dot, _))) || !matches!(after, Some((_, Token::Dot, _)))
+        {
+            return None;
+        }
+        if let Some(Located::Expression(TypedExpr::Var { location, .. })) =
+            module.find_node(byte_index)
+        {
+            if location.contains(byte_index) {
+                return None;
+            }
+        }
+
+        module
+            .ast
+            .definitions
+            .iter()
+            .find_map(|definition| match definition {
+                Definition::Import(import) if import.used_name().as_ref() == Some(name) => {
+                    Some(import.module.clone())
+                }
+                _ => None,
+            })
+    }
+}
+
+fn identifier(token: &Token) -> Option<EcoString> {
+    let operator = match token {
+        Token::Name { name } | Token::UpName { name } | Token::DiscardName { name } => {
+            return Some(name.clone())
+        }
+        Token::Plus => "+",
+        Token::Minus => "-",
+        Token::Star => "*",
+        Token::Slash => "/",
+        Token::Less => "<",
+        Token::Greater => ">",
+        Token::LessEqual => "<=",
+        Token::GreaterEqual => ">=",
+        Token::Percent => "%",
+        Token::PlusDot => "+.",
+        Token::MinusDot => "-.",
+        Token::StarDot => "*.",
+        Token::SlashDot => "/.",
+        Token::LessDot => "<.",
+        Token::GreaterDot => ">.",
+        Token::LessEqualDot => "<=.",
+        Token::GreaterEqualDot => ">=.",
+        Token::LtGt => "<>",
+        Token::EqualEqual => "==",
+        Token::NotEqual => "!=",
+        Token::VbarVbar => "||",
+        Token::AmperAmper => "&&",
+        Token::Pipe => "|>",
+        _ => return None,
+    };
+    Some(operator.into())
+}
@@ -0,0 +1,461 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::{
+    analyse::Inferred,
+    ast::{
+        AssignName, ClauseGuard, Constant, Definition, Import, Pattern, SrcSpan, TypedArg,
+        TypedClauseGuard, TypedConstant, TypedDefinition, TypedExpr, TypedPattern,
+        UnqualifiedImport,
+    },
+    build::Module,
+    language_server::{
+        compiler::LspProjectCompiler,
+        path_to_uri, src_span_to_lsp_range,
+        visit::{walk_module, Visit},
+    },
+    line_numbers::LineNumbers,
+    parse::{lexer::make_tokenizer, token::Token},
+    type_::{PatternConstructor, Type, ValueConstructorVariant},
+};
+use ecow::EcoString;
+use itertools::Itertools;
+use lsp_types::{TextEdit, WorkspaceEdit};
+
+impl<IO> LspProjectCompiler<IO> {
+    /// Switch the import at the byte index between the qualified and the
+    /// unqualified style, changing the uses of the values it imports to match.
+    ///
+    /// An import with unqualified values has them removed, and each use of
+    /// them is qualified with the name of the module, so `map` becomes
+    /// `list.map`. An import without any is given the values of the module
+    /// that are used qualified, and those uses become unqualified. A value
+    /// whose name is already used or defined for something else in the module
+    /// is kept qualified so that it does not clash. Unqualified types are left
+    /// as they are.
+    ///
+    /// Returns `None` if there is nothing to change, if the module is
+    /// imported with a discarded name or has a value with the name of the
+    /// module, as then values cannot be qualified, or if a local variable
+    /// shadows one of the unqualified values that would be qualified. Only
+    /// modules that compiled in the most recent compilation can be changed.
+    pub fn toggle_import_style(&self, module: &str, byte_index: u32) -> Option<WorkspaceEdit> {
+        let module = self.current_module(module)?;
+        let import = import_at(module, byte_index)?;
+        let qualifier = import.used_name()?;
+        let code = module.code.as_str();
+
+        let mut finder = UsageFinder {
+            code,
+            import,
+            qualifier: &qualifier,
+            qualified: vec![],
+            unqualified: vec![],
+            bound: HashSet::new(),
+            used: HashSet::new(),
+        };
+        for definition in &module.ast.definitions {
+            finder.bind_definition(definition);
+        }
+        walk_module(&module.ast, &mut finder);
+
+        let mut edits = vec![];
+        let values = if import.unqualified_values.is_empty() {
+            let labels = finder
+                .qualified
+                .iter()
+                .map(|(_, label)| label)
+                .filter(|label| !finder.bound.contains(*label) && !finder.used.contains(*label))
+                .sorted()
+                .dedup()
+                .cloned()
+                .collect_vec();
+            if labels.is_empty() {
+                return None;
+            }
+            for (prefix, label) in &finder.qualified {
+                if labels.contains(label) {
+                    edits.push((*prefix, String::new()));
+                }
+            }
+            labels
+        } else {
+            if finder.bound.contains(&qualifier) {
+                return None;
+            }
+            let guard_constants = finder.guard_constants()?;
+            for (location, name) in finder.unqualified.iter().chain(&guard_constants) {
+                edits.push((*location, format!("{qualifier}.{name}")));
+            }
+            vec![]
+        };
+        edits.push((import.location, import_text(code, import, &values)));
+
+        let line_numbers = LineNumbers::new(code);
+        let edits = edits
+            .into_iter()
+            .sorted_by_key(|(location, _)| location.start)
+            .map(|(location, new_text)| TextEdit {
+                range: src_span_to_lsp_range(location, &line_numbers),
+                new_text,
+            })
+            .collect();
+        Some(WorkspaceEdit {
+            changes: Some(HashMap::from([(
+                path_to_uri(module.input_path.clone()),
+                edits,
+            )])),
+            ..Default::default()
+        })
+    }
+}
+
+/// The import with its unqualified values replaced, keeping its unqualified
+/// types and its alias.
+/// The import of the module that the byte index is within.
+pub(super) fn import_at(module: &Module, byte_index: u32) -> Option<&Import<EcoString>> {
+    module
+        .ast
+        .definitions
+        .iter()
+        .find_map(|definition| match definition {
+            Definition::Import(import) if import.location.contains(byte_index) => Some(import),
+            _ => None,
+        })
+}
+
+fn import_text(code: &str, import: &Import<EcoString>, values: &[EcoString]) -> String {
+    let types = import
+        .unqualified_types
+        .iter()
+        .map(|type_| match &type_.as_name {
+            Some(as_name) => format!("type {} as {as_name}", type_.name),
+            None => format!("type {}", type_.name),
+        });
+    let items = types
+        .chain(values.iter().map(|value| value.to_string()))
+        .join(", ");
+    let mut text = format!("import {}", import.module);
+    if !items.is_empty() {
+        text.push_str(&format!(".{{{items}}}"));
+    }
+    if let Some((_, location)) = &import.as_name {
+        let alias = code
+            .get(location.start as usize..location.end as usize)
+            .unwrap_or_default();
+        text.push(' ');
+        text.push_str(alias);
+    }
+    text
+}
+
+/// Finds the uses of the values of an imported module, and the names of the
+/// values that are defined or used in the module.
+struct UsageFinder<'a> {
+    code: &'a str,
+    import: &'a Import<EcoString>,
+    qualifier: &'a str,
+    /// The `qualifier.` before each qualified use, with the name used.
+    qualified: Vec<(SrcSpan, EcoString)>,
+    /// Each unqualified use of a value imported by the import, with the name
+    /// it has in its module.
+    unqualified: Vec<(SrcSpan, EcoString)>,
+    /// The names defined by the module, its other imports and its patterns.
+    bound: HashSet<EcoString>,
+    /// The names used unqualified in the module.
+    used: HashSet<EcoString>,
+}
+
+impl<'a> UsageFinder<'a> {
+    fn bind_definition(&mut self, definition: &TypedDefinition) {
+        match definition {
+            Definition::Function(function) => {
+                _ = self.bound.insert(function.name.clone());
+                self.bind_arguments(&function.arguments);
+            }
+            Definition::ModuleConstant(constant) => _ = self.bound.insert(constant.name.clone()),
+            Definition::CustomType(type_) => self
+                .bound
+                .extend(type_.constructors.iter().map(|c| c.name.clone())),
+            Definition::Import(import) if import.location != self.import.location => {
+                self.bound.extend(
+                    import
+                        .unqualified_values
+                        .iter()
+                        .map(|v| v.used_name().clone()),
+                )
+            }
+            Definition::Import(_) | Definition::TypeAlias(_) => (),
+        }
+    }
+
+    fn bind_arguments(&mut self, arguments: &[TypedArg]) {
+        self.bound.extend(
+            arguments
+                .iter()
+                .filter_map(|argument| argument.get_variable_name())
+                .cloned(),
+        );
+    }
+
+    fn is_imported_from(&self, module: &str) -> bool {
+        module == self.import.module
+    }
+
+    /// The unqualified import that a name written in the module refers to.
+    fn unqualified_import(&self, name: &str) -> Option<&UnqualifiedImport> {
+        self.import
+            .unqualified_values
+            .iter()
+            .find(|value| value.used_name() == name)
+    }
+
+    /// Record an unqualified name written at the start of the location.
+    fn unqualified_use(&mut self, start: u32, name: &EcoString, module: Option<&str>) {
+        _ = self.used.insert(name.clone());
+        if !module.is_some_and(|module| self.is_imported_from(module)) {
+            return;
+        }
+        if let Some(import) = self.unqualified_import(name) {
+            let location = SrcSpan::new(start, start + name.len() as u32);
+            self.unqualified.push((location, import.name.clone()));
+        }
+    }
+
+    /// Record a qualified name written at the start of the location, if it
+    /// is qualified with the module of the import.
+    fn qualified_use(&mut self, start: u32, alias: &str, label: &EcoString) {
+        let prefix = SrcSpan::new(start, start + alias.len() as u32 + 1);
+        let text = self
+            .code
+            .get(prefix.start as usize..prefix.end as usize)
+            .and_then(|text| text.strip_suffix('.'));
+        if alias == self.qualifier && text == Some(alias) {
+            self.qualified.push((prefix, label.clone()));
+        }
+    }
+
+    /// Record a qualified name that ends at the end of the location. The
+    /// location of a module select in an expression is only that of its
+    /// label.
+    fn qualified_use_ending_at(&mut self, location: SrcSpan, alias: &str, label: &EcoString) {
+        let length = alias.len() + 1 + label.len();
+        if let Some(start) = location.end.checked_sub(length as u32) {
+            self.qualified_use(start, alias, label);
+        }
+    }
+
+    /// The unqualified uses of the imported constants in clause guards, which
+    /// the type checker replaces with their values, so they are found from
+    /// the tokens of the module instead: the names of the imported values
+    /// that are not labels, not accessed with a `.` and not otherwise found.
+    /// Returns `None` if one of these could be a local variable.
+    fn guard_constants(&self) -> Option<Vec<(SrcSpan, EcoString)>> {
+        let tokens = make_tokenizer(self.code)
+            .map_while(Result::ok)
+            .filter(|(_, token, _)| !matches!(token, Token::CommentNormal | Token::EmptyLine))
+            .collect_vec();
+        let found: HashSet<_> = self
+            .unqualified
+            .iter()
+            .map(|(location, _)| location.start)
+            .collect();
+        let mut constants = vec![];
+        for (index, (start, token, end)) in tokens.iter().enumerate() {
+            let Token::Name { name } = token else {
+                continue;
+            };
+            let Some(import) = self.unqualified_import(name) else {
+                continue;
+            };
+            let after_dot = index
+                .checked_sub(1)
+                .and_then(|previous| tokens.get(previous))
+                .is_some_and(|(_, previous, _)| *previous == Token::Dot);
+            let label = tokens
+                .get(index + 1)
+                .is_some_and(|(_, next, _)| *next == Token::Colon);
+            if after_dot || label || found.contains(start) || self.import.location.contains(*start)
+            {
+                continue;
+            }
+            if self.bound.contains(name) {
+                return None;
+            }
+            constants.push((SrcSpan::new(*start, *end), import.name.clone()));
+        }
+        Some(constants)
+    }
+
+    /// The name of the constructor a record update is written with, which
+    /// the typed AST does not keep.
+    fn record_update_constructor(&self, location: SrcSpan) -> Option<(Option<&'a str>, &'a str)> {
+        let code: &'a str = self.code;
+        let code = code.get(location.start as usize..location.end as usize)?;
+        let (name, _) = code.split_once('(')?;
+        Some(match name.trim_end().split_once('.') {
+            Some((alias, name)) => (Some(alias), name),
+            None => (None, name.trim_end()),
+        })
+    }
+}
+
+fn type_module(type_: &Type) -> Option<EcoString> {
+    type_.named_type_name().map(|(module, _)| module)
+}
+
+impl<'ast> Visit<'ast> for UsageFinder<'_> {
+    fn visit_expr(&mut self, expr: &'ast TypedExpr) {
+        match expr {
+            TypedExpr::Var {
+                location,
+                constructor,
+                name,
+            } => {
+                let module = match &constructor.variant {
+                    ValueConstructorVariant::ModuleConstant { module, .. }
+                    | ValueConstructorVariant::ModuleFn { module, .. }
+                    | ValueConstructorVariant::Record { module, .. } => Some(module.as_str()),
+                    ValueConstructorVariant::LocalVariable { .. }
+                    | ValueConstructorVariant::LocalConstant { .. } => None,
+                };
+                self.unqualified_use(location.start, name, module);
+            }
+
+            TypedExpr::ModuleSelect {
+                location,
+                label,
+                module_name,
+                module_alias,
+                ..
+            } if self.is_imported_from(module_name) => {
+                self.qualified_use_ending_at(*location, module_alias, label)
+            }
+
+            TypedExpr::RecordUpdate { location, typ, .. } => {
+                let module = type_module(typ);
+                let from_import = module.as_deref() == Some(self.import.module.as_str());
+                match self.record_update_constructor(*location) {
+                    // Only the constructors of the type of the record can be
+                    // used to update it.
+                    Some((Some(alias), name)) if from_import => {
+                        self.qualified_use(location.start, alias, &name.into())
+                    }
+                    Some((Some(_), _)) => (),
+                    Some((None, name)) => {
+                        self.unqualified_use(location.start, &name.into(), module.as_deref())
+                    }
+                    None => (),
+                }
+            }
+
+            TypedExpr::Fn { args, .. } => self.bind_arguments(args),
+
+            _ => (),
+        }
+    }
+
+    fn visit_pattern(&mut self, pattern: &'ast TypedPattern) {
+        match pattern {
+            Pattern::Variable { name, .. } | Pattern::Assign { name, .. } => {
+                _ = self.bound.insert(name.clone())
+            }
+
+            Pattern::StringPrefix {
+                left_side_assignment,
+                right_side_assignment,
+                ..
+            } => {
+                if let Some((name, _)) = left_side_assignment {
+                    _ = self.bound.insert(name.clone());
+                }
+                if let AssignName::Variable(name) = right_side_assignment {
+                    _ = self.bound.insert(name.clone());
+                }
+            }
+
+            Pattern::Constructor {
+                location,
+                name,
+                module,
+                constructor,
+                ..
+            } => {
+                let constructor_module = match constructor {
+                    Inferred::Known(PatternConstructor { module, .. }) => module.as_deref(),
+                    Inferred::Unknown => None,
+                };
+                match module {
+                    Some(alias) if constructor_module.is_some_and(|m| self.is_imported_from(m)) => {
+                        self.qualified_use(location.start, alias, name)
+                    }
+                    Some(_) => (),
+                    None => self.unqualified_use(location.start, name, constructor_module),
+                }
+            }
+
+            _ => (),
+        }
+    }
+
+    fn visit_constant(&mut self, constant: &'ast TypedConstant) {
+        match constant {
+            Constant::Var {
+                location,
+                module,
+                name,
+                constructor,
+                ..
+            } => match module {
+                Some(alias) => self.qualified_use(location.start, alias, name),
+                None => {
+                    let module =
+                        constructor
+                            .as_ref()
+                            .and_then(|constructor| match &constructor.variant {
+                                ValueConstructorVariant::ModuleConstant { module, .. }
+                                | ValueConstructorVariant::ModuleFn { module, .. }
+                                | ValueConstructorVariant::Record { module, .. } => {
+                                    Some(module.as_str())
+                                }
+                                ValueConstructorVariant::LocalVariable { .. }
+                                | ValueConstructorVariant::LocalConstant { .. } => None,
+                            });
+                    self.unqualified_use(location.start, name, module)
+                }
+            },
+
+            Constant::Record {
+                location,
+                module,
+                name,
+                typ,
+                ..
+            } => match module {
+                Some(alias) => self.qualified_use(location.start, alias, name),
+                None => {
+                    let module = type_module(typ);
+                    self.unqualified_use(location.start, name, module.as_deref())
+                }
+            },
+
+            _ => (),
+        }
+    }
+
+    fn visit_clause_guard(&mut self, guard: &'ast TypedClauseGuard) {
+        if let ClauseGuard::ModuleSelect {
+            location,
+            label,
+            module_name,
+            module_alias,
+            ..
+        } = guard
+        {
+            // The location of a module select in a clause guard is that of
+            // the name of the module.
+            if self.is_imported_from(module_name) {
+                self.qualified_use(location.start, module_alias, label);
+            }
+        }
+    }
+}
@@ -0,0 +1,290 @@
+use std::collections::HashMap;
+
+use crate::{
+    ast::{Definition, SrcSpan, UnqualifiedImport},
+    build::Origin,
+    diagnostic::{Diagnostic, Label, Level, Location},
+    io::{CommandExecutor, FileSystemReader, FileSystemWriter},
+    language_server::{
+        compiler::{LspProjectCompiler, ModuleTypeError},
+        src_span_to_lsp_range,
+    },
+    line_numbers::LineNumbers,
+    type_::{self, ModuleInterface, PRELUDE_MODULE_NAME},
+};
+use camino::{Utf8Path, Utf8PathBuf};
+use ecow::EcoString;
+use itertools::Itertools;
+use lsp_types::TextEdit;
+
+/// A name imported unqualified by two imports, or twice by one.
+struct AmbiguousImport<'a> {
+    first_module: &'a EcoString,
+    first: &'a UnqualifiedImport,
+    second_module: &'a EcoString,
+    second: &'a UnqualifiedImport,
+}
+
+impl<IO> LspProjectCompiler<IO>
+where
+    IO: CommandExecutor + FileSystemWriter + FileSystemReader + Clone,
+{
+    /// The problems with the imports of a module of the root package: each
+    /// import that is not used, with the code `unused_import`, and the name
+    /// that failed to type check because its module is not imported, with the
+    /// code `missing_import` and a hint of the imports that would define it.
+    ///
+    /// Unused imports are only known if the module compiled in the most
+    /// recent compilation, and a missing import only if it did not.
+    pub fn import_diagnostics(&self, module: &str) -> Vec<Diagnostic> {
+        let mut diagnostics = vec![];
+
+        if let Some(compiled) = self.current_module(module) {
+            for location in &compiled.ast.type_info.unused_imports {
+                diagnostics.push(Diagnostic {
+                    title: "Unused import".into(),
+                    text: "".into(),
+                    level: Level::Warning,
+                    tags: vec![],
+                    code: Some("unused_import"),
+                    location: Some(Location {
+                        src: compiled.code.clone(),
+                        path: compiled.input_path.clone(),
+                        label: Label {
+                            text: Some("This is never used".into()),
+                            span: *location,
+                        },
+                        extra_labels: vec![],
+                    }),
+                    hint: None,
+                });
+            }
+        }
+
+        if let Some(error) = self.type_error(module) {
+            let imports = self.missing_imports(module, &error.path, &error.error);
+            if !imports.is_empty() {
+                let mut diagnostic = error.to_error().to_diagnostic();
+                diagnostic.code = Some("missing_import");
+                diagnostic.hint = Some(format!(
+                    "It can be imported with one of:\n\n{}",
+                    imports
+                        .iter()
+                        .map(|import| format!("    {import}"))
+                        .join("\n")
+                ));
+                diagnostics.push(diagnostic);
+            }
+        }
+
+        diagnostics
+    }
+
+    /// The span from the start of the first import of a module to the end of
+    /// the last of the imports that follow it without any other definition in
+    /// between, which is what organising the imports of the module replaces.
+    /// Comments and blank lines between the imports do not end the block, so
+    /// they are within the span, but comments before the first import are
+    /// not. Returns `None` if the module does not start with an import, as
+    /// imports after other definitions are not leading imports.
+    ///
+    /// The module is taken as it was last parsed, so this works when it has
+    /// type errors.
+    pub fn import_block_span(&self, module: &str) -> Option<SrcSpan> {
+        let imports = self
+            .untyped_module(module)?
+            .definitions
+            .iter()
+            .map_while(|definition| match &definition.definition {
+                Definition::Import(import) => Some(import.location),
+                _ => None,
+            })
+            .collect_vec();
+        let (first, last) = (imports.first()?, imports.last()?);
+        Some(SrcSpan::new(first.start, last.end))
+    }
+
+    /// An error for each name that is imported unqualified more than once,
+    /// whether by different imports or by the same one, with the code
+    /// `ambiguous_import`. Each points at where the name is imported again
+    /// and where it was first imported. Values and types are named apart, so
+    /// a value and a type of the same name are not ambiguous.
+    ///
+    /// The module is taken as it was last parsed, so every ambiguous name is
+    /// reported rather than only the first, which is all that type checking
+    /// reports.
+    pub fn ambiguous_imports(&self, module: &str) -> Vec<Diagnostic> {
+        let Some((path, src)) = self.parsed_source(module) else {
+            return vec![];
+        };
+        self.ambiguous_import_pairs(module)
+            .into_iter()
+            .map(|ambiguity| {
+                let name = ambiguity.second.used_name();
+                let text = if ambiguity.first_module == ambiguity.second_module {
+                    format!(
+                        "`{name}` is imported from `{}` twice.",
+                        ambiguity.first_module
+                    )
+                } else {
+                    format!(
+                        "`{name}` is imported from both `{}` and `{}`.",
+                        ambiguity.first_module, ambiguity.second_module
+                    )
+                };
+                Diagnostic {
+                    title: "Ambiguous import".into(),
+                    text,
+                    level: Level::Error,
+                    tags: vec![],
+                    code: Some("ambiguous_import"),
+                    location: Some(Location {
+                        src: src.clone(),
+                        path: path.clone(),
+                        label: Label {
+                            text: Some("Imported again here".into()),
+                            span: ambiguity.second.location,
+                        },
+                        extra_labels: vec![Label {
+                            text: Some("First imported here".into()),
+                            span: ambiguity.first.location,
+                        }],
+                    }),
+                    hint: Some("One of them can be given another name with `as`.".into()),
+                }
+            })
+            .collect()
+    }
+
+    /// Give the name that is imported again at the byte index a name of its
+    /// own, made from the name and the module it is imported from, such as
+    /// `list_map` or `ListWibble`, so that it no longer clashes with the name
+    /// imported first.
+    pub fn ambiguous_import_fix(&self, module: &str, byte_index: u32) -> Option<TextEdit> {
+        let (_, src) = self.parsed_source(module)?;
+        let ambiguity = self
+            .ambiguous_import_pairs(module)
+            .into_iter()
+            .find(|ambiguity| ambiguity.second.location.contains(byte_index))?;
+        let import = ambiguity.second;
+        let segment = ambiguity.second_module.rsplit('/').next()?;
+        let alias = if import.name.starts_with(|c: char| c.is_ascii_uppercase()) {
+            let segment = segment
+                .split('_')
+                .map(|word| {
+                    let mut chars = word.chars();
+                    chars
+                        .next()
+                        .map(|first| first.to_ascii_uppercase().to_string() + chars.as_str())
+                        .unwrap_or_default()
+                })
+                .collect::<String>();
+            format!("{segment}{}", import.name)
+        } else {
+            format!("{segment}_{}", import.name)
+        };
+
+        // The location of the import is only of its name, so any alias it is
+        // already given follows it, after the `as` keyword.
+        let end = match &import.as_name {
+            Some(as_name) => {
+                let after = src.get(import.location.end as usize..)?;
+                let rest = after
+                    .trim_start()
+                    .strip_prefix("as")?
+                    .trim_start()
+                    .strip_prefix(as_name.as_str())?;
+                import.location.end + (after.len() - rest.len()) as u32
+            }
+            None => import.location.end,
+        };
+        let line_numbers = LineNumbers::new(src);
+        Some(TextEdit {
+            range: src_span_to_lsp_range(SrcSpan::new(import.location.end, end), &line_numbers),
+            new_text: format!(" as {alias}"),
+        })
+    }
+
+    fn ambiguous_import_pairs(&self, module: &str) -> Vec<AmbiguousImport<'_>> {
+        let Some(untyped) = self.untyped_module(module) else {
+            return vec![];
+        };
+        let mut values: HashMap<&EcoString, (&EcoString, &UnqualifiedImport)> = HashMap::new();
+        let mut types: HashMap<&EcoString, (&EcoString, &UnqualifiedImport)> = HashMap::new();
+        let mut ambiguities = vec![];
+        let imports = untyped
+            .iter_statements(self.project_compiler.target())
+            .filter_map(|definition| match definition {
+                Definition::Import(import) => Some(import),
+                _ => None,
+            });
+        for import in imports {
+            let unqualified = import
+                .unqualified_values
+                .iter()
+                .map(|value| (value, false))
+                .chain(import.unqualified_types.iter().map(|type_| (type_, true)));
+            for (second, is_type) in unqualified {
+                let seen = if is_type { &mut types } else { &mut values };
+                match seen.get(second.used_name()) {
+                    Some((first_module, first)) => ambiguities.push(AmbiguousImport {
+                        first_module,
+                        first,
+                        second_module: &import.module,
+                        second,
+                    }),
+                    None => _ = seen.insert(second.used_name(), (&import.module, second)),
+                }
+            }
+        }
+        ambiguities
+    }
+
+    /// The path and source of a module as it was last parsed, if that was
+    /// in the most recent compilation.
+    fn parsed_source(&self, module: &str) -> Option<(&Utf8PathBuf, &EcoString)> {
+        match (self.current_module(module), self.type_error(module)) {
+            (Some(compiled), _) => Some((&compiled.input_path, &compiled.code)),
+            (None, Some(ModuleTypeError { path, src, .. })) => Some((path, src)),
+            (None, _) => None,
+        }
+    }
+
+    /// The imports that would define the unknown name of a type error, for
+    /// the importing module at the given path.
+    fn missing_imports(&self, module: &str, path: &Utf8Path, error: &type_::Error) -> Vec<String> {
+        let origin = if path.starts_with(self.project_compiler.paths.test_directory()) {
+            Origin::Test
+        } else {
+            Origin::Src
+        };
+        let importable = |interface: &&ModuleInterface| {
+            interface.name != module
+                && interface.name != PRELUDE_MODULE_NAME
+                && self.is_importable_from(origin, interface)
+                && !self.would_create_cycle(module, &interface.name)
+        };
+        let modules = self
+            .project_compiler
+            .get_importable_modules()
+            .values()
+            .filter(importable);
+
+        let imports = match error {
+            type_::Error::UnknownVariable { name, .. } => modules
+                .filter(|interface| interface.get_public_value(name).is_some())
+                .map(|interface| format!("import {}.{{{name}}}", interface.name))
+                .collect_vec(),
+            type_::Error::UnknownType { name, .. } => modules
+                .filter(|interface| interface.get_public_type(name).is_some())
+                .map(|interface| format!("import {}.{{type {name}}}", interface.name))
+                .collect_vec(),
+            type_::Error::UnknownModule { name, .. } => modules
+                .filter(|interface| interface.name.rsplit('/').next() == Some(name.as_str()))
+                .map(|interface| format!("import {}", interface.name))
+                .collect_vec(),
+            _ => vec![],
+        };
+        imports.into_iter().sorted().collect()
+    }
+}
@@ -0,0 +1,310 @@
+use crate::{
+    ast::{
+        AssignName, ClauseGuard, Definition, Pattern, SrcSpan, TypedArg, TypedClauseGuard,
+        TypedExpr, TypedPattern,
+    },
+    build::Module,
+    language_server::{
+        compiler::LspProjectCompiler,
+        highlights::HighlightKind,
+        visit::{walk_clause_guard, walk_module, walk_pattern, Visit},
+    },
+    type_::{ValueConstructor, ValueConstructorVariant},
+};
+use ecow::EcoString;
+
+impl<IO> LspProjectCompiler<IO> {
+    /// The name of the local variable at the byte index where it is defined,
+    /// followed by everywhere it is used, so that they can all be edited at
+    /// once. A variable bound by each alternative pattern of a case clause is
+    /// defined once for each alternative.
+    ///
+    /// Module level values and values of other modules are not included, as
+    /// they may be used outside of the module. Only modules that compiled in
+    /// the most recent compilation can be searched.
+    pub fn linked_editing_ranges(&self, module: &str, byte_index: u32) -> Option<Vec<SrcSpan>> {
+        let module = self.modules.get(module)?;
+        let occurrences = local_variable_occurrences(module, byte_index)?;
+        Some(occurrences.into_iter().map(|(span, _)| span).collect())
+    }
+}
+
+/// Where the local variable at the byte index is defined, which are writes,
+/// and where it is used, which are reads, sorted by where they are.
+pub(super) fn local_variable_occurrences(
+    module: &Module,
+    byte_index: u32,
+) -> Option<Vec<(SrcSpan, HighlightKind)>> {
+    let mut finder = LocalFinder {
+        code: &module.code,
+        bindings: vec![],
+        usages: vec![],
+        aliases: vec![],
+    };
+    for definition in &module.ast.definitions {
+        if let Definition::Function(function) = definition {
+            finder.bind_arguments(&function.arguments);
+        }
+    }
+    walk_module(&module.ast, &mut finder);
+
+    let contains = |span: &SrcSpan| (span.start..=span.end).contains(&byte_index);
+    let target = finder
+        .bindings
+        .iter()
+        .find(|binding| contains(&binding.name_location))
+        .map(|binding| binding.definition)
+        .or_else(|| {
+            finder
+                .usages
+                .iter()
+                .find(|usage| contains(&usage.location))
+                .and_then(|usage| finder.definition_of(usage))
+        })?;
+    let target = finder.canonical(target);
+
+    let mut occurrences: Vec<_> = finder
+        .bindings
+        .iter()
+        .filter(|binding| finder.canonical(binding.definition) == target)
+        .map(|binding| (binding.name_location, HighlightKind::Write))
+        .chain(
+            finder
+                .usages
+                .iter()
+                .filter(|usage| {
+                    finder
+                        .definition_of(usage)
+                        .is_some_and(|definition| finder.canonical(definition) == target)
+                })
+                .map(|usage| (usage.location, HighlightKind::Read)),
+        )
+        .collect();
+    occurrences.sort_by_key(|(span, _)| span.start);
+    occurrences.dedup_by_key(|(span, _)| *span);
+    Some(occurrences)
+}
+
+/// Where a local variable is defined.
+struct Binding {
+    name: EcoString,
+    /// The location that the type checker records as the definition of the
+    /// variable, which for a function argument includes its annotation.
+    definition: SrcSpan,
+    /// The location of only the name.
+    name_location: SrcSpan,
+}
+
+/// Where a local variable is used.
+struct Usage {
+    name: EcoString,
+    location: SrcSpan,
+    definition: Defined,
+}
+
+enum Defined {
+    /// The definition recorded by the type checker.
+    At(SrcSpan),
+    /// Variables in clause guards do not record their definition, so one that
+    /// is not bound by the patterns of its clause is taken to be the closest
+    /// variable with its name defined before the case expression.
+    Before(u32),
+}
+
+struct LocalFinder<'a> {
+    code: &'a str,
+    bindings: Vec<Binding>,
+    usages: Vec<Usage>,
+    /// The definitions of a variable in the alternative patterns of a case
+    /// clause, with the definition in the first pattern they stand for.
+    aliases: Vec<(SrcSpan, SrcSpan)>,
+}
+
+impl LocalFinder<'_> {
+    fn canonical(&self, definition: SrcSpan) -> SrcSpan {
+        self.aliases
+            .iter()
+            .find(|(alias, _)| *alias == definition)
+            .map_or(definition, |(_, canonical)| *canonical)
+    }
+
+    /// Clause guards are found before the variables defined before them, so
+    /// this is only correct once the whole module has been walked.
+    fn definition_of(&self, usage: &Usage) -> Option<SrcSpan> {
+        match usage.definition {
+            Defined::At(definition) => Some(definition),
+            Defined::Before(position) => self
+                .bindings
+                .iter()
+                .filter(|binding| {
+                    binding.name == usage.name && binding.name_location.start < position
+                })
+                .max_by_key(|binding| binding.name_location.start)
+                .map(|binding| binding.definition),
+        }
+    }
+
+    fn bind(&mut self, name: &EcoString, definition: SrcSpan, name_start: u32) {
+        let name_location = SrcSpan::new(name_start, name_start + name.len() as u32);
+        let text = self
+            .code
+            .get(name_location.start as usize..name_location.end as usize);
+        // Variables added by the compiler, such as for pipelines and `use`
+        // patterns, have a location where their name is not written.
+        if text == Some(name.as_str()) {
+            self.bindings.push(Binding {
+                name: name.clone(),
+                definition,
+                name_location,
+            });
+        }
+    }
+
+    fn bind_arguments(&mut self, arguments: &[TypedArg]) {
+        for argument in arguments {
+            if let Some((name, start)) = argument.variable_name_start(self.code) {
+                self.bind(name, argument.location, start);
+            }
+        }
+    }
+
+    fn use_local(&mut self, name: &EcoString, location: SrcSpan, constructor: &ValueConstructor) {
+        if let ValueConstructorVariant::LocalVariable {
+            location: definition,
+        } = &constructor.variant
+        {
+            self.usages.push(Usage {
+                name: name.clone(),
+                location,
+                definition: Defined::At(*definition),
+            });
+        }
+    }
+}
+
+impl<'ast> Visit<'ast> for LocalFinder<'_> {
+    fn visit_expr(&mut self, expr: &'ast TypedExpr) {
+        match expr {
+            TypedExpr::Var {
+                location,
+                constructor,
+                name,
+            } => self.use_local(name, *location, constructor),
+
+            TypedExpr::Fn { args, .. } => self.bind_arguments(args),
+
+            TypedExpr::Case {
+                location, clauses, ..
+            } => {
+                for clause in clauses {
+                    let mut first = Bindings::default();
+                    for pattern in &clause.pattern {
+                        walk_pattern(pattern, &mut first);
+                    }
+                    for alternative in &clause.alternative_patterns {
+                        let mut bindings = Bindings::default();
+                        for pattern in alternative {
+                            walk_pattern(pattern, &mut bindings);
+                        }
+                        for (name, definition) in bindings.0 {
+                            if let Some((_, canonical)) = first.0.iter().find(|(n, _)| *n == name) {
+                                self.aliases.push((definition, *canonical));
+                            }
+                        }
+                    }
+
+                    // A guard can only use the variables of the first pattern
+                    // of its clause, in place of those of the alternatives.
+                    let mut guard_vars = GuardVars::default();
+                    if let Some(guard) = &clause.guard {
+                        walk_clause_guard(guard, &mut guard_vars);
+                    }
+                    for (name, guard_location) in guard_vars.0 {
+                        let definition = match first.0.iter().find(|(n, _)| *n == name) {
+                            Some((_, definition)) => Defined::At(*definition),
+                            None => Defined::Before(location.start),
+                        };
+                        self.usages.push(Usage {
+                            name,
+                            location: guard_location,
+                            definition,
+                        });
+                    }
+                }
+            }
+
+            _ => (),
+        }
+    }
+
+    fn visit_pattern(&mut self, pattern: &'ast TypedPattern) {
+        match pattern {
+            Pattern::Variable { name, location, .. } | Pattern::Assign { name, location, .. } => {
+                self.bind(name, *location, location.start)
+            }
+
+            Pattern::StringPrefix {
+                left_side_assignment,
+                right_side_assignment,
+                right_location,
+                ..
+            } => {
+                if let Some((name, location)) = left_side_assignment {
+                    self.bind(name, *location, location.start);
+                }
+                if let AssignName::Variable(name) = right_side_assignment {
+                    self.bind(name, *right_location, right_location.start);
+                }
+            }
+
+            Pattern::VarUsage {
+                name,
+                location,
+                constructor: Some(constructor),
+                ..
+            } => self.use_local(name, *location, constructor),
+
+            _ => (),
+        }
+    }
+}
+
+/// The variables defined by a pattern, with their definitions.
+#[derive(Default)]
+struct Bindings(Vec<(EcoString, SrcSpan)>);
+
+impl<'ast> Visit<'ast> for Bindings {
+    fn visit_pattern(&mut self, pattern: &'ast TypedPattern) {
+        match pattern {
+            Pattern::Variable { name, location, .. } | Pattern::Assign { name, location, .. } => {
+                self.0.push((name.clone(), *location))
+            }
+            Pattern::StringPrefix {
+                left_side_assignment,
+                right_side_assignment,
+                right_location,
+                ..
+            } => {
+                if let Some((name, location)) = left_side_assignment {
+                    self.0.push((name.clone(), *location));
+                }
+                if let AssignName::Variable(name) = right_side_assignment {
+                    self.0.push((name.clone(), *right_location));
+                }
+            }
+            _ => (),
+        }
+    }
+}
+
+/// The variables used by a clause guard.
+#[derive(Default)]
+struct GuardVars(Vec<(EcoString, SrcSpan)>);
+
+impl<'ast> Visit<'ast> for GuardVars {
+    fn visit_clause_guard(&mut self, guard: &'ast TypedClauseGuard) {
+        if let ClauseGuard::Var { name, location, .. } = guard {
+            self.0.push((name.clone(), *location));
+        }
+    }
+}
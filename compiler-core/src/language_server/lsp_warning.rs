@@ -0,0 +1,116 @@
+use camino::Utf8PathBuf;
+use ecow::EcoString;
+
+use crate::{
+    ast::SrcSpan,
+    diagnostic::{self, Diagnostic, Location},
+};
+
+/// A warning that only the language server reports, found by one of its own
+/// passes over the typed AST of a module. The compiler does not consider
+/// these to be problems, so they are never counted as warnings that fail the
+/// build.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LspWarning {
+    pub path: Utf8PathBuf,
+    pub src: EcoString,
+    pub kind: LspWarningKind,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LspWarningKind {
+    /// A use of `panic`. Panicking is a normal way for a program to stop, so
+    /// this is only reported if the user has asked for it.
+    Panic { location: SrcSpan },
+
+    /// A local variable with the same name as one that is already in scope.
+    /// Shadowing is allowed, so this too is only reported if the user has
+    /// asked for it.
+    ShadowedVariable {
+        location: SrcSpan,
+        name: EcoString,
+        shadowed: SrcSpan,
+    },
+
+    /// A public function of the root package that no other module uses, so
+    /// could be made private. This is only reported if the user has asked
+    /// for it, as a hint rather than a warning.
+    PublicFunctionCouldBePrivate { location: SrcSpan, name: EcoString },
+}
+
+/// The codes of the language server's warnings that are suggestions rather
+/// than problems with the code, which the client is told to show less
+/// prominently as hints.
+const HINT_CODES: &[&str] = &["could_be_private"];
+
+/// Whether the diagnostic is one of the language server's hints. These are
+/// reported in the same way as warnings other than in their severity.
+pub fn is_hint(diagnostic: &Diagnostic) -> bool {
+    diagnostic
+        .code
+        .is_some_and(|code| HINT_CODES.contains(&code))
+}
+
+impl LspWarning {
+    pub fn to_diagnostic(&self) -> Diagnostic {
+        let location = |text: &str, span: SrcSpan, extra_labels| {
+            Some(Location {
+                path: self.path.clone(),
+                src: self.src.clone(),
+                label: diagnostic::Label {
+                    text: Some(text.into()),
+                    span,
+                },
+                extra_labels,
+            })
+        };
+        match &self.kind {
+            LspWarningKind::Panic { location: span } => Diagnostic {
+                title: "Panic found".into(),
+                text: "This code will crash if it is run.".into(),
+                level: diagnostic::Level::Warning,
+                tags: vec![],
+                code: Some("panic"),
+                location: location("This code panics", *span, vec![]),
+                hint: None,
+            },
+
+            LspWarningKind::ShadowedVariable {
+                location: span,
+                name,
+                shadowed,
+            } => Diagnostic {
+                title: "Shadowed variable".into(),
+                text: format!(
+                    "This variable has the same name as one that is already in scope,
+so `{name}` no longer refers to the earlier variable after it."
+                ),
+                level: diagnostic::Level::Warning,
+                tags: vec![],
+                code: Some("shadowing"),
+                location: location(
+                    &format!("This shadows `{name}`"),
+                    *span,
+                    vec![diagnostic::Label {
+                        text: Some("Previously defined here".into()),
+                        span: *shadowed,
+                    }],
+                ),
+                hint: None,
+            },
+
+            LspWarningKind::PublicFunctionCouldBePrivate {
+                location: span,
+                name,
+            } => Diagnostic {
+                title: "Public function could be private".into(),
+                text: format!("No other module uses `{name}`, so it could be made private."),
+                level: diagnostic::Level::Warning,
+                tags: vec![],
+                code: Some("could_be_private"),
+                location: location("Only used by this module", *span, vec![]),
+                hint: None,
+            },
+        }
+    }
+}
@@ -0,0 +1,52 @@
+use crate::{
+    ast::SrcSpan,
+    language_server::compiler::{LspProjectCompiler, ModuleTypeError},
+    type_::{self, pretty::Printer},
+};
+
+/// The two types of a type error where a value of one type was given where
+/// a value of another type was expected, printed as they would be written in
+/// Gleam.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TypeMismatch {
+    pub expected: String,
+    pub given: String,
+    /// The location of the expression that has the wrong type.
+    pub location: SrcSpan,
+}
+
+impl<IO> LspProjectCompiler<IO> {
+    /// The expected and given types of the type error of a module whose
+    /// location contains the span, if its error is one where the types do
+    /// not match. Type variables are named as they are in the error message,
+    /// including those named by annotations.
+    ///
+    /// Only the error of the module from the most recent compilation is
+    /// known, and if compilation failed at another module first it has none.
+    pub fn type_error_details(&self, module: &str, span: SrcSpan) -> Option<TypeMismatch> {
+        let Some(ModuleTypeError {
+            error:
+                type_::Error::CouldNotUnify {
+                    location,
+                    expected,
+                    given,
+                    rigid_type_names,
+                    ..
+                },
+            ..
+        }) = self.type_error(module)
+        else {
+            return None;
+        };
+        if span.start < location.start || location.end < span.end {
+            return None;
+        }
+        let mut printer = Printer::new();
+        printer.with_names(rigid_type_names.clone());
+        Some(TypeMismatch {
+            expected: printer.pretty_print(expected, 0),
+            given: printer.pretty_print(given, 0),
+            location: *location,
+        })
+    }
+}
@@ -0,0 +1,376 @@
+use std::{collections::HashMap, ops::Deref, sync::Arc};
+
+use crate::{
+    ast::{Definition, SrcSpan, TypedDefinition, TypedFunction},
+    build::{Module, Origin, Target},
+    io::{CommandExecutor, FileSystemReader, FileSystemWriter},
+    language_server::{compiler::LspProjectCompiler, src_span_to_lsp_range},
+    line_numbers::LineNumbers,
+    type_::{pretty::Printer, Type, TypeVar, ValueConstructor, ValueConstructorVariant},
+};
+use ecow::EcoString;
+use itertools::Itertools;
+use lsp_types::TextEdit;
+use serde::{Deserialize, Serialize};
+
+/// Identifies a definition at the top level of a module across compilations,
+/// such as in the document symbols and code lenses sent to the client.
+///
+/// The id is made from the module and the name of the definition rather than
+/// its location, so it stays the same when the code around it is edited. Type
+/// names start with an uppercase letter and value names with a lowercase one,
+/// so the name is unique within the module.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct DefinitionId {
+    pub module: EcoString,
+    pub name: EcoString,
+}
+
+/// A custom type defined in a module.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TypeDefInfo {
+    pub name: EcoString,
+    /// The location of the `pub type Name` header.
+    pub location: SrcSpan,
+    pub public: bool,
+    pub opaque: bool,
+    pub parameters: Vec<TypeParameterInfo>,
+    pub constructors: Vec<ConstructorInfo>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TypeParameterInfo {
+    pub name: EcoString,
+    pub type_: Arc<Type>,
+}
+
+/// One of the variants of a custom type.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConstructorInfo {
+    pub name: EcoString,
+    pub location: SrcSpan,
+    pub fields: Vec<FieldInfo>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldInfo {
+    pub label: Option<EcoString>,
+    pub location: SrcSpan,
+    pub type_: Arc<Type>,
+}
+
+impl<IO> LspProjectCompiler<IO> {
+    /// The custom types defined in a module of the root package, in the order
+    /// they are defined.
+    ///
+    /// Dependency modules are not type checked by the language server, so
+    /// there is no typed AST for them and this returns nothing.
+    pub fn module_types(&self, module: &str) -> Vec<TypeDefInfo> {
+        let Some(module) = self.modules.get(module) else {
+            return vec![];
+        };
+
+        module
+            .ast
+            .definitions
+            .iter()
+            .filter_map(|definition| match definition {
+                Definition::CustomType(type_) => Some(type_),
+                _ => None,
+            })
+            .map(|type_| TypeDefInfo {
+                name: type_.name.clone(),
+                location: type_.location,
+                public: type_.public,
+                opaque: type_.opaque,
+                parameters: type_
+                    .parameters
+                    .iter()
+                    .zip(&type_.typed_parameters)
+                    .map(|(name, type_)| TypeParameterInfo {
+                        name: name.clone(),
+                        type_: type_.clone(),
+                    })
+                    .collect(),
+                constructors: type_
+                    .constructors
+                    .iter()
+                    .map(|constructor| ConstructorInfo {
+                        name: constructor.name.clone(),
+                        location: constructor.location,
+                        fields: constructor
+                            .arguments
+                            .iter()
+                            .map(|argument| FieldInfo {
+                                label: argument.label.clone(),
+                                location: argument.location,
+                                type_: argument.type_.clone(),
+                            })
+                            .collect(),
+                    })
+                    .collect(),
+            })
+            .collect()
+    }
+
+    /// The functions of a module of the root package that have an external
+    /// implementation, with an entry for each target that they have one for.
+    /// The location is that of the function's head.
+    pub fn external_functions(&self, module: &str) -> Vec<(EcoString, SrcSpan, Target)> {
+        let Some(module) = self.modules.get(module) else {
+            return vec![];
+        };
+
+        let mut functions = vec![];
+        for definition in &module.ast.definitions {
+            let Definition::Function(function) = definition else {
+                continue;
+            };
+            let externals = [
+                (Target::Erlang, &function.external_erlang),
+                (Target::JavaScript, &function.external_javascript),
+            ];
+            for (target, external) in externals {
+                if external.is_some() {
+                    functions.push((function.name.clone(), function.location, target));
+                }
+            }
+        }
+        functions
+    }
+
+    /// The constants defined in a module of the root package, in the order
+    /// they are defined, with the type of each printed as it would be written
+    /// in Gleam and the location of its name. As with `module_types`
+    /// dependency modules have no typed AST, so this returns nothing for them.
+    pub fn module_constants(&self, module: &str) -> Vec<(EcoString, String, SrcSpan)> {
+        let Some(module) = self.modules.get(module) else {
+            return vec![];
+        };
+
+        module
+            .ast
+            .definitions
+            .iter()
+            .filter_map(|definition| match definition {
+                Definition::ModuleConstant(constant) => Some((
+                    constant.name.clone(),
+                    Printer::new().pretty_print(&constant.type_, 0),
+                    constant.location,
+                )),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// The ids of the definitions in a module of the root package along with
+    /// their current locations, in the order they are defined. Imports are not
+    /// definitions.
+    pub fn definition_ids(&self, module: &str) -> Vec<(DefinitionId, SrcSpan)> {
+        let Some(compiled) = self.modules.get(module) else {
+            return vec![];
+        };
+        compiled
+            .ast
+            .definitions
+            .iter()
+            .filter_map(|definition| {
+                let id = DefinitionId {
+                    module: compiled.name.clone(),
+                    name: definition_name(definition)?.clone(),
+                };
+                Some((id, definition.location()))
+            })
+            .collect()
+    }
+
+    /// The current location of a definition, such as one that a client is
+    /// resolving a code lens for after the module has been recompiled. Returns
+    /// `None` if the definition no longer exists.
+    pub fn locate_definition(&self, id: &DefinitionId) -> Option<SrcSpan> {
+        self.modules
+            .get(&id.module)?
+            .ast
+            .definitions
+            .iter()
+            .find(|definition| definition_name(definition) == Some(&id.name))
+            .map(|definition| definition.location())
+    }
+
+    /// The module and name of the test function containing the byte index,
+    /// as run by gleeunit: a public function of a module in the `test`
+    /// directory that takes no arguments and has a name ending in `_test`.
+    /// Only module functions can be tests, so an anonymous function is part
+    /// of the test that defines it, and one defined in a function that is
+    /// not a test has no enclosing test.
+    ///
+    /// The last version of the module that compiled is used, as a module with
+    /// errors cannot be run.
+    pub fn enclosing_test(&self, module: &str, byte_index: u32) -> Option<(EcoString, EcoString)> {
+        let compiled = self.modules.get(module)?;
+        compiled
+            .ast
+            .definitions
+            .iter()
+            .find_map(|definition| match definition {
+                Definition::Function(function)
+                    if function.full_location().contains(byte_index)
+                        && is_test_function(compiled, function) =>
+                {
+                    Some((compiled.name.clone(), function.name.clone()))
+                }
+                _ => None,
+            })
+    }
+
+    /// The inferred return type of the module function containing the byte
+    /// index, printed as it would be written in Gleam. Type variables are
+    /// named as they are in the type of the whole function, so they match
+    /// those shown when hovering over it rather than any written in the
+    /// annotations of its arguments.
+    ///
+    /// The last version of the module that compiled is used.
+    pub fn enclosing_function_return_type(&self, module: &str, byte_index: u32) -> Option<String> {
+        let function = enclosing_function(self.modules.get(module)?, byte_index)?;
+        let mut printer = Printer::new();
+        for argument in &function.arguments {
+            _ = printer.pretty_print(&argument.type_, 0);
+        }
+        Some(printer.pretty_print(&function.return_type, 0))
+    }
+
+    /// Annotate the module function containing the byte index with its
+    /// inferred return type. Returns `None` if the function already has a
+    /// return annotation, or if its return type has type variables, as their
+    /// names may not match those of the annotations of its arguments.
+    ///
+    /// Only modules that compiled in the most recent compilation can be
+    /// annotated.
+    pub fn annotate_return_type(&self, module: &str, byte_index: u32) -> Option<TextEdit> {
+        let compiled = self.current_module(module)?;
+        let function = enclosing_function(compiled, byte_index)?;
+        if function.return_annotation.is_some() || has_type_variables(&function.return_type) {
+            return None;
+        }
+        let type_ = self.enclosing_function_return_type(module, byte_index)?;
+        let end = function.location.end;
+        let line_numbers = LineNumbers::new(&compiled.code);
+        Some(TextEdit {
+            range: src_span_to_lsp_range(SrcSpan::new(end, end), &line_numbers),
+            new_text: format!(" -> {type_}"),
+        })
+    }
+}
+
+impl<IO> LspProjectCompiler<IO>
+where
+    IO: CommandExecutor + FileSystemWriter + FileSystemReader + Clone,
+{
+    /// The name, arity and signature of each public function of a module,
+    /// in the order they are defined. The signature is written as the head of
+    /// the function would be without the names of its arguments, such as
+    /// `fn map(List(a), with: fn(a) -> b) -> List(b)`.
+    ///
+    /// This reads the module's interface, so it works for the modules of
+    /// dependencies as well as those of the root package.
+    pub fn module_function_signatures(&self, module: &str) -> Vec<(EcoString, usize, String)> {
+        let Some(interface) = self.get_module_inferface(module) else {
+            return vec![];
+        };
+        interface
+            .values
+            .iter()
+            .filter(|(_, value)| value.public)
+            .filter_map(|(name, value)| match &value.variant {
+                ValueConstructorVariant::ModuleFn {
+                    arity, location, ..
+                } => Some((name, value, *arity, location.start)),
+                _ => None,
+            })
+            .sorted_by_key(|(name, _, _, start)| (*start, *name))
+            .map(|(name, value, arity, _)| (name.clone(), arity, function_signature(name, value)))
+            .collect()
+    }
+}
+
+/// The head of a module function as it would be written without the names of
+/// its arguments.
+fn function_signature(name: &str, value: &ValueConstructor) -> String {
+    let Some((arguments, return_)) = value.type_.fn_types() else {
+        return format!("fn {name}()");
+    };
+    let labels: HashMap<_, _> = value
+        .field_map()
+        .map(|field_map| {
+            field_map
+                .fields
+                .iter()
+                .map(|(label, index)| (*index as usize, label))
+                .collect()
+        })
+        .unwrap_or_default();
+    let mut printer = Printer::new();
+    let arguments = arguments
+        .iter()
+        .enumerate()
+        .map(|(index, argument)| {
+            let type_ = printer.pretty_print(argument, 0);
+            match labels.get(&index) {
+                Some(label) => format!("{label}: {type_}"),
+                None => type_,
+            }
+        })
+        .join(", ");
+    let return_ = printer.pretty_print(&return_, 0);
+    format!("fn {name}({arguments}) -> {return_}")
+}
+
+/// Whether a function is a test run by gleeunit, being a public function of
+/// a module in the `test` directory that takes no arguments and has a name
+/// ending in `_test`.
+pub(super) fn is_test_function(module: &Module, function: &TypedFunction) -> bool {
+    module.origin == Origin::Test
+        && function.public
+        && function.arguments.is_empty()
+        && function.name.ends_with("_test")
+}
+
+/// The function defined at the top level of a module that contains the byte
+/// index.
+fn enclosing_function(module: &Module, byte_index: u32) -> Option<&TypedFunction> {
+    module
+        .ast
+        .definitions
+        .iter()
+        .find_map(|definition| match definition {
+            Definition::Function(function) if function.full_location().contains(byte_index) => {
+                Some(function)
+            }
+            _ => None,
+        })
+}
+
+/// Whether a type has any type variables, whether generic or not yet known.
+fn has_type_variables(type_: &Type) -> bool {
+    match type_ {
+        Type::Var { type_ } => match type_.borrow().deref() {
+            TypeVar::Link { type_ } => has_type_variables(type_),
+            TypeVar::Unbound { .. } | TypeVar::Generic { .. } => true,
+        },
+        Type::Named { args, .. } => args.iter().any(|arg| has_type_variables(arg)),
+        Type::Fn { args, retrn } => {
+            args.iter().any(|arg| has_type_variables(arg)) || has_type_variables(retrn)
+        }
+        Type::Tuple { elems } => elems.iter().any(|elem| has_type_variables(elem)),
+    }
+}
+
+fn definition_name(definition: &TypedDefinition) -> Option<&EcoString> {
+    match definition {
+        Definition::Function(function) => Some(&function.name),
+        Definition::TypeAlias(alias) => Some(&alias.alias),
+        Definition::CustomType(type_) => Some(&type_.name),
+        Definition::ModuleConstant(constant) => Some(&constant.name),
+        Definition::Import(_) => None,
+    }
+}
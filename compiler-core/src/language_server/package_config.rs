@@ -0,0 +1,90 @@
+use crate::{
+    ast::SrcSpan,
+    config::PackageConfig,
+    diagnostic::{Diagnostic, Label, Level, Location},
+};
+
+/// Check the source of a `gleam.toml` file, returning a diagnostic for the
+/// first invalid or missing field found.
+///
+/// Unlike creating a compiler for the project this never fails, so the
+/// problem can be shown in the file itself while the project cannot be
+/// compiled.
+pub fn validate_config(config_source: &str) -> Vec<Diagnostic> {
+    let error = match toml::from_str::<PackageConfig>(config_source) {
+        Ok(_) => return vec![],
+        Err(error) => error,
+    };
+
+    // The message ends with the position of the error, which is shown by the
+    // location of the diagnostic instead.
+    let message = error.to_string();
+    let message = match message.rsplit_once(" at line ") {
+        Some((message, _)) => message.to_string(),
+        None => message,
+    };
+
+    // Errors in the value of a field are given the position of the table the
+    // field is in, so the field is found by its key where possible.
+    let value_span = message
+        .rsplit_once(" for key `")
+        .and_then(|(_, key)| key.strip_suffix('`'))
+        .and_then(|key| value_span(config_source, key));
+    let span = match (value_span, error.line_col()) {
+        (Some(span), _) => span,
+        (None, Some((line, column))) => line_span(config_source, line, column),
+        (None, None) => SrcSpan::new(0, 0),
+    };
+
+    vec![Diagnostic {
+        title: "Invalid gleam.toml".into(),
+        text: "".into(),
+        level: Level::Error,
+        tags: vec![],
+        code: None,
+        location: Some(Location {
+            src: config_source.into(),
+            path: "gleam.toml".into(),
+            label: Label {
+                text: Some(message),
+                span,
+            },
+            extra_labels: vec![],
+        }),
+        hint: None,
+    }]
+}
+
+/// The value of the first field with the last part of the dotted key.
+fn value_span(src: &str, key: &str) -> Option<SrcSpan> {
+    let key = key.rsplit('.').next()?;
+    let mut line_start = 0;
+    for line in src.split_terminator('\n') {
+        let indent = line.len() - line.trim_start().len();
+        let value = line
+            .trim_start()
+            .strip_prefix(key)
+            .map(str::trim_start)
+            .and_then(|rest| rest.strip_prefix('='));
+        if let Some(value) = value {
+            let start = line_start + line.len() - value.trim_start().len();
+            let end = line_start + indent + line.trim().len();
+            return Some(SrcSpan::new(start as u32, end as u32));
+        }
+        line_start += line.len() + 1;
+    }
+    None
+}
+
+/// From the position to the end of its line, leaving out trailing whitespace.
+fn line_span(src: &str, line: usize, column: usize) -> SrcSpan {
+    let line_start = src
+        .split_terminator('\n')
+        .take(line)
+        .map(|line| line.len() + 1)
+        .sum::<usize>();
+    let start = (line_start + column).min(src.len());
+    let rest = src.get(start..).unwrap_or_default();
+    let end = start + rest.lines().next().unwrap_or_default().trim_end().len();
+    SrcSpan::new(start as u32, end as u32)
+}
@@ -0,0 +1,103 @@
+use crate::{
+    analyse::Inferred,
+    ast::{Pattern, TypedPattern},
+    diagnostic::Diagnostic,
+    language_server::{
+        compiler::LspProjectCompiler,
+        visit::{walk_module, Visit},
+    },
+    type_, Warning,
+};
+use ecow::EcoString;
+
+impl<IO> LspProjectCompiler<IO> {
+    /// The case clauses of a module of the root package that can never match
+    /// because an earlier clause matches the same values, as found by the
+    /// exhaustiveness checking of the type checker.
+    ///
+    /// Each diagnostic is for the whole redundant clause and has the code
+    /// `unreachable_case_clause`. The clauses are those of the most recent
+    /// compilation in which the module was type checked.
+    pub fn redundant_patterns(&self, module: &str) -> Vec<Diagnostic> {
+        self.redundant_clauses
+            .get(module)
+            .into_iter()
+            .flatten()
+            .map(Warning::to_diagnostic)
+            .collect()
+    }
+
+    /// The module of the type and the name of the constructor matched by the
+    /// innermost constructor pattern at the byte index, such as
+    /// `("gleam", "Ok")` for `Ok(value)`. The name is that of the constructor
+    /// where it is defined, even if the pattern uses an alias of it.
+    ///
+    /// The module is searched as it was when it last compiled successfully.
+    pub fn pattern_constructor(
+        &self,
+        module: &str,
+        byte_index: u32,
+    ) -> Option<(EcoString, EcoString)> {
+        let module = self.modules.get(module)?;
+        let mut finder = ConstructorPatternFinder {
+            byte_index,
+            found: None,
+        };
+        walk_module(&module.ast, &mut finder);
+        finder.found
+    }
+
+    /// Keep the warnings for redundant clauses of the modules that were type
+    /// checked in the compilation that just finished, replacing those from
+    /// earlier compilations. The warnings of the root package are all still
+    /// held by the emitter at this point, to be taken away and reported.
+    pub(crate) fn record_redundant_clauses(&mut self) {
+        for module in self.compile_order.iter().chain(&self.failed_modules) {
+            _ = self.redundant_clauses.remove(module);
+        }
+        let warnings = self.warnings.warnings.read().expect("Vector lock poisoned");
+        for warning in warnings.iter() {
+            let Warning::Type {
+                path,
+                warning: type_::Warning::UnreachableCaseClause { .. },
+                ..
+            } = warning
+            else {
+                continue;
+            };
+            if let Some(module) = self.module_name_for_path(path) {
+                self.redundant_clauses
+                    .entry(module)
+                    .or_default()
+                    .push(warning.clone());
+            }
+        }
+    }
+}
+
+struct ConstructorPatternFinder {
+    byte_index: u32,
+    found: Option<(EcoString, EcoString)>,
+}
+
+impl<'ast> Visit<'ast> for ConstructorPatternFinder {
+    fn visit_pattern(&mut self, pattern: &'ast TypedPattern) {
+        let Pattern::Constructor {
+            location,
+            constructor: Inferred::Known(constructor),
+            type_,
+            ..
+        } = pattern
+        else {
+            return;
+        };
+        if !(location.start..=location.end).contains(&self.byte_index) {
+            return;
+        }
+        // Patterns are visited before the patterns within them, so the last
+        // one found is the innermost.
+        if let Some((type_module, _)) = type_.named_type_name() {
+            self.found = Some((type_module, constructor.name.clone()));
+        }
+    }
+}
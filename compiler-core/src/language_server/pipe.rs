@@ -0,0 +1,269 @@
+use crate::{
+    ast::{CallArg, SrcSpan, TypedExpr, PIPE_VARIABLE},
+    language_server::{
+        compiler::LspProjectCompiler,
+        src_span_to_lsp_range,
+        visit::{walk_module, Visit},
+    },
+    line_numbers::LineNumbers,
+    type_::pretty::Printer,
+};
+use itertools::Itertools;
+use lsp_types::TextEdit;
+
+/// The precedence of `|>`, as used by the parser.
+const PIPE_PRECEDENCE: u8 = 6;
+
+impl<IO> LspProjectCompiler<IO> {
+    /// Rewrite the innermost pipeline or function call containing the byte
+    /// index, turning `f(g(x))` into `x |> g |> f` and the other way around.
+    ///
+    /// The piped value is always given as the first argument that has no
+    /// label, which is where `|>` inserts it. Calls for which that is not
+    /// possible, such as ones where every argument is labelled, are not
+    /// rewritten.
+    pub fn toggle_pipe(&self, module: &str, byte_index: u32) -> Option<TextEdit> {
+        self.pipe_refactoring(module, byte_index)
+            .map(|(_, edit)| edit)
+    }
+
+    /// The edit made by `toggle_pipe` along with a description of it.
+    pub(crate) fn pipe_refactoring(
+        &self,
+        module: &str,
+        byte_index: u32,
+    ) -> Option<(&'static str, TextEdit)> {
+        let module = self.modules.get(module)?;
+        let mut finder = PipeTargetFinder {
+            byte_index,
+            target: None,
+            stages: vec![],
+        };
+        walk_module(&module.ast, &mut finder);
+        let target = finder.target?;
+
+        let code = module.code.as_str();
+        let (title, new_text) = match target {
+            TypedExpr::Pipeline {
+                assignments,
+                finally,
+                ..
+            } => {
+                let values = assignments.iter().map(|a| a.value.as_ref());
+                ("Convert from pipe", from_pipe(code, values, finally)?)
+            }
+            _ => ("Convert to pipe", to_pipe(code, target)?),
+        };
+        let line_numbers = LineNumbers::new(code);
+        let edit = TextEdit {
+            range: src_span_to_lsp_range(target.location(), &line_numbers),
+            new_text,
+        };
+        Some((title, edit))
+    }
+
+    /// The type of the value returned by the stage of the innermost pipeline
+    /// containing the byte index. The first stage is the value being piped.
+    pub fn pipe_stage_type(&self, module: &str, byte_index: u32) -> Option<String> {
+        let module = self.modules.get(module)?;
+        let mut finder = PipelineFinder {
+            byte_index,
+            pipeline: None,
+        };
+        walk_module(&module.ast, &mut finder);
+        let TypedExpr::Pipeline {
+            assignments,
+            finally,
+            ..
+        } = finder.pipeline?
+        else {
+            return None;
+        };
+
+        let stage = assignments
+            .iter()
+            .map(|assignment| assignment.value.as_ref())
+            .chain(std::iter::once(finally.as_ref()))
+            .find(|stage| full_span(stage).contains(byte_index))?;
+        Some(Printer::new().pretty_print(&stage.type_(), 0))
+    }
+}
+
+/// Finds the smallest pipeline that contains the byte index.
+struct PipelineFinder<'ast> {
+    byte_index: u32,
+    pipeline: Option<&'ast TypedExpr>,
+}
+
+impl<'ast> Visit<'ast> for PipelineFinder<'ast> {
+    fn visit_expr(&mut self, expr: &'ast TypedExpr) {
+        if matches!(expr, TypedExpr::Pipeline { .. }) && expr.location().contains(self.byte_index) {
+            self.pipeline = Some(expr);
+        }
+    }
+}
+
+/// Finds the smallest pipeline or call written by the programmer that
+/// contains the byte index.
+struct PipeTargetFinder<'ast> {
+    byte_index: u32,
+    target: Option<&'ast TypedExpr>,
+    /// The locations of calls made by pipeline stages. In `x |> f(y)` where
+    /// `f(y)` returns a function, `f(y)` is part of the stage rather than a
+    /// call that can be rewritten on its own.
+    stages: Vec<SrcSpan>,
+}
+
+impl<'ast> Visit<'ast> for PipeTargetFinder<'ast> {
+    fn visit_expr(&mut self, expr: &'ast TypedExpr) {
+        let is_candidate = match expr {
+            TypedExpr::Pipeline { .. } => true,
+            // Calls with implicit arguments are the stages of a pipeline or
+            // the desugaring of `use`.
+            TypedExpr::Call { location, args, .. } if args.iter().any(|arg| arg.implicit) => {
+                self.stages.push(*location);
+                false
+            }
+            TypedExpr::Call { location, .. } => !self.stages.contains(location),
+            _ => false,
+        };
+        // Children are visited after their parents, so a later match is
+        // always nested within an earlier one.
+        if is_candidate && expr.location().contains(self.byte_index) {
+            self.target = Some(expr);
+        }
+    }
+}
+
+/// `f(g(x), y)` becomes `x |> g |> f(y)`, threading the first unlabelled
+/// argument of each call through the pipeline for as long as that argument
+/// is itself a call.
+fn to_pipe(code: &str, call: &TypedExpr) -> Option<String> {
+    let mut stages = vec![];
+    let mut current = call;
+    while let Some((fun, args)) = call_parts(current) {
+        let Some(piped) = args.iter().copied().find(|arg| arg.label.is_none()) else {
+            break;
+        };
+        let rest = args
+            .into_iter()
+            .filter(|arg| !std::ptr::eq(*arg, piped))
+            .collect_vec();
+        stages.push(stage(code, current, fun, &rest));
+        current = &piped.value;
+    }
+    if stages.is_empty() {
+        return None;
+    }
+
+    let head = text(code, full_span(current));
+    let head = match current {
+        TypedExpr::BinOp { name, .. } if name.precedence() < PIPE_PRECEDENCE => {
+            format!("{{ {head} }}")
+        }
+        _ => head.to_string(),
+    };
+    Some(
+        std::iter::once(head)
+            .chain(stages.into_iter().rev())
+            .join(" |> "),
+    )
+}
+
+/// `x |> g |> f(y)` becomes `f(g(x), y)`.
+fn from_pipe<'a>(
+    code: &str,
+    mut assignments: impl Iterator<Item = &'a TypedExpr>,
+    finally: &'a TypedExpr,
+) -> Option<String> {
+    let first = assignments.next()?;
+    let mut nested = text(code, full_span(first)).to_string();
+    for stage in assignments.chain(std::iter::once(finally)) {
+        let TypedExpr::Call {
+            location,
+            fun,
+            args,
+            ..
+        } = stage
+        else {
+            return None;
+        };
+        let (piped, rest): (Vec<_>, Vec<_>) = args.iter().partition(|arg| is_piped(arg));
+        if piped.len() != 1 {
+            return None;
+        }
+
+        // For `x |> f` and `x |> f(y)` where `f(y)` returns a function the
+        // piped value is the only argument of a call to the stage. Otherwise
+        // it was inserted in front of the stage's own arguments.
+        let applied_function = if fun.location() == *location {
+            Some(full_span(fun))
+        } else if matches!(fun.as_ref(), TypedExpr::Call { location: inner, .. } if inner == location)
+        {
+            Some(*location)
+        } else {
+            None
+        };
+        nested = if let Some(function) = applied_function {
+            let function = text(code, function);
+            format!("{function}({nested})")
+        } else {
+            let function = text(code, SrcSpan::new(location.start, fun.location().end));
+            let arguments = std::iter::once(nested)
+                .chain(rest.iter().map(|arg| text(code, arg.location).to_string()))
+                .join(", ");
+            format!("{function}({arguments})")
+        };
+    }
+    Some(nested)
+}
+
+/// The function and the arguments in source order of a call that could be
+/// rewritten as a pipeline stage.
+fn call_parts(expr: &TypedExpr) -> Option<(&TypedExpr, Vec<&CallArg<TypedExpr>>)> {
+    match expr {
+        TypedExpr::Call { fun, args, .. } if !args.iter().any(|arg| arg.implicit) => {
+            let args = args
+                .iter()
+                .sorted_by_key(|arg| arg.location.start)
+                .collect();
+            Some((fun, args))
+        }
+        _ => None,
+    }
+}
+
+/// The source of a pipeline stage calling `fun` with the remaining
+/// arguments of `call`.
+fn stage(code: &str, call: &TypedExpr, fun: &TypedExpr, rest: &[&CallArg<TypedExpr>]) -> String {
+    let function = text(
+        code,
+        SrcSpan::new(call.location().start, fun.location().end),
+    );
+    if rest.is_empty() {
+        return function.to_string();
+    }
+    let arguments = rest.iter().map(|arg| text(code, arg.location)).join(", ");
+    format!("{function}({arguments})")
+}
+
+fn is_piped(arg: &CallArg<TypedExpr>) -> bool {
+    arg.implicit && matches!(&arg.value, TypedExpr::Var { name, .. } if name == PIPE_VARIABLE)
+}
+
+/// The location of a qualified value such as `list.map` only covers the
+/// `.map`, so extend it to include the module name.
+pub(super) fn full_span(expr: &TypedExpr) -> SrcSpan {
+    match expr {
+        TypedExpr::ModuleSelect {
+            location,
+            module_alias,
+            ..
+        } => SrcSpan::new(location.start - module_alias.len() as u32, location.end),
+        _ => expr.location(),
+    }
+}
+
+fn text(code: &str, span: SrcSpan) -> &str {
+    &code[span.start as usize..span.end as usize]
+}
@@ -0,0 +1,90 @@
+use crate::{
+    ast::{Definition, SrcSpan},
+    language_server::{
+        compiler::{LspProjectCompiler, ModuleTypeError},
+        src_span_to_lsp_range,
+    },
+    line_numbers::LineNumbers,
+    type_,
+};
+use ecow::EcoString;
+use lsp_types::TextEdit;
+
+impl<IO> LspProjectCompiler<IO> {
+    /// A fix for a type error at the byte index from using a value or type
+    /// of another module that exists but is private, by adding `pub` to its
+    /// definition. Returns the name of the module it is defined in along with
+    /// the edit to that module. A private record constructor is made public
+    /// by making its type public.
+    ///
+    /// Only the definitions of root package modules are changed, as the code
+    /// of dependencies cannot be edited. The module with the definition is
+    /// used as it was in the last compilation in which it compiled.
+    pub fn make_public_fix(&self, module: &str, byte_index: u32) -> Option<(EcoString, TextEdit)> {
+        let ModuleTypeError { error, .. } = self.type_error(module)?;
+        let (location, owner, name, is_type) = match error {
+            type_::Error::UnknownModuleValue {
+                location,
+                module_name,
+                name,
+                ..
+            }
+            | type_::Error::UnknownModuleField {
+                location,
+                module_name,
+                name,
+                ..
+            } => (location, module_name, name, false),
+            type_::Error::UnknownModuleType {
+                location,
+                module_name,
+                name,
+                ..
+            } => (location, module_name, name, true),
+            _ => return None,
+        };
+        if !(location.start..=location.end).contains(&byte_index) {
+            return None;
+        }
+
+        let owner_module = self.modules.get(owner)?;
+        let definition =
+            owner_module
+                .ast
+                .definitions
+                .iter()
+                .find_map(|definition| match definition {
+                    Definition::Function(function) if !is_type && function.name == *name => {
+                        Some((function.public, function.location))
+                    }
+                    Definition::ModuleConstant(constant) if !is_type && constant.name == *name => {
+                        Some((constant.public, constant.location))
+                    }
+                    Definition::CustomType(custom_type)
+                        if (is_type && custom_type.name == *name)
+                            || (!is_type
+                                && custom_type
+                                    .constructors
+                                    .iter()
+                                    .any(|constructor| constructor.name == *name)) =>
+                    {
+                        Some((custom_type.public, custom_type.location))
+                    }
+                    Definition::TypeAlias(alias) if is_type && alias.alias == *name => {
+                        Some((alias.public, alias.location))
+                    }
+                    _ => None,
+                });
+        let (false, location) = definition? else {
+            return None;
+        };
+
+        let line_numbers = LineNumbers::new(&owner_module.code);
+        let start = SrcSpan::new(location.start, location.start);
+        let edit = TextEdit {
+            range: src_span_to_lsp_range(start, &line_numbers),
+            new_text: "pub ".into(),
+        };
+        Some((owner.clone(), edit))
+    }
+}
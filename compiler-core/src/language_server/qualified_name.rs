@@ -0,0 +1,84 @@
+use crate::{
+    analyse::Inferred,
+    ast::{Definition, Pattern, TypedExpr},
+    build::Located,
+    io::{CommandExecutor, FileSystemReader, FileSystemWriter},
+    language_server::compiler::LspProjectCompiler,
+    type_::{ValueConstructor, ValueConstructorVariant},
+};
+use ecow::EcoString;
+
+impl<IO> LspProjectCompiler<IO>
+where
+    IO: CommandExecutor + FileSystemWriter + FileSystemReader + Clone,
+{
+    /// The name of the module level value or type at the byte index,
+    /// qualified with the module it is defined in, such as `gleam/list.map`.
+    /// This is the same however the value is referred to, so a value imported
+    /// unqualified or with another name has the name it is defined with.
+    ///
+    /// Local variables have no qualified name. The module is searched as it
+    /// was in the last compilation in which it compiled.
+    pub fn qualified_name_at(&self, module: &str, byte_index: u32) -> Option<String> {
+        let module = self.modules.get(module)?;
+        let this_module = &module.name;
+        let (module, name) = match module.find_node(byte_index)? {
+            Located::Expression(TypedExpr::Var { constructor, .. }) => {
+                self.defined_name(constructor)?
+            }
+            Located::Expression(TypedExpr::ModuleSelect {
+                module_name, label, ..
+            }) => (module_name.clone(), label.clone()),
+            Located::Pattern(Pattern::Constructor {
+                constructor: Inferred::Known(constructor),
+                ..
+            }) => (constructor.module.clone()?, constructor.name.clone()),
+            Located::ModuleStatement(definition) => {
+                let name = match definition {
+                    Definition::Function(function) => &function.name,
+                    Definition::ModuleConstant(constant) => &constant.name,
+                    Definition::CustomType(custom_type) => &custom_type.name,
+                    Definition::TypeAlias(alias) => &alias.alias,
+                    Definition::Import(_) => return None,
+                };
+                (this_module.clone(), name.clone())
+            }
+            Located::VariantConstructorDefinition(constructor) => {
+                (this_module.clone(), constructor.name.clone())
+            }
+            Located::Expression(_)
+            | Located::Pattern(_)
+            | Located::Statement(_)
+            | Located::FunctionBody(_)
+            | Located::Arg(_) => return None,
+        };
+        Some(format!("{module}.{name}"))
+    }
+
+    /// The module a value is defined in and the name it is defined with.
+    fn defined_name(&self, constructor: &ValueConstructor) -> Option<(EcoString, EcoString)> {
+        match &constructor.variant {
+            ValueConstructorVariant::ModuleFn { name, module, .. }
+            | ValueConstructorVariant::Record { name, module, .. } => {
+                Some((module.clone(), name.clone()))
+            }
+            // Constants do not record their name, so find the constant of
+            // their module that is defined in the same place.
+            ValueConstructorVariant::ModuleConstant {
+                module, location, ..
+            } => {
+                let interface = self.get_module_inferface(module)?;
+                let (name, _) = interface.values.iter().find(|(_, value)| {
+                    matches!(
+                        &value.variant,
+                        ValueConstructorVariant::ModuleConstant { location: defined, .. }
+                            if defined == location
+                    )
+                })?;
+                Some((module.clone(), name.clone()))
+            }
+            ValueConstructorVariant::LocalVariable { .. }
+            | ValueConstructorVariant::LocalConstant { .. } => None,
+        }
+    }
+}
@@ -0,0 +1,128 @@
+use crate::{
+    ast::Constant,
+    io::{CommandExecutor, FileSystemReader, FileSystemWriter},
+    language_server::compiler::LspProjectCompiler,
+    type_::{ModuleInterface, Type, ValueConstructorVariant},
+};
+use ecow::EcoString;
+use itertools::Itertools;
+
+/// Whether a name is that of a value or of a type, as a module can have a
+/// value and a type with the same name.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Namespace {
+    Value,
+    Type,
+}
+
+impl<IO> LspProjectCompiler<IO>
+where
+    IO: CommandExecutor + FileSystemWriter + FileSystemReader + Clone,
+{
+    /// Every module that the public value or type with the name defined in
+    /// the module can be imported from, starting with the module itself and
+    /// followed by the others sorted by name.
+    ///
+    /// Gleam has no re-exports as such, so a module re-exports a value with a
+    /// public constant that is just a reference to it, such as
+    /// `pub const map = list.map`, and a type with a public alias of it. The
+    /// re-export can have another name, and modules re-exporting a re-export
+    /// are included too. Nothing is returned if the module does not define a
+    /// public value or type with the name.
+    pub fn reexport_paths(&self, origin_module: &str, name: &str) -> Vec<EcoString> {
+        let modules = self.project_compiler.get_importable_modules();
+        let Some(origin) = modules.get(origin_module) else {
+            return vec![];
+        };
+
+        let mut found: Vec<(EcoString, EcoString, Namespace)> = vec![];
+        if origin.values.get(name).is_some_and(|value| value.public) {
+            found.push((origin_module.into(), name.into(), Namespace::Value));
+        }
+        if origin.types.get(name).is_some_and(|type_| type_.public) {
+            found.push((origin_module.into(), name.into(), Namespace::Type));
+        }
+        if found.is_empty() {
+            return vec![];
+        }
+
+        let mut index = 0;
+        while let Some((module, name, namespace)) = found.get(index).cloned() {
+            index += 1;
+            for (reexporter, interface) in modules {
+                for reexport in reexports(interface, &module, &name, namespace) {
+                    let reexport = (reexporter.clone(), reexport, namespace);
+                    if !found.contains(&reexport) {
+                        found.push(reexport);
+                    }
+                }
+            }
+        }
+
+        let others = found
+            .into_iter()
+            .map(|(module, _, _)| module)
+            .filter(|module| module != origin_module)
+            .sorted()
+            .dedup();
+        std::iter::once(origin_module.into())
+            .chain(others)
+            .collect()
+    }
+}
+
+/// The names of the public constants or type aliases of the module that refer
+/// to the value or type with the name in the other module.
+fn reexports(
+    interface: &ModuleInterface,
+    module: &str,
+    name: &str,
+    namespace: Namespace,
+) -> Vec<EcoString> {
+    match namespace {
+        Namespace::Value => interface
+            .values
+            .iter()
+            .filter(|(_, value)| value.public)
+            .filter(|(_, value)| constant_reference(&value.variant) == Some((module, name)))
+            .map(|(reexport, _)| reexport.clone())
+            .collect(),
+        Namespace::Type => interface
+            .types
+            .iter()
+            .filter(|(_, type_)| type_.public && type_.module != module)
+            .filter(|(_, type_)| match type_.typ.as_ref() {
+                Type::Named {
+                    module: aliased_module,
+                    name: aliased_name,
+                    ..
+                } => aliased_module == module && aliased_name == name,
+                _ => false,
+            })
+            .map(|(reexport, _)| reexport.clone())
+            .collect(),
+    }
+}
+
+/// The module and name of the module value that a constant is a reference to,
+/// if it is one.
+fn constant_reference(variant: &ValueConstructorVariant) -> Option<(&str, &str)> {
+    let ValueConstructorVariant::ModuleConstant {
+        literal: Constant::Var {
+            name, constructor, ..
+        },
+        ..
+    } = variant
+    else {
+        return None;
+    };
+    match &constructor.as_ref()?.variant {
+        ValueConstructorVariant::ModuleFn { module, name, .. }
+        | ValueConstructorVariant::Record { module, name, .. } => Some((module, name)),
+        // The constant can only be named as it is referred to here, which
+        // is its own name unless it was imported with another.
+        ValueConstructorVariant::ModuleConstant { module, .. } => Some((module, name)),
+        ValueConstructorVariant::LocalVariable { .. }
+        | ValueConstructorVariant::LocalConstant { .. } => None,
+    }
+}
@@ -0,0 +1,728 @@
+use std::collections::HashSet;
+
+use crate::{
+    analyse::Inferred,
+    ast::{
+        Constant, Definition, Pattern, SrcSpan, Statement, TypeAst, TypeAstConstructor, TypeAstFn,
+        TypeAstTuple, TypedArg, TypedConstant, TypedExpr, TypedModule, TypedPattern,
+        TypedStatement,
+    },
+    io::{CommandExecutor, FileSystemReader, FileSystemWriter},
+    language_server::{
+        compiler::LspProjectCompiler,
+        lsp_warning::{LspWarning, LspWarningKind},
+        outline::{is_test_function, DefinitionId},
+        src_span_to_lsp_range,
+        visit::{walk_module, Visit},
+    },
+    line_numbers::LineNumbers,
+    type_::{ValueConstructor, ValueConstructorVariant, PRELUDE_MODULE_NAME},
+};
+use camino::{Utf8Path, Utf8PathBuf};
+use ecow::EcoString;
+use itertools::Itertools;
+use lsp_types::{CodeLens, Command};
+use serde::{Deserialize, Serialize};
+
+/// A place where a module level value is used.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Reference {
+    /// The name of the module the value is used in.
+    pub module: EcoString,
+    /// The path of that module's source file.
+    pub path: Utf8PathBuf,
+    pub location: SrcSpan,
+}
+
+/// The data of a reference count lens, which identifies the value the lens
+/// is for across compilations so that a client can resolve the lens again
+/// after the module has been edited.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReferenceLensData {
+    /// The path of the source file of the module the value is defined in.
+    pub path: Utf8PathBuf,
+    pub definition: DefinitionId,
+}
+
+/// Where to look for references to a value.
+#[derive(Debug, Default)]
+pub enum ReferenceScope<'a, IO> {
+    /// Only the modules of the root package. Dependencies are read-only so
+    /// usages within them are not interesting.
+    #[default]
+    RootPackage,
+    /// The modules of the root package and of every other project in the
+    /// workspace, such as the sibling packages of a monorepo.
+    Workspace(&'a Workspace<'a, IO>),
+}
+
+/// The projects that are open in the editor at the same time.
+#[derive(Debug)]
+pub struct Workspace<'a, IO> {
+    projects: Vec<&'a LspProjectCompiler<IO>>,
+}
+
+impl<'a, IO> Workspace<'a, IO> {
+    pub fn new(projects: Vec<&'a LspProjectCompiler<IO>>) -> Self {
+        Self { projects }
+    }
+
+    /// The project that a source file belongs to, if any of them do.
+    pub fn project_for(&self, path: &Utf8Path) -> Option<&'a LspProjectCompiler<IO>> {
+        self.projects
+            .iter()
+            .find(|project| project.owns_file(path))
+            .copied()
+    }
+}
+
+impl<IO> LspProjectCompiler<IO>
+where
+    IO: CommandExecutor + FileSystemWriter + FileSystemReader + Clone,
+{
+    /// Find everywhere the public or private module level value `name`
+    /// defined in `module` is used. Modules that have not been compiled yet
+    /// in this session are not searched.
+    pub fn references(
+        &self,
+        module: &str,
+        name: &str,
+        scope: ReferenceScope<'_, IO>,
+    ) -> Vec<Reference> {
+        let mut references = vec![];
+        self.references_streaming(module, name, scope, |batch| references.extend(batch));
+        references
+    }
+
+    /// Find the same references as [`Self::references`], giving them to
+    /// `on_batch` one module at a time as each is searched so that they can
+    /// be shown before the search of a large project has finished. A module
+    /// without any references is not given. The batches, one after the other,
+    /// are the references in the same order as `references` gives them.
+    pub fn references_streaming(
+        &self,
+        module: &str,
+        name: &str,
+        scope: ReferenceScope<'_, IO>,
+        mut on_batch: impl FnMut(Vec<Reference>),
+    ) {
+        let mut found = vec![];
+        self.root_package_references(module, name, &mut |batch| {
+            found.extend(batch.iter().cloned());
+            on_batch(batch);
+        });
+
+        if let ReferenceScope::Workspace(workspace) = scope {
+            let others = workspace
+                .projects
+                .iter()
+                .filter(|project| !std::ptr::eq(**project, self));
+            for project in others {
+                project.root_package_references(module, name, &mut |batch| {
+                    let batch = batch
+                        .into_iter()
+                        .filter(|reference| !found.contains(reference))
+                        .collect::<Vec<_>>();
+                    if !batch.is_empty() {
+                        found.extend(batch.iter().cloned());
+                        on_batch(batch);
+                    }
+                });
+            }
+        }
+    }
+
+    /// The references in the modules of the root package, given one module
+    /// at a time in the order of their paths.
+    fn root_package_references(
+        &self,
+        module: &str,
+        name: &str,
+        on_batch: &mut impl FnMut(Vec<Reference>),
+    ) {
+        let Some(value) = self
+            .get_module_inferface(module)
+            .and_then(|interface| interface.values.get(name))
+        else {
+            return;
+        };
+        let Some(target) = Target::new(module, value) else {
+            return;
+        };
+        let targets = [target];
+
+        let compiled_modules = self
+            .modules
+            .values()
+            .sorted_by(|a, b| a.input_path.cmp(&b.input_path));
+        for compiled in compiled_modules {
+            let mut finder = ReferenceFinder {
+                targets: &targets,
+                locations: vec![],
+            };
+            walk_module(&compiled.ast, &mut finder);
+            let mut references = finder
+                .locations
+                .into_iter()
+                .map(|(_, location)| Reference {
+                    module: compiled.name.clone(),
+                    path: compiled.input_path.clone(),
+                    location,
+                })
+                .collect::<Vec<_>>();
+            if !references.is_empty() {
+                references.sort_by_key(|reference| reference.location.start);
+                on_batch(references);
+            }
+        }
+    }
+
+    /// A code lens above each module level function and constant of the
+    /// module, with the number of times it is used by the modules of the root
+    /// package, not counting its definition. The lens has the
+    /// `gleam.findReferences` command, which the client is expected to
+    /// implement, given the name of the module and the value.
+    ///
+    /// The usages of all of the values are counted in one walk of the root
+    /// package when the lenses are first requested, and the lenses are then
+    /// cached until the next compilation. Modules that have not been compiled
+    /// yet in this session are not counted.
+    ///
+    /// The data of each lens is a [`ReferenceLensData`], which
+    /// [`Self::resolve_reference_count_lens`] uses to find the lens again.
+    pub fn reference_count_lenses(&self, module: &str) -> Vec<CodeLens> {
+        let generation = self.compile_generation();
+        if let Some((cached, lenses)) = self.reference_lenses.borrow().get(module) {
+            if *cached == generation {
+                return lenses.clone();
+            }
+        }
+        let lenses = self.count_references(module);
+        _ = self
+            .reference_lenses
+            .borrow_mut()
+            .insert(module.into(), (generation, lenses.clone()));
+        lenses
+    }
+
+    fn count_references(&self, module: &str) -> Vec<CodeLens> {
+        let (Some(compiled), Some(interface)) =
+            (self.modules.get(module), self.get_module_inferface(module))
+        else {
+            return vec![];
+        };
+        let definitions = compiled
+            .ast
+            .definitions
+            .iter()
+            .filter_map(|definition| match definition {
+                Definition::Function(function) => Some((&function.name, function.location)),
+                Definition::ModuleConstant(constant) => Some((&constant.name, constant.location)),
+                _ => None,
+            })
+            .filter_map(|(name, location)| {
+                let target = Target::new(module, interface.values.get(name)?)?;
+                Some(((name, location), target))
+            });
+        let (definitions, targets): (Vec<_>, Vec<_>) = definitions.unzip();
+
+        let mut counts = vec![0; targets.len()];
+        for compiled in self.modules.values() {
+            let mut finder = ReferenceFinder {
+                targets: &targets,
+                locations: vec![],
+            };
+            walk_module(&compiled.ast, &mut finder);
+            for (index, _) in finder.locations {
+                if let Some(count) = counts.get_mut(index) {
+                    *count += 1;
+                }
+            }
+        }
+
+        let line_numbers = LineNumbers::new(&compiled.code);
+        definitions
+            .iter()
+            .zip(counts)
+            .map(|((name, location), count)| CodeLens {
+                range: src_span_to_lsp_range(*location, &line_numbers),
+                command: Some(Command {
+                    title: match count {
+                        1 => "1 reference".into(),
+                        _ => format!("{count} references"),
+                    },
+                    command: "gleam.findReferences".into(),
+                    arguments: Some(vec![module.into(), name.as_str().into()]),
+                }),
+                data: serde_json::to_value(ReferenceLensData {
+                    path: compiled.input_path.clone(),
+                    definition: DefinitionId {
+                        module: module.into(),
+                        name: (*name).clone(),
+                    },
+                })
+                .ok(),
+            })
+            .collect()
+    }
+
+    /// The reference count lens for the value that a lens given by
+    /// [`Self::reference_count_lenses`] is for, with its current location
+    /// and count. The lens is returned as it is if the value no longer
+    /// exists.
+    pub fn resolve_reference_count_lens(&self, lens: CodeLens) -> CodeLens {
+        let Some(data) = lens
+            .data
+            .clone()
+            .and_then(|data| serde_json::from_value::<ReferenceLensData>(data).ok())
+        else {
+            return lens;
+        };
+        if self.locate_definition(&data.definition).is_none() {
+            return lens;
+        }
+        self.reference_count_lenses(&data.definition.module)
+            .into_iter()
+            .find(|resolved| resolved.data == lens.data)
+            .unwrap_or(lens)
+    }
+
+    /// The module, name and location of each public function of the root
+    /// package that is not used by any module other than the one defining it,
+    /// so could be made private, sorted by module and then by location. The
+    /// `main` functions and the tests run by gleeunit are not included, as
+    /// they are used by the build tool rather than by other modules.
+    ///
+    /// Every function is searched for in one walk of the root package.
+    /// Modules that have not been compiled yet in this session are not
+    /// searched, and nor are other projects in the workspace that may use the
+    /// functions.
+    pub fn unused_public_functions(&self) -> Vec<(EcoString, EcoString, SrcSpan)> {
+        let functions = self
+            .modules
+            .values()
+            .flat_map(|compiled| {
+                compiled
+                    .ast
+                    .definitions
+                    .iter()
+                    .filter_map(move |definition| match definition {
+                        Definition::Function(function)
+                            if function.public
+                                && function.name != "main"
+                                && !is_test_function(compiled, function) =>
+                        {
+                            let value = compiled.ast.type_info.values.get(&function.name)?;
+                            let target = Target::new(&compiled.name, value)?;
+                            Some(((compiled, function), target))
+                        }
+                        _ => None,
+                    })
+            })
+            .sorted_by_key(|((compiled, function), _)| (&compiled.name, function.location.start));
+        let (functions, targets): (Vec<_>, Vec<_>) = functions.unzip();
+
+        let mut used = HashSet::new();
+        for compiled in self.modules.values() {
+            let mut finder = ReferenceFinder {
+                targets: &targets,
+                locations: vec![],
+            };
+            walk_module(&compiled.ast, &mut finder);
+            used.extend(
+                finder
+                    .locations
+                    .into_iter()
+                    .map(|(index, _)| index)
+                    .filter(|index| {
+                        targets
+                            .get(*index)
+                            .is_some_and(|target| target.module != compiled.name)
+                    }),
+            );
+        }
+
+        functions
+            .into_iter()
+            .enumerate()
+            .filter(|(index, _)| !used.contains(index))
+            .map(|(_, (compiled, function))| {
+                (
+                    compiled.name.clone(),
+                    function.name.clone(),
+                    function.location,
+                )
+            })
+            .collect()
+    }
+
+    /// A hint for each public function found by
+    /// [`Self::unused_public_functions`] that is defined in one of the
+    /// modules. Only the modules that have just been compiled are given
+    /// hints, as the client keeps the diagnostics of the other modules until
+    /// they are compiled again.
+    pub(super) fn emit_private_function_hints(&mut self, modules: &[EcoString]) {
+        for (module, name, location) in self.unused_public_functions() {
+            let Some(compiled) = self
+                .modules
+                .get(&module)
+                .filter(|_| modules.contains(&module))
+            else {
+                continue;
+            };
+            self.lsp_warnings.push(LspWarning {
+                path: compiled.input_path.clone(),
+                src: compiled.code.clone(),
+                kind: LspWarningKind::PublicFunctionCouldBePrivate { location, name },
+            });
+        }
+    }
+
+    /// Find everywhere the type `type_name` defined in `module` is referred
+    /// to by the modules of the root package, giving the module and the
+    /// location of the name as it is written there.
+    ///
+    /// This includes type annotations, the definitions of other types and
+    /// type aliases, the imports of the type, and constructor patterns that
+    /// match on a value of the type. Modules that have not been compiled yet
+    /// in this session are not searched.
+    pub fn type_references(&self, module: &str, type_name: &str) -> Vec<(EcoString, SrcSpan)> {
+        let mut references = vec![];
+        for compiled in self.modules.values() {
+            let mut finder = TypeReferenceFinder::new(&compiled.code, module, type_name);
+            finder.find_in_module(&compiled.ast);
+            walk_module(&compiled.ast, &mut finder);
+            references.extend(
+                finder
+                    .locations
+                    .into_iter()
+                    .map(|location| (compiled.name.clone(), location)),
+            );
+        }
+        references.sort_by(|a, b| (&a.0, a.1.start).cmp(&(&b.0, b.1.start)));
+        references
+    }
+}
+
+/// The location of the name at the end of a location, as the location of a
+/// value selected from another module also covers the module.
+fn name_at_end(location: SrcSpan, name: &str) -> SrcSpan {
+    SrcSpan::new(location.end.saturating_sub(name.len() as u32), location.end)
+}
+
+/// How the value being searched for is identified in the typed AST.
+#[derive(Debug)]
+pub(super) struct Target<'a> {
+    /// The module the value is defined in.
+    module: &'a str,
+    /// The module recorded in the value's constructor. This is the same as
+    /// `module` except for external functions, where it is the module that
+    /// implements the function.
+    constructor_module: &'a str,
+    location: SrcSpan,
+}
+
+impl<'a> Target<'a> {
+    pub(super) fn new(module: &'a str, value: &'a ValueConstructor) -> Option<Self> {
+        let (constructor_module, location) = match &value.variant {
+            ValueConstructorVariant::ModuleConstant {
+                module, location, ..
+            }
+            | ValueConstructorVariant::ModuleFn {
+                module, location, ..
+            }
+            | ValueConstructorVariant::Record {
+                module, location, ..
+            } => (module.as_str(), *location),
+            ValueConstructorVariant::LocalVariable { .. }
+            | ValueConstructorVariant::LocalConstant { .. } => return None,
+        };
+        Some(Self {
+            module,
+            constructor_module,
+            location,
+        })
+    }
+
+    fn is_constructor(&self, constructor: &ValueConstructor) -> bool {
+        match &constructor.variant {
+            ValueConstructorVariant::ModuleConstant {
+                module, location, ..
+            }
+            | ValueConstructorVariant::ModuleFn {
+                module, location, ..
+            }
+            | ValueConstructorVariant::Record {
+                module, location, ..
+            } => module == self.constructor_module && *location == self.location,
+            ValueConstructorVariant::LocalVariable { .. }
+            | ValueConstructorVariant::LocalConstant { .. } => false,
+        }
+    }
+}
+
+/// Finds the usages of any of the targets, with the index of the target each
+/// one is a usage of. The location of a usage is that of just the name it is
+/// used by, which is the name it was imported with if it was imported with
+/// `as`, without the module it is selected from or the arguments of a
+/// constructor pattern.
+pub(super) struct ReferenceFinder<'a> {
+    pub(super) targets: &'a [Target<'a>],
+    pub(super) locations: Vec<(usize, SrcSpan)>,
+}
+
+impl ReferenceFinder<'_> {
+    fn push_if(&mut self, location: SrcSpan, is_target: impl Fn(&Target<'_>) -> bool) {
+        if let Some(index) = self.targets.iter().position(is_target) {
+            self.locations.push((index, location));
+        }
+    }
+}
+
+impl<'ast> Visit<'ast> for ReferenceFinder<'_> {
+    fn visit_expr(&mut self, expr: &'ast TypedExpr) {
+        match expr {
+            TypedExpr::Var {
+                location,
+                constructor,
+                ..
+            } => self.push_if(*location, |target| target.is_constructor(constructor)),
+
+            TypedExpr::ModuleSelect {
+                location,
+                label,
+                module_name,
+                constructor,
+                ..
+            } => self.push_if(name_at_end(*location, label), |target| {
+                module_name == target.module && constructor.location() == target.location
+            }),
+
+            _ => (),
+        }
+    }
+
+    fn visit_pattern(&mut self, pattern: &'ast TypedPattern) {
+        if let Pattern::Constructor {
+            location,
+            name,
+            module: qualifier,
+            constructor: Inferred::Known(constructor),
+            ..
+        } = pattern
+        {
+            // The location covers the module the constructor is selected
+            // from, if any, and the arguments.
+            let start =
+                location.start + qualifier.as_ref().map_or(0, |module| module.len() + 1) as u32;
+            let location = SrcSpan::new(start, start + name.len() as u32);
+            let module = constructor.module.as_deref();
+            self.push_if(location, |target| {
+                module == Some(target.module) && constructor.location == target.location
+            });
+        }
+    }
+
+    fn visit_constant(&mut self, constant: &'ast TypedConstant) {
+        if let Constant::Var {
+            location,
+            name,
+            constructor: Some(constructor),
+            ..
+        } = constant
+        {
+            self.push_if(name_at_end(*location, name), |target| {
+                target.is_constructor(constructor)
+            });
+        }
+    }
+}
+
+struct TypeReferenceFinder<'a> {
+    code: &'a str,
+    module: &'a str,
+    name: &'a str,
+    /// The names that refer to the type without a module qualifier in the
+    /// module being searched.
+    unqualified: Vec<EcoString>,
+    /// The names that the module defining the type is imported with.
+    qualifiers: Vec<EcoString>,
+    locations: Vec<SrcSpan>,
+}
+
+impl<'a> TypeReferenceFinder<'a> {
+    fn new(code: &'a str, module: &'a str, name: &'a str) -> Self {
+        Self {
+            code,
+            module,
+            name,
+            unqualified: vec![],
+            qualifiers: vec![],
+            locations: vec![],
+        }
+    }
+
+    /// Find the references in the imports and the module level definitions,
+    /// which are not walked by the visitor.
+    fn find_in_module(&mut self, module: &'a TypedModule) {
+        let defines_name = module
+            .definitions
+            .iter()
+            .any(|definition| match definition {
+                Definition::CustomType(custom_type) => custom_type.name == self.name,
+                Definition::TypeAlias(alias) => alias.alias == self.name,
+                Definition::Function(_) | Definition::Import(_) | Definition::ModuleConstant(_) => {
+                    false
+                }
+            });
+        if module.name == self.module && defines_name {
+            self.unqualified.push(self.name.into());
+        }
+
+        for definition in &module.definitions {
+            let Definition::Import(import) = definition else {
+                continue;
+            };
+            if import.module != self.module {
+                continue;
+            }
+            self.qualifiers.extend(import.used_name());
+            for type_ in &import.unqualified_types {
+                if type_.name == self.name {
+                    self.unqualified.push(type_.used_name().clone());
+                    self.push_name(type_.location, &type_.name);
+                }
+            }
+        }
+
+        // Types of the prelude are in scope unless the module has a type with
+        // the same name.
+        let shadowed = defines_name || self.unqualified.iter().any(|name| name == self.name);
+        if self.module == PRELUDE_MODULE_NAME && !shadowed {
+            self.unqualified.push(self.name.into());
+        }
+
+        for definition in &module.definitions {
+            match definition {
+                Definition::Function(function) => {
+                    self.find_in_arguments(&function.arguments);
+                    if let Some(annotation) = &function.return_annotation {
+                        self.find_in_type(annotation);
+                    }
+                }
+                Definition::CustomType(custom_type) => {
+                    for constructor in &custom_type.constructors {
+                        for argument in &constructor.arguments {
+                            self.find_in_type(&argument.ast);
+                        }
+                    }
+                }
+                Definition::TypeAlias(alias) => self.find_in_type(&alias.type_ast),
+                Definition::ModuleConstant(constant) => {
+                    if let Some(annotation) = &constant.annotation {
+                        self.find_in_type(annotation);
+                    }
+                }
+                Definition::Import(_) => (),
+            }
+        }
+    }
+
+    fn find_in_arguments(&mut self, arguments: &[TypedArg]) {
+        for argument in arguments {
+            if let Some(annotation) = &argument.annotation {
+                self.find_in_type(annotation);
+            }
+        }
+    }
+
+    fn find_in_type(&mut self, type_: &TypeAst) {
+        match type_ {
+            TypeAst::Constructor(TypeAstConstructor {
+                location,
+                module,
+                name,
+                arguments,
+            }) => {
+                let refers = match module {
+                    Some(module) => self.qualifiers.contains(module) && name == self.name,
+                    None => self.unqualified.contains(name),
+                };
+                if refers {
+                    self.push_name(*location, name);
+                }
+                for argument in arguments {
+                    self.find_in_type(argument);
+                }
+            }
+            TypeAst::Fn(TypeAstFn {
+                arguments, return_, ..
+            }) => {
+                for argument in arguments {
+                    self.find_in_type(argument);
+                }
+                self.find_in_type(return_);
+            }
+            TypeAst::Tuple(TypeAstTuple { elems, .. }) => {
+                for elem in elems {
+                    self.find_in_type(elem);
+                }
+            }
+            TypeAst::Var(_) | TypeAst::Hole(_) => (),
+        }
+    }
+
+    /// Record the location of the name within the location of a node. Type
+    /// and constructor names start with an uppercase letter and module names
+    /// do not, so the first occurrence of the name is always the name itself.
+    fn push_name(&mut self, location: SrcSpan, name: &str) {
+        let offset = self
+            .code
+            .get(location.start as usize..location.end as usize)
+            .and_then(|text| text.find(name))
+            .unwrap_or(0) as u32;
+        let start = location.start + offset;
+        self.locations
+            .push(SrcSpan::new(start, start + name.len() as u32));
+    }
+}
+
+impl<'ast> Visit<'ast> for TypeReferenceFinder<'_> {
+    fn visit_statement(&mut self, statement: &'ast TypedStatement) {
+        if let Statement::Assignment(assignment) = statement {
+            if let Some(annotation) = &assignment.annotation {
+                self.find_in_type(annotation);
+            }
+        }
+    }
+
+    fn visit_expr(&mut self, expr: &'ast TypedExpr) {
+        if let TypedExpr::Fn {
+            args,
+            return_annotation,
+            ..
+        } = expr
+        {
+            self.find_in_arguments(args);
+            if let Some(annotation) = return_annotation {
+                self.find_in_type(annotation);
+            }
+        }
+    }
+
+    fn visit_pattern(&mut self, pattern: &'ast TypedPattern) {
+        if let Pattern::Constructor {
+            location,
+            name,
+            type_,
+            ..
+        } = pattern
+        {
+            if let Some((module, type_name)) = type_.named_type_name() {
+                if module == self.module && type_name == self.name {
+                    self.push_name(*location, name);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,200 @@
+use std::collections::HashMap;
+
+use crate::{
+    ast::{
+        CallArg, ClauseGuard, Constant, Definition, Pattern, SrcSpan, TypedClauseGuard,
+        TypedConstant, TypedExpr, TypedModule, TypedPattern,
+    },
+    language_server::{
+        compiler::LspProjectCompiler,
+        path_to_uri, src_span_to_lsp_range,
+        visit::{walk_module, Visit},
+    },
+    line_numbers::LineNumbers,
+    type_::{ModuleValueConstructor, Type, ValueConstructor, ValueConstructorVariant},
+};
+use lsp_types::{TextEdit, WorkspaceEdit};
+
+impl<IO> LspProjectCompiler<IO> {
+    /// Rename a labelled field of a custom type defined in a root package
+    /// module, changing the label in every variant of the type that has it
+    /// and everywhere the label is used: in constructor calls, patterns and
+    /// constants, in record updates, and in field accesses.
+    ///
+    /// Uses of the constructors that do not give the label, such as calls
+    /// with positional arguments, need no edit. Modules that have not been
+    /// compiled in this editor session are not edited.
+    pub fn rename_field(
+        &self,
+        module: &str,
+        type_name: &str,
+        old_field: &str,
+        new_field: &str,
+    ) -> WorkspaceEdit {
+        let mut changes = HashMap::new();
+        for compiled in self.modules.values() {
+            let mut finder = FieldFinder {
+                module,
+                type_name,
+                field: old_field,
+                locations: vec![],
+            };
+            if compiled.name == module {
+                finder.find_in_definition(&compiled.ast);
+            }
+            walk_module(&compiled.ast, &mut finder);
+            if finder.locations.is_empty() {
+                continue;
+            }
+
+            let line_numbers = LineNumbers::new(&compiled.code);
+            finder.locations.sort_by_key(|location| location.start);
+            let edits = finder
+                .locations
+                .into_iter()
+                .map(|location| TextEdit {
+                    range: src_span_to_lsp_range(location, &line_numbers),
+                    new_text: new_field.into(),
+                })
+                .collect();
+            _ = changes.insert(path_to_uri(compiled.input_path.clone()), edits);
+        }
+
+        WorkspaceEdit {
+            changes: Some(changes),
+            ..Default::default()
+        }
+    }
+}
+
+/// Finds the locations of a field's label wherever it is used with the custom
+/// type the field belongs to.
+struct FieldFinder<'a> {
+    module: &'a str,
+    type_name: &'a str,
+    field: &'a str,
+    locations: Vec<SrcSpan>,
+}
+
+impl FieldFinder<'_> {
+    fn find_in_definition(&mut self, module: &TypedModule) {
+        for definition in &module.definitions {
+            let Definition::CustomType(type_) = definition else {
+                continue;
+            };
+            if type_.name != self.type_name {
+                continue;
+            }
+            for argument in type_.constructors.iter().flat_map(|c| &c.arguments) {
+                if argument.label.as_deref() == Some(self.field) {
+                    self.locations.push(self.label_at_start(argument.location));
+                }
+            }
+        }
+    }
+
+    fn is_target_type(&self, type_: &Type) -> bool {
+        type_
+            .named_type_name()
+            .is_some_and(|(module, name)| module == self.module && name == self.type_name)
+    }
+
+    fn find_in_arguments<A>(&mut self, arguments: &[CallArg<A>]) {
+        for argument in arguments {
+            if argument.label.as_deref() == Some(self.field) {
+                self.locations.push(self.label_at_start(argument.location));
+            }
+        }
+    }
+
+    /// Labelled arguments and fields start with their label.
+    fn label_at_start(&self, location: SrcSpan) -> SrcSpan {
+        SrcSpan::new(location.start, location.start + self.field.len() as u32)
+    }
+
+    /// Field accesses end with the label being accessed.
+    fn label_at_end(&self, location: SrcSpan) -> SrcSpan {
+        SrcSpan::new(location.end - self.field.len() as u32, location.end)
+    }
+}
+
+impl<'ast> Visit<'ast> for FieldFinder<'_> {
+    fn visit_expr(&mut self, expr: &'ast TypedExpr) {
+        match expr {
+            // Functions can have labelled arguments too, so only calls to the
+            // type's constructors are of interest.
+            TypedExpr::Call { fun, args, typ, .. }
+                if is_record_constructor(fun) && self.is_target_type(typ) =>
+            {
+                self.find_in_arguments(args)
+            }
+
+            TypedExpr::RecordUpdate { args, typ, .. } if self.is_target_type(typ) => {
+                for arg in args {
+                    if arg.label == self.field {
+                        self.locations.push(self.label_at_start(arg.location));
+                    }
+                }
+            }
+
+            TypedExpr::RecordAccess {
+                location,
+                label,
+                record,
+                ..
+            } if label == self.field && self.is_target_type(&record.type_()) => {
+                self.locations.push(self.label_at_end(*location))
+            }
+
+            _ => (),
+        }
+    }
+
+    fn visit_pattern(&mut self, pattern: &'ast TypedPattern) {
+        match pattern {
+            Pattern::Constructor {
+                arguments, type_, ..
+            } if self.is_target_type(type_) => self.find_in_arguments(arguments),
+            _ => (),
+        }
+    }
+
+    fn visit_constant(&mut self, constant: &'ast TypedConstant) {
+        match constant {
+            Constant::Record { args, typ, .. } if self.is_target_type(typ) => {
+                self.find_in_arguments(args)
+            }
+            _ => (),
+        }
+    }
+
+    fn visit_clause_guard(&mut self, guard: &'ast TypedClauseGuard) {
+        match guard {
+            ClauseGuard::FieldAccess {
+                location,
+                label,
+                container,
+                ..
+            } if label == self.field && self.is_target_type(&container.type_()) => {
+                self.locations.push(self.label_at_end(*location))
+            }
+            _ => (),
+        }
+    }
+}
+
+fn is_record_constructor(fun: &TypedExpr) -> bool {
+    matches!(
+        fun,
+        TypedExpr::Var {
+            constructor: ValueConstructor {
+                variant: ValueConstructorVariant::Record { .. },
+                ..
+            },
+            ..
+        } | TypedExpr::ModuleSelect {
+            constructor: ModuleValueConstructor::Record { .. },
+            ..
+        }
+    )
+}
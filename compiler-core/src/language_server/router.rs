@@ -1,18 +1,26 @@
 use crate::{
+    diagnostic::Diagnostic,
     error::{FileIoAction, FileKind},
     io::{CommandExecutor, FileSystemReader, FileSystemWriter},
     language_server::{
         engine::LanguageServerEngine, files::FileSystemProxy, progress::ProgressReporter,
-        DownloadDependencies, MakeLocker,
+        settings::LspSettings, DownloadDependencies, MakeLocker, TraceLevel,
     },
     paths::ProjectPaths,
     Error, Result,
 };
-use std::collections::{hash_map::Entry, HashMap};
+use std::{
+    collections::{
+        hash_map::{DefaultHasher, Entry},
+        HashMap,
+    },
+    hash::{Hash, Hasher},
+};
 
 use camino::{Utf8Path, Utf8PathBuf};
+use itertools::Itertools;
 
-use super::feedback::FeedbackBookKeeper;
+use super::feedback::{Feedback, FeedbackBookKeeper};
 
 /// The language server instance serves a language client, typically a text
 /// editor. The editor could have multiple Gleam projects open at once, so run
@@ -26,6 +34,8 @@ pub struct Router<IO, Reporter> {
     io: FileSystemProxy<IO>,
     engines: HashMap<Utf8PathBuf, Project<IO, Reporter>>,
     progress_reporter: Reporter,
+    settings: LspSettings,
+    trace_level: TraceLevel,
 }
 
 impl<'a, IO, Reporter> Router<IO, Reporter>
@@ -45,6 +55,8 @@ where
             io,
             engines: HashMap::new(),
             progress_reporter,
+            settings: LspSettings::default(),
+            trace_level: TraceLevel::Off,
         }
     }
 
@@ -64,7 +76,7 @@ where
 
         tracing::info!(?path, "creating_new_language_server_engine");
 
-        let paths = ProjectPaths::new(path);
+        let paths = ProjectPaths::new(path.clone());
         let config_path = paths.root_config();
         let toml = self.io.read(&config_path)?;
         let config = toml::from_str(&toml).map_err(|e| Error::FileIo {
@@ -73,19 +85,81 @@ where
             path: config_path,
             err: Some(e.to_string()),
         })?;
-        let engine = LanguageServerEngine::new(
+        let build_directory = if self.settings.isolated_build_directory {
+            Some(isolated_build_directory(&path)?)
+        } else {
+            None
+        };
+        let mut engine = LanguageServerEngine::new(
             config,
             self.progress_reporter.clone(),
             self.io.clone(),
             paths,
+            build_directory,
         )?;
-        let project = Project {
-            engine,
-            feedback: FeedbackBookKeeper::default(),
-        };
+        _ = engine.compiler.set_settings(self.settings.clone())?;
+        engine.compiler.set_trace_level(self.trace_level);
+        let mut feedback = FeedbackBookKeeper::default()
+            .with_suppression_comments(self.settings.suppression_comments);
+        feedback.unfinished_code_as_errors = self.settings.unfinished_code_as_errors;
+        feedback.set_ignored_diagnostic_codes(self.settings.ignored_diagnostic_codes.clone());
+        let project = Project { engine, feedback };
         Ok(Some(entry.insert(project)))
     }
 
+    /// Use the user's new settings for every project, including those that
+    /// are opened later.
+    pub fn apply_settings(&mut self, settings: LspSettings) -> Feedback {
+        let mut feedback = Feedback::default();
+        for project in self.engines.values_mut() {
+            project.feedback.unfinished_code_as_errors = settings.unfinished_code_as_errors;
+            project.feedback.suppression_comments = settings.suppression_comments;
+            project
+                .feedback
+                .set_ignored_diagnostic_codes(settings.ignored_diagnostic_codes.clone());
+            let response = project.engine.apply_settings(settings.clone());
+            let mut project_feedback = match response.result {
+                Ok(()) => project
+                    .feedback
+                    .response(response.compilation, response.warnings),
+                Err(error) => project.feedback.build_with_error(
+                    error,
+                    response.compilation,
+                    response.warnings,
+                ),
+            };
+            project
+                .feedback
+                .append_lsp_warnings(&mut project_feedback, response.lsp_warnings);
+            feedback.append_feedback(project_feedback);
+        }
+        self.settings = settings;
+        feedback
+    }
+
+    /// Change how much every project logs, including those that are opened
+    /// later.
+    pub fn set_trace_level(&mut self, level: TraceLevel) {
+        for project in self.engines.values_mut() {
+            project.engine.compiler.set_trace_level(level);
+        }
+        self.trace_level = level;
+    }
+
+    /// Every diagnostic the client currently has for the files of every
+    /// project, sorted by file and then by where it is in the file.
+    pub fn all_diagnostics_sorted(&mut self) -> Vec<(Utf8PathBuf, Diagnostic)> {
+        let mut all = self
+            .engines
+            .values_mut()
+            .flat_map(|project| project.feedback.all_diagnostics_sorted())
+            .collect_vec();
+        // Projects do not share files, and the sort is stable so the
+        // diagnostics of each file stay in order.
+        all.sort_by(|(one, _), (other, _)| one.cmp(other));
+        all
+    }
+
     pub fn delete_engine_for_path(&mut self, path: &Utf8Path) {
         if let Some(path) = find_gleam_project_parent(&self.io, path) {
             _ = self.engines.remove(&path);
@@ -93,6 +167,20 @@ where
     }
 }
 
+/// A build directory for the project in the system's temporary directory,
+/// named by the path of the project so that each project has its own. It is
+/// an error for the temporary directory not to be a UTF-8 path, rather than
+/// the project's own build directory being used without the user knowing.
+fn isolated_build_directory(root: &Utf8Path) -> Result<Utf8PathBuf> {
+    let mut hasher = DefaultHasher::new();
+    root.hash(&mut hasher);
+    let temp = Utf8PathBuf::from_path_buf(std::env::temp_dir())
+        .map_err(|path| Error::NonUtf8Path { path })?;
+    Ok(temp
+        .join("gleam-lsp")
+        .join(format!("{:016x}", hasher.finish())))
+}
+
 /// Given a given path, find the nearest parent directory containing a
 /// `gleam.toml` file.
 ///
@@ -129,6 +217,22 @@ pub struct Project<A, B> {
     pub feedback: FeedbackBookKeeper,
 }
 
+impl<IO, Reporter> Project<IO, Reporter>
+where
+    IO: CommandExecutor + FileSystemWriter + FileSystemReader + Clone,
+{
+    /// The diagnostics the client has for the source file of a root package
+    /// module from the most recent compilations, sorted by where they are in
+    /// the file, such as for showing them again when the file is focused
+    /// without compiling or going through the diagnostics of every file.
+    pub fn diagnostics_for(&mut self, module: &str) -> Vec<Diagnostic> {
+        match self.engine.compiler.root_module_path(module) {
+            Some((path, _)) => self.feedback.file_diagnostics_sorted(&path),
+            None => vec![],
+        }
+    }
+}
+
 #[cfg(test)]
 mod find_gleam_project_parent_tests {
     use super::*;
@@ -0,0 +1,50 @@
+use crate::{
+    ast::{Definition, SrcSpan},
+    language_server::{compiler::LspProjectCompiler, src_span_to_lsp_range},
+    line_numbers::LineNumbers,
+};
+use lsp_types::TextEdit;
+
+impl<IO> LspProjectCompiler<IO> {
+    /// Add a `pub fn main()` that returns `Nil` to the end of the module, so
+    /// that it can be run with `gleam run`. Returns `None` if the module
+    /// already has a value named `main`, whether it is a function, a constant
+    /// or an unqualified import, as the new function would clash with it.
+    ///
+    /// Only modules that compiled in the most recent compilation are given a
+    /// stub.
+    pub fn generate_main_stub(&self, module: &str) -> Option<TextEdit> {
+        let module = self.current_module(module)?;
+        let has_main = module
+            .ast
+            .definitions
+            .iter()
+            .any(|definition| match definition {
+                Definition::Function(function) => function.name == "main",
+                Definition::ModuleConstant(constant) => constant.name == "main",
+                Definition::Import(import) => import
+                    .unqualified_values
+                    .iter()
+                    .any(|value| value.used_name() == "main"),
+                Definition::TypeAlias(_) | Definition::CustomType(_) => false,
+            });
+        if has_main {
+            return None;
+        }
+
+        let code = module.code.as_str();
+        let separator = if code.trim().is_empty() {
+            ""
+        } else if code.ends_with('\n') {
+            "\n"
+        } else {
+            "\n\n"
+        };
+        let end = code.len() as u32;
+        let line_numbers = LineNumbers::new(code);
+        Some(TextEdit {
+            range: src_span_to_lsp_range(SrcSpan::new(end, end), &line_numbers),
+            new_text: format!("{separator}pub fn main() {{\n  Nil\n}}\n"),
+        })
+    }
+}
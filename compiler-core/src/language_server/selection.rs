@@ -0,0 +1,43 @@
+use crate::{
+    ast::{SrcSpan, TypedExpr},
+    language_server::{
+        compiler::LspProjectCompiler,
+        pipe::full_span,
+        visit::{walk_module, Visit},
+    },
+    type_::pretty::Printer,
+};
+
+impl<IO> LspProjectCompiler<IO> {
+    /// The type of the expression whose code is exactly the range, printed as
+    /// it would be written in Gleam. Returns `None` if the range does not
+    /// start and end with a single expression, such as when it covers only
+    /// part of one or more than one statement.
+    ///
+    /// Where several expressions share the range the outermost is used. The
+    /// module is searched as it was in the last compilation in which it
+    /// compiled.
+    pub fn selection_type(&self, module: &str, range: SrcSpan) -> Option<String> {
+        let module = self.modules.get(module)?;
+        let mut finder = SelectionFinder {
+            range,
+            expression: None,
+        };
+        walk_module(&module.ast, &mut finder);
+        let type_ = finder.expression?.type_();
+        Some(Printer::new().pretty_print(&type_, 0))
+    }
+}
+
+struct SelectionFinder<'ast> {
+    range: SrcSpan,
+    expression: Option<&'ast TypedExpr>,
+}
+
+impl<'ast> Visit<'ast> for SelectionFinder<'ast> {
+    fn visit_expr(&mut self, expr: &'ast TypedExpr) {
+        if self.expression.is_none() && full_span(expr) == self.range {
+            self.expression = Some(expr);
+        }
+    }
+}
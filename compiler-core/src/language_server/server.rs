@@ -1,32 +1,43 @@
 use crate::{
-    diagnostic::{Diagnostic, Level},
+    ast::SrcSpan,
+    diagnostic::{Diagnostic, Level, Tag},
     io::{CommandExecutor, FileSystemReader, FileSystemWriter},
     language_server::{
-        engine::{self, LanguageServerEngine},
-        feedback::{Feedback, FeedbackBookKeeper},
+        engine::{self, HoverFormat, LanguageServerEngine},
+        feedback::{Feedback, FeedbackBookKeeper, ProjectHealth},
         files::FileSystemProxy,
+        format_range,
+        lsp_warning::is_hint,
+        path_to_uri,
+        references::ReferenceLensData,
         router::Router,
-        src_span_to_lsp_range, DownloadDependencies, MakeLocker,
+        settings::LspSettings,
+        src_span_to_lsp_range, validate_config, DefinitionWithExternalRequest,
+        DownloadDependencies, MakeLocker,
     },
     line_numbers::LineNumbers,
     Result,
 };
 use debug_ignore::DebugIgnore;
+use ecow::EcoString;
 use lsp::{
-    notification::{DidChangeWatchedFiles, DidOpenTextDocument},
+    notification::{DidChangeConfiguration, DidChangeWatchedFiles, DidOpenTextDocument, SetTrace},
     request::GotoDefinition,
     HoverProviderCapability, Position, Range, TextEdit, Url,
 };
 use lsp_types::{
     self as lsp,
     notification::{DidChangeTextDocument, DidCloseTextDocument, DidSaveTextDocument},
-    request::{CodeActionRequest, Completion, Formatting, HoverRequest},
+    request::{
+        CodeActionRequest, CodeLensRequest, CodeLensResolve, Completion, DocumentHighlightRequest,
+        Formatting, HoverRequest, LinkedEditingRange, RangeFormatting,
+    },
     InitializeParams, PublishDiagnosticsParams,
 };
 use serde_json::Value as Json;
 use std::collections::HashMap;
 
-use camino::Utf8PathBuf;
+use camino::{Utf8Path, Utf8PathBuf};
 
 use super::progress::ConnectionProgressReporter;
 
@@ -62,7 +73,10 @@ where
         let initialise_params = initialisation_handshake(connection);
         let reporter = ConnectionProgressReporter::new(connection, &initialise_params);
         let io = FileSystemProxy::new(io);
-        let router = Router::new(reporter, io.clone());
+        let mut router = Router::new(reporter, io.clone());
+        if let Some(trace) = initialise_params.trace {
+            router.set_trace_level(trace.into());
+        }
         Ok(Self {
             connection: connection.into(),
             initialise_params,
@@ -118,6 +132,11 @@ where
                 self.format(params)
             }
 
+            "textDocument/rangeFormatting" => {
+                let params = cast_request::<RangeFormatting>(request);
+                self.format_range(params)
+            }
+
             "textDocument/hover" => {
                 let params = cast_request::<HoverRequest>(request);
                 self.hover(params)
@@ -138,6 +157,31 @@ where
                 self.code_action(params)
             }
 
+            "gleam/definitionWithExternal" => {
+                let params = cast_request::<DefinitionWithExternalRequest>(request);
+                self.definition_with_external(params)
+            }
+
+            "textDocument/linkedEditingRange" => {
+                let params = cast_request::<LinkedEditingRange>(request);
+                self.linked_editing_range(params)
+            }
+
+            "textDocument/documentHighlight" => {
+                let params = cast_request::<DocumentHighlightRequest>(request);
+                self.document_highlight(params)
+            }
+
+            "textDocument/codeLens" => {
+                let params = cast_request::<CodeLensRequest>(request);
+                self.code_lens(params)
+            }
+
+            "codeLens/resolve" => {
+                let lens = cast_request::<CodeLensResolve>(request);
+                self.code_lens_resolve(lens)
+            }
+
             name => panic!("Unsupported LSP request {}", name),
         };
 
@@ -181,12 +225,42 @@ where
                 self.watched_files_changed(params)
             }
 
+            "workspace/didChangeConfiguration" => {
+                let params = cast_notification::<DidChangeConfiguration>(notification);
+                self.configuration_changed(params)
+            }
+
+            "$/setTrace" => {
+                let params = cast_notification::<SetTrace>(notification);
+                self.router.set_trace_level(params.value.into());
+                return;
+            }
+
             _ => return,
         };
 
         self.publish_feedback(feedback);
     }
 
+    /// Every diagnostic the client currently has, for the files of every
+    /// project and those outside of any project, sorted by file and then by
+    /// where it is in the file. This is for showing all of the problems in
+    /// one list.
+    pub fn all_diagnostics_sorted(&mut self) -> Vec<(Utf8PathBuf, Diagnostic)> {
+        let mut all = self.router.all_diagnostics_sorted();
+        all.extend(self.outside_of_project_feedback.all_diagnostics_sorted());
+        all.sort_by(|(one, _), (other, _)| one.cmp(other));
+        all
+    }
+
+    /// The health of the project the file belongs to, such as for showing
+    /// in the status bar. Nothing is returned if the file is not in a
+    /// project.
+    pub fn project_health(&mut self, path: &Utf8Path) -> Result<Option<ProjectHealth>> {
+        let project = self.router.project_for_path(path)?;
+        Ok(project.map(|project| project.feedback.project_health()))
+    }
+
     fn publish_feedback(&self, feedback: Feedback) {
         self.publish_diagnostics(feedback.diagnostics);
         self.publish_messages(feedback.messages);
@@ -298,16 +372,24 @@ where
                 let engine::Response {
                     result,
                     warnings,
+                    lsp_warnings,
                     compilation,
                 } = handler(&mut project.engine);
                 match result {
                     Ok(value) => {
-                        let feedback = project.feedback.response(compilation, warnings);
+                        let mut feedback = project.feedback.response(compilation, warnings);
+                        project
+                            .feedback
+                            .append_lsp_warnings(&mut feedback, lsp_warnings);
                         let json = serde_json::to_value(value).expect("response to json");
                         (json, feedback)
                     }
                     Err(e) => {
-                        let feedback = project.feedback.build_with_error(e, compilation, warnings);
+                        let mut feedback =
+                            project.feedback.build_with_error(e, compilation, warnings);
+                        project
+                            .feedback
+                            .append_lsp_warnings(&mut feedback, lsp_warnings);
                         (Json::Null, feedback)
                     }
                 }
@@ -360,9 +442,54 @@ where
         (json, Feedback::default())
     }
 
+    fn format_range(&mut self, params: lsp::DocumentRangeFormattingParams) -> (Json, Feedback) {
+        let path = path(&params.text_document.uri);
+        let mut error_response = |error| {
+            let feedback = match self.router.project_for_path(&path) {
+                Ok(Some(project)) => project.feedback.error(error),
+                Ok(None) | Err(_) => self.outside_of_project_feedback.error(error),
+            };
+            (Json::Null, feedback)
+        };
+
+        let src: EcoString = match self.io.read(&path) {
+            Ok(src) => src.into(),
+            Err(error) => return error_response(error),
+        };
+
+        let line_numbers = LineNumbers::new(&src);
+        let range = params.range;
+        let range = SrcSpan::new(
+            line_numbers.byte_index(range.start.line, range.start.character),
+            line_numbers.byte_index(range.end.line, range.end.character),
+        );
+        let edits = match format_range(&path, &src, range) {
+            Ok(edits) => edits,
+            Err(error) => return error_response(error),
+        };
+        let json = serde_json::to_value(edits).expect("to JSON value");
+
+        (json, Feedback::default())
+    }
+
     fn hover(&mut self, params: lsp::HoverParams) -> (Json, Feedback) {
         let path = path(&params.text_document_position_params.text_document.uri);
-        self.respond_with_engine(path, |engine| engine.hover(params))
+        let formats = self
+            .initialise_params
+            .capabilities
+            .text_document
+            .as_ref()
+            .and_then(|document| document.hover.as_ref())
+            .and_then(|hover| hover.content_format.as_ref());
+        // The formats are in the client's order of preference, and markdown
+        // is assumed if the client does not say.
+        let format = match formats {
+            Some(formats) if formats.first() == Some(&lsp::MarkupKind::PlainText) => {
+                HoverFormat::PlainText
+            }
+            _ => HoverFormat::Markdown,
+        };
+        self.respond_with_engine(path, |engine| engine.hover(params, format))
     }
 
     fn goto_definition(&mut self, params: lsp::GotoDefinitionParams) -> (Json, Feedback) {
@@ -372,8 +499,17 @@ where
 
     fn completion(&mut self, params: lsp::CompletionParams) -> (Json, Feedback) {
         let path = path(&params.text_document_position.text_document.uri);
+        let snippets = self
+            .initialise_params
+            .capabilities
+            .text_document
+            .as_ref()
+            .and_then(|document| document.completion.as_ref())
+            .and_then(|completion| completion.completion_item.as_ref())
+            .and_then(|item| item.snippet_support)
+            .unwrap_or(false);
         self.respond_with_engine(path, |engine| {
-            engine.completion(params.text_document_position)
+            engine.completion(params.text_document_position, snippets)
         })
     }
 
@@ -382,6 +518,40 @@ where
         self.respond_with_engine(path, |engine| engine.action(params))
     }
 
+    fn definition_with_external(&mut self, params: lsp::GotoDefinitionParams) -> (Json, Feedback) {
+        let path = path(&params.text_document_position_params.text_document.uri);
+        self.respond_with_engine(path, |engine| engine.definition_with_external(params))
+    }
+
+    fn linked_editing_range(&mut self, params: lsp::LinkedEditingRangeParams) -> (Json, Feedback) {
+        let path = path(&params.text_document_position_params.text_document.uri);
+        self.respond_with_engine(path, |engine| engine.linked_editing_range(params))
+    }
+
+    fn document_highlight(&mut self, params: lsp::DocumentHighlightParams) -> (Json, Feedback) {
+        let path = path(&params.text_document_position_params.text_document.uri);
+        self.respond_with_engine(path, |engine| engine.document_highlight(params))
+    }
+
+    fn code_lens(&mut self, params: lsp::CodeLensParams) -> (Json, Feedback) {
+        let path = path(&params.text_document.uri);
+        self.respond_with_engine(path, |engine| engine.code_lens(params))
+    }
+
+    fn code_lens_resolve(&mut self, lens: lsp::CodeLens) -> (Json, Feedback) {
+        let data = lens
+            .data
+            .clone()
+            .and_then(|data| serde_json::from_value::<ReferenceLensData>(data).ok());
+        let Some(data) = data else {
+            return (
+                serde_json::to_value(lens).expect("lens to json"),
+                Feedback::default(),
+            );
+        };
+        self.respond_with_engine(data.path, |engine| engine.code_lens_resolve(lens))
+    }
+
     /// A file opened in the editor may be unsaved, so store a copy of the
     /// new content in memory and compile.
     fn text_document_did_open(&mut self, params: lsp::DidOpenTextDocumentParams) -> Feedback {
@@ -390,7 +560,22 @@ where
             return self.outside_of_project_feedback.error(error);
         }
 
-        self.notified_with_engine(path, |engine| engine.compile_please())
+        let mut feedback =
+            self.notified_with_engine(path.clone(), |engine| engine.compile_please());
+
+        // A module that did not need compiling again is sent the diagnostics
+        // it already had, as the client may have dropped them when the file
+        // was closed.
+        if !feedback.diagnostics.contains_key(&path) {
+            if let Ok(Some(project)) = self.router.project_for_path(&path) {
+                if let Some(module) = project.engine.compiler.module_name_for_path(&path) {
+                    for diagnostic in project.diagnostics_for(&module) {
+                        feedback.append_diagnostic(path.clone(), diagnostic);
+                    }
+                }
+            }
+        }
+        with_config_diagnostics(feedback, path, &params.text_document.text)
     }
 
     fn text_document_did_save(&mut self, params: lsp::DidSaveTextDocumentParams) -> Feedback {
@@ -431,7 +616,8 @@ where
         }
 
         // The files on disc have changed, so compile the project with the new changes
-        self.notified_with_engine(path, |engine| engine.compile_please())
+        let feedback = self.notified_with_engine(path.clone(), |engine| engine.compile_please());
+        with_config_diagnostics(feedback, path, &changes.text)
     }
 
     fn watched_files_changed(&mut self, params: lsp::DidChangeWatchedFilesParams) -> Feedback {
@@ -444,6 +630,24 @@ where
         self.router.delete_engine_for_path(&path);
         self.notified_with_engine(path, LanguageServerEngine::compile_please)
     }
+
+    /// The user has changed their settings. Clients may send the settings of
+    /// every extension, in which case ours are under the `gleam` key.
+    fn configuration_changed(&mut self, params: lsp::DidChangeConfigurationParams) -> Feedback {
+        let settings = match params.settings {
+            Json::Object(mut map) if map.contains_key("gleam") => map.remove("gleam"),
+            settings => Some(settings),
+        };
+        let settings = match settings.map(serde_json::from_value::<LspSettings>) {
+            Some(Ok(settings)) => settings,
+            Some(Err(error)) => {
+                tracing::warn!(%error, "invalid_settings");
+                return Feedback::default();
+            }
+            None => return Feedback::default(),
+        };
+        self.router.apply_settings(settings)
+    }
 }
 
 fn initialisation_handshake(connection: &lsp_server::Connection) -> InitializeParams {
@@ -477,13 +681,15 @@ fn initialisation_handshake(connection: &lsp_server::Connection) -> InitializePa
         type_definition_provider: None,
         implementation_provider: None,
         references_provider: None,
-        document_highlight_provider: None,
+        document_highlight_provider: Some(lsp::OneOf::Left(true)),
         document_symbol_provider: None,
         workspace_symbol_provider: None,
         code_action_provider: Some(lsp::CodeActionProviderCapability::Simple(true)),
-        code_lens_provider: None,
+        code_lens_provider: Some(lsp::CodeLensOptions {
+            resolve_provider: Some(true),
+        }),
         document_formatting_provider: Some(lsp::OneOf::Left(true)),
-        document_range_formatting_provider: None,
+        document_range_formatting_provider: Some(lsp::OneOf::Left(true)),
         document_on_type_formatting_provider: None,
         rename_provider: None,
         document_link_provider: None,
@@ -495,7 +701,9 @@ fn initialisation_handshake(connection: &lsp_server::Connection) -> InitializePa
         call_hierarchy_provider: None,
         semantic_tokens_provider: None,
         moniker_provider: None,
-        linked_editing_range_provider: None,
+        linked_editing_range_provider: Some(lsp::LinkedEditingRangeServerCapabilities::Simple(
+            true,
+        )),
         experimental: None,
         position_encoding: None,
         inline_value_provider: None,
@@ -537,12 +745,34 @@ where
         .expect("cast notification")
 }
 
-fn diagnostic_to_lsp(diagnostic: Diagnostic) -> Vec<lsp::Diagnostic> {
+/// A `gleam.toml` that cannot be read stops the project from being compiled,
+/// so its problems are shown in the file itself rather than only as a message.
+fn with_config_diagnostics(mut feedback: Feedback, path: Utf8PathBuf, src: &str) -> Feedback {
+    if path.file_name() != Some("gleam.toml") {
+        return feedback;
+    }
+    feedback.unset_existing_diagnostics(path.clone());
+    for diagnostic in validate_config(src) {
+        feedback.append_diagnostic(path.clone(), diagnostic);
+    }
+    feedback
+}
+
+pub(crate) fn diagnostic_to_lsp(diagnostic: Diagnostic) -> Vec<lsp::Diagnostic> {
     let severity = match diagnostic.level {
         Level::Error => lsp::DiagnosticSeverity::ERROR,
+        Level::Warning if is_hint(&diagnostic) => lsp::DiagnosticSeverity::HINT,
         Level::Warning => lsp::DiagnosticSeverity::WARNING,
     };
     let hint = diagnostic.hint;
+    let tags = diagnostic
+        .tags
+        .iter()
+        .map(|tag| match tag {
+            Tag::Unnecessary => lsp::DiagnosticTag::UNNECESSARY,
+            Tag::Deprecated => lsp::DiagnosticTag::DEPRECATED,
+        })
+        .collect::<Vec<_>>();
     let mut text = diagnostic.title;
 
     if let Some(label) = diagnostic
@@ -572,12 +802,14 @@ fn diagnostic_to_lsp(diagnostic: Diagnostic) -> Vec<lsp::Diagnostic> {
     let main = lsp::Diagnostic {
         range: src_span_to_lsp_range(location.label.span, &line_numbers),
         severity: Some(severity),
-        code: None,
+        code: diagnostic
+            .code
+            .map(|code| lsp::NumberOrString::String(code.into())),
         code_description: None,
         source: None,
         message: text,
         related_information: None,
-        tags: None,
+        tags: (!tags.is_empty()).then_some(tags),
         data: None,
     };
 
@@ -594,12 +826,6 @@ fn diagnostic_to_lsp(diagnostic: Diagnostic) -> Vec<lsp::Diagnostic> {
     }
 }
 
-fn path_to_uri(path: Utf8PathBuf) -> Url {
-    let mut file: String = "file://".into();
-    file.push_str(&path.as_os_str().to_string_lossy());
-    Url::parse(&file).expect("path_to_uri URL parse")
-}
-
 fn path(uri: &Url) -> Utf8PathBuf {
     // The to_file_path method is available on these platforms
     #[cfg(any(unix, windows, target_os = "redox", target_os = "wasi"))]
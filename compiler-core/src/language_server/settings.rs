@@ -0,0 +1,192 @@
+use crate::{
+    build::{Mode, SourceFilter, Target},
+    io::{CommandExecutor, FileSystemReader, FileSystemWriter},
+    language_server::{compiler::LspProjectCompiler, WarningLevel},
+    Error, Result, Warning,
+};
+use camino::{Utf8Path, Utf8PathBuf};
+use globset::{Glob, GlobSetBuilder};
+use serde::Deserialize;
+use std::collections::HashSet;
+
+/// The user's settings for the language server, as sent by the client in
+/// `workspace/didChangeConfiguration` notifications.
+///
+/// Changing any of these settings causes the project to be recompiled:
+///
+/// - `target`
+/// - `include_tests`
+/// - `exclude`
+/// - `warnings_as_errors`
+/// - `panic_warnings`
+/// - `shadowing_warnings`
+/// - `private_function_hints`
+/// - `warning_level`
+/// - `unfinished_code_as_errors`
+///
+/// Changing `isolated_build_directory` only affects projects opened after
+/// the change.
+///
+/// The remaining settings only change how the server presents its results.
+///
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct LspSettings {
+    /// Overrides the target given in the project's `gleam.toml`.
+    pub target: Option<Target>,
+    /// Whether the modules in the `test` directory are compiled.
+    pub include_tests: bool,
+    /// Source files of the root package that are not compiled, matched
+    /// against their path relative to the project root.
+    pub exclude: Vec<Glob>,
+    /// Whether warnings in the root package are an error.
+    pub warnings_as_errors: bool,
+    /// Whether uses of `panic` in the root package are reported as warnings,
+    /// in the same way as uses of `todo` are.
+    pub panic_warnings: bool,
+    /// Whether local variables with the same name as a variable that is
+    /// already in scope are reported as warnings in the root package.
+    pub shadowing_warnings: bool,
+    /// Whether public functions of the root package that no other module
+    /// uses are reported as hints that they could be made private.
+    pub private_function_hints: bool,
+    /// Which of the warnings of the root package are reported.
+    pub warning_level: WarningLevel,
+    /// Whether the diagnostics for `todo`, and for `panic` if reported, are
+    /// shown as errors. Unlike `warnings_as_errors` this does not cause
+    /// compilation to fail.
+    pub unfinished_code_as_errors: bool,
+    /// The codes of diagnostics that are not reported, such as `todo`.
+    pub ignored_diagnostic_codes: HashSet<String>,
+    /// Whether a `// gleam-ignore` comment listing the codes of warnings
+    /// stops them being reported for the line after it.
+    pub suppression_comments: bool,
+    /// Whether hover, go to definition, completion and code actions use the
+    /// last version of a module that compiled while the module fails to
+    /// compile, giving results that may be out of date with its source. Code
+    /// actions are only offered for the code before the first change made
+    /// since then.
+    pub use_last_good_modules: bool,
+    /// Whether projects are built in a directory outside of the project, so
+    /// that the language server does not write to the same build directory
+    /// as `gleam` commands run in a terminal. Dependency packages are still
+    /// downloaded into the project's build directory.
+    pub isolated_build_directory: bool,
+}
+
+impl Default for LspSettings {
+    fn default() -> Self {
+        Self {
+            target: None,
+            include_tests: true,
+            exclude: vec![],
+            warnings_as_errors: false,
+            panic_warnings: false,
+            shadowing_warnings: false,
+            private_function_hints: false,
+            warning_level: WarningLevel::All,
+            unfinished_code_as_errors: false,
+            ignored_diagnostic_codes: HashSet::new(),
+            suppression_comments: true,
+            use_last_good_modules: true,
+            isolated_build_directory: false,
+        }
+    }
+}
+
+impl LspSettings {
+    /// Whether switching from these settings to the other settings changes
+    /// the result of compiling the project.
+    pub fn affects_compilation(&self, other: &Self) -> bool {
+        self.target != other.target
+            || self.include_tests != other.include_tests
+            || self.exclude != other.exclude
+            || self.warnings_as_errors != other.warnings_as_errors
+            || self.panic_warnings != other.panic_warnings
+            || self.shadowing_warnings != other.shadowing_warnings
+            || self.private_function_hints != other.private_function_hints
+            || self.warning_level != other.warning_level
+            || self.unfinished_code_as_errors != other.unfinished_code_as_errors
+    }
+
+    fn source_filter(&self) -> SourceFilter {
+        let mut builder = GlobSetBuilder::new();
+        for glob in &self.exclude {
+            _ = builder.add(glob.clone());
+        }
+        SourceFilter {
+            include_tests: self.include_tests,
+            exclude: builder.build().expect("exclude globs"),
+        }
+    }
+}
+
+impl<IO> LspProjectCompiler<IO>
+where
+    IO: CommandExecutor + FileSystemWriter + FileSystemReader + Clone,
+{
+    /// Use new settings without compiling. Returns whether compilation is
+    /// affected by the change, in which case the project is to be compiled
+    /// again, as the engine's `apply_settings` does.
+    pub(crate) fn set_settings(&mut self, settings: LspSettings) -> Result<bool> {
+        let affects_compilation = self.settings.affects_compilation(&settings);
+        if affects_compilation {
+            // The root package is compiled from scratch, both because modules
+            // that are now excluded must not be kept from the previous
+            // compilation and because the build caches do not contain the
+            // typed AST that the language server needs for modules that have
+            // not changed. The build directory is deleted before anything is
+            // changed so that the old settings are kept if it cannot be.
+            let target = settings
+                .target
+                .unwrap_or(self.project_compiler.config.target);
+            let _guard = self.locker.lock_for_build();
+            let path = self.root_build_directory(target);
+            self.project_compiler.io.delete_directory(&path)?;
+            self.project_compiler.set_target(settings.target);
+            self.project_compiler.root_source_filter = settings.source_filter();
+            self.modules.clear();
+            self.sources.clear();
+            self.untyped_modules.clear();
+            self.dependency_modules.clear();
+        }
+        self.settings = settings;
+        Ok(affects_compilation)
+    }
+
+    /// Returns an error if warnings are to be treated as errors and any were
+    /// emitted for the root package. Warnings for the modules of dependencies
+    /// are not counted.
+    pub(crate) fn check_warnings_as_errors(&self) -> Result<()> {
+        if !self.settings.warnings_as_errors {
+            return Ok(());
+        }
+        match self
+            .warnings
+            .warnings
+            .read()
+            .expect("Vector lock poisoned")
+            .iter()
+            .filter(|warning| self.owns_file(warning_path(warning)))
+            .count()
+        {
+            0 => Ok(()),
+            count => Err(Error::ForbiddenWarnings { count }),
+        }
+    }
+
+    /// The build directory of the root package for a target.
+    fn root_build_directory(&self, target: Target) -> Utf8PathBuf {
+        self.project_compiler.paths.build_directory_for_package(
+            Mode::Lsp,
+            target,
+            &self.project_compiler.config.name,
+        )
+    }
+}
+
+fn warning_path(warning: &Warning) -> &Utf8Path {
+    match warning {
+        Warning::Type { path, .. } | Warning::InvalidSource { path } => path,
+    }
+}
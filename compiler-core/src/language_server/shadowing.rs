@@ -0,0 +1,144 @@
+use crate::{
+    ast::{
+        Definition, Pattern, SrcSpan, Statement, TypedArg, TypedExpr, TypedPattern, TypedStatement,
+    },
+    build::Module,
+    language_server::{
+        lsp_warning::{LspWarning, LspWarningKind},
+        visit::{walk_module, walk_pattern, Visit},
+    },
+};
+use ecow::EcoString;
+
+/// Report each local variable that is given the same name as a variable that
+/// is already in scope, which Gleam allows, as a warning.
+pub(super) fn emit_shadowing_warnings(module: &Module, warnings: &mut Vec<LspWarning>) {
+    let mut finder = BindingFinder { bindings: vec![] };
+    for definition in &module.ast.definitions {
+        if let Definition::Function(function) = definition {
+            finder.function(&function.arguments, &function.body);
+        }
+    }
+    walk_module(&module.ast, &mut finder);
+
+    for binding in &finder.bindings {
+        // The nearest variable that the binding shadows, if there are more
+        // than one.
+        let shadowed = finder
+            .bindings
+            .iter()
+            .filter(|earlier| {
+                earlier.name == binding.name
+                    && earlier.location.start < binding.location.start
+                    && earlier.scope.contains(binding.location.start)
+            })
+            .max_by_key(|earlier| earlier.location.start);
+        if let Some(shadowed) = shadowed {
+            warnings.push(LspWarning {
+                path: module.input_path.clone(),
+                src: module.code.clone(),
+                kind: LspWarningKind::ShadowedVariable {
+                    location: binding.location,
+                    name: binding.name.clone(),
+                    shadowed: shadowed.location,
+                },
+            });
+        }
+    }
+}
+
+/// A local variable, with the span of code in which it can be used.
+struct Binding {
+    name: EcoString,
+    location: SrcSpan,
+    scope: SrcSpan,
+}
+
+/// Finds every local variable of a module. The variables of the arguments
+/// and the body of each module function are added before the module is
+/// walked, as the visitor is not called for module functions.
+struct BindingFinder {
+    bindings: Vec<Binding>,
+}
+
+impl BindingFinder {
+    fn function(&mut self, arguments: &[TypedArg], body: &[TypedStatement]) {
+        if let (Some(first), Some(last)) = (body.first(), body.last()) {
+            let scope = SrcSpan::new(first.location().start, last.location().end);
+            for argument in arguments {
+                if let Some(name) = argument.get_variable_name() {
+                    self.bindings.push(Binding {
+                        name: name.clone(),
+                        location: argument.location,
+                        scope,
+                    });
+                }
+            }
+        }
+        self.statements(body);
+    }
+
+    /// A variable assigned by a statement can be used by the statements
+    /// after it.
+    fn statements(&mut self, statements: &[TypedStatement]) {
+        let Some(end) = statements.last().map(|statement| statement.location().end) else {
+            return;
+        };
+        for statement in statements {
+            if let Statement::Assignment(assignment) = statement {
+                let scope = SrcSpan::new(assignment.location.end, end);
+                self.pattern(&assignment.pattern, scope);
+            }
+        }
+    }
+
+    fn pattern(&mut self, pattern: &TypedPattern, scope: SrcSpan) {
+        let mut names = PatternVariables { variables: vec![] };
+        walk_pattern(pattern, &mut names);
+        self.bindings
+            .extend(names.variables.into_iter().map(|(name, location)| Binding {
+                name,
+                location,
+                scope,
+            }));
+    }
+}
+
+impl<'ast> Visit<'ast> for BindingFinder {
+    fn visit_expr(&mut self, expr: &'ast TypedExpr) {
+        match expr {
+            TypedExpr::Block { statements, .. } => self.statements(statements),
+            TypedExpr::Fn { args, body, .. } => self.function(args, body),
+            TypedExpr::Case { clauses, .. } => {
+                for clause in clauses {
+                    // The alternative patterns of a clause all assign the same
+                    // variables, so the variables are only in scope in the
+                    // code run for the clause.
+                    let scope = clause.then.location();
+                    let patterns = std::iter::once(&clause.pattern)
+                        .chain(&clause.alternative_patterns)
+                        .flatten();
+                    for pattern in patterns {
+                        self.pattern(pattern, scope);
+                    }
+                }
+            }
+            _ => (),
+        }
+    }
+}
+
+struct PatternVariables {
+    variables: Vec<(EcoString, SrcSpan)>,
+}
+
+impl<'ast> Visit<'ast> for PatternVariables {
+    fn visit_pattern(&mut self, pattern: &'ast TypedPattern) {
+        match pattern {
+            Pattern::Variable { name, location, .. } | Pattern::Assign { name, location, .. } => {
+                self.variables.push((name.clone(), *location));
+            }
+            _ => (),
+        }
+    }
+}
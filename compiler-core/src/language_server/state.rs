@@ -0,0 +1,63 @@
+use std::collections::HashMap;
+
+use crate::{
+    ast::UntypedModule,
+    build::Module,
+    io::{CommandExecutor, FileSystemReader, FileSystemWriter},
+    language_server::compiler::{LspProjectCompiler, ModuleSourceInformation, ModuleTypeError},
+    type_::ModuleInterface,
+};
+use ecow::EcoString;
+
+/// A copy of everything the language server knows about a project from
+/// compiling it, so that a test can compile a project once and then run
+/// features against the result any number of times.
+///
+/// The snapshot is kept in memory. The build directory is not part of it, so
+/// the next compilation after the state is loaded compiles the root package
+/// from its sources as usual.
+///
+#[derive(Debug, Clone)]
+pub struct CompilerState {
+    pub modules: HashMap<EcoString, Module>,
+    pub sources: HashMap<EcoString, ModuleSourceInformation>,
+    pub untyped_modules: HashMap<EcoString, UntypedModule>,
+    pub failed_modules: Vec<EcoString>,
+    pub compile_order: Vec<EcoString>,
+    pub type_errors: HashMap<EcoString, ModuleTypeError>,
+    pub dependency_modules: HashMap<EcoString, Module>,
+    pub importable_modules: im::HashMap<EcoString, ModuleInterface>,
+}
+
+impl<IO> LspProjectCompiler<IO>
+where
+    IO: CommandExecutor + FileSystemWriter + FileSystemReader + Clone,
+{
+    pub fn dump_state(&self) -> CompilerState {
+        CompilerState {
+            modules: self.modules.clone(),
+            sources: self.sources.clone(),
+            untyped_modules: self.untyped_modules.clone(),
+            failed_modules: self.failed_modules.clone(),
+            compile_order: self.compile_order.clone(),
+            type_errors: self.type_errors.clone(),
+            dependency_modules: self.dependency_modules.clone(),
+            importable_modules: self.project_compiler.get_importable_modules().clone(),
+        }
+    }
+
+    /// Replace the results of compiling the project with those of a snapshot,
+    /// which should be of the same project.
+    pub fn load_state(&mut self, state: CompilerState) {
+        self.modules = state.modules;
+        self.sources = state.sources;
+        self.untyped_modules = state.untyped_modules;
+        self.failed_modules = state.failed_modules;
+        self.compile_order = state.compile_order;
+        self.type_errors = state.type_errors;
+        self.dependency_modules = state.dependency_modules;
+        self.advance_generation();
+        self.project_compiler
+            .set_importable_modules(state.importable_modules);
+    }
+}
@@ -1,4 +1,5 @@
 use crate::line_numbers::LineNumbers;
+use itertools::Itertools;
 use lsp_types::{
     CodeActionContext, CodeActionParams, PartialResultParams, Position, Range,
     TextDocumentIdentifier, Url, WorkDoneProgressParams, WorkspaceEdit,
@@ -96,6 +97,34 @@ fn apply_code_edit(
     result
 }
 
+// Unlike `apply_code_edit` this handles edits that insert or replace text as
+// well as those that remove it. The ranges of the edits are all in the
+// original text, so they are applied from the end backwards.
+fn apply_code_edits_from_end(src: &str, url: &Url, action: &lsp_types::CodeAction) -> String {
+    let Some(WorkspaceEdit {
+        changes: Some(changes),
+        ..
+    }) = &action.edit
+    else {
+        panic!("No text edit found")
+    };
+    let mut result = src.to_string();
+    let line_numbers = LineNumbers::new(src);
+    for (change_url, change) in changes {
+        if url != change_url {
+            panic!("Unknown url {}", change_url)
+        }
+        let mut edits = change.iter().collect_vec();
+        edits.sort_by_key(|edit| std::cmp::Reverse(edit.range.start));
+        for edit in edits {
+            let start = line_numbers.byte_index(edit.range.start.line, edit.range.start.character);
+            let end = line_numbers.byte_index(edit.range.end.line, edit.range.end.character);
+            result.replace_range(start as usize..end as usize, &edit.new_text);
+        }
+    }
+    result
+}
+
 #[test]
 fn test_remove_unused_simple() {
     let code = "
@@ -225,3 +254,461 @@ pub fn main() {
     assert_eq!(remove_unused_action(code), expected.to_string())
 }
 */
+
+fn pipe_action(src: &str, title: &str, position: Position) -> String {
+    let io = LanguageServerTestIO::new();
+    let mut engine = setup_engine(&io);
+
+    _ = io.src_module("app", src);
+    engine.compile_please().result.expect("compiled");
+
+    let path = Utf8PathBuf::from(if cfg!(target_family = "windows") {
+        r"\\?\C:\src\app.gleam"
+    } else {
+        "/src/app.gleam"
+    });
+    let url = Url::from_file_path(path).unwrap();
+
+    let params = CodeActionParams {
+        text_document: TextDocumentIdentifier::new(url.clone()),
+        context: CodeActionContext {
+            diagnostics: vec![],
+            only: None,
+            trigger_kind: None,
+        },
+        range: Range::new(position, position),
+        work_done_progress_params: WorkDoneProgressParams {
+            work_done_token: None,
+        },
+        partial_result_params: PartialResultParams {
+            partial_result_token: None,
+        },
+    };
+
+    let action = engine
+        .action(params)
+        .result
+        .unwrap()
+        .and_then(|actions| actions.into_iter().find(|action| action.title == title))
+        .expect("No code action produced by the engine");
+    apply_code_action(src, &url, &action)
+}
+
+// Apply the action with the title at the position in the code of `app`,
+// after adding the other modules, which the action can edit `app` with.
+fn action_with_modules(
+    src: &str,
+    modules: &[(&str, &str)],
+    title: &str,
+    position: Position,
+) -> String {
+    let io = LanguageServerTestIO::new();
+    let mut engine = setup_engine(&io);
+
+    for (name, code) in modules {
+        _ = io.src_module(name, code);
+    }
+    _ = io.src_module("app", src);
+    engine.compile_please().result.expect("compiled");
+
+    let path = Utf8PathBuf::from(if cfg!(target_family = "windows") {
+        r"\\?\C:\src\app.gleam"
+    } else {
+        "/src/app.gleam"
+    });
+    let url = Url::from_file_path(path).unwrap();
+
+    let params = CodeActionParams {
+        text_document: TextDocumentIdentifier::new(url.clone()),
+        context: CodeActionContext {
+            diagnostics: vec![],
+            only: None,
+            trigger_kind: None,
+        },
+        range: Range::new(position, position),
+        work_done_progress_params: WorkDoneProgressParams {
+            work_done_token: None,
+        },
+        partial_result_params: PartialResultParams {
+            partial_result_token: None,
+        },
+    };
+
+    let action = engine
+        .action(params)
+        .result
+        .unwrap()
+        .and_then(|actions| actions.into_iter().find(|action| action.title == title))
+        .expect("No code action produced by the engine");
+    apply_code_edits_from_end(src, &url, &action)
+}
+
+#[test]
+fn convert_to_and_from_pipe() {
+    let nested = "fn double(x) { x * 2 }
+pub fn main() {
+  double(double(1))
+}
+";
+    let piped = "fn double(x) { x * 2 }
+pub fn main() {
+  1 |> double |> double
+}
+";
+    assert_eq!(
+        pipe_action(nested, "Convert to pipe", Position::new(2, 3)),
+        piped
+    );
+    assert_eq!(
+        pipe_action(piped, "Convert from pipe", Position::new(2, 3)),
+        nested
+    );
+}
+
+#[test]
+fn generate_main_stub() {
+    let src = "pub fn double(x) { x * 2 }";
+    let generated = pipe_action(src, "Generate `main` function", Position::new(0, 0));
+    assert_eq!(
+        generated,
+        "pub fn double(x) { x * 2 }
+
+pub fn main() {
+  Nil
+}
+"
+    );
+
+    // The stub compiles, and is not generated again.
+    let io = LanguageServerTestIO::new();
+    let mut engine = setup_engine(&io);
+    _ = io.src_module("app", &generated);
+    _ = io.src_module("other", "import app.{double as main}");
+    assert!(engine.compile_please().result.is_ok());
+    assert_eq!(engine.compiler.generate_main_stub("app"), None);
+    assert_eq!(engine.compiler.generate_main_stub("other"), None);
+}
+
+#[test]
+fn annotate_return_type() {
+    let src = "pub fn main(x: Int) {
+  [x]
+}
+";
+    assert_eq!(
+        pipe_action(src, "Annotate return type", Position::new(1, 2)),
+        "pub fn main(x: Int) -> List(Int) {
+  [x]
+}
+"
+    );
+
+    // Functions that are annotated or are generic in what they return are
+    // not annotated.
+    let io = LanguageServerTestIO::new();
+    let mut engine = setup_engine(&io);
+    _ = io.src_module("app", "pub fn one() -> Int { 1 }\npub fn id(x) { x }\n");
+    assert!(engine.compile_please().result.is_ok());
+    assert_eq!(engine.compiler.annotate_return_type("app", 20), None);
+    assert_eq!(engine.compiler.annotate_return_type("app", 40), None);
+}
+
+#[test]
+fn label_arguments() {
+    let src = "fn area(width width: Int, height height: Int) { width * height }
+pub fn main() {
+  area(3, height: 2)
+  area(height: 2, width: 1 + 1)
+}
+";
+    assert_eq!(
+        pipe_action(src, "Label arguments", Position::new(2, 3)),
+        "fn area(width width: Int, height height: Int) { width * height }
+pub fn main() {
+  area(width: 3, height: 2)
+  area(height: 2, width: 1 + 1)
+}
+"
+    );
+    assert_eq!(
+        pipe_action(src, "Label arguments", Position::new(3, 3)),
+        "fn area(width width: Int, height height: Int) { width * height }
+pub fn main() {
+  area(3, height: 2)
+  area(width: 1 + 1, height: 2)
+}
+"
+    );
+
+    // Arguments that could have effects are not moved, as they are evaluated
+    // in the order they are written, and calls already in the order of the
+    // declaration are not rewritten.
+    let src = "fn area(width width: Int, height height: Int) { width * height }
+fn one() { 1 }
+pub fn main() {
+  area(height: one(), width: one())
+  area(width: 1, height: 2)
+}
+";
+    let io = LanguageServerTestIO::new();
+    let mut engine = setup_engine(&io);
+    _ = io.src_module("app", src);
+    assert!(engine.compile_please().result.is_ok());
+    let call = src.find("area(height: one()").expect("call") as u32;
+    assert_eq!(engine.compiler.normalize_call_labels("app", call), None);
+    let call = src.find("area(width: 1").expect("call") as u32;
+    assert_eq!(engine.compiler.normalize_call_labels("app", call), None);
+}
+
+#[test]
+fn simplify_boolean_case() {
+    let src = "pub fn main(is_admin, verified) {
+  case is_admin {
+    False -> \"guest\"
+    _ ->
+      case verified {
+        True -> \"admin\"
+        False -> \"pending\"
+      }
+  }
+}
+";
+    assert_eq!(
+        pipe_action(src, "Simplify boolean case", Position::new(1, 3)),
+        "pub fn main(is_admin, verified) {
+  case is_admin {
+    True if verified -> \"admin\"
+    True -> \"pending\"
+    False -> \"guest\"
+  }
+}
+"
+    );
+
+    let src = "pub fn main(x) {
+  let a = case x > 1 {
+    True -> False
+    _ -> True
+  }
+  case a {
+    False -> False
+    True -> True
+  }
+}
+";
+    assert_eq!(
+        pipe_action(src, "Simplify boolean case", Position::new(1, 11)),
+        "pub fn main(x) {
+  let a = !{ x > 1 }
+  case a {
+    False -> False
+    True -> True
+  }
+}
+"
+    );
+    assert_eq!(
+        pipe_action(src, "Simplify boolean case", Position::new(5, 3)),
+        "pub fn main(x) {
+  let a = case x > 1 {
+    True -> False
+    _ -> True
+  }
+  a
+}
+"
+    );
+
+    // Cases already in this form, and cases matching anything other than
+    // the two values, are not rewritten.
+    let src = "pub fn main(x, y) {
+  case x {
+    True -> 1
+    False -> 2
+  }
+  case x {
+    True if y -> 1
+    _ -> 2
+  }
+}
+";
+    let io = LanguageServerTestIO::new();
+    let mut engine = setup_engine(&io);
+    _ = io.src_module("app", src);
+    assert!(engine.compile_please().result.is_ok());
+    for (index, _) in src.match_indices("case x") {
+        assert_eq!(
+            engine.compiler.simplify_boolean_case("app", index as u32),
+            None
+        );
+    }
+}
+
+#[test]
+fn toggle_import_style() {
+    let list = "pub type Wibble { Wibble(Int) }
+pub const size = 3
+pub fn map(x) { x }
+pub fn length(x) { x }
+";
+    let modules = [("gleam/list", list)];
+    let qualified = "import gleam/list as l
+
+pub fn main(length) {
+  let assert l.Wibble(x) = l.map(l.Wibble(l.size))
+  l.length(length) + x
+}
+";
+    let unqualified = "import gleam/list.{Wibble, map, size} as l
+
+pub fn main(length) {
+  let assert Wibble(x) = map(Wibble(size))
+  l.length(length) + x
+}
+";
+    let fully_qualified = "import gleam/list as l
+
+pub fn main(length) {
+  let assert l.Wibble(x) = l.map(l.Wibble(l.size))
+  l.length(length) + x
+}
+";
+
+    // `length` is a variable, so the function stays qualified.
+    assert_eq!(
+        action_with_modules(
+            qualified,
+            &modules,
+            "Import the values used from `gleam/list` unqualified",
+            Position::new(0, 3)
+        ),
+        unqualified
+    );
+    assert_eq!(
+        action_with_modules(
+            unqualified,
+            &modules,
+            "Qualify the values imported from `gleam/list`",
+            Position::new(0, 3)
+        ),
+        fully_qualified
+    );
+}
+
+#[test]
+fn toggle_import_style_keeps_types() {
+    let list = "pub type Wibble { Wibble(Int) }\npub fn new() { Wibble(1) }";
+    let modules = [("gleam/list", list)];
+    let src = "import gleam/list.{type Wibble, new as make}
+
+pub fn main() -> Wibble {
+  make()
+}
+";
+    assert_eq!(
+        action_with_modules(
+            src,
+            &modules,
+            "Qualify the values imported from `gleam/list`",
+            Position::new(0, 3)
+        ),
+        "import gleam/list.{type Wibble}
+
+pub fn main() -> Wibble {
+  list.new()
+}
+"
+    );
+}
+
+#[test]
+fn toggle_import_style_in_guards_constants_and_updates() {
+    let list = "pub type Wibble { Wibble(a: Int, b: Int) }\npub const size = 3";
+    let modules = [("gleam/list", list)];
+    let qualified = "import gleam/list
+
+const default = list.Wibble(list.size, 1)
+
+pub fn main(x) {
+  case x {
+    _ if x == list.size -> list.Wibble(..default, a: x)
+    _ -> default
+  }
+}
+";
+    let unqualified = "import gleam/list.{Wibble, size}
+
+const default = Wibble(size, 1)
+
+pub fn main(x) {
+  case x {
+    _ if x == size -> Wibble(..default, a: x)
+    _ -> default
+  }
+}
+";
+    let title = "Import the values used from `gleam/list` unqualified";
+    assert_eq!(
+        action_with_modules(qualified, &modules, title, Position::new(0, 3)),
+        unqualified
+    );
+    let title = "Qualify the values imported from `gleam/list`";
+    assert_eq!(
+        action_with_modules(unqualified, &modules, title, Position::new(0, 3)),
+        qualified
+    );
+}
+
+#[test]
+fn actions_in_module_that_no_longer_compiles() {
+    let io = LanguageServerTestIO::new();
+    let mut engine = setup_engine(&io);
+
+    let src = "fn double(x) { x * 2 }
+pub fn main() {
+  double(double(1))
+}
+";
+    _ = io.src_module("app", src);
+    engine.compile_please().result.expect("compiled");
+
+    let url = Url::from_file_path(io.paths.src_directory().join("app.gleam")).unwrap();
+    let mut titles = |code: &str| {
+        _ = io.src_module("app", code);
+        assert!(engine.compile_please().result.is_err());
+        let position = Position::new(2, 3);
+        let params = CodeActionParams {
+            text_document: TextDocumentIdentifier::new(url.clone()),
+            context: CodeActionContext {
+                diagnostics: vec![],
+                only: None,
+                trigger_kind: None,
+            },
+            range: Range::new(position, position),
+            work_done_progress_params: WorkDoneProgressParams {
+                work_done_token: None,
+            },
+            partial_result_params: PartialResultParams {
+                partial_result_token: None,
+            },
+        };
+        engine
+            .action(params)
+            .result
+            .unwrap()
+            .unwrap_or_default()
+            .into_iter()
+            .map(|action| action.title)
+            .collect_vec()
+    };
+
+    // A type error after the code keeps the actions of the last version of
+    // the module that compiled.
+    let broken_after = format!("{src}pub fn broken() {{ 1 + 1.0 }}\n");
+    assert!(titles(&broken_after).contains(&"Convert to pipe".to_string()));
+
+    // They are not offered once the code before them has changed, as their
+    // spans would no longer be right.
+    let broken_before = format!("pub fn broken() {{ 1 + 1.0 }}\n{src}");
+    assert!(!titles(&broken_before).contains(&"Convert to pipe".to_string()));
+}
@@ -1,4 +1,26 @@
-use crate::language_server::engine::Compilation;
+use crate::language_server::{
+    engine::Compilation,
+    lsp_warning::{LspWarning, LspWarningKind},
+    server::diagnostic_to_lsp,
+};
+use crate::{
+    analyse::TargetSupport,
+    ast::SrcSpan,
+    build::Mode,
+    language_server::{
+        compiler::LspProjectCompiler,
+        feedback::FeedbackBookKeeper,
+        router::{Project, Router},
+        settings::LspSettings,
+        TraceLevel, WarningKind, WarningLevel,
+    },
+    line_numbers::LineNumbers,
+    warning::WarningEmitterIO,
+    Error,
+};
+use ecow::EcoString;
+use globset::Glob;
+use itertools::Itertools;
 
 use super::*;
 
@@ -212,3 +234,1559 @@ fn dep_compile_recompile() {
         ]
     )
 }
+
+#[test]
+fn compile_streaming_reports_each_module() {
+    let io = LanguageServerTestIO::new();
+    let mut engine = setup_engine(&io);
+
+    let one = io.src_module("one", "pub fn one() { 1 }");
+    let two = io.src_module("two", "import one pub fn two() { one.one() }");
+
+    let mut streamed = vec![];
+    let compiled = engine
+        .compiler
+        .compile_streaming(|module| streamed.push(module.name.clone()))
+        .unwrap();
+
+    // Modules are reported in the order they were compiled, dependencies first.
+    assert_eq!(
+        streamed,
+        vec![EcoString::from("one"), EcoString::from("two")]
+    );
+    assert_eq!(compiled, vec![one, two]);
+}
+
+#[test]
+fn feature_only_settings_do_not_recompile() {
+    let io = LanguageServerTestIO::new();
+    let mut engine = setup_engine(&io);
+
+    _ = io.src_module("app", "pub fn main() { 0 }");
+    assert!(engine.compile_please().result.is_ok());
+
+    let settings = LspSettings {
+        use_last_good_modules: false,
+        ..Default::default()
+    };
+    let response = engine.apply_settings(settings);
+    assert!(response.result.is_ok());
+    assert_eq!(response.compilation, Compilation::No);
+}
+
+#[test]
+fn settings_excluding_tests_recompile() {
+    let io = LanguageServerTestIO::new();
+    let mut engine = setup_engine(&io);
+
+    let src = io.src_module("app", "pub fn main() { 0 }");
+    let test = io.test_module("app_test", "pub fn main() { 0 }");
+    let generated = io.src_module("app/generated/thing", "pub fn main() { 0 }");
+
+    let response = engine.compile_please();
+    assert!(response.result.is_ok());
+    assert_eq!(engine.compiler.modules.len(), 3);
+
+    let settings = LspSettings {
+        include_tests: false,
+        exclude: vec![Glob::new("src/app/generated/**").unwrap()],
+        ..Default::default()
+    };
+    let response = engine.apply_settings(settings);
+    assert!(response.result.is_ok());
+
+    // Everything that is still included is compiled again, as the caches do
+    // not contain the information the language server needs.
+    assert_eq!(response.compilation, Compilation::Yes(vec![src.clone()]));
+    assert!(engine.compiler.modules.contains_key("app"));
+    assert!(!engine.compiler.modules.contains_key("app_test"));
+    assert!(!engine.compiler.modules.contains_key("app/generated/thing"));
+
+    // Including them again brings them back.
+    let response = engine.apply_settings(LspSettings::default());
+    assert!(response.result.is_ok());
+    let Compilation::Yes(mut compiled) = response.compilation else {
+        panic!("expected compilation");
+    };
+    compiled.sort();
+    let mut expected = vec![src, generated, test];
+    expected.sort();
+    assert_eq!(compiled, expected);
+}
+
+#[test]
+fn settings_warnings_as_errors() {
+    let io = LanguageServerTestIO::new();
+    let mut engine = setup_engine(&io);
+
+    _ = io.src_module("app", "pub fn main() { let x = 1 0 }");
+
+    let response = engine.compile_please();
+    assert!(response.result.is_ok());
+    assert_eq!(response.warnings.len(), 1);
+
+    let settings = LspSettings {
+        warnings_as_errors: true,
+        ..Default::default()
+    };
+    let response = engine.apply_settings(settings);
+    assert_eq!(response.result, Err(Error::ForbiddenWarnings { count: 1 }));
+    assert_eq!(response.warnings.len(), 1);
+}
+
+#[test]
+fn settings_warnings_as_errors_only_count_the_root_package() {
+    let io = LanguageServerTestIO::new();
+    let mut engine = setup_engine(&io);
+    add_path_dep(&mut engine, "dep");
+    let dep = io.path_dep_module("dep", "dep", "pub fn dep() { 0 }");
+    let app = io.src_module("app", "import dep\npub fn main() { dep.dep() }");
+    let settings = LspSettings {
+        warnings_as_errors: true,
+        ..Default::default()
+    };
+    assert!(engine.apply_settings(settings).result.is_ok());
+
+    let unused = |path: Utf8PathBuf| crate::Warning::Type {
+        path,
+        src: "".into(),
+        warning: crate::type_::Warning::UnusedVariable {
+            location: SrcSpan::new(0, 0),
+            name: "x".into(),
+        },
+    };
+    let compiler = &engine.compiler;
+    compiler.warnings.emit_warning(unused(dep));
+    assert_eq!(compiler.check_warnings_as_errors(), Ok(()));
+    compiler.warnings.emit_warning(unused(app));
+    assert_eq!(
+        compiler.check_warnings_as_errors(),
+        Err(Error::ForbiddenWarnings { count: 1 })
+    );
+}
+
+#[test]
+fn settings_shadowing_warnings() {
+    let io = LanguageServerTestIO::new();
+    let mut engine = setup_engine(&io);
+
+    let code = "pub fn main(x) {
+  let y = x
+  let x = [y]
+  case x {
+    [y, ..] | [_, y] -> {
+      let y = y + 1
+      y
+    }
+    _ -> 0
+  }
+}
+";
+    _ = io.src_module("app", code);
+
+    let shadowed = |warnings: &[LspWarning]| {
+        warnings
+            .iter()
+            .filter_map(|warning| match &warning.kind {
+                LspWarningKind::ShadowedVariable {
+                    location, shadowed, ..
+                } => Some((
+                    &code[location.start as usize..location.end as usize],
+                    shadowed.start,
+                )),
+                _ => None,
+            })
+            .sorted()
+            .collect_vec()
+    };
+
+    let response = engine.compile_please();
+    assert!(response.result.is_ok());
+    assert_eq!(shadowed(&response.lsp_warnings), vec![]);
+
+    // As with panics, shadowing is not counted as a warning that fails
+    // compilation.
+    let settings = LspSettings {
+        shadowing_warnings: true,
+        warnings_as_errors: true,
+        ..Default::default()
+    };
+    let response = engine.apply_settings(settings);
+    assert!(response.result.is_ok());
+    let at = |text: &str| code.find(text).expect("binding") as u32;
+    assert_eq!(
+        shadowed(&response.lsp_warnings),
+        vec![
+            ("x", at("x)")),
+            // The alternative patterns of a clause do not shadow each other.
+            ("y", at("y =")),
+            ("y", at("y =")),
+            ("y", at("y] ->")),
+        ]
+    );
+}
+
+#[test]
+fn settings_private_function_hints() {
+    let io = LanguageServerTestIO::new();
+    let mut engine = setup_engine(&io);
+
+    _ = io.src_module("shared", "pub fn used() { 1 }\npub fn local() { 2 }\n");
+    _ = io.src_module("app", "import shared\npub fn main() { shared.used() }\n");
+
+    let hints = |warnings: &[LspWarning]| {
+        warnings
+            .iter()
+            .map(LspWarning::to_diagnostic)
+            .filter(|diagnostic| diagnostic.code == Some("could_be_private"))
+            .map(|diagnostic| {
+                let severity = diagnostic_to_lsp(diagnostic.clone())
+                    .first()
+                    .and_then(|diagnostic| diagnostic.severity);
+                let location = diagnostic.location.expect("location");
+                (severity, location.path, location.label.span)
+            })
+            .collect_vec()
+    };
+
+    let response = engine.compile_please();
+    assert!(response.result.is_ok());
+    assert_eq!(hints(&response.lsp_warnings), vec![]);
+
+    // The hints are not counted as warnings that fail compilation.
+    let settings = LspSettings {
+        private_function_hints: true,
+        warnings_as_errors: true,
+        ..Default::default()
+    };
+    let response = engine.apply_settings(settings);
+    assert!(response.result.is_ok());
+    assert_eq!(
+        hints(&response.lsp_warnings),
+        vec![(
+            Some(lsp_types::DiagnosticSeverity::HINT),
+            Utf8PathBuf::from("/src/shared.gleam"),
+            SrcSpan::new(20, 34)
+        )]
+    );
+}
+
+#[test]
+fn untyped_module_is_kept_when_type_checking_fails() {
+    let io = LanguageServerTestIO::new();
+    let mut engine = setup_engine(&io);
+
+    _ = io.src_module("app", "pub fn main() { 1 + \"one\" }\npub fn other() { 2 }");
+
+    let response = engine.compile_please();
+    assert!(response.result.is_err());
+    assert!(!engine.compiler.modules.contains_key("app"));
+
+    let module = engine.compiler.untyped_module("app").unwrap();
+    assert_eq!(module.definitions.len(), 2);
+    assert!(engine.compiler.untyped_module("unknown").is_none());
+}
+
+#[test]
+fn failed_modules() {
+    let io = LanguageServerTestIO::new();
+    let mut engine = setup_engine(&io);
+
+    _ = io.src_module("a", "pub fn main() { 1 }");
+    _ = io.src_module("b", "import a pub fn main() { a.main() + \"one\" }");
+    _ = io.src_module("c", "import b pub fn main() { b.main() }");
+
+    // `b` has a type error and `c` can't be checked without it.
+    assert!(engine.compile_please().result.is_err());
+    assert_eq!(
+        engine.compiler.failed_modules(),
+        vec![EcoString::from("b"), EcoString::from("c")]
+    );
+
+    // A parse error fails only that module.
+    _ = io.src_module("b", "import a pub fn main() { a.main() ");
+    assert!(engine.compile_please().result.is_err());
+    assert_eq!(engine.compiler.failed_modules(), vec![EcoString::from("b")]);
+
+    // Only the most recent compilation is reported.
+    _ = io.src_module("b", "import a pub fn main() { a.main() }");
+    assert!(engine.compile_please().result.is_ok());
+    assert!(engine.compiler.failed_modules().is_empty());
+}
+
+#[test]
+fn root_package_modules() {
+    let io = LanguageServerTestIO::new();
+    let mut engine = setup_engine(&io);
+
+    // Modules are listed before they are compiled, and when they fail to.
+    _ = io.src_module("app", "import missing pub fn main() { 1 }");
+    _ = io.src_module("app/nested", "pub fn main() { 1 }");
+    _ = io.test_module("app_test", "pub fn main_test() { 1 }");
+    assert_eq!(
+        engine.compiler.root_package_modules(),
+        vec![
+            EcoString::from("app"),
+            EcoString::from("app/nested"),
+            EcoString::from("app_test"),
+        ]
+    );
+    assert!(engine.compile_please().result.is_err());
+    assert_eq!(engine.compiler.root_package_modules().len(), 3);
+}
+
+#[test]
+fn module_target_support() {
+    let io = LanguageServerTestIO::new();
+    let mut engine = setup_engine(&io);
+
+    _ = io.src_module(
+        "app",
+        r#"pub fn main() { 1 }
+
+@external(javascript, "./ffi.mjs", "now")
+fn now() -> Int
+
+@external(javascript, "./ffi.mjs", "later")
+fn later() -> Int
+"#,
+    );
+    _ = io.src_module("other", "pub fn main() { 1 }");
+    assert!(engine.compile_please().result.is_ok());
+
+    assert_eq!(
+        engine.compiler.module_target_support("app"),
+        TargetSupport::NotEnforced
+    );
+    assert_eq!(
+        engine
+            .compiler
+            .functions_without_target_support("app")
+            .into_iter()
+            .map(|(name, _)| name)
+            .collect_vec(),
+        vec![EcoString::from("now"), EcoString::from("later")]
+    );
+    assert_eq!(
+        engine.compiler.module_target_support("other"),
+        TargetSupport::Enforced
+    );
+
+    // A public function must support the target for its module to compile.
+    _ = io.src_module(
+        "broken",
+        r#"@external(javascript, "./ffi.mjs", "now")
+pub fn now() -> Int
+"#,
+    );
+    assert!(engine.compile_please().result.is_err());
+    assert_eq!(
+        engine
+            .compiler
+            .functions_without_target_support("broken")
+            .into_iter()
+            .map(|(name, _)| name)
+            .collect_vec(),
+        vec![EcoString::from("now")]
+    );
+}
+
+#[test]
+fn diagnostics_for() {
+    let io = LanguageServerTestIO::new();
+    let mut project = Project {
+        engine: setup_engine(&io),
+        feedback: FeedbackBookKeeper::default(),
+    };
+
+    _ = io.src_module("app", "pub fn main() { let y = 2 let x = 1 }");
+    _ = io.src_module("other", "pub fn main() { let z = 1 }");
+    let response = project.engine.compile_please();
+    assert!(response.result.is_ok());
+    _ = project
+        .feedback
+        .response(response.compilation, response.warnings);
+
+    let diagnostics = project.diagnostics_for("app");
+    assert_eq!(
+        diagnostics
+            .iter()
+            .map(|diagnostic| diagnostic.location.as_ref().map(|l| l.label.span))
+            .collect_vec(),
+        vec![Some(SrcSpan::new(20, 21)), Some(SrcSpan::new(30, 31))]
+    );
+    assert_eq!(project.diagnostics_for("other").len(), 1);
+    assert!(project.diagnostics_for("missing").is_empty());
+}
+
+#[test]
+fn last_compile_order() {
+    let io = LanguageServerTestIO::new();
+    let mut engine = setup_engine(&io);
+    assert!(engine.compiler.last_compile_order().is_empty());
+
+    _ = io.src_module(
+        "c",
+        "import b import a pub fn main() { b.main() + a.main() }",
+    );
+    _ = io.src_module("b", "import a pub fn main() { a.main() }");
+    _ = io.src_module("a", "pub fn main() { 1 }");
+
+    // Modules are checked after the modules they import.
+    assert!(engine.compile_please().result.is_ok());
+    assert_eq!(
+        engine.compiler.last_compile_order(),
+        [EcoString::from("a"), "b".into(), "c".into()]
+    );
+
+    // Modules that fail or are not reached are left out, as are those that
+    // have not changed and so are not checked again.
+    _ = io.src_module("b", "import a pub fn main() { a.main() + \"one\" }");
+    assert!(engine.compile_please().result.is_err());
+    assert!(engine.compiler.last_compile_order().is_empty());
+
+    _ = io.src_module("b", "import a pub fn main() { a.main() + 1 }");
+    assert!(engine.compile_please().result.is_ok());
+    assert_eq!(
+        engine.compiler.last_compile_order(),
+        [EcoString::from("b"), "c".into()]
+    );
+}
+
+#[test]
+fn extra_source_directories() {
+    let io = LanguageServerTestIO::new();
+    let manifest = io.download_dependencies(&io.paths).unwrap();
+    let locker = io
+        .make_locker(&io.paths, crate::build::Target::Erlang)
+        .unwrap();
+    let mut compiler = LspProjectCompiler::new(
+        manifest,
+        PackageConfig::default(),
+        io.paths.clone(),
+        FileSystemProxy::new(io.clone()),
+        locker,
+        vec!["generated".into()],
+        WarningLevel::All,
+    )
+    .unwrap();
+
+    let generated = io.paths.root().join("generated/proto/user.gleam");
+    io.module(&generated, "pub type User { User(name: String) }");
+    _ = io.src_module("app", "import proto/user pub fn main() { user.User(\"\") }");
+    let elsewhere = io.paths.root().join("scripts/build.gleam");
+    io.module(&elsewhere, "pub fn main() { 1 }");
+
+    assert!(compiler.compile().is_ok());
+    assert!(compiler.modules.contains_key("proto/user"));
+    assert!(compiler.modules.contains_key("app"));
+    assert_eq!(compiler.modules.len(), 2);
+
+    assert_eq!(
+        compiler.module_name_for_path(&generated),
+        Some(EcoString::from("proto/user"))
+    );
+    assert_eq!(
+        compiler.module_name_for_path(&io.paths.src_directory().join("app.gleam")),
+        Some(EcoString::from("app"))
+    );
+    assert_eq!(compiler.module_name_for_path(&elsewhere), None);
+}
+
+#[test]
+fn warning_diagnostic_tags() {
+    let io = LanguageServerTestIO::new();
+    let mut engine = setup_engine(&io);
+
+    _ = io.src_module(
+        "old",
+        "@deprecated(\"Use new instead\")
+pub fn old() { 1 }
+",
+    );
+    _ = io.src_module(
+        "unused",
+        "pub fn wibble() { 1 }
+",
+    );
+    _ = io.src_module(
+        "app",
+        "import old
+import unused
+
+pub fn main() {
+  let x = 1
+  old.old()
+}
+",
+    );
+
+    let response = engine.compile_please();
+    assert!(response.result.is_ok());
+
+    let mut tags = response
+        .warnings
+        .iter()
+        .flat_map(|warning| diagnostic_to_lsp(warning.to_diagnostic()))
+        .filter(|diagnostic| diagnostic.severity == Some(lsp_types::DiagnosticSeverity::WARNING))
+        .map(|diagnostic| (diagnostic.range.start.line, diagnostic.tags))
+        .collect_vec();
+    tags.sort_by_key(|(line, _)| *line);
+    assert_eq!(
+        tags,
+        vec![
+            (1, Some(vec![lsp_types::DiagnosticTag::UNNECESSARY])),
+            (4, Some(vec![lsp_types::DiagnosticTag::UNNECESSARY])),
+            (5, Some(vec![lsp_types::DiagnosticTag::DEPRECATED])),
+        ]
+    );
+}
+
+#[test]
+fn todo_and_panic_diagnostics() {
+    let io = LanguageServerTestIO::new();
+    let mut engine = setup_engine(&io);
+
+    let code = "pub fn main() {
+  todo as \"wibble\"
+  panic as \"wobble\"
+}
+";
+    _ = io.src_module("app", code);
+
+    let diagnostics = |response: &crate::language_server::engine::Response<()>| {
+        let warnings = response
+            .warnings
+            .iter()
+            .map(|warning| warning.to_diagnostic());
+        let lsp_warnings = response
+            .lsp_warnings
+            .iter()
+            .map(|warning| warning.to_diagnostic());
+        warnings
+            .chain(lsp_warnings)
+            .flat_map(diagnostic_to_lsp)
+            .filter(|diagnostic| diagnostic.code.is_some())
+            .map(|diagnostic| (diagnostic.code.unwrap(), diagnostic.range))
+            .collect_vec()
+    };
+    let todo = (
+        lsp_types::NumberOrString::String("todo".into()),
+        lsp_types::Range::new(
+            lsp_types::Position::new(1, 2),
+            lsp_types::Position::new(1, 18),
+        ),
+    );
+    let panic = (
+        lsp_types::NumberOrString::String("panic".into()),
+        lsp_types::Range::new(
+            lsp_types::Position::new(2, 2),
+            lsp_types::Position::new(2, 19),
+        ),
+    );
+
+    let response = engine.compile_please();
+    assert!(response.result.is_ok());
+    assert_eq!(diagnostics(&response), vec![todo.clone()]);
+
+    // Panics are not counted as warnings that fail compilation.
+    let settings = LspSettings {
+        panic_warnings: true,
+        warnings_as_errors: true,
+        ..Default::default()
+    };
+    let response = engine.apply_settings(settings);
+    assert_eq!(response.result, Err(Error::ForbiddenWarnings { count: 1 }));
+    assert_eq!(diagnostics(&response), vec![todo, panic]);
+}
+
+#[test]
+fn warning_level() {
+    let io = LanguageServerTestIO::new();
+    let manifest = io.download_dependencies(&io.paths).unwrap();
+    let locker = io
+        .make_locker(&io.paths, crate::build::Target::Erlang)
+        .unwrap();
+    let mut compiler = LspProjectCompiler::new(
+        manifest,
+        PackageConfig::default(),
+        io.paths.clone(),
+        FileSystemProxy::new(io.clone()),
+        locker,
+        vec![],
+        WarningLevel::Custom([WarningKind::Unfinished].into()),
+    )
+    .unwrap();
+
+    _ = io.src_module("app", "import gleam pub fn main() { todo }");
+    assert!(compiler.compile().is_ok());
+    let kinds = compiler
+        .take_warnings()
+        .iter()
+        .map(WarningKind::of)
+        .collect_vec();
+    assert_eq!(kinds, vec![WarningKind::Unfinished]);
+
+    // Features that do not rely on warnings still work.
+    assert!(!compiler.modules["app"]
+        .ast
+        .type_info
+        .unused_imports
+        .is_empty());
+
+    // The level is replaced by the one set by the client.
+    let settings: LspSettings =
+        serde_json::from_value(serde_json::json!({ "warningLevel": "errorsOnly" }))
+            .expect("settings");
+    assert!(compiler.set_settings(settings).expect("settings applied"));
+    assert_eq!(compiler.warning_level(), &WarningLevel::ErrorsOnly);
+    _ = io.src_module("app", "import gleam pub fn main() { todo as \"later\" }");
+    assert!(compiler.compile().is_ok());
+    assert!(compiler.take_warnings().is_empty());
+
+    let settings: LspSettings = serde_json::from_value(serde_json::json!({
+        "warningLevel": { "custom": ["unused", "unfinished"] }
+    }))
+    .expect("settings");
+    assert_eq!(
+        settings.warning_level,
+        WarningLevel::Custom([WarningKind::Unused, WarningKind::Unfinished].into())
+    );
+}
+
+#[test]
+fn dump_and_load_state() {
+    let io = LanguageServerTestIO::new();
+    let mut engine = setup_engine(&io);
+    _ = io.src_module("wibble", "pub fn wobble() { 1 }");
+    _ = io.src_module("app", "import wibble pub fn main() { wibble.wobble() }");
+    assert!(engine.compile_please().result.is_ok());
+    let state = engine.compiler.dump_state();
+
+    // The features of a compiler with the loaded state work without it
+    // compiling anything.
+    let io = LanguageServerTestIO::new();
+    let mut engine = setup_engine(&io);
+    engine.compiler.load_state(state.clone());
+    assert_eq!(engine.compiler.definition_ids("app").len(), 1);
+    assert!(engine.compiler.get_module_inferface("wibble").is_some());
+    assert_eq!(
+        engine.compiler.last_compile_order(),
+        state.compile_order.as_slice()
+    );
+}
+
+#[test]
+fn redundant_patterns() {
+    let io = LanguageServerTestIO::new();
+    let mut engine = setup_engine(&io);
+
+    let code = "pub fn main(x) {
+  case x {
+    Ok(_) -> 1
+    Error(_) -> 2
+    Ok(1) -> 3
+  }
+}
+";
+    _ = io.src_module("app", code);
+    _ = io.src_module("other", "pub fn main() { 1 }");
+    assert!(engine.compile_please().result.is_ok());
+
+    let diagnostics = engine.compiler.redundant_patterns("app");
+    let clauses = diagnostics
+        .iter()
+        .map(|diagnostic| {
+            let span = diagnostic
+                .location
+                .as_ref()
+                .map(|location| location.label.span);
+            (diagnostic.code, span)
+        })
+        .collect_vec();
+    let start = code.find("Ok(1)").unwrap() as u32;
+    assert_eq!(
+        clauses,
+        vec![(
+            Some("unreachable_case_clause"),
+            Some(SrcSpan::new(start, start + 10))
+        )]
+    );
+    assert!(engine.compiler.redundant_patterns("other").is_empty());
+
+    // The clauses are kept while the module is not checked again.
+    _ = io.src_module("other", "pub fn main() { 2 }");
+    assert!(engine.compile_please().result.is_ok());
+    assert_eq!(engine.compiler.redundant_patterns("app").len(), 1);
+
+    _ = io.src_module("app", "pub fn main() { 1 }");
+    assert!(engine.compile_please().result.is_ok());
+    assert!(engine.compiler.redundant_patterns("app").is_empty());
+}
+
+#[test]
+fn pattern_constructor() {
+    let io = LanguageServerTestIO::new();
+    let mut engine = setup_engine(&io);
+
+    let code = "import other.{Wobble as Wubble}
+
+pub fn main(x) {
+  case x {
+    Ok(Wubble) -> 1
+    Ok(other.Wibble(n)) -> n
+    Error(_) -> 0
+  }
+}
+";
+    _ = io.src_module("app", code);
+    _ = io.src_module("other", "pub type Thing { Wibble(Int) Wobble }");
+    assert!(engine.compile_please().result.is_ok());
+
+    let at = |needle: &str| code.find(needle).unwrap() as u32;
+    let constructor = |byte_index| engine.compiler.pattern_constructor("app", byte_index);
+    assert_eq!(
+        constructor(at("Ok(Wubble)")),
+        Some(("gleam".into(), "Ok".into()))
+    );
+    assert_eq!(
+        constructor(at("Wubble)")),
+        Some(("other".into(), "Wobble".into()))
+    );
+    assert_eq!(
+        constructor(at("n))")),
+        Some(("other".into(), "Wibble".into()))
+    );
+    assert_eq!(constructor(at("case")), None);
+}
+
+#[test]
+fn dependencies_were_cached() {
+    let io = LanguageServerTestIO::new();
+    let mut engine = setup_engine(&io);
+    add_path_dep(&mut engine, "dep");
+    _ = io.path_dep_module("dep", "dep/thing", "pub fn main() { 1 }");
+    _ = io.src_module("app", "pub fn main() { 1 }");
+    assert!(!engine.compiler.dependencies_were_cached());
+
+    assert!(engine.compile_please().result.is_ok());
+    assert!(!engine.compiler.dependencies_were_cached());
+
+    _ = io.src_module("app", "pub fn main() { 2 }");
+    assert!(engine.compile_please().result.is_ok());
+    assert!(engine.compiler.dependencies_were_cached());
+
+    _ = io.path_dep_module("dep", "dep/thing", "pub fn main() { 2 }");
+    assert!(engine.compile_please().result.is_ok());
+    assert!(!engine.compiler.dependencies_were_cached());
+}
+
+#[test]
+fn warm_dependencies() {
+    let io = LanguageServerTestIO::new();
+    let mut engine = setup_engine(&io);
+    add_path_dep(&mut engine, "dep");
+    _ = io.path_dep_module("dep", "dep/thing", "pub fn main() { 1 }");
+    _ = io.src_module("app", "import dep/thing pub fn main() { thing.main() }");
+
+    let progress = std::cell::RefCell::new(vec![]);
+    let result = engine
+        .compiler
+        .warm_dependencies(|compiled, total| progress.borrow_mut().push((compiled, total)));
+    assert!(result.is_ok());
+    assert_eq!(progress.into_inner(), vec![(0, 1), (1, 1)]);
+
+    // The dependencies are not compiled again by the first compilation.
+    assert!(engine.compile_please().result.is_ok());
+    assert!(engine.compiler.dependencies_were_cached());
+}
+
+#[test]
+fn last_module_timings() {
+    let io = LanguageServerTestIO::new();
+    let mut engine = setup_engine(&io);
+    _ = io.src_module("app", "import other\npub fn main() { other.main() }");
+    _ = io.src_module("other", "pub fn main() { 1 }");
+
+    // Timings are only recorded at the verbose trace level.
+    assert!(engine.compile_please().result.is_ok());
+    assert!(engine.compiler.last_module_timings().is_empty());
+
+    engine.compiler.set_trace_level(TraceLevel::Verbose);
+    _ = io.src_module("app", "import other\npub fn main() { other.main() + 1 }");
+    assert!(engine.compile_please().result.is_ok());
+    let timings = engine.compiler.last_module_timings();
+    assert_eq!(timings.keys().collect::<Vec<_>>(), vec!["app"]);
+}
+
+#[test]
+fn trace_level_is_set_for_every_project() {
+    let io = LanguageServerTestIO::new();
+    _ = io.write(
+        &io.paths.root_config(),
+        "name = \"app\"\nversion = \"1.0.0\"",
+    );
+    _ = io.src_module("app", "pub fn main() { 1 }");
+    let path = io.paths.src_directory().join("app.gleam");
+    let mut router = Router::new(io.clone(), FileSystemProxy::new(io.clone()));
+
+    // The level set by `$/setTrace` is used by projects opened after it.
+    router.set_trace_level(lsp_types::TraceValue::Verbose.into());
+    let project = router.project_for_path(&path).unwrap().unwrap();
+    assert_eq!(project.engine.compiler.trace_level(), TraceLevel::Verbose);
+    assert!(project.engine.compile_please().result.is_ok());
+    let timings = project.engine.compiler.last_module_timings();
+    assert_eq!(timings.keys().collect_vec(), vec!["app"]);
+
+    // Turning tracing off applies to the projects that are already open.
+    router.set_trace_level(lsp_types::TraceValue::Off.into());
+    let project = router.project_for_path(&path).unwrap().unwrap();
+    assert_eq!(project.engine.compiler.trace_level(), TraceLevel::Off);
+    _ = io.src_module("app", "pub fn main() { 2 }");
+    assert!(project.engine.compile_please().result.is_ok());
+    assert!(project.engine.compiler.last_module_timings().is_empty());
+}
+
+#[test]
+fn recompile_dependents() {
+    let io = LanguageServerTestIO::new();
+    let mut engine = setup_engine(&io);
+    _ = io.src_module("other", "pub fn value() { 1 }");
+    _ = io.src_module("app", "import other\npub fn main() { other.value() }");
+    _ = io.src_module("third", "import app\npub fn go() { app.main() + 1 }");
+    _ = io.src_module("unrelated", "pub fn main() { 1 }");
+    assert!(engine.compile_please().result.is_ok());
+
+    _ = io.src_module("other", "pub fn value() { \"a\" }");
+    let results = engine.compiler.recompile_dependents(&"other".into());
+    let results = results
+        .iter()
+        .map(|(module, diagnostics)| {
+            let titles = diagnostics.iter().map(|d| d.title.clone()).collect_vec();
+            (module.as_str(), titles)
+        })
+        .collect_vec();
+    assert_eq!(
+        results,
+        vec![
+            ("app", vec![]),
+            ("third", vec!["Type mismatch".to_string()])
+        ]
+    );
+
+    // Nothing is kept until the next compilation.
+    let interface = engine.compiler.get_module_inferface("other").unwrap();
+    let return_type = interface.values["value"].type_.return_type().unwrap();
+    assert!(return_type.is_int());
+}
+
+#[test]
+fn check_active_module() {
+    let io = LanguageServerTestIO::new();
+    let mut engine = setup_engine(&io);
+    _ = io.src_module("other", "pub fn value() { 1 }");
+    _ = io.src_module("app", "import other\npub fn main() { other.value() }");
+    assert!(engine.compile_please().result.is_ok());
+
+    let mut titles = |source: &str| {
+        let diagnostics = engine.compiler.check_active_module("app", source);
+        diagnostics.into_iter().map(|d| d.title).collect_vec()
+    };
+    assert!(titles("import other\npub fn main() { other.value() + 1 }").is_empty());
+    assert_eq!(
+        titles("import other\npub fn main() { other.value() <> \"a\" }"),
+        vec!["Type mismatch"]
+    );
+    assert_eq!(titles("pub fn main() { 1 + }"), vec!["Syntax error"]);
+    assert_eq!(
+        titles("import other\npub fn main() { 1 }"),
+        vec!["Unused imported module"]
+    );
+
+    // Nothing is kept until the next compilation.
+    let module = engine.compiler.modules.get("app").unwrap();
+    assert!(module.code.contains("other.value() }"));
+}
+
+#[test]
+fn identifier_at() {
+    let io = LanguageServerTestIO::new();
+    let mut engine = setup_engine(&io);
+
+    let code = "import other
+
+// A comment about main
+pub fn main(x) {
+  other.value() +. x |> other.Wibble
+}
+";
+    _ = io.src_module("app", code);
+    _ = io.src_module(
+        "other",
+        "pub type W { Wibble(Float) }\npub fn value() { 1.0 }",
+    );
+    assert!(engine.compile_please().result.is_ok());
+
+    let at = |needle: &str| code.find(needle).unwrap() as u32;
+    let identifier = |byte_index| {
+        engine
+            .compiler
+            .identifier_at("app", byte_index)
+            .map(|(name, span)| (name.to_string(), span.start, span.end))
+    };
+    let value = at("value");
+    assert_eq!(
+        identifier(value + 2),
+        Some(("value".into(), value, value + 5))
+    );
+    // The end of a name is within it.
+    assert_eq!(
+        identifier(value + 5),
+        Some(("value".into(), value, value + 5))
+    );
+    assert_eq!(
+        identifier(at("other.value")),
+        Some(("other".into(), at("other.value"), at("other.value") + 5))
+    );
+    assert_eq!(
+        identifier(at("+.")),
+        Some(("+.".into(), at("+."), at("+.") + 2))
+    );
+    assert_eq!(
+        identifier(at("|>") + 1),
+        Some(("|>".into(), at("|>"), at("|>") + 2))
+    );
+    assert_eq!(
+        identifier(at("Wibble")),
+        Some(("Wibble".into(), at("Wibble"), at("Wibble") + 6))
+    );
+    assert_eq!(identifier(at("fn main") + 1), None);
+    assert_eq!(identifier(at("about")), None);
+    assert_eq!(identifier(at("  other") + 1), None);
+}
+
+#[test]
+fn module_export_names() {
+    let io = LanguageServerTestIO::new();
+    let mut engine = setup_engine(&io);
+    _ = io.src_module(
+        "other",
+        "pub type Wibble { Wibble }\ntype Wobble\npub fn main() { 1 }\nfn hidden() { 1 }",
+    );
+    assert!(engine.compile_please().result.is_ok());
+
+    let names = |engine: &LanguageServerEngine<_, _>| engine.compiler.module_export_names("other");
+    assert_eq!(names(&engine), Some(vec!["Wibble".into(), "main".into()]));
+    assert_eq!(engine.compiler.module_export_names("missing"), None);
+
+    // The cached names are replaced once the module is compiled again.
+    _ = io.src_module("other", "pub const wobble = 1");
+    assert!(engine.compile_please().result.is_ok());
+    assert_eq!(names(&engine), Some(vec!["wobble".into()]));
+}
+
+#[test]
+fn compile_generation() {
+    let io = LanguageServerTestIO::new();
+    let mut engine = setup_engine(&io);
+    _ = io.src_module("app", "pub fn main() { 1 }");
+    let start = engine.compiler.compile_generation();
+
+    assert!(engine.compile_please().result.is_ok());
+    let compiled = engine.compiler.compile_generation();
+    assert!(compiled > start);
+    // Reading the modules does not change the generation.
+    _ = engine.compiler.module_export_names("app");
+    assert_eq!(engine.compiler.compile_generation(), compiled);
+
+    _ = io.src_module("app", "pub fn main() { 1 + }");
+    assert!(engine.compile_please().result.is_err());
+    assert!(engine.compiler.compile_generation() > compiled);
+}
+
+#[test]
+fn last_edit_range() {
+    let io = LanguageServerTestIO::new();
+    let mut engine = setup_engine(&io);
+    let path = io.src_module("app", "pub fn main() { 1 }");
+    assert!(engine.compile_please().result.is_ok());
+    assert_eq!(engine.compiler.last_edit_range("app"), None);
+
+    let mut edit = |content: &str| {
+        engine
+            .compiler
+            .project_compiler
+            .io
+            .write_mem_cache(&path, content)
+            .expect("edited");
+        engine.compiler.last_edit_range("app")
+    };
+    // `+ 2 ` was added.
+    assert_eq!(edit("pub fn main() { 1 + 2 }"), Some(SrcSpan::new(18, 22)));
+    // `1 + ` was removed.
+    assert_eq!(edit("pub fn main() { 2 }"), Some(SrcSpan::new(16, 16)));
+    assert_eq!(edit("pub fn main() { 3 }"), Some(SrcSpan::new(16, 17)));
+    assert_eq!(edit("pub fn main() { 3 }"), Some(SrcSpan::new(19, 19)));
+    assert_eq!(engine.compiler.last_edit_range("missing"), None);
+
+    // Closing the file discards its edits.
+    engine
+        .compiler
+        .project_compiler
+        .io
+        .delete_mem_cache(&path)
+        .expect("closed");
+    assert_eq!(engine.compiler.last_edit_range("app"), None);
+}
+
+#[test]
+fn reexport_paths() {
+    let io = LanguageServerTestIO::new();
+    let mut engine = setup_engine(&io);
+    _ = io.src_module(
+        "wibble/dict",
+        "pub type Dict(k, v) { Dict }
+pub fn new() -> Dict(k, v) { Dict }
+pub const empty = Dict",
+    );
+    _ = io.src_module(
+        "wibble",
+        "import wibble/dict
+pub type Dict(k, v) = dict.Dict(k, v)
+pub const new_dict = dict.new
+const private_new = dict.new",
+    );
+    _ = io.src_module(
+        "wobble",
+        "import wibble
+pub const new = wibble.new_dict",
+    );
+    _ = io.src_module("app", "import wibble/dict.{new}\npub fn main() { new() }");
+    assert!(engine.compile_please().result.is_ok());
+
+    let paths = |module, name| engine.compiler.reexport_paths(module, name);
+    // A re-export of a re-export is found, whatever its name.
+    assert_eq!(
+        paths("wibble/dict", "new"),
+        vec!["wibble/dict", "wibble", "wobble"]
+    );
+    // The type is re-exported with an alias, but not its constructor.
+    assert_eq!(paths("wibble/dict", "Dict"), vec!["wibble/dict", "wibble"]);
+    assert_eq!(paths("wibble/dict", "empty"), vec!["wibble/dict"]);
+    assert_eq!(paths("wibble", "new_dict"), vec!["wibble", "wobble"]);
+    assert!(paths("wibble", "private_new").is_empty());
+    assert!(paths("wibble/dict", "missing").is_empty());
+}
+
+#[test]
+fn incompatible_gleam_version() {
+    let io = LanguageServerTestIO::new();
+    let mut engine = setup_engine(&io);
+    _ = io.src_module("app", "pub fn main() { 1 }");
+
+    // As with `gleam build`, a project that requires another version of Gleam
+    // is reported as such rather than with errors for the language features
+    // it uses.
+    engine.compiler.project_compiler.config.gleam_version = Some("< 0.1.0".into());
+    let response = engine.compile_please();
+    assert!(matches!(
+        response.result,
+        Err(Error::IncompatibleCompilerVersion { .. })
+    ));
+}
+
+#[test]
+fn anonymous_functions() {
+    let io = LanguageServerTestIO::new();
+    let mut engine = setup_engine(&io);
+
+    let code = "fn add(x, y) { x + y }
+
+fn apply(f: fn(Int) -> a) -> a { f(1) }
+
+pub fn main() {
+  let double = fn(x) { x * 2 }
+  let add_one = add(_, 1)
+  use x <- apply
+  apply(fn(y) { fn() { #(y, x) } })
+}
+";
+    _ = io.src_module("app", code);
+    assert!(engine.compile_please().result.is_ok());
+
+    let functions = engine
+        .compiler
+        .anonymous_functions("app")
+        .into_iter()
+        .map(|(location, type_)| (&code[location.start as usize..location.end as usize], type_))
+        .collect_vec();
+    assert_eq!(
+        functions,
+        vec![
+            ("fn(x) { x * 2 }", "fn(Int) -> Int".to_string()),
+            (
+                "fn(y) { fn() { #(y, x) } }",
+                "fn(Int) -> fn() -> #(Int, Int)".to_string()
+            ),
+            ("fn() { #(y, x) }", "fn() -> #(Int, Int)".to_string()),
+        ]
+    );
+    assert!(engine.compiler.anonymous_functions("missing").is_empty());
+}
+
+#[test]
+fn captured_variables() {
+    let io = LanguageServerTestIO::new();
+    let mut engine = setup_engine(&io);
+
+    let code = "const offset = 1
+
+fn add(x, y) { x + y }
+
+pub fn main(items: List(a), limit) {
+  let total = add(limit, offset)
+  let pair = #(items, total)
+  case total {
+    n if n > limit -> pair
+    n -> #(items, total + n)
+  }
+}
+";
+    _ = io.src_module("app", code);
+    assert!(engine.compile_please().result.is_ok());
+
+    let captured = |from: &str, to: &str| {
+        let start = code.find(from).expect("start") as u32;
+        let end = code.find(to).expect("end") as u32 + to.len() as u32;
+        engine
+            .compiler
+            .captured_variables("app", SrcSpan::new(start, end))
+            .into_iter()
+            .map(|(name, type_)| (name.to_string(), type_))
+            .collect_vec()
+    };
+    let variable = |name: &str, type_: &str| (name.to_string(), type_.to_string());
+
+    // Module functions and constants are not captured.
+    assert_eq!(
+        captured("add(limit", "offset)"),
+        vec![variable("limit", "Int")]
+    );
+    assert_eq!(
+        captured("let pair", "#(items, total)"),
+        vec![variable("items", "List(a)"), variable("total", "Int")]
+    );
+    // Variables defined within the span are not captured, including those
+    // used in guards.
+    assert_eq!(
+        captured("case total", "total + n)"),
+        vec![
+            variable("total", "Int"),
+            variable("limit", "Int"),
+            variable("pair", "#(List(a), Int)"),
+            variable("items", "List(a)"),
+        ]
+    );
+    assert_eq!(
+        captured("n > limit", "-> pair"),
+        vec![
+            variable("n", "Int"),
+            variable("limit", "Int"),
+            variable("pair", "#(List(a), Int)")
+        ]
+    );
+    assert!(engine
+        .compiler
+        .captured_variables("missing", SrcSpan::new(0, 1))
+        .is_empty());
+}
+
+#[test]
+fn argument_type() {
+    let io = LanguageServerTestIO::new();
+    let mut engine = setup_engine(&io);
+    _ = io.src_module(
+        "app",
+        "pub type Pair(a) { Pair(first: a, second: Int) }
+pub fn map(over list: List(a), with f: fn(a) -> b) -> List(b) { todo }
+const zero = 0
+",
+    );
+    assert!(engine.compile_please().result.is_ok());
+
+    let argument_type = |func, index| engine.compiler.argument_type("app", func, index);
+    assert_eq!(argument_type("map", 0), Some("List(a)".into()));
+    assert_eq!(argument_type("map", 1), Some("fn(a) -> b".into()));
+    assert_eq!(argument_type("map", 2), None);
+    assert_eq!(argument_type("Pair", 1), Some("Int".into()));
+    assert_eq!(argument_type("zero", 0), None);
+    assert_eq!(argument_type("missing", 0), None);
+}
+
+#[test]
+fn isolated_build_directory() {
+    let io = LanguageServerTestIO::new();
+    let mut engine = LanguageServerEngine::new(
+        PackageConfig::default(),
+        io.clone(),
+        FileSystemProxy::new(io.clone()),
+        io.paths.clone(),
+        Some(Utf8PathBuf::from("/isolated")),
+    )
+    .unwrap();
+    _ = io.src_module("app", "pub fn main() { 1 }");
+    assert!(engine.compile_please().result.is_ok());
+
+    let name = &engine.compiler.project_compiler.config.name;
+    let build = Utf8PathBuf::from("/isolated/erlang").join(name.as_str());
+    assert!(io.is_directory(&build));
+    assert!(!io.is_directory(&io.paths.build_directory_for_mode(Mode::Lsp)));
+}
+
+#[test]
+fn unreachable_code() {
+    let io = LanguageServerTestIO::new();
+    let mut engine = setup_engine(&io);
+
+    let code = "pub fn main() {
+  let x = 1
+  panic as \"stop\"
+  let y = x + 1
+  y
+}
+
+pub fn nested(x) {
+  let f = fn() {
+    todo
+    x
+  }
+  let y = {
+    let z = x
+    z
+  }
+  case x {
+    _ -> #(f, y)
+  }
+}
+
+pub fn all_clauses(x) {
+  case x {
+    1 -> panic
+    _ -> todo
+  }
+  { panic 1 }
+}
+
+pub fn short_circuit(x) {
+  x || panic
+  x
+}
+";
+    _ = io.src_module("app", code);
+    assert!(engine.compile_please().result.is_ok());
+
+    let diagnostics = engine.compiler.unreachable_code("app");
+    let unreachable = diagnostics
+        .iter()
+        .map(|diagnostic| {
+            assert_eq!(diagnostic.code, Some("unreachable_code"));
+            let span = diagnostic.location.as_ref().unwrap().label.span;
+            &code[span.start as usize..span.end as usize]
+        })
+        .collect_vec();
+    assert_eq!(unreachable, vec!["let y = x + 1\n  y", "x", "{ panic 1 }"]);
+    assert!(engine.compiler.unreachable_code("missing").is_empty());
+}
+
+#[test]
+fn selection_type() {
+    let io = LanguageServerTestIO::new();
+    let mut engine = setup_engine(&io);
+
+    _ = io.src_module("numbers", "pub fn double(x) { x * 2 }");
+    let code = "import numbers
+
+pub fn main() {
+  let x = numbers.double(1) + 2
+  #(x, [1.0])
+}
+";
+    _ = io.src_module("app", code);
+    assert!(engine.compile_please().result.is_ok());
+
+    let selection_type = |selected: &str| {
+        let start = code.find(selected).unwrap() as u32;
+        let range = SrcSpan::new(start, start + selected.len() as u32);
+        engine.compiler.selection_type("app", range)
+    };
+    assert_eq!(
+        selection_type("numbers.double(1) + 2"),
+        Some("Int".to_string())
+    );
+    assert_eq!(
+        selection_type("numbers.double"),
+        Some("fn(Int) -> Int".to_string())
+    );
+    assert_eq!(
+        selection_type("#(x, [1.0])"),
+        Some("#(Int, List(Float))".to_string())
+    );
+    assert_eq!(selection_type("[1.0]"), Some("List(Float)".to_string()));
+    assert_eq!(selection_type("numbers.double(1) +"), None);
+    assert_eq!(selection_type("x = numbers.double(1) + 2"), None);
+    assert_eq!(selection_type(" #(x, [1.0])"), None);
+}
+
+#[test]
+fn type_parameters() {
+    let io = LanguageServerTestIO::new();
+    let mut engine = setup_engine(&io);
+
+    add_path_dep(&mut engine, "dep");
+    _ = io.path_dep_module(
+        "dep",
+        "dep/pairs",
+        "pub type Pair(left, right) { Pair(left: left, right: right) }
+pub fn swap(pair: Pair(x, y)) -> Pair(y, x) { Pair(pair.right, pair.left) }",
+    );
+    _ = io.src_module(
+        "app",
+        "pub type Box(inner) { Box(inner) }
+pub type Boxes(item) = List(Box(item))
+pub fn map(box: Box(element), f: fn(element) -> new) -> Box(new) {
+  let Box(inner) = box
+  Box(f(inner))
+}
+pub fn main() { 1 }",
+    );
+    assert!(engine.compile_please().result.is_ok());
+
+    let compiler = &engine.compiler;
+    assert_eq!(compiler.type_parameters("app", "Box"), vec!["inner"]);
+    assert_eq!(compiler.type_parameters("app", "Boxes"), vec!["item"]);
+    assert_eq!(compiler.type_parameters("app", "map"), vec!["a", "b"]);
+    assert!(compiler.type_parameters("app", "main").is_empty());
+    assert_eq!(
+        compiler.type_parameters("dep/pairs", "Pair"),
+        vec!["a", "b"]
+    );
+    assert_eq!(
+        compiler.type_parameters("dep/pairs", "swap"),
+        vec!["a", "b"]
+    );
+    assert!(compiler.type_parameters("app", "missing").is_empty());
+    assert!(compiler.type_parameters("missing", "Box").is_empty());
+}
+
+#[test]
+fn qualified_name_at() {
+    let io = LanguageServerTestIO::new();
+    let mut engine = setup_engine(&io);
+
+    _ = io.src_module(
+        "wibble/things",
+        "pub type Thing { Thing(Int) }
+pub const limit = 10
+pub fn map(x) { x }",
+    );
+    let code = "import wibble/things.{Thing, limit as max, map as apply}
+
+pub fn main() {
+  let local = apply(max) + things.limit
+  case Thing(local) {
+    Thing(_) -> things.map(local)
+  }
+}
+";
+    _ = io.src_module("app", code);
+    assert!(engine.compile_please().result.is_ok());
+
+    let qualified_name = |text: &str, nth: usize| {
+        let (start, _) = code.match_indices(text).nth(nth).unwrap();
+        engine.compiler.qualified_name_at("app", start as u32 + 1)
+    };
+    let name = |name: &str| Some(name.to_string());
+    assert_eq!(qualified_name("apply(", 0), name("wibble/things.map"));
+    assert_eq!(qualified_name("max)", 0), name("wibble/things.limit"));
+    assert_eq!(qualified_name("limit\n", 0), name("wibble/things.limit"));
+    assert_eq!(
+        qualified_name("Thing(local)", 0),
+        name("wibble/things.Thing")
+    );
+    assert_eq!(qualified_name("Thing(_)", 0), name("wibble/things.Thing"));
+    assert_eq!(qualified_name("map(local)", 0), name("wibble/things.map"));
+    assert_eq!(qualified_name("main", 0), name("app.main"));
+    assert_eq!(qualified_name("local)", 0), None);
+    assert_eq!(engine.compiler.qualified_name_at("missing", 0), None);
+}
+
+#[test]
+fn documentation_at() {
+    let io = LanguageServerTestIO::new();
+    let mut engine = setup_engine(&io);
+
+    _ = io.src_module(
+        "wibble",
+        "//// Things for wibbling.
+
+/// Doubles the number.
+pub fn double(x) { x * 2 }",
+    );
+    let code = "import wibble.{double as twice}
+
+/// Runs the program.
+pub fn main() {
+  let x = twice(1)
+  wibble.double(x)
+}
+";
+    _ = io.src_module("app", code);
+    assert!(engine.compile_please().result.is_ok());
+
+    let documentation = |text: &str| {
+        let start = code.find(text).unwrap() as u32;
+        engine.compiler.documentation_at("app", start + 1)
+    };
+    let docs = |docs: &str| Some(docs.to_string());
+    assert_eq!(documentation("twice(1)"), docs(" Doubles the number.\n"));
+    assert_eq!(documentation("double(x)"), docs(" Doubles the number.\n"));
+    assert_eq!(documentation("main"), docs(" Runs the program.\n"));
+    assert_eq!(documentation("wibble."), docs(" Things for wibbling."));
+    assert_eq!(documentation("x)"), None);
+    assert_eq!(documentation("double as"), None);
+}
+
+#[test]
+fn make_public_fix() {
+    let io = LanguageServerTestIO::new();
+    let mut engine = setup_engine(&io);
+    add_path_dep(&mut engine, "dep");
+    _ = io.path_dep_module("dep", "dep/hidden", "fn secret() { 1 }");
+    let other = "fn secret() { 1 }
+
+const limit = 2
+
+type Box {
+  Box(Int)
+}
+";
+    _ = io.src_module("other", other);
+    _ = io.src_module("app", "pub fn main() { 1 }");
+    assert!(engine.compile_please().result.is_ok());
+
+    let mut fix = |code: &str, cursor: &str| {
+        _ = io.src_module("app", code);
+        assert!(engine.compile_please().result.is_err());
+        let byte_index = code.find(cursor).unwrap() as u32 + 1;
+        engine
+            .compiler
+            .make_public_fix("app", byte_index)
+            .map(|(module, edit)| (module, edit.range.start.line, edit.new_text))
+    };
+    let at_line = |line| Some((EcoString::from("other"), line, "pub ".to_string()));
+
+    let code = "import other\npub fn main() { other.secret() }";
+    assert_eq!(fix(code, "secret"), at_line(0));
+    assert_eq!(fix(code, "main"), None);
+    let code = "import other.{limit}\npub fn main() { limit }";
+    assert_eq!(fix(code, "limit}"), at_line(2));
+    let code = "import other.{type Box}\npub fn main(box: Box) { box }";
+    assert_eq!(fix(code, "Box"), at_line(4));
+    let code = "import other\npub fn main() { other.Box(1) }";
+    assert_eq!(fix(code, "Box"), at_line(4));
+
+    // Dependencies cannot be changed.
+    let code = "import dep/hidden\npub fn main() { hidden.secret() }";
+    assert_eq!(fix(code, "secret"), None);
+}
+
+#[test]
+fn implement_for_target_fix() {
+    let io = LanguageServerTestIO::new();
+    let mut engine = setup_engine(&io);
+    let code = r#"pub fn main() { 1 }
+
+@external(javascript, "./ffi.mjs", "now")
+fn now() -> Int
+"#;
+    _ = io.src_module("app/clock", code);
+    assert!(engine.compile_please().result.is_ok());
+
+    let fix = |compiler: &LspProjectCompiler<_>, code: &str, cursor: &str| {
+        let byte_index = code.find(cursor).unwrap() as u32 + 1;
+        compiler
+            .implement_for_target_fix("app/clock", byte_index)
+            .map(|edit| (edit.range.start.line, edit.new_text))
+    };
+    let external = |line, name| {
+        Some((
+            line,
+            format!("@external(erlang, \"app@clock_ffi\", \"{name}\")\n"),
+        ))
+    };
+    assert_eq!(fix(&engine.compiler, code, "now() ->"), external(3, "now"));
+    assert_eq!(fix(&engine.compiler, code, "main"), None);
+
+    // A function using one without an implementation for the target stops
+    // the module compiling.
+    let code = r#"pub fn main() { now() }
+
+@external(javascript, "./ffi.mjs", "now")
+fn now() -> Int
+"#;
+    _ = io.src_module("app/clock", code);
+    assert!(engine.compile_please().result.is_err());
+    assert_eq!(fix(&engine.compiler, code, "main"), external(0, "main"));
+    assert_eq!(fix(&engine.compiler, code, "now() ->"), None);
+}
+
+#[test]
+fn add_missing_argument_fix() {
+    let io = LanguageServerTestIO::new();
+    let mut engine = setup_engine(&io);
+    _ = io.src_module(
+        "shape",
+        "pub type Shape { Rect(width: Int, height: Int) }
+pub fn move(shape: Shape, x: Int, by dx: Int, and dy: Int) { shape }",
+    );
+    _ = io.src_module("app", "pub fn main() { 1 }");
+    assert!(engine.compile_please().result.is_ok());
+
+    let mut fix = |code: &str| {
+        _ = io.src_module("app", code);
+        assert!(engine.compile_please().result.is_err());
+        let byte_index = code.rfind('(').unwrap_or(0) as u32;
+        let edit = engine
+            .compiler
+            .add_missing_argument_fix("app", byte_index)?;
+        let line_numbers = LineNumbers::new(code);
+        let start = line_numbers.byte_index(edit.range.start.line, edit.range.start.character);
+        let end = line_numbers.byte_index(edit.range.end.line, edit.range.end.character);
+        let mut fixed = code.to_string();
+        fixed.replace_range(start as usize..end as usize, &edit.new_text);
+        Some(fixed)
+    };
+
+    assert_eq!(
+        fix("import shape\npub fn main() { shape.Rect() }").as_deref(),
+        Some("import shape\npub fn main() { shape.Rect(width: todo, height: todo) }")
+    );
+    assert_eq!(
+        fix("import shape.{Rect}\npub fn main() { Rect(height: 1) }").as_deref(),
+        Some("import shape.{Rect}\npub fn main() { Rect(height: 1, width: todo) }")
+    );
+    assert_eq!(
+        fix("import shape\npub fn main(s) { shape.move(s, and: 2) }").as_deref(),
+        Some("import shape\npub fn main(s) { shape.move(s, todo, and: 2, by: todo) }")
+    );
+    // Arguments given positionally count for their labels.
+    assert_eq!(
+        fix("import shape\npub fn main(s) { shape.move(s, 1, 2) }").as_deref(),
+        Some("import shape\npub fn main(s) { shape.move(s, 1, 2, and: todo) }")
+    );
+    // A pipe gives the first argument.
+    assert_eq!(
+        fix("import shape\npub fn main(s) { s |> shape.move(1) }").as_deref(),
+        Some("import shape\npub fn main(s) { s |> shape.move(1, by: todo, and: todo) }")
+    );
+}
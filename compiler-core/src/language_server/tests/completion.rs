@@ -1,7 +1,11 @@
 use itertools::Itertools;
 use lsp_types::{
-    CompletionItem, CompletionItemKind, Documentation, MarkupContent, MarkupKind, Position,
-    TextDocumentIdentifier, TextDocumentPositionParams, Url,
+    CompletionItem, CompletionItemKind, Documentation, InsertTextFormat, MarkupContent, MarkupKind,
+    Position, Range, TextDocumentIdentifier, TextDocumentPositionParams, TextEdit, Url,
+};
+
+use crate::language_server::{
+    completion::import_edit, CompletionContext, CompletionTier, LspSettings,
 };
 
 use super::*;
@@ -36,14 +40,23 @@ fn positioned_expression_completions(
 
     let url = Url::from_file_path(path).unwrap();
 
-    let response = engine.completion(TextDocumentPositionParams::new(
-        TextDocumentIdentifier::new(url),
-        position,
-    ));
+    let response = engine.completion(
+        TextDocumentPositionParams::new(TextDocumentIdentifier::new(url), position),
+        false,
+    );
 
     let mut completions = response.result.unwrap().unwrap_or_default();
     completions.sort_by(|a, b| a.label.cmp(&b.label));
-    completions
+    completions.into_iter().map(without_ranking).collect()
+}
+
+/// The ranking of completions is tested separately, so most tests ignore it.
+fn without_ranking(completion: CompletionItem) -> CompletionItem {
+    CompletionItem {
+        sort_text: None,
+        data: None,
+        ..completion
+    }
 }
 
 fn prelude_type_completions() -> Vec<CompletionItem> {
@@ -603,7 +616,7 @@ pub type Wibble = Result(
             vec![CompletionItem {
                 label: "Wibble".into(),
                 kind: Some(CompletionItemKind::CLASS),
-                detail: Some("Type".into()),
+                detail: Some("Type alias".into()),
                 documentation: None,
                 ..Default::default()
             },]
@@ -650,7 +663,7 @@ pub fn wibble(
             vec![CompletionItem {
                 label: "dep.Zoo".into(),
                 kind: Some(CompletionItemKind::CLASS),
-                detail: Some("Type".into()),
+                detail: Some("Type alias".into()),
                 documentation: None,
                 ..Default::default()
             },]
@@ -682,14 +695,14 @@ pub fn wibble(
                 CompletionItem {
                     label: "Zoo".into(),
                     kind: Some(CompletionItemKind::CLASS),
-                    detail: Some("Type".into()),
+                    detail: Some("Type alias".into()),
                     documentation: None,
                     ..Default::default()
                 },
                 CompletionItem {
                     label: "dep.Zoo".into(),
                     kind: Some(CompletionItemKind::CLASS),
-                    detail: Some("Type".into()),
+                    detail: Some("Type alias".into()),
                     documentation: None,
                     ..Default::default()
                 },
@@ -718,7 +731,7 @@ pub fn wibble(
             vec![CompletionItem {
                 label: "Zoo".into(),
                 kind: Some(CompletionItemKind::CLASS),
-                detail: Some("Type".into()),
+                detail: Some("Type alias".into()),
                 documentation: None,
                 ..Default::default()
             }],
@@ -726,3 +739,717 @@ pub fn wibble(
         .concat()
     );
 }
+
+#[test]
+fn type_completions_in_let_annotation() {
+    let io = LanguageServerTestIO::new();
+    let mut engine = setup_engine(&io);
+    add_path_dep(&mut engine, "dep");
+
+    _ = io.path_dep_module(
+        "dep",
+        "shapes",
+        "pub type Shape {\n  Square\n}\npub type Shapes = List(Shape)\npub fn area() { 1 }",
+    );
+    _ = io.src_module("app", "pub fn main() {\n  let x: Int = 1\n  x\n}\n");
+    assert!(engine.compile_please().result.is_ok());
+    _ = io.src_module("app", "pub fn main() {\n  let x: Sh = 1\n  x\n}\n");
+
+    let url = Url::from_file_path(io.paths.src_directory().join("app.gleam")).unwrap();
+    let response = engine.completion(
+        TextDocumentPositionParams::new(TextDocumentIdentifier::new(url), Position::new(1, 11)),
+        false,
+    );
+    let completions = response.result.unwrap().unwrap_or_default();
+
+    // Only types are offered, and not the functions or constructors of the
+    // module that is not imported yet.
+    let importable = completions
+        .iter()
+        .filter(|completion| completion.additional_text_edits.is_some())
+        .map(|completion| (completion.label.as_str(), completion.detail.as_deref()))
+        .sorted()
+        .collect_vec();
+    assert_eq!(
+        importable,
+        vec![
+            ("shapes.Shape", Some("Type")),
+            ("shapes.Shapes", Some("Type alias")),
+        ]
+    );
+    assert!(completions
+        .iter()
+        .all(|completion| completion.kind == Some(CompletionItemKind::CLASS)));
+
+    let start = Position::new(0, 0);
+    assert_eq!(
+        completions
+            .iter()
+            .find(|completion| completion.label == "shapes.Shape")
+            .and_then(|completion| completion.additional_text_edits.clone()),
+        Some(vec![TextEdit::new(
+            Range::new(start, start),
+            "import shapes\n".into()
+        )])
+    );
+}
+
+fn dev_dep_completions(module_path: &str, position: Position) -> Vec<CompletionItem> {
+    let io = LanguageServerTestIO::new();
+    let mut engine = setup_engine(&io);
+    add_dev_path_dep(&mut engine, "mydep");
+
+    let code = "import testing
+fn typing_in_here() {
+  0
+}
+";
+    _ = io.path_dep_module("mydep", "testing", "pub fn wobble() { 1 }");
+    _ = io.src_module("app", code);
+    _ = io.test_module("app_test", code);
+    let response = engine.compile_please();
+    assert!(response.result.is_ok());
+
+    let url = Url::from_file_path(Utf8PathBuf::from(module_path)).unwrap();
+    let response = engine.completion(
+        TextDocumentPositionParams::new(TextDocumentIdentifier::new(url), position),
+        false,
+    );
+
+    response
+        .result
+        .unwrap()
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|c| c.label != "typing_in_here")
+        .map(without_ranking)
+        .collect_vec()
+}
+
+#[test]
+fn dev_dependency_module_in_test_module() {
+    let path = if cfg!(target_family = "windows") {
+        r"\\?\C:\test\app_test.gleam"
+    } else {
+        "/test/app_test.gleam"
+    };
+
+    assert_eq!(
+        dev_dep_completions(path, Position::new(2, 0)),
+        vec![CompletionItem {
+            label: "testing.wobble".into(),
+            kind: Some(CompletionItemKind::FUNCTION),
+            detail: Some("fn() -> Int".into()),
+            documentation: None,
+            ..Default::default()
+        }]
+    );
+}
+
+#[test]
+fn dev_dependency_module_in_src_module() {
+    let path = if cfg!(target_family = "windows") {
+        r"\\?\C:\src\app.gleam"
+    } else {
+        "/src/app.gleam"
+    };
+
+    // The dev-dependency is not available in production code, so nothing from
+    // it is suggested.
+    assert_eq!(dev_dep_completions(path, Position::new(2, 0)), vec![]);
+}
+
+#[test]
+fn completions_are_ranked() {
+    let io = LanguageServerTestIO::new();
+    let mut engine = setup_engine(&io);
+
+    _ = io.src_module("dep", "pub fn wibble() { 1 }\npub fn wobble() { 2 }");
+    _ = io.src_module("other", "pub fn wibble() { 3 }");
+    _ = io.src_module(
+        "app",
+        "import dep
+pub fn wibble() { 0 }
+pub fn main() {
+  wibble()
+}
+",
+    );
+    assert!(engine.compile_please().result.is_ok());
+
+    let url = Url::from_file_path(io.paths.src_directory().join("app.gleam")).unwrap();
+    let response = engine.completion(
+        TextDocumentPositionParams::new(TextDocumentIdentifier::new(url), Position::new(3, 5)),
+        false,
+    );
+    let mut completions = response.result.unwrap().unwrap_or_default();
+    completions.sort_by(|a, b| a.sort_text.cmp(&b.sort_text));
+
+    let ranked = completions
+        .iter()
+        .map(|completion| {
+            (
+                completion.label.as_str(),
+                CompletionTier::of(completion).unwrap(),
+            )
+        })
+        .collect_vec();
+    assert_eq!(
+        ranked,
+        vec![
+            ("wibble", CompletionTier::Local),
+            ("main", CompletionTier::Local),
+            ("dep.wibble", CompletionTier::Imported),
+            ("dep.wobble", CompletionTier::Imported),
+            ("other.wibble", CompletionTier::Importable),
+        ]
+    );
+
+    // Completing a value from a module that is not imported also imports it,
+    // after the imports the module already has.
+    let start = Position::new(1, 0);
+    assert_eq!(
+        completions[4].additional_text_edits,
+        Some(vec![TextEdit::new(
+            Range::new(start, start),
+            "import other\n".into()
+        )])
+    );
+}
+
+#[test]
+fn importable_completions_need_a_prefix() {
+    let io = LanguageServerTestIO::new();
+    let mut engine = setup_engine(&io);
+
+    let many = (0..60)
+        .map(|index| format!("pub fn wibble{index}() {{ {index} }}"))
+        .join("\n");
+    _ = io.src_module("dep", &many);
+    _ = io.src_module("other", "pub fn wobble() { 1 }");
+    _ = io.src_module("app", "pub fn main() {\n  0\n}\n");
+    assert!(engine.compile_please().result.is_ok());
+
+    let mut importable = |code: &str| {
+        _ = io.src_module("app", code);
+        let url = Url::from_file_path(io.paths.src_directory().join("app.gleam")).unwrap();
+        let response = engine.completion(
+            TextDocumentPositionParams::new(TextDocumentIdentifier::new(url), Position::new(1, 5)),
+            false,
+        );
+        response
+            .result
+            .unwrap()
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|completion| completion.additional_text_edits.is_some())
+            .map(|completion| completion.label)
+            .collect_vec()
+    };
+
+    assert!(importable("pub fn main() {\n  \n}\n").is_empty());
+    assert_eq!(
+        importable("pub fn main() {\n  wob\n}\n"),
+        vec!["other.wobble"]
+    );
+    assert_eq!(importable("pub fn main() {\n  wib\n}\n").len(), 50);
+}
+
+#[test]
+fn importing_a_module_adds_it_after_the_imports() {
+    let edit = |code: &str| {
+        let edit = import_edit(code, "wibble");
+        (edit.range.start, edit.new_text)
+    };
+
+    assert_eq!(
+        edit("pub fn main() { 0 }\n"),
+        (Position::new(0, 0), "import wibble\n".into())
+    );
+    assert_eq!(
+        edit("//// The app.\n//// More.\n\npub fn main() { 0 }\n"),
+        (Position::new(2, 0), "import wibble\n".into())
+    );
+    assert_eq!(
+        edit("//// The app.\nimport dep\n// Comment\nimport other.{\n  x,\n}\n\npub fn main() { 0 }\n"),
+        (Position::new(6, 0), "import wibble\n".into())
+    );
+    assert_eq!(
+        edit("import dep"),
+        (Position::new(0, 10), "\nimport wibble".into())
+    );
+}
+
+/// The context at the `|` in `edited`, a version of `compiled` that has not
+/// been compiled yet.
+fn completion_context(compiled: &str, edited: &str) -> CompletionContext {
+    let io = LanguageServerTestIO::new();
+    let mut engine = setup_engine(&io);
+
+    _ = io.src_module("dep", "pub fn wibble() { 1 }\npub type Wobble { Wobble }");
+    _ = io.src_module("wobble/io", "pub fn println() { 1 }");
+    _ = io.src_module("app", compiled);
+    assert!(engine.compile_please().result.is_ok());
+
+    let byte_index = edited.find('|').expect("cursor") as u32;
+    _ = io.src_module("app", &edited.replacen('|', "", 1));
+    engine.compiler.completion_context("app", byte_index)
+}
+
+#[test]
+fn completion_context_of_compiled_module() {
+    let code = "import dep
+pub fn main(x: Int) -> Int {
+  let y = x
+  let z: Int = y
+  z
+}
+";
+    let context =
+        |cursor: &str| completion_context(code, &code.replacen(cursor, &format!("|{cursor}"), 1));
+
+    assert_eq!(context("import"), CompletionContext::Import);
+    assert_eq!(context("Int) ->"), CompletionContext::TypeAnnotation);
+    assert_eq!(context("y = x"), CompletionContext::Pattern);
+    assert_eq!(context("x\n"), CompletionContext::Expression);
+    assert_eq!(context("Int = y"), CompletionContext::TypeAnnotation);
+    assert_eq!(context("y\n"), CompletionContext::Expression);
+    assert_eq!(
+        completion_context(code, &format!("{code}|")),
+        CompletionContext::None
+    );
+}
+
+#[test]
+fn completion_context_while_editing() {
+    let code = "import dep\nimport wobble/io as out\n";
+    let context = |edited: &str| completion_context(code, &format!("{code}{edited}"));
+
+    assert_eq!(
+        context("pub fn main() {\n  dep.|"),
+        CompletionContext::QualifiedAccess {
+            module: "dep".into(),
+            in_type_annotation: false
+        }
+    );
+    assert_eq!(
+        context("pub fn main(x: dep.Wo|"),
+        CompletionContext::QualifiedAccess {
+            module: "dep".into(),
+            in_type_annotation: true
+        }
+    );
+    assert_eq!(
+        context("pub fn main() {\n  out.|"),
+        CompletionContext::QualifiedAccess {
+            module: "wobble/io".into(),
+            in_type_annotation: false
+        }
+    );
+    assert_eq!(context("import wobble/|"), CompletionContext::Import);
+    assert_eq!(context("import dep.{ty|"), CompletionContext::Import);
+    assert_eq!(context("pub fn main(x, |"), CompletionContext::Pattern);
+    assert_eq!(
+        context("pub fn main(x: |"),
+        CompletionContext::TypeAnnotation
+    );
+    assert_eq!(
+        context("pub fn main() -> Result(|"),
+        CompletionContext::TypeAnnotation
+    );
+    assert_eq!(
+        context("pub fn main() {\n  |"),
+        CompletionContext::Expression
+    );
+    assert_eq!(
+        context("pub fn main() {\n  let |"),
+        CompletionContext::Pattern
+    );
+    assert_eq!(
+        context("pub fn main() {\n  let x: |"),
+        CompletionContext::TypeAnnotation
+    );
+    assert_eq!(
+        context("pub fn main() {\n  let x = wib|"),
+        CompletionContext::Expression
+    );
+    assert_eq!(
+        context("pub fn main() {\n  f(label: |"),
+        CompletionContext::Expression
+    );
+    assert_eq!(
+        context("pub fn main() {\n  list.map(xs, fn(x: |"),
+        CompletionContext::TypeAnnotation
+    );
+    assert_eq!(
+        context("pub fn main() {\n  case x {\n    Ok(|"),
+        CompletionContext::Pattern
+    );
+    assert_eq!(
+        context("pub fn main() {\n  case x {\n    Ok(y) -> |"),
+        CompletionContext::Expression
+    );
+    assert_eq!(
+        context("pub fn main() {\n  case x {\n    Ok(y) -> y\n    |"),
+        CompletionContext::Pattern
+    );
+    let guard = |variables: &[&str], after_operand| CompletionContext::Guard {
+        variables: variables.iter().map(|name| (*name).into()).collect(),
+        after_operand,
+    };
+    assert_eq!(
+        context("pub fn main() {\n  case x {\n    Ok(#(a, b)) as c if |"),
+        guard(&["a", "b", "c"], false)
+    );
+    assert_eq!(
+        context("pub fn main() {\n  case x {\n    Ok(y) -> y\n    Error(e) if e > 1 && { e < |"),
+        guard(&["e"], false)
+    );
+    assert_eq!(
+        context("pub fn main() {\n  case x {\n    Ok(y) if y |"),
+        guard(&["y"], true)
+    );
+    // The parameters of the function and the variables assigned before the
+    // case expression are in scope too, but not those of the assignment the
+    // case expression is the value of.
+    assert_eq!(
+        context(
+            "pub fn main(a, label b: Int, c: Result(d, e)) {
+  let Wibble(f, g: h) = a
+  let <<i:int-size(8), j:bytes>> = b
+  use k, l <- list.map(a)
+  let m = case x {
+    n if |"
+        ),
+        guard(&["n", "k", "l", "i", "j", "f", "h", "a", "b", "c"], false)
+    );
+    assert_eq!(
+        context("pub fn main(a) {\n  fn(b) {\n  let c = {\n   let d = 1\n   d\n  }\n  case x {\n    _ if |"),
+        guard(&["c", "b", "a"], false)
+    );
+    assert_eq!(
+        context("pub fn main() {\n  case x {\n    Ok(y) if y -> |"),
+        CompletionContext::Expression
+    );
+    assert_eq!(context("pub type Wibble {\n  |"), CompletionContext::None);
+    assert_eq!(
+        context("pub type Wibble {\n  Wibble(label: |"),
+        CompletionContext::TypeAnnotation
+    );
+    assert_eq!(
+        context("pub type Alias = |"),
+        CompletionContext::TypeAnnotation
+    );
+    assert_eq!(context("pub const x: |"), CompletionContext::TypeAnnotation);
+    assert_eq!(context("pub const x = |"), CompletionContext::Expression);
+    assert_eq!(
+        context("pub fn main() {\n  // dep.|"),
+        CompletionContext::None
+    );
+    assert_eq!(
+        context("pub fn main() {\n  \"dep.|"),
+        CompletionContext::None
+    );
+    assert_eq!(context("pub |"), CompletionContext::None);
+}
+
+#[test]
+fn qualified_completions_while_editing() {
+    let io = LanguageServerTestIO::new();
+    let mut engine = setup_engine(&io);
+
+    _ = io.src_module("dep", "pub fn wibble() { 1 }");
+    _ = io.src_module("other", "pub fn wobble() { 2 }");
+    _ = io.src_module("app", "import dep\nimport other\npub fn main() { 0 }\n");
+    assert!(engine.compile_please().result.is_ok());
+
+    // The module does not parse while the qualified name is being typed.
+    _ = io.src_module("app", "import dep\nimport other\npub fn main() { dep. }\n");
+    let url = Url::from_file_path(io.paths.src_directory().join("app.gleam")).unwrap();
+    let response = engine.completion(
+        TextDocumentPositionParams::new(TextDocumentIdentifier::new(url), Position::new(2, 20)),
+        false,
+    );
+
+    let labels = response
+        .result
+        .unwrap()
+        .unwrap_or_default()
+        .into_iter()
+        .map(|completion| completion.label)
+        .collect_vec();
+    assert_eq!(labels, vec!["dep.wibble".to_string()]);
+}
+
+#[test]
+fn completions_in_module_that_no_longer_compiles() {
+    let io = LanguageServerTestIO::new();
+    let mut engine = setup_engine(&io);
+
+    _ = io.src_module("dep", "pub fn wibble() { 1 }");
+    _ = io.src_module("app", "import dep\npub fn main() { 0 }\n");
+    assert!(engine.compile_please().result.is_ok());
+
+    _ = io.src_module("app", "import dep\npub fn main() { dep. }\n");
+    assert!(engine.compile_please().result.is_err());
+    let url = Url::from_file_path(io.paths.src_directory().join("app.gleam")).unwrap();
+    let params =
+        TextDocumentPositionParams::new(TextDocumentIdentifier::new(url), Position::new(1, 20));
+    let labels = engine
+        .completion(params.clone(), false)
+        .result
+        .unwrap()
+        .unwrap_or_default()
+        .into_iter()
+        .map(|completion| completion.label)
+        .collect_vec();
+    assert_eq!(labels, vec!["dep.wibble".to_string()]);
+
+    // As with hover, the last version that compiled is not used if the
+    // settings say so.
+    let settings = LspSettings {
+        use_last_good_modules: false,
+        ..Default::default()
+    };
+    assert!(engine.apply_settings(settings).result.is_ok());
+    assert_eq!(engine.completion(params, false).result.unwrap(), None);
+}
+
+#[test]
+fn guard_completions() {
+    let io = LanguageServerTestIO::new();
+    let mut engine = setup_engine(&io);
+
+    let code = "pub const limit = 10
+pub fn double(x) { x * 2 }
+pub fn main(x) {
+  case x {
+    n if n > 1 -> n
+    _ -> 0
+  }
+}
+";
+    _ = io.src_module("app", code);
+    assert!(engine.compile_please().result.is_ok());
+
+    let url = Url::from_file_path(io.paths.src_directory().join("app.gleam")).unwrap();
+    let mut labels = |position| {
+        let response = engine.completion(
+            TextDocumentPositionParams::new(TextDocumentIdentifier::new(url.clone()), position),
+            true,
+        );
+        response
+            .result
+            .unwrap()
+            .unwrap_or_default()
+            .into_iter()
+            .map(|completion| completion.label)
+            .sorted()
+            .collect_vec()
+    };
+
+    assert_eq!(
+        labels(Position::new(4, 9)),
+        vec!["limit".to_string(), "n".to_string(), "x".to_string()]
+    );
+    // Only operators can follow a variable.
+    assert_eq!(
+        labels(Position::new(4, 11)),
+        ["!=", "&&", "<", "<.", "<=", "<=.", "==", ">", ">.", ">=", ">=.", "||"]
+            .map(String::from)
+            .to_vec()
+    );
+}
+
+#[test]
+fn function_call_snippets() {
+    let io = LanguageServerTestIO::new();
+    let mut engine = setup_engine(&io);
+
+    _ = io.src_module(
+        "dep",
+        "pub type Wibble { Wibble(Int, label: Int) }
+pub fn map(list: List(a), with fun: fn(a) -> b) { todo }
+pub fn ignore(_value: a) { Nil }
+pub const wobble = 1
+",
+    );
+    _ = io.src_module("app", "import dep\npub fn main() {\n  0\n}\n");
+    assert!(engine.compile_please().result.is_ok());
+
+    let url = Url::from_file_path(io.paths.src_directory().join("app.gleam")).unwrap();
+    let response = engine.completion(
+        TextDocumentPositionParams::new(TextDocumentIdentifier::new(url), Position::new(2, 0)),
+        true,
+    );
+
+    let snippets = response
+        .result
+        .unwrap()
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|completion| completion.label.starts_with("dep."))
+        .map(|completion| {
+            let is_snippet = completion.insert_text_format == Some(InsertTextFormat::SNIPPET);
+            (completion.label, completion.insert_text, is_snippet)
+        })
+        .sorted()
+        .collect_vec();
+    assert_eq!(
+        snippets,
+        vec![
+            (
+                "dep.Wibble".into(),
+                Some("dep.Wibble(${1:Int}, label: ${2})".into()),
+                true
+            ),
+            (
+                "dep.ignore".into(),
+                Some("dep.ignore(${1:value})".into()),
+                true
+            ),
+            (
+                "dep.map".into(),
+                Some("dep.map(${1:list}, with: ${2})".into()),
+                true
+            ),
+            ("dep.wobble".into(), None, false),
+        ]
+    );
+}
+
+#[test]
+fn modules_that_would_create_an_import_cycle_are_not_importable() {
+    let io = LanguageServerTestIO::new();
+    let mut engine = setup_engine(&io);
+
+    _ = io.src_module("top", "import middle\npub fn wibble() { middle.wibble() }");
+    _ = io.src_module("middle", "import app\npub fn wibble() { app.main() }");
+    _ = io.src_module("other", "pub fn wibble() { 1 }");
+    _ = io.src_module("app", "pub fn main() {\n  0\n}\n");
+    assert!(engine.compile_please().result.is_ok());
+    _ = io.src_module("app", "pub fn main() {\n  wib\n}\n");
+
+    assert!(engine.compiler.would_create_cycle("app", "top"));
+    assert!(engine.compiler.would_create_cycle("app", "app"));
+    assert!(!engine.compiler.would_create_cycle("app", "other"));
+    assert!(!engine.compiler.would_create_cycle("top", "app"));
+
+    let url = Url::from_file_path(io.paths.src_directory().join("app.gleam")).unwrap();
+    let response = engine.completion(
+        TextDocumentPositionParams::new(TextDocumentIdentifier::new(url), Position::new(1, 5)),
+        false,
+    );
+    let importable = response
+        .result
+        .unwrap()
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|completion| completion.additional_text_edits.is_some())
+        .map(|completion| completion.label)
+        .sorted()
+        .collect_vec();
+    assert_eq!(importable, vec!["other.wibble".to_string()]);
+}
+
+#[test]
+fn completion_context_of_call_arguments() {
+    let code = "import dep\nimport wobble/io as out\n";
+    let context = |edited: &str| completion_context(code, &format!("{code}{edited}"));
+    let call = |function: &str, labels: &[&str]| CompletionContext::CallArgument {
+        function: function.into(),
+        labels: labels.iter().map(|label| (*label).into()).collect(),
+    };
+
+    assert_eq!(context("pub fn main() {\n  f(|"), call("f", &[]));
+    assert_eq!(
+        context("pub fn main() {\n  dep.wibble(1, x: 2, [y], y: z, |"),
+        call("dep.wibble", &["x", "y"])
+    );
+    assert_eq!(
+        context("pub fn main() {\n  Wobble(x: 1, |"),
+        call("Wobble", &["x"])
+    );
+    assert_eq!(context("pub fn main() {\n  f(1, g(|"), call("g", &[]));
+    assert_eq!(
+        context("pub fn main() {\n  f(1 |"),
+        CompletionContext::Expression
+    );
+    assert_eq!(
+        context("pub fn main() {\n  f(1)(|"),
+        CompletionContext::Expression
+    );
+    assert_eq!(
+        context("pub fn main() {\n  case x {\n    Ok(|"),
+        CompletionContext::Pattern
+    );
+}
+
+#[test]
+fn labelled_argument_completions() {
+    let io = LanguageServerTestIO::new();
+    let mut engine = setup_engine(&io);
+
+    _ = io.src_module(
+        "dep",
+        "pub fn wibble(a: Int, x x: Int, y y: String, z z: Float) { todo }",
+    );
+    _ = io.src_module(
+        "app",
+        "import dep.{wibble as wobble}\npub fn local(n n: Int) { n }\npub fn main() { 0 }\n",
+    );
+    assert!(engine.compile_please().result.is_ok());
+
+    let mut labels = |code: &str| {
+        _ = io.src_module("app", code);
+        let url = Url::from_file_path(io.paths.src_directory().join("app.gleam")).unwrap();
+        let line = code.lines().count() as u32 - 1;
+        let character = code.lines().last().unwrap_or_default().len() as u32;
+        let response = engine.completion(
+            TextDocumentPositionParams::new(
+                TextDocumentIdentifier::new(url),
+                Position::new(line, character),
+            ),
+            false,
+        );
+        response
+            .result
+            .unwrap()
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|completion| completion.kind == Some(CompletionItemKind::FIELD))
+            .map(|completion| {
+                (
+                    CompletionTier::of(&completion),
+                    completion.label,
+                    completion.insert_text.unwrap_or_default(),
+                    completion.detail.unwrap_or_default(),
+                )
+            })
+            .collect_vec()
+    };
+
+    let imported = Some(CompletionTier::Imported);
+    assert_eq!(
+        labels("import dep.{wibble as wobble}\npub fn main() {\n  dep.wibble(1, y: \"\", "),
+        vec![
+            (imported, "x:".into(), "x: ".into(), "Int".into()),
+            (imported, "z:".into(), "z: ".into(), "Float".into())
+        ]
+    );
+    // Only the labels of the functions of the module itself are local.
+    assert_eq!(
+        labels("import dep.{wibble as wobble}\npub fn main() {\n  local("),
+        vec![(
+            Some(CompletionTier::Local),
+            "n:".into(),
+            "n: ".into(),
+            "Int".into()
+        )]
+    );
+    assert_eq!(
+        labels("import dep.{wibble as wobble}\npub fn main() {\n  wobble(").len(),
+        3
+    );
+    assert!(labels("import dep.{wibble as wobble}\npub fn main() {\n  wibble(").is_empty());
+}
@@ -0,0 +1,30 @@
+use super::*;
+
+#[test]
+fn node_at() {
+    let io = LanguageServerTestIO::new();
+    let mut engine = setup_engine(&io);
+    let src = "pub fn main(x) {
+  let y = add(x, 1)
+  case y {
+    0 -> y
+    _ -> 1
+  }
+}
+
+fn add(a, b) { a + b }
+";
+    _ = io.src_module("app", src);
+    assert!(engine.compile_please().result.is_ok());
+
+    let node_at = |at: &str| {
+        let (byte_index, _) = src.match_indices(at).next().expect("position");
+        engine.compiler.node_at("app", byte_index as u32)
+    };
+    assert_eq!(node_at("add("), Some("Var".into()));
+    assert_eq!(node_at(", 1"), Some("Call".into()));
+    assert_eq!(node_at("let"), Some("Let".into()));
+    assert_eq!(node_at("0 ->"), Some("Case clause pattern (Int)".into()));
+    assert_eq!(node_at("a + b"), Some("Var".into()));
+    assert_eq!(node_at("fn add"), Some("Function".into()));
+}
@@ -0,0 +1,334 @@
+use lsp_types::{
+    GotoDefinitionParams, GotoDefinitionResponse, Location, Position, Range,
+    TextDocumentIdentifier, TextDocumentPositionParams, Url,
+};
+
+use crate::language_server::{settings::LspSettings, ExternalTarget};
+
+use super::*;
+
+fn definition(src: &str, position: Position) -> Option<Range> {
+    let io = LanguageServerTestIO::new();
+    let mut engine = setup_engine(&io);
+
+    _ = io.src_module("app", src);
+    let response = engine.compile_please();
+    assert!(response.result.is_ok());
+
+    let path = Utf8PathBuf::from(if cfg!(target_family = "windows") {
+        r"\\?\C:\src\app.gleam"
+    } else {
+        "/src/app.gleam"
+    });
+    let url = Url::from_file_path(path).unwrap();
+
+    let params = GotoDefinitionParams {
+        text_document_position_params: TextDocumentPositionParams::new(
+            TextDocumentIdentifier::new(url),
+            position,
+        ),
+        work_done_progress_params: Default::default(),
+        partial_result_params: Default::default(),
+    };
+    let response = engine.goto_definition(params);
+
+    response
+        .result
+        .unwrap()
+        .map(|response| single(response).range)
+}
+
+fn single(response: GotoDefinitionResponse) -> Location {
+    match response {
+        GotoDefinitionResponse::Scalar(location) => location,
+        response => panic!("Expected a single definition, got {response:?}"),
+    }
+}
+
+const SHAPES: &str = "pub type Shape {
+  Circle(radius: Float)
+  Square(side: Float)
+}
+
+pub fn main(shape: Shape) {
+  case shape {
+    Square(_) -> Square(1.0)
+    Circle(_) -> Circle(2.0)
+  }
+}
+";
+
+fn circle() -> Range {
+    Range::new(Position::new(1, 2), Position::new(1, 23))
+}
+
+fn square() -> Range {
+    Range::new(Position::new(2, 2), Position::new(2, 21))
+}
+
+#[test]
+fn constructor_in_expression() {
+    assert_eq!(definition(SHAPES, Position::new(8, 18)), Some(circle()));
+    assert_eq!(definition(SHAPES, Position::new(7, 18)), Some(square()));
+}
+
+#[test]
+fn constructor_in_pattern() {
+    assert_eq!(definition(SHAPES, Position::new(7, 5)), Some(square()));
+}
+
+#[test]
+fn constructor_in_type_definition() {
+    assert_eq!(definition(SHAPES, Position::new(2, 3)), Some(square()));
+}
+
+#[test]
+fn type_name_in_type_definition() {
+    assert_eq!(
+        definition(SHAPES, Position::new(0, 10)),
+        Some(Range::new(Position::new(0, 0), Position::new(0, 14)))
+    );
+}
+
+#[test]
+fn definition_in_dependency() {
+    let io = LanguageServerTestIO::new();
+    let mut engine = setup_engine(&io);
+    add_path_dep(&mut engine, "shapes");
+
+    let dep_path = io.path_dep_module("shapes", "shapes", SHAPES);
+    _ = io.src_module(
+        "app",
+        "import shapes
+pub fn main() {
+  shapes.Square(1.0)
+}
+",
+    );
+    assert!(engine.compile_please().result.is_ok());
+    assert!(engine.compiler.get_source("shapes").is_none());
+
+    let url = Url::from_file_path(io.paths.src_directory().join("app.gleam")).unwrap();
+    let params = GotoDefinitionParams {
+        text_document_position_params: TextDocumentPositionParams::new(
+            TextDocumentIdentifier::new(url),
+            Position::new(2, 10),
+        ),
+        work_done_progress_params: Default::default(),
+        partial_result_params: Default::default(),
+    };
+    let location = single(engine.goto_definition(params).result.unwrap().unwrap());
+    assert!(location.uri.path().ends_with("shapes/src/shapes.gleam"));
+    assert_eq!(location.range, square());
+
+    let module = engine.compiler.load_dependency_module("shapes").unwrap();
+    assert_eq!(module.input_path, dep_path);
+    assert_eq!(module.ast.definitions.len(), 2);
+
+    // Root package modules are not dependency modules.
+    assert!(engine.compiler.load_dependency_module("app").is_none());
+}
+
+#[test]
+fn module_qualifier() {
+    let io = LanguageServerTestIO::new();
+    let mut engine = setup_engine(&io);
+    add_path_dep(&mut engine, "shapes");
+
+    _ = io.path_dep_module("shapes", "shapes", SHAPES);
+    _ = io.src_module("wibble/other", "pub type Box { Box(size: Int) }");
+    _ = io.src_module(
+        "app",
+        "import shapes as s
+import wibble/other
+pub fn main() {
+  #(s.Square(1.0), other.Box(1))
+}
+pub fn size(other: other.Box) {
+  other.size
+}
+",
+    );
+    assert!(engine.compile_please().result.is_ok());
+
+    let url = Url::from_file_path(io.paths.src_directory().join("app.gleam")).unwrap();
+    let mut definition = |line, character| {
+        let params = GotoDefinitionParams {
+            text_document_position_params: TextDocumentPositionParams::new(
+                TextDocumentIdentifier::new(url.clone()),
+                Position::new(line, character),
+            ),
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        };
+        engine.goto_definition(params).result.unwrap().map(single)
+    };
+    let top = Range::new(Position::new(0, 0), Position::new(0, 0));
+
+    let location = definition(3, 4).unwrap();
+    assert!(location.uri.path().ends_with("shapes/src/shapes.gleam"));
+    assert_eq!(location.range, top);
+
+    let location = definition(3, 20).unwrap();
+    assert!(location.uri.path().ends_with("src/wibble/other.gleam"));
+    assert_eq!(location.range, top);
+
+    // The module qualifier of a type.
+    let location = definition(5, 21).unwrap();
+    assert!(location.uri.path().ends_with("src/wibble/other.gleam"));
+
+    // A variable with the name of a module is not a module qualifier.
+    let location = definition(6, 3).unwrap();
+    assert!(location.uri.path().ends_with("src/app.gleam"));
+    assert_eq!(location.range.start, Position::new(5, 12));
+}
+
+#[test]
+fn definition_with_external() {
+    let io = LanguageServerTestIO::new();
+    let mut engine = setup_engine(&io);
+    _ = io.src_module(
+        "app",
+        "@external(erlang, \"erlang\", \"abs\")
+@external(javascript, \"./ffi.mjs\", \"abs\")
+fn abs(x: Int) -> Int
+
+fn double(x: Int) -> Int { x * 2 }
+
+pub fn main() {
+  double(abs(-1))
+}
+",
+    );
+    assert!(engine.compile_please().result.is_ok());
+
+    let url = Url::from_file_path(io.paths.src_directory().join("app.gleam")).unwrap();
+    let mut definition = |position| {
+        let params = GotoDefinitionParams {
+            text_document_position_params: TextDocumentPositionParams::new(
+                TextDocumentIdentifier::new(url.clone()),
+                position,
+            ),
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        };
+        engine.definition_with_external(params).result.unwrap()
+    };
+
+    // The external for the project's target is given.
+    let abs = definition(Position::new(7, 10)).unwrap();
+    assert_eq!(abs.location.range.start, Position::new(2, 0));
+    assert_eq!(
+        abs.external,
+        Some(ExternalTarget {
+            module: "erlang".into(),
+            function: "abs".into(),
+        })
+    );
+
+    let double = definition(Position::new(7, 3)).unwrap();
+    assert_eq!(double.location.range.start, Position::new(4, 0));
+    assert_eq!(double.external, None);
+}
+
+#[test]
+fn definition_in_module_that_no_longer_compiles() {
+    let io = LanguageServerTestIO::new();
+    let mut engine = setup_engine(&io);
+
+    _ = io.src_module("app", SHAPES);
+    assert!(engine.compile_please().result.is_ok());
+
+    // A type error is added after the code being navigated.
+    _ = io.src_module("app", &format!("{SHAPES}pub fn broken() {{ 1 + 1.0 }}\n"));
+    assert!(engine.compile_please().result.is_err());
+    assert!(engine.compiler.current_module("app").is_none());
+    assert!(engine.compiler.last_good_module("app").is_some());
+
+    let url = Url::from_file_path(io.paths.src_directory().join("app.gleam")).unwrap();
+    let params = GotoDefinitionParams {
+        text_document_position_params: TextDocumentPositionParams::new(
+            TextDocumentIdentifier::new(url),
+            Position::new(7, 5),
+        ),
+        work_done_progress_params: Default::default(),
+        partial_result_params: Default::default(),
+    };
+    let location = engine.goto_definition(params.clone()).result.unwrap();
+    assert_eq!(
+        location.map(|response| single(response).range),
+        Some(square())
+    );
+
+    // Lines with a type error are added before the definition, so the
+    // position and the location are both in the code as it is now.
+    _ = io.src_module(
+        "app",
+        &format!("pub fn broken() {{\n  1 + 1.0\n}}\n{SHAPES}"),
+    );
+    assert!(engine.compile_please().result.is_err());
+    let moved = GotoDefinitionParams {
+        text_document_position_params: TextDocumentPositionParams::new(
+            params.text_document_position_params.text_document.clone(),
+            Position::new(10, 5),
+        ),
+        ..params.clone()
+    };
+    let location = engine.goto_definition(moved).result.unwrap();
+    assert_eq!(
+        location.map(|response| single(response).range),
+        Some(Range::new(Position::new(5, 2), Position::new(5, 21)))
+    );
+
+    // The possibly out of date results can be turned off.
+    let settings = LspSettings {
+        use_last_good_modules: false,
+        ..Default::default()
+    };
+    assert!(engine.apply_settings(settings).result.is_ok());
+    assert_eq!(engine.goto_definition(params).result.unwrap(), None);
+}
+
+#[test]
+fn duplicate_definitions() {
+    let io = LanguageServerTestIO::new();
+    let mut engine = setup_engine(&io);
+
+    let code = "pub fn wibble() { 1 }
+
+pub fn main() { wibble() }
+";
+    _ = io.src_module("app", code);
+    assert!(engine.compile_please().result.is_ok());
+
+    // The name is defined again after the code being navigated.
+    _ = io.src_module("app", &format!("{code}\npub fn wibble() {{ 2 }}\n"));
+    assert!(engine.compile_please().result.is_err());
+
+    let url = Url::from_file_path(io.paths.src_directory().join("app.gleam")).unwrap();
+    let params = GotoDefinitionParams {
+        text_document_position_params: TextDocumentPositionParams::new(
+            TextDocumentIdentifier::new(url),
+            Position::new(2, 17),
+        ),
+        work_done_progress_params: Default::default(),
+        partial_result_params: Default::default(),
+    };
+    let Some(GotoDefinitionResponse::Array(locations)) =
+        engine.goto_definition(params.clone()).result.unwrap()
+    else {
+        panic!("Expected more than one definition");
+    };
+    let starts = locations
+        .iter()
+        .map(|location| location.range.start)
+        .collect::<Vec<_>>();
+    assert_eq!(starts, vec![Position::new(0, 0), Position::new(4, 0)]);
+
+    // Only the first is given with its external.
+    let definition = engine.definition_with_external(params).result.unwrap();
+    assert_eq!(
+        definition.map(|definition| definition.location.range.start),
+        Some(Position::new(0, 0))
+    );
+}
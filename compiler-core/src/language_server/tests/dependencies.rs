@@ -0,0 +1,81 @@
+use crate::language_server::PackageSummary;
+
+use super::*;
+
+#[test]
+fn dependency_packages() {
+    let io = LanguageServerTestIO::new();
+    let mut engine = setup_engine(&io);
+    add_path_dep(&mut engine, "wobble");
+    add_dev_path_dep(&mut engine, "testing");
+    add_path_dep(&mut engine, "app_lib");
+
+    assert_eq!(
+        engine.compiler.dependency_packages(),
+        vec![
+            PackageSummary {
+                name: "app_lib".into(),
+                version: Version::new(1, 0, 0),
+                dev_dependency: false,
+            },
+            PackageSummary {
+                name: "testing".into(),
+                version: Version::new(1, 0, 0),
+                dev_dependency: true,
+            },
+            PackageSummary {
+                name: "wobble".into(),
+                version: Version::new(1, 0, 0),
+                dev_dependency: false,
+            },
+        ]
+    );
+}
+
+#[test]
+fn stdlib_source_root() {
+    let io = LanguageServerTestIO::new();
+    let mut engine = setup_engine(&io);
+    assert_eq!(engine.compiler.stdlib_source_root(), None);
+
+    add_package_from_manifest(
+        &mut engine,
+        ManifestPackage {
+            name: "gleam_stdlib".into(),
+            version: Version::new(0, 34, 0),
+            build_tools: vec!["gleam".into()],
+            otp_app: None,
+            requirements: vec![],
+            source: ManifestPackageSource::Hex {
+                outer_checksum: Base16Checksum(vec![]),
+            },
+        },
+    );
+    assert_eq!(
+        engine.compiler.stdlib_source_root(),
+        Some(io.paths.build_packages_package("gleam_stdlib").join("src"))
+    );
+}
+
+#[test]
+fn module_package() {
+    let io = LanguageServerTestIO::new();
+    let mut engine = setup_engine(&io);
+    engine.compiler.project_compiler.config.name = "app".into();
+    add_path_dep(&mut engine, "shapes");
+
+    _ = io.path_dep_module("shapes", "shapes", "pub type Shape { Circle }");
+    _ = io.src_module("app", "import shapes\npub fn main() { shapes.Circle }");
+    assert!(engine.compile_please().result.is_ok());
+
+    assert_eq!(
+        engine.compiler.module_package("shapes"),
+        Some(("shapes".into(), "1.0.0".into()))
+    );
+    assert_eq!(
+        engine.compiler.module_package("app"),
+        Some(("app".into(), "0.1.0".into()))
+    );
+    assert_eq!(engine.compiler.module_package("gleam"), None);
+    assert_eq!(engine.compiler.module_package("nope"), None);
+}
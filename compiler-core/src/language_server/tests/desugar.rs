@@ -0,0 +1,153 @@
+use lsp_types::{Position, Range, TextEdit, Url};
+
+use super::*;
+
+const HELPERS: &str = "pub fn try(x: a, f: fn(a) -> b) -> b { f(x) }
+pub fn labelled(f: fn(a) -> b, with x: a) -> b { f(x) }
+pub fn wrap(f: fn() -> a) -> a { f() }
+pub fn curried(x: a) -> fn(fn(a) -> b) -> b { fn(f) { f(x) } }
+";
+
+/// Desugar the `use` in `main`, with the cursor at the first occurrence of
+/// `at`.
+fn desugar(body: &str, at: &str) -> Option<String> {
+    let io = LanguageServerTestIO::new();
+    let mut engine = setup_engine(&io);
+
+    let code = format!(
+        "import helpers.{{try, labelled, wrap, curried}}
+pub fn main() {{
+{body}
+}}
+"
+    );
+    _ = io.src_module("helpers", HELPERS);
+    _ = io.src_module("app", &code);
+    let response = engine.compile_please();
+    assert!(response.result.is_ok());
+
+    let byte_index = code.find(at).expect("cursor position") as u32;
+    let edit = engine.compiler.desugar_use("app", byte_index)?;
+
+    // Apply the edit so that the result can be checked as a whole.
+    let line_numbers = crate::line_numbers::LineNumbers::new(&code);
+    let start = line_numbers.byte_index(edit.range.start.line, edit.range.start.character);
+    let end = line_numbers.byte_index(edit.range.end.line, edit.range.end.character);
+    let mut code = code;
+    code.replace_range(start as usize..end as usize, &edit.new_text);
+    Some(code)
+}
+
+fn main_with(body: &str) -> Option<String> {
+    Some(format!(
+        "import helpers.{{try, labelled, wrap, curried}}
+pub fn main() {{
+{body}
+}}
+"
+    ))
+}
+
+#[test]
+fn desugar_use_with_arguments() {
+    assert_eq!(
+        desugar(
+            "  use x <- try(1)
+  let y = x + 1
+  y",
+            "use"
+        ),
+        main_with(
+            "  try(1, fn(x) {
+    let y = x + 1
+    y
+  })"
+        )
+    );
+}
+
+#[test]
+fn desugar_use_without_parentheses() {
+    assert_eq!(
+        desugar("  use <- wrap\n  1", "<-"),
+        main_with("  wrap(fn() {\n    1\n  })")
+    );
+}
+
+#[test]
+fn desugar_use_of_returned_function() {
+    assert_eq!(
+        desugar("  use x <- curried(1)()\n  x", "<-"),
+        main_with("  curried(1)(fn(x) {\n    x\n  })")
+    );
+}
+
+#[test]
+fn desugar_use_with_labelled_arguments() {
+    assert_eq!(
+        desugar("  use x <- labelled(with: 1)\n  x", "use"),
+        main_with("  labelled(fn(x) {\n    x\n  }, with: 1)")
+    );
+}
+
+#[test]
+fn desugar_use_with_patterns() {
+    assert_eq!(
+        desugar("  use #(a, b): #(Int, Int) <- try(#(1, 2))\n  a + b", "use"),
+        main_with(
+            "  try(#(1, 2), fn(use_0) {
+    let #(a, b): #(Int, Int) = use_0
+    a + b
+  })"
+        )
+    );
+}
+
+#[test]
+fn desugar_nested_use() {
+    assert_eq!(
+        desugar("  use x <- try(1)\n  use y <- try(2)\n  x + y", "use y"),
+        main_with("  use x <- try(1)\n  try(2, fn(y) {\n    x + y\n  })")
+    );
+}
+
+#[test]
+fn desugar_use_outside_use() {
+    assert_eq!(desugar("  use x <- try(1)\n  x", "x\n}"), None);
+}
+
+#[test]
+fn desugar_use_code_action() {
+    let io = LanguageServerTestIO::new();
+    let mut engine = setup_engine(&io);
+
+    _ = io.src_module("helpers", HELPERS);
+    _ = io.src_module(
+        "app",
+        "import helpers\npub fn main() {\n  use <- helpers.wrap\n  1\n}\n",
+    );
+    assert!(engine.compile_please().result.is_ok());
+
+    let url = Url::from_file_path(io.paths.src_directory().join("app.gleam")).unwrap();
+    let position = Position::new(2, 3);
+    let response = engine.action(lsp_types::CodeActionParams {
+        text_document: lsp_types::TextDocumentIdentifier::new(url.clone()),
+        range: Range::new(position, position),
+        context: Default::default(),
+        work_done_progress_params: Default::default(),
+        partial_result_params: Default::default(),
+    });
+    let actions = response.result.unwrap().unwrap_or_default();
+    let desugar = actions
+        .into_iter()
+        .find(|action| action.title == "Desugar use expression")
+        .expect("desugar action");
+    let edits = desugar.edit.unwrap().changes.unwrap().remove(&url).unwrap();
+    assert_eq!(
+        edits,
+        vec![TextEdit::new(
+            Range::new(Position::new(2, 2), Position::new(3, 3)),
+            "helpers.wrap(fn() {\n    1\n  })".into()
+        )]
+    );
+}
@@ -0,0 +1,65 @@
+use camino::Utf8Path;
+use ecow::EcoString;
+use lsp_types::{Position, Range, TextEdit};
+
+use crate::{ast::SrcSpan, language_server::format_range};
+
+fn format(src: &str, range: SrcSpan) -> Vec<TextEdit> {
+    format_range(Utf8Path::new("app.gleam"), &EcoString::from(src), range).unwrap()
+}
+
+#[test]
+fn format_range_only_formats_overlapping_definitions() {
+    let src = "pub fn one() {   1 }
+
+pub fn two() {   2 }
+
+pub fn three() {   3 }
+";
+    let start = src.find("two").unwrap() as u32;
+    let edits = format(src, SrcSpan::new(start, start));
+    assert_eq!(
+        edits,
+        vec![TextEdit {
+            range: Range::new(Position::new(0, 20), Position::new(2, 20)),
+            new_text: "\n\npub fn two() {\n  2\n}".into(),
+        }]
+    );
+}
+
+#[test]
+fn format_range_of_several_definitions() {
+    let src = "pub fn one() {   1 }
+pub fn two() {   2 }
+pub fn three() { 3 }
+";
+    let end = src.find("two").unwrap() as u32;
+    let edits = format(src, SrcSpan::new(0, end));
+    assert_eq!(
+        edits,
+        vec![TextEdit {
+            range: Range::new(Position::new(0, 0), Position::new(1, 20)),
+            new_text: "pub fn one() {\n  1\n}\n\npub fn two() {\n  2\n}".into(),
+        }]
+    );
+}
+
+#[test]
+fn format_range_of_formatted_code() {
+    let src = "pub fn one() {\n  1\n}\n";
+    assert_eq!(format(src, SrcSpan::new(0, src.len() as u32)), vec![]);
+}
+
+#[test]
+fn format_empty_range_at_start_of_module() {
+    let src = "pub fn one() {   1 }
+pub fn two() {   2 }
+";
+    assert_eq!(
+        format(src, SrcSpan::new(0, 0)),
+        vec![TextEdit {
+            range: Range::new(Position::new(0, 0), Position::new(0, 20)),
+            new_text: "pub fn one() {\n  1\n}".into(),
+        }]
+    );
+}
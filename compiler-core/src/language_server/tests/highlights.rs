@@ -0,0 +1,129 @@
+use crate::language_server::HighlightKind;
+
+use super::*;
+
+/// The highlights of the symbol at the `nth` occurrence of `at` in the
+/// module, as the text of each with its start and kind.
+fn highlights(src: &str, at: &str, nth: usize) -> Vec<(String, u32, HighlightKind)> {
+    let io = LanguageServerTestIO::new();
+    let mut engine = setup_engine(&io);
+    _ = io.src_module("other", "pub fn double(x) { x * 2 }");
+    _ = io.src_module("app", src);
+    assert!(engine.compile_please().result.is_ok());
+
+    let (byte_index, _) = src.match_indices(at).nth(nth).expect("position");
+    engine
+        .compiler
+        .document_highlights("app", byte_index as u32)
+        .into_iter()
+        .map(|(span, kind)| {
+            let text = src
+                .get(span.start as usize..span.end as usize)
+                .unwrap_or_default();
+            (text.to_string(), span.start, kind)
+        })
+        .collect()
+}
+
+#[test]
+fn local_variable() {
+    let src = "pub fn main() {
+  let count = 1
+  let total = count + count
+  total
+}
+
+fn other() {
+  let count = 2
+  count
+}
+";
+    assert_eq!(
+        highlights(src, "count", 1),
+        vec![
+            ("count".to_string(), 22, HighlightKind::Write),
+            ("count".to_string(), 46, HighlightKind::Read),
+            ("count".to_string(), 54, HighlightKind::Read),
+        ]
+    );
+}
+
+#[test]
+fn module_function() {
+    let src = "pub fn main() {
+  helper(helper(1))
+}
+
+fn helper(x) {
+  x
+}
+";
+    let expected = vec![
+        ("helper".to_string(), 18, HighlightKind::Read),
+        ("helper".to_string(), 25, HighlightKind::Read),
+        ("helper".to_string(), 42, HighlightKind::Write),
+    ];
+    assert_eq!(highlights(src, "helper", 0), expected);
+    assert_eq!(highlights(src, "helper", 2), expected);
+}
+
+#[test]
+fn record_constructor() {
+    let src = "pub type Box {
+  Box(Int)
+}
+
+pub fn main() {
+  let Box(x) = Box(1)
+  x
+}
+";
+    assert_eq!(
+        highlights(src, "Box", 3),
+        vec![
+            ("Box".to_string(), 17, HighlightKind::Write),
+            ("Box".to_string(), 51, HighlightKind::Read),
+            ("Box".to_string(), 60, HighlightKind::Read),
+        ]
+    );
+}
+
+#[test]
+fn imported_function() {
+    let src = "import other.{double}
+
+pub fn main() {
+  other.double(double(1))
+}
+";
+    assert_eq!(
+        highlights(src, "double", 2),
+        vec![
+            ("double".to_string(), 47, HighlightKind::Read),
+            ("double".to_string(), 54, HighlightKind::Read),
+        ]
+    );
+}
+
+#[test]
+fn nothing_at_position() {
+    let src = "pub fn main() {
+  1 + 2
+}
+";
+    assert_eq!(highlights(src, "+", 0), vec![]);
+}
+
+#[test]
+fn module_that_no_longer_compiles() {
+    let io = LanguageServerTestIO::new();
+    let mut engine = setup_engine(&io);
+    let src = "pub fn main() {\n  let x = 1\n  x\n}\n";
+    _ = io.src_module("app", src);
+    assert!(engine.compile_please().result.is_ok());
+    assert_eq!(engine.compiler.document_highlights("app", 22).len(), 2);
+
+    _ = io.src_module("app", "pub fn main() {\n  let x = 1\n  x +\n}\n");
+    assert!(engine.compile_please().result.is_err());
+    assert_eq!(engine.compiler.document_highlights("app", 22), vec![]);
+}
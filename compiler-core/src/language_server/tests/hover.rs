@@ -1,11 +1,22 @@
 use lsp_types::{
-    Hover, HoverContents, HoverParams, MarkedString, Position, Range, TextDocumentIdentifier,
-    TextDocumentPositionParams, Url,
+    Hover, HoverContents, HoverParams, MarkedString, MarkupContent, MarkupKind, Position, Range,
+    TextDocumentIdentifier, TextDocumentPositionParams, Url,
 };
 
+use crate::language_server::engine::HoverFormat;
+
 use super::*;
 
 fn positioned_with_io(src: &str, position: Position, io: &LanguageServerTestIO) -> Option<Hover> {
+    formatted_with_io(src, position, io, HoverFormat::Markdown)
+}
+
+fn formatted_with_io(
+    src: &str,
+    position: Position,
+    io: &LanguageServerTestIO,
+    format: HoverFormat,
+) -> Option<Hover> {
     let mut engine = setup_engine(io);
 
     _ = io.src_module("app", src);
@@ -30,7 +41,7 @@ fn positioned_with_io(src: &str, position: Position, io: &LanguageServerTestIO)
         ),
         work_done_progress_params: Default::default(),
     };
-    let response = engine.hover(params);
+    let response = engine.hover(params, format);
 
     response.result.unwrap()
 }
@@ -511,3 +522,224 @@ fn do_stuff() {
         })
     );
 }
+
+fn hovered_range(src: &str, position: Position) -> Option<Range> {
+    positioned_hover(src, position).and_then(|hover| hover.range)
+}
+
+#[test]
+fn hover_at_the_end_of_a_name() {
+    let code = "
+fn wibble(x) {
+  x
+}
+
+fn main() {
+  wibble(1)
+}
+";
+
+    // Just after `wibble`, at the start of the arguments of the call, the
+    // name is hovered rather than the whole call.
+    assert_eq!(
+        hovered_range(code, Position::new(6, 8)),
+        Some(Range::new(Position::new(6, 2), Position::new(6, 8)))
+    );
+}
+
+#[test]
+fn hover_between_two_names() {
+    let code = "
+fn add(x, y) {
+  x+y
+}
+";
+
+    // Between `x` and `+` the name ends at the cursor and nothing nested
+    // within the addition starts there.
+    assert_eq!(
+        hovered_range(code, Position::new(2, 3)),
+        Some(Range::new(Position::new(2, 2), Position::new(2, 3)))
+    );
+    // Between `+` and `y` the name that starts at the cursor is hovered.
+    assert_eq!(
+        hovered_range(code, Position::new(2, 4)),
+        Some(Range::new(Position::new(2, 4), Position::new(2, 5)))
+    );
+}
+
+#[test]
+fn hover_after_a_call() {
+    let code = "
+fn wibble(x) {
+  x
+}
+
+fn main() {
+  wibble(1)
+}
+";
+
+    // Just after the closing parenthesis the whole call is hovered.
+    assert_eq!(
+        hovered_range(code, Position::new(6, 11)),
+        Some(Range::new(Position::new(6, 2), Position::new(6, 11)))
+    );
+}
+
+#[test]
+fn hover_use_callback_parameters() {
+    let code = "import result
+
+pub fn main() {
+  use x <- result.try(Ok(1))
+  use #(a, b) <- result.try(Ok(#(x, \"b\")))
+  Ok(a + x)
+}
+";
+    let io = LanguageServerTestIO::new();
+    _ = io.src_module(
+        "result",
+        "pub fn try(result: Result(a, e), fun: fn(a) -> Result(b, e)) -> Result(b, e) {
+  case result {
+    Ok(x) -> fun(x)
+    Error(e) -> Error(e)
+  }
+}",
+    );
+    let hover = |line, character| {
+        positioned_with_io(code, Position::new(line, character), &io).map(|hover| {
+            let HoverContents::Scalar(MarkedString::String(contents)) = hover.contents else {
+                panic!("Unexpected hover contents {:?}", hover.contents);
+            };
+            (contents, hover.range)
+        })
+    };
+    let range = |line, start, end| {
+        Some(Range::new(
+            Position::new(line, start),
+            Position::new(line, end),
+        ))
+    };
+
+    assert_eq!(
+        hover(3, 6),
+        Some(("```gleam\nInt\n```".into(), range(3, 6, 7)))
+    );
+    assert_eq!(
+        hover(4, 8),
+        Some(("```gleam\nInt\n```\n".into(), range(4, 8, 9)))
+    );
+    assert_eq!(
+        hover(4, 11),
+        Some(("```gleam\nString\n```\n".into(), range(4, 11, 12)))
+    );
+    let x_usage = "```gleam\nInt\n```\nA locally defined variable.".to_string();
+    assert_eq!(hover(5, 9), Some((x_usage, range(5, 9, 10))));
+}
+
+#[test]
+fn hover_as_plain_text() {
+    let mut io = LanguageServerTestIO::new();
+    io.add_hex_package("my_dep");
+    _ = io.hex_dep_module(
+        "my_dep",
+        "example_module",
+        "/// Returns `Nil`, see [the docs](https://gleam.run).
+pub fn my_fn() { Nil }",
+    );
+
+    let code = "
+import example_module
+fn main() {
+    example_module.my_fn
+}
+";
+
+    let hover = formatted_with_io(code, Position::new(3, 22), &io, HoverFormat::PlainText);
+    assert_eq!(
+        hover.map(|hover| hover.contents),
+        Some(HoverContents::Markup(MarkupContent {
+            kind: MarkupKind::PlainText,
+            value: "fn() -> Nil
+ Returns Nil, see the docs (https://gleam.run).
+
+View on HexDocs (https://hexdocs.pm/my_dep/example_module.html#my_fn)"
+                .into(),
+        }))
+    );
+}
+
+#[test]
+fn hover_bit_array_segments() {
+    let code = "
+fn main(x: Int, data: BitArray) {
+  let assert <<length:8, body:bytes-size(length)>> = data
+  <<x:size(8), body:bits>>
+}
+";
+    let hover = |line, character| {
+        positioned_hover(code, Position::new(line, character)).map(|hover| {
+            let HoverContents::Scalar(MarkedString::String(contents)) = hover.contents else {
+                panic!("Unexpected hover contents {:?}", hover.contents);
+            };
+            (contents, hover.range)
+        })
+    };
+    let range = |line, start, end| {
+        Some(Range::new(
+            Position::new(line, start),
+            Position::new(line, end),
+        ))
+    };
+
+    let x = "```gleam\nInt\n```\nA locally defined variable.".to_string();
+    assert_eq!(hover(3, 4), Some((x, range(3, 4, 5))));
+    let size = "```gleam\nsize\n```\nThe number of units in the segment. A unit is a bit, or a \
+byte for `bytes` segments, unless another is given with `unit`."
+        .to_string();
+    assert_eq!(hover(3, 7), Some((size, range(3, 6, 13))));
+    let bits = "```gleam\nbits\n```\nThe value is a `BitArray` of any number of bits.".to_string();
+    assert_eq!(hover(3, 21), Some((bits, range(3, 20, 24))));
+
+    // The size of a pattern segment can be a variable bound earlier in the
+    // pattern.
+    let length = "```gleam\nInt\n```\n".to_string();
+    assert_eq!(hover(2, 41), Some((length, range(2, 41, 47))));
+    let bytes =
+        "```gleam\nbytes\n```\nThe value is a `BitArray` of a whole number of bytes.".to_string();
+    assert_eq!(hover(2, 31), Some((bytes, range(2, 30, 35))));
+}
+
+#[test]
+fn hover_record_access() {
+    let code = "
+pub type Server {
+  Server(host: String, port: Int)
+}
+
+pub type Config {
+  Config(name: String, server: Server)
+}
+
+fn main(config: Config) {
+  config.server.port
+}
+";
+    let hover = |character| {
+        positioned_hover(code, Position::new(10, character)).map(|hover| {
+            let HoverContents::Scalar(MarkedString::String(contents)) = hover.contents else {
+                panic!("Unexpected hover contents {:?}", hover.contents);
+            };
+            (contents, hover.range)
+        })
+    };
+    let range = |start, end| Some(Range::new(Position::new(10, start), Position::new(10, end)));
+
+    let config = "```gleam\nConfig\n```\nA locally defined variable.".to_string();
+    assert_eq!(hover(4), Some((config, range(2, 8))));
+    let server = "```gleam\nServer\n```\nThe `server` field of a `Config`.".to_string();
+    assert_eq!(hover(11), Some((server, range(9, 15))));
+    let port = "```gleam\nInt\n```\nThe `port` field of a `Server`.".to_string();
+    assert_eq!(hover(17), Some((port, range(16, 20))));
+}
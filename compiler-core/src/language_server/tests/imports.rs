@@ -0,0 +1,202 @@
+use crate::diagnostic::Diagnostic;
+use itertools::Itertools;
+use lsp_types::{Position, Range};
+
+use super::*;
+
+fn summary(diagnostics: Vec<Diagnostic>) -> Vec<(Option<&'static str>, u32, Option<String>)> {
+    diagnostics
+        .into_iter()
+        .map(|diagnostic| {
+            let start = diagnostic
+                .location
+                .map_or(0, |location| location.label.span.start);
+            (diagnostic.code, start, diagnostic.hint)
+        })
+        .collect()
+}
+
+#[test]
+fn unused_imports() {
+    let io = LanguageServerTestIO::new();
+    let mut engine = setup_engine(&io);
+
+    _ = io.src_module("wibble", "pub fn wobble() { 1 }");
+    _ = io.src_module(
+        "app",
+        "import wibble\nimport gleam\npub fn main() { wibble.wobble() }",
+    );
+    assert!(engine.compile_please().result.is_ok());
+
+    assert_eq!(
+        summary(engine.compiler.import_diagnostics("app")),
+        vec![(Some("unused_import"), 14, None)]
+    );
+}
+
+#[test]
+fn missing_imports() {
+    let io = LanguageServerTestIO::new();
+    let mut engine = setup_engine(&io);
+
+    _ = io.src_module(
+        "one/wibble",
+        "pub fn wobble() { 1 }\npub type Wubble { Wubble }",
+    );
+    _ = io.src_module("two/wibble", "pub fn wobble() { 2 }");
+    assert!(engine.compile_please().result.is_ok());
+
+    _ = io.src_module("app", "pub fn main() { wobble() }");
+    assert!(engine.compile_please().result.is_err());
+    assert_eq!(
+        summary(engine.compiler.import_diagnostics("app")),
+        vec![(
+            Some("missing_import"),
+            16,
+            Some(
+                "It can be imported with one of:
+
+    import one/wibble.{wobble}
+    import two/wibble.{wobble}"
+                    .into()
+            )
+        )]
+    );
+
+    _ = io.src_module("app", "pub fn main() { wibble.wobble() }");
+    assert!(engine.compile_please().result.is_err());
+    let diagnostics = summary(engine.compiler.import_diagnostics("app"));
+    assert_eq!(
+        diagnostics.first().and_then(|(_, _, hint)| hint.as_deref()),
+        Some("It can be imported with one of:\n\n    import one/wibble\n    import two/wibble")
+    );
+
+    _ = io.src_module("app", "pub fn main() -> Wubble { todo }");
+    assert!(engine.compile_please().result.is_err());
+    let diagnostics = summary(engine.compiler.import_diagnostics("app"));
+    assert_eq!(
+        diagnostics.first().and_then(|(_, _, hint)| hint.as_deref()),
+        Some("It can be imported with one of:\n\n    import one/wibble.{type Wubble}")
+    );
+
+    // Names that are not defined anywhere have nothing to import.
+    _ = io.src_module("app", "pub fn main() { wubble() }");
+    assert!(engine.compile_please().result.is_err());
+    assert_eq!(engine.compiler.import_diagnostics("app"), vec![]);
+}
+
+#[test]
+fn import_block_span() {
+    let io = LanguageServerTestIO::new();
+    let mut engine = setup_engine(&io);
+
+    let app = "//// The app.
+
+import wibble
+// The standard library.
+import gleam
+
+import wobble as w
+pub fn main() { wibble.wobble() }
+import later
+";
+    _ = io.src_module("wibble", "pub fn wobble() { 1 }");
+    _ = io.src_module("wobble", "");
+    _ = io.src_module("later", "");
+    _ = io.src_module("app", app);
+    _ = io.src_module("none", "pub fn main() { 1 }\nimport wibble\n");
+    _ = engine.compile_please();
+
+    let block = engine.compiler.import_block_span("app").expect("span");
+    assert_eq!(
+        app.get(block.start as usize..block.end as usize),
+        Some("import wibble\n// The standard library.\nimport gleam\n\nimport wobble as w")
+    );
+    assert_eq!(engine.compiler.import_block_span("none"), None);
+    assert_eq!(engine.compiler.import_block_span("wibble"), None);
+}
+
+#[test]
+fn ambiguous_imports() {
+    let io = LanguageServerTestIO::new();
+    let mut engine = setup_engine(&io);
+
+    _ = io.src_module(
+        "one/wibble",
+        "pub fn wobble() { 1 }\npub type Wubble { Wubble }",
+    );
+    _ = io.src_module(
+        "my_list",
+        "pub fn wobble() { 2 }\npub type Wubble { Wubble }",
+    );
+    assert!(engine.compile_please().result.is_ok());
+
+    let code = "import one/wibble.{wobble, type Wubble}
+import my_list.{wobble, type Wubble, Wubble as Wubble}
+pub fn main() { wobble() }";
+    _ = io.src_module("app", code);
+    assert!(engine.compile_please().result.is_err());
+
+    let positions = engine
+        .compiler
+        .ambiguous_imports("app")
+        .into_iter()
+        .map(|diagnostic| {
+            let location = diagnostic.location.expect("location");
+            let first = location.extra_labels.first().map(|label| label.span.start);
+            (diagnostic.code, location.label.span.start, first)
+        })
+        .collect_vec();
+    // Types and values are named apart, so the constructor `Wubble` is not
+    // ambiguous with the type of the same name.
+    assert_eq!(
+        positions,
+        vec![
+            (Some("ambiguous_import"), 56, Some(19)),
+            (Some("ambiguous_import"), 64, Some(27)),
+        ]
+    );
+
+    let fix = |cursor: u32| {
+        engine
+            .compiler
+            .ambiguous_import_fix("app", cursor)
+            .map(|edit| (edit.range.start, edit.new_text))
+    };
+    assert_eq!(
+        fix(57),
+        Some((Position::new(1, 22), " as my_list_wobble".into()))
+    );
+    assert_eq!(
+        fix(70),
+        Some((Position::new(1, 35), " as MyListWubble".into()))
+    );
+    assert_eq!(fix(20), None);
+}
+
+#[test]
+fn ambiguous_import_fix_replaces_one_letter_alias() {
+    let io = LanguageServerTestIO::new();
+    let mut engine = setup_engine(&io);
+
+    _ = io.src_module("one/wibble", "pub fn wobble() { 1 }");
+    _ = io.src_module("my_list", "pub fn wubble() { 2 }");
+    assert!(engine.compile_please().result.is_ok());
+
+    let code = "import one/wibble.{wobble as s}
+import my_list.{wubble as s}
+pub fn main() { s() }";
+    _ = io.src_module("app", code);
+    assert!(engine.compile_please().result.is_err());
+
+    // The alias is replaced as a whole, rather than from the `s` of `as`.
+    let cursor = code.find("wubble").expect("wubble") as u32;
+    let edit = engine.compiler.ambiguous_import_fix("app", cursor);
+    assert_eq!(
+        edit.map(|edit| (edit.range, edit.new_text)),
+        Some((
+            Range::new(Position::new(1, 22), Position::new(1, 27)),
+            " as my_list_wubble".into()
+        ))
+    );
+}
@@ -0,0 +1,133 @@
+use crate::ast::SrcSpan;
+
+use super::*;
+
+/// The linked editing ranges of the local variable at the `nth` occurrence
+/// of `at` in the module, as the text of each range with its start.
+fn ranges(src: &str, at: &str, nth: usize) -> Option<Vec<(String, u32)>> {
+    let io = LanguageServerTestIO::new();
+    let mut engine = setup_engine(&io);
+    _ = io.src_module("app", src);
+    assert!(engine.compile_please().result.is_ok());
+
+    let (byte_index, _) = src.match_indices(at).nth(nth).expect("position");
+    let ranges = engine
+        .compiler
+        .linked_editing_ranges("app", byte_index as u32)?;
+    Some(
+        ranges
+            .into_iter()
+            .map(|SrcSpan { start, end }| {
+                let text = src.get(start as usize..end as usize).unwrap_or_default();
+                (text.to_string(), start)
+            })
+            .collect(),
+    )
+}
+
+#[test]
+fn let_variable() {
+    let src = "pub fn main() {
+  let count = 1
+  let total = count + count
+  total
+}
+";
+    let expected = Some(vec![
+        ("count".to_string(), 22),
+        ("count".to_string(), 46),
+        ("count".to_string(), 54),
+    ]);
+    assert_eq!(ranges(src, "count", 0), expected);
+    assert_eq!(ranges(src, "count", 2), expected);
+}
+
+#[test]
+fn shadowed_variable() {
+    let src = "pub fn main() {
+  let x = 1
+  let x = x + 1
+  x
+}
+";
+    assert_eq!(
+        ranges(src, "x", 1),
+        Some(vec![("x".to_string(), 34), ("x".to_string(), 46)])
+    );
+    assert_eq!(
+        ranges(src, "x", 2),
+        Some(vec![("x".to_string(), 22), ("x".to_string(), 38)])
+    );
+}
+
+#[test]
+fn function_arguments() {
+    let src = "pub fn add(amount, to total: Int) {
+  let f = fn(n) { n + amount }
+  f(total)
+}
+";
+    assert_eq!(
+        ranges(src, "total", 0),
+        Some(vec![("total".to_string(), 22), ("total".to_string(), 71)])
+    );
+    assert_eq!(
+        ranges(src, "amount", 0),
+        Some(vec![("amount".to_string(), 11), ("amount".to_string(), 58)])
+    );
+    assert_eq!(
+        ranges(src, "n)", 0),
+        Some(vec![("n".to_string(), 49), ("n".to_string(), 54)])
+    );
+}
+
+#[test]
+fn alternative_patterns_and_guards() {
+    let src = "pub fn main(limit) {
+  case Ok(1) {
+    Ok(x) | Error(x) if x > limit -> x
+    _ -> limit
+  }
+}
+";
+    assert_eq!(
+        ranges(src, "x", 1),
+        Some(vec![
+            ("x".to_string(), 43),
+            ("x".to_string(), 54),
+            ("x".to_string(), 60),
+            ("x".to_string(), 73),
+        ])
+    );
+    assert_eq!(
+        ranges(src, "limit", 0),
+        Some(vec![
+            ("limit".to_string(), 12),
+            ("limit".to_string(), 64),
+            ("limit".to_string(), 84),
+        ])
+    );
+}
+
+#[test]
+fn module_values_are_not_linked() {
+    let src = "fn one() { 1 }
+pub fn main() { one() }
+";
+    assert_eq!(ranges(src, "one", 1), None);
+}
+
+#[test]
+fn use_assignment() {
+    let src = "fn apply(f) { f(1) }
+
+pub fn main() {
+  use value <- apply
+  value + 1
+}
+";
+    assert_eq!(
+        ranges(src, "value", 1),
+        Some(vec![("value".to_string(), 44), ("value".to_string(), 61)])
+    );
+}
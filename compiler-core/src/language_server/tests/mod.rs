@@ -1,7 +1,23 @@
 mod action;
 mod compilation;
 mod completion;
+#[cfg(feature = "lsp-debug")]
+mod debug;
+mod definition;
+mod dependencies;
+mod desugar;
+mod formatting;
+mod highlights;
 mod hover;
+mod imports;
+mod linked_editing;
+mod outline;
+mod package_config;
+mod pipe;
+mod references;
+mod rename;
+mod unused;
+mod wrap;
 
 use std::{
     collections::HashMap,
@@ -61,6 +77,15 @@ impl LanguageServerTestIO {
         }
     }
 
+    /// For a project in a subdirectory, such as one of several in a
+    /// workspace.
+    fn at(root: &str) -> Self {
+        Self {
+            paths: ProjectPaths::new(Utf8PathBuf::from(root)),
+            ..Self::new()
+        }
+    }
+
     /// Panics if there are other references to the actions.
     pub fn into_actions(self) -> Vec<Action> {
         Arc::try_unwrap(self.actions).unwrap().into_inner().unwrap()
@@ -279,7 +304,7 @@ fn add_package_from_manifest<B>(
     package: ManifestPackage,
 ) {
     let compiler = &mut engine.compiler.project_compiler;
-    let toml_path = engine.paths.build_packages_package_config(&package.name);
+    let toml_path = compiler.paths.build_packages_package_config(&package.name);
     let toml = format!(
         r#"name = "{}"
     version = "{}""#,
@@ -291,7 +316,7 @@ fn add_package_from_manifest<B>(
 }
 
 fn add_path_dep<B>(engine: &mut LanguageServerEngine<LanguageServerTestIO, B>, name: &str) {
-    let path = engine.paths.root().join(name);
+    let path = engine.compiler.project_compiler.paths.root().join(name);
     let compiler = &mut engine.compiler.project_compiler;
     _ = compiler
         .config
@@ -315,6 +340,13 @@ version = "1.0.0""#
     _ = compiler.io.write(&path.join("gleam.toml"), &toml);
 }
 
+fn add_dev_path_dep<B>(engine: &mut LanguageServerEngine<LanguageServerTestIO, B>, name: &str) {
+    add_path_dep(engine, name);
+    let config = &mut engine.compiler.project_compiler.config;
+    let requirement = config.dependencies.remove(name).unwrap();
+    _ = config.dev_dependencies.insert(name.into(), requirement);
+}
+
 fn setup_engine(
     io: &LanguageServerTestIO,
 ) -> LanguageServerEngine<LanguageServerTestIO, LanguageServerTestIO> {
@@ -323,6 +355,7 @@ fn setup_engine(
         io.clone(),
         FileSystemProxy::new(io.clone()),
         io.paths.clone(),
+        None,
     )
     .unwrap()
 }
@@ -0,0 +1,338 @@
+use itertools::Itertools;
+
+use crate::{build::Target, language_server::DefinitionId, type_::pretty::Printer};
+
+use super::*;
+
+#[test]
+fn module_types() {
+    let io = LanguageServerTestIO::new();
+    let mut engine = setup_engine(&io);
+
+    _ = io.src_module(
+        "app",
+        "pub type Wibble(a) {
+  Wibble(a, label: Int)
+  Wobble
+}
+
+pub opaque type Wubble {
+  Wubble(inner: String)
+}
+
+type Private {
+  Private
+}
+",
+    );
+    let response = engine.compile_please();
+    assert!(response.result.is_ok());
+
+    let types = engine.compiler.module_types("app");
+    let mut printer = Printer::new();
+    let summary = types
+        .iter()
+        .map(|type_| {
+            let parameters = type_
+                .parameters
+                .iter()
+                .map(|parameter| parameter.name.to_string())
+                .collect_vec();
+            let constructors = type_
+                .constructors
+                .iter()
+                .map(|constructor| {
+                    let fields = constructor
+                        .fields
+                        .iter()
+                        .map(|field| {
+                            let type_ = printer.pretty_print(&field.type_, 0);
+                            match &field.label {
+                                Some(label) => format!("{label}: {type_}"),
+                                None => type_,
+                            }
+                        })
+                        .collect_vec();
+                    format!("{}({})", constructor.name, fields.join(", "))
+                })
+                .collect_vec();
+            (
+                type_.name.to_string(),
+                parameters,
+                type_.public,
+                type_.opaque,
+                constructors,
+            )
+        })
+        .collect_vec();
+
+    assert_eq!(
+        summary,
+        vec![
+            (
+                "Wibble".into(),
+                vec!["a".into()],
+                true,
+                false,
+                vec!["Wibble(a, label: Int)".into(), "Wobble()".into()]
+            ),
+            (
+                "Wubble".into(),
+                vec![],
+                true,
+                true,
+                vec!["Wubble(inner: String)".into()]
+            ),
+            (
+                "Private".into(),
+                vec![],
+                false,
+                false,
+                vec!["Private()".into()]
+            ),
+        ]
+    );
+
+    assert_eq!(engine.compiler.module_types("unknown"), vec![]);
+}
+
+#[test]
+fn definition_ids_are_stable() {
+    let io = LanguageServerTestIO::new();
+    let mut engine = setup_engine(&io);
+
+    _ = io.src_module(
+        "app",
+        "import gleam
+pub type Wibble {
+  Wibble
+}
+pub fn main() { 1 }
+",
+    );
+    assert!(engine.compile_please().result.is_ok());
+    let before = engine.compiler.definition_ids("app");
+    let names = before.iter().map(|(id, _)| id.name.as_str()).collect_vec();
+    assert_eq!(names, vec!["Wibble", "main"]);
+
+    // Adding a definition moves the others but does not change their ids.
+    _ = io.src_module(
+        "app",
+        "import gleam
+const one = 1
+pub type Wibble {
+  Wibble
+}
+pub fn main() { one }
+",
+    );
+    assert!(engine.compile_please().result.is_ok());
+    let after = engine.compiler.definition_ids("app");
+    assert_eq!(after.len(), 3);
+    let main = DefinitionId {
+        module: "app".into(),
+        name: "main".into(),
+    };
+    assert_eq!(after[2].0, main);
+    assert_eq!(before[1].0, main);
+    assert_ne!(after[2].1, before[1].1);
+    assert_eq!(engine.compiler.locate_definition(&main), Some(after[2].1));
+
+    let removed = DefinitionId {
+        module: "app".into(),
+        name: "removed".into(),
+    };
+    assert_eq!(engine.compiler.locate_definition(&removed), None);
+}
+
+#[test]
+fn external_functions() {
+    let io = LanguageServerTestIO::new();
+    let mut engine = setup_engine(&io);
+
+    _ = io.src_module(
+        "app",
+        "@external(erlang, \"app_ffi\", \"both\")
+@external(javascript, \"./app_ffi.mjs\", \"both\")
+pub fn both() -> Int
+
+@external(javascript, \"./app_ffi.mjs\", \"javascript_only\")
+pub fn javascript_only() -> Int {
+  1
+}
+
+pub fn native() -> Int {
+  both() + javascript_only()
+}
+",
+    );
+    assert!(engine.compile_please().result.is_ok());
+
+    let externals = engine
+        .compiler
+        .external_functions("app")
+        .into_iter()
+        .map(|(name, _, target)| (name.to_string(), target))
+        .collect_vec();
+    assert_eq!(
+        externals,
+        vec![
+            ("both".into(), Target::Erlang),
+            ("both".into(), Target::JavaScript),
+            ("javascript_only".into(), Target::JavaScript),
+        ]
+    );
+}
+
+#[test]
+fn module_constants() {
+    let io = LanguageServerTestIO::new();
+    let mut engine = setup_engine(&io);
+
+    let code = "pub const limit = 10
+
+const names = [\"Lucy\", \"Nubi\"]
+
+pub fn main() {
+  let local = 1
+  #(local, limit, names)
+}
+";
+    _ = io.src_module("app", code);
+    assert!(engine.compile_please().result.is_ok());
+
+    let constants = engine
+        .compiler
+        .module_constants("app")
+        .into_iter()
+        .map(|(name, type_, location)| {
+            let text = &code[location.start as usize..location.end as usize];
+            (name.to_string(), type_, text)
+        })
+        .collect_vec();
+    assert_eq!(
+        constants,
+        vec![
+            ("limit".into(), "Int".into(), "limit"),
+            ("names".into(), "List(String)".into(), "names"),
+        ]
+    );
+    assert!(engine.compiler.module_constants("missing").is_empty());
+}
+
+#[test]
+fn enclosing_test() {
+    let io = LanguageServerTestIO::new();
+    let mut engine = setup_engine(&io);
+
+    let code = "pub fn addition_test() {
+  let x = 1 + 1
+  let add = fn() { x + 2 }
+  add()
+}
+
+fn private_test() { 1 }
+
+pub fn helper() { fn() { 3 } }
+
+pub fn arguments_test(x) { x }
+";
+    _ = io.test_module("app_test", code);
+    _ = io.src_module("app", "pub fn main_test() { 1 }");
+    assert!(engine.compile_please().result.is_ok());
+
+    let at = |module, text: &str| {
+        let byte_index = code.find(text).expect("text") as u32;
+        engine.compiler.enclosing_test(module, byte_index)
+    };
+    assert_eq!(
+        at("app_test", "1 + 1"),
+        Some(("app_test".into(), "addition_test".into()))
+    );
+    assert_eq!(
+        at("app_test", "pub fn addition"),
+        Some(("app_test".into(), "addition_test".into()))
+    );
+    assert_eq!(
+        at("app_test", "x + 2"),
+        Some(("app_test".into(), "addition_test".into()))
+    );
+    assert_eq!(at("app_test", "private_test"), None);
+    assert_eq!(at("app_test", "helper"), None);
+    assert_eq!(at("app_test", "3"), None);
+    assert_eq!(at("app_test", "arguments_test"), None);
+
+    // Only modules in the test directory have tests.
+    assert_eq!(engine.compiler.enclosing_test("app", 8), None);
+}
+
+#[test]
+fn enclosing_function_return_type() {
+    let io = LanguageServerTestIO::new();
+    let mut engine = setup_engine(&io);
+
+    let code = "pub fn main() {
+  let x = [1, 2]
+  x
+}
+
+fn pair(x: element, y: other) {
+  #(y, x)
+}
+
+const size = 1
+";
+    _ = io.src_module("app", code);
+    assert!(engine.compile_please().result.is_ok());
+
+    let at = |text: &str| {
+        let byte_index = code.find(text).expect("text") as u32;
+        engine
+            .compiler
+            .enclosing_function_return_type("app", byte_index)
+    };
+    assert_eq!(at("[1, 2]"), Some("List(Int)".into()));
+    assert_eq!(at("pub fn main"), Some("List(Int)".into()));
+    assert_eq!(at("#(y, x)"), Some("#(b, a)".into()));
+    assert_eq!(at("size"), None);
+}
+
+#[test]
+fn module_function_signatures() {
+    let io = LanguageServerTestIO::new();
+    let mut engine = setup_engine(&io);
+    add_path_dep(&mut engine, "dep");
+
+    _ = io.path_dep_module(
+        "dep",
+        "list",
+        "pub fn map(list: List(a), with fun: fn(a) -> b) -> List(b) { todo }
+fn private() { 1 }
+pub type Wibble { Wibble(Int) }
+pub const size = 1
+pub fn length(list: List(a)) -> Int { 0 }
+",
+    );
+    _ = io.src_module("app", "pub fn main() { Nil }");
+    assert!(engine.compile_please().result.is_ok());
+
+    assert_eq!(
+        engine.compiler.module_function_signatures("list"),
+        vec![
+            (
+                "map".into(),
+                2,
+                "fn map(List(a), with: fn(a) -> b) -> List(b)".into()
+            ),
+            ("length".into(), 1, "fn length(List(a)) -> Int".into()),
+        ]
+    );
+    assert_eq!(
+        engine.compiler.module_function_signatures("app"),
+        vec![("main".into(), 0, "fn main() -> Nil".into())]
+    );
+    assert!(engine
+        .compiler
+        .module_function_signatures("missing")
+        .is_empty());
+}
@@ -0,0 +1,45 @@
+use crate::{ast::SrcSpan, language_server::validate_config};
+
+fn labels(config: &str) -> Vec<(String, SrcSpan)> {
+    validate_config(config)
+        .into_iter()
+        .filter_map(|diagnostic| diagnostic.location)
+        .map(|location| (location.label.text.unwrap_or_default(), location.label.span))
+        .collect()
+}
+
+#[test]
+fn valid_config() {
+    assert_eq!(labels("name = \"app\"\nversion = \"1.0.0\"\n"), vec![]);
+}
+
+#[test]
+fn invalid_field() {
+    let config = "name = \"app\"\ntarget = \"wasm\"\n";
+    assert_eq!(
+        labels(config),
+        vec![(
+            "unknown variant `wasm`, expected `erlang` or `javascript` for key `target`".into(),
+            SrcSpan::new(22, 28)
+        )]
+    );
+}
+
+#[test]
+fn missing_field() {
+    assert_eq!(
+        labels("version = \"1.0.0\"\n"),
+        vec![("missing field `name`".into(), SrcSpan::new(0, 17))]
+    );
+}
+
+#[test]
+fn invalid_syntax() {
+    assert_eq!(
+        labels("name = \"app\"\nversion = \n"),
+        vec![(
+            "expected a value, found a newline".into(),
+            SrcSpan::new(23, 23)
+        )]
+    );
+}
@@ -0,0 +1,154 @@
+use lsp_types::{Position, Range, TextEdit};
+
+use super::*;
+
+const NUMBERS: &str = "pub fn double(x: Int) -> Int { x * 2 }
+pub fn add(x: Int, y: Int) -> Int { x + y }
+pub fn sub(x x: Int, from y: Int) -> Int { y - x }
+pub fn adder(x: Int) -> fn(Int) -> Int { fn(y) { x + y } }
+pub fn not(x: Bool) -> Bool { !x }
+";
+
+/// Toggle the pipe in the expression of `main`, with the cursor at the first
+/// occurrence of `at`.
+fn toggle_pipe(expression: &str, at: &str) -> Option<TextEdit> {
+    let io = LanguageServerTestIO::new();
+    let mut engine = setup_engine(&io);
+
+    let code = format!(
+        "import numbers.{{double, add, sub, adder, not}}
+pub fn main() {{
+  {expression}
+}}
+"
+    );
+    _ = io.src_module("numbers", NUMBERS);
+    _ = io.src_module("app", &code);
+    let response = engine.compile_please();
+    assert!(response.result.is_ok());
+
+    let byte_index = code.find(at).expect("cursor position") as u32;
+    engine.compiler.toggle_pipe("app", byte_index)
+}
+
+/// The edit replacing the whole expression of `main`.
+fn edit(expression: &str, new_text: &str) -> Option<TextEdit> {
+    let end = Position::new(2, 2 + expression.len() as u32);
+    Some(TextEdit::new(
+        Range::new(Position::new(2, 2), end),
+        new_text.into(),
+    ))
+}
+
+#[test]
+fn nested_calls_to_pipe() {
+    let expression = "add(double(1), 2)";
+    assert_eq!(
+        toggle_pipe(expression, "add("),
+        edit(expression, "1 |> double |> add(2)")
+    );
+}
+
+#[test]
+fn pipe_to_nested_calls() {
+    let expression = "1 |> double |> add(2)";
+    assert_eq!(
+        toggle_pipe(expression, "add("),
+        edit(expression, "add(double(1), 2)")
+    );
+}
+
+#[test]
+fn inner_call_to_pipe() {
+    assert_eq!(
+        toggle_pipe("add(double(1), 2)", "double("),
+        Some(TextEdit::new(
+            Range::new(Position::new(2, 6), Position::new(2, 15)),
+            "1 |> double".into()
+        ))
+    );
+}
+
+#[test]
+fn labelled_arguments_are_kept() {
+    let expression = "sub(3, from: 10)";
+    assert_eq!(
+        toggle_pipe(expression, "sub("),
+        edit(expression, "3 |> sub(from: 10)")
+    );
+    let expression = "3 |> sub(from: 10)";
+    assert_eq!(
+        toggle_pipe(expression, "sub("),
+        edit(expression, "sub(3, from: 10)")
+    );
+}
+
+#[test]
+fn only_labelled_arguments() {
+    assert_eq!(toggle_pipe("sub(x: 3, from: 10)", "sub("), None);
+}
+
+#[test]
+fn qualified_functions() {
+    let expression = "numbers.double(1)";
+    assert_eq!(
+        toggle_pipe(expression, "numbers.double"),
+        edit(expression, "1 |> numbers.double")
+    );
+    let expression = "1 |> numbers.double";
+    assert_eq!(
+        toggle_pipe(expression, "numbers.double"),
+        edit(expression, "numbers.double(1)")
+    );
+}
+
+#[test]
+fn pipe_into_returned_function() {
+    // `adder(2)` takes one argument, so the pipe calls the function it
+    // returns rather than inserting into `adder`'s arguments.
+    let expression = "1 |> adder(2)";
+    assert_eq!(
+        toggle_pipe(expression, "adder("),
+        edit(expression, "adder(2)(1)")
+    );
+}
+
+#[test]
+fn low_precedence_operator_is_wrapped() {
+    let expression = "not(1 == 2)";
+    assert_eq!(
+        toggle_pipe(expression, "not("),
+        edit(expression, "{ 1 == 2 } |> not")
+    );
+}
+
+#[test]
+fn outside_any_call() {
+    assert_eq!(toggle_pipe("1", "1\n"), None);
+}
+
+#[test]
+fn pipe_stage_types() {
+    let io = LanguageServerTestIO::new();
+    let mut engine = setup_engine(&io);
+
+    let code = "import numbers.{double}
+pub fn main() {
+  1 |> double |> int_to_string |> fn(s) { [s] }
+}
+fn int_to_string(x: Int) -> String { \"\" }
+";
+    _ = io.src_module("numbers", NUMBERS);
+    _ = io.src_module("app", code);
+    assert!(engine.compile_please().result.is_ok());
+
+    let stage_type = |at: &str| {
+        let byte_index = code.find(at).expect("cursor position") as u32;
+        engine.compiler.pipe_stage_type("app", byte_index)
+    };
+    assert_eq!(stage_type("1 |>"), Some("Int".into()));
+    assert_eq!(stage_type("double |>"), Some("Int".into()));
+    assert_eq!(stage_type("int_to_string |>"), Some("String".into()));
+    assert_eq!(stage_type("fn(s)"), Some("List(String)".into()));
+    assert_eq!(stage_type("pub fn main"), None);
+}
@@ -0,0 +1,464 @@
+use itertools::Itertools;
+
+use crate::language_server::{
+    compiler::LspProjectCompiler,
+    references::{Reference, ReferenceScope, Workspace},
+};
+
+use super::*;
+
+/// The module, source text and start of each reference, for easier to read
+/// assertions.
+fn describe(
+    compilers: &[&LspProjectCompiler<FileSystemProxy<LanguageServerTestIO>>],
+    references: Vec<Reference>,
+) -> Vec<(String, String, u32)> {
+    references
+        .into_iter()
+        .map(|reference| {
+            let module = compilers
+                .iter()
+                .filter_map(|compiler| compiler.modules.get(&reference.module))
+                .find(|module| module.input_path == reference.path)
+                .expect("referenced module");
+            let text =
+                &module.code[reference.location.start as usize..reference.location.end as usize];
+            (
+                reference.module.to_string(),
+                text.to_string(),
+                reference.location.start,
+            )
+        })
+        .collect()
+}
+
+const SHARED: &str = "pub type Wibble { Wibble(Int) }
+pub const wobble = 1
+pub fn wubble() { wobble }
+";
+
+#[test]
+fn references_in_root_package() {
+    let io = LanguageServerTestIO::new();
+    let mut engine = setup_engine(&io);
+
+    _ = io.src_module("shared", SHARED);
+    let app = "import shared.{wubble as wub, Wibble}
+pub fn main() {
+  let Wibble(_) = Wibble(shared.wobble)
+  wub()
+  shared.wubble
+}
+";
+    let app_test = "import shared\npub const f = shared.wubble\n";
+    _ = io.src_module("app", app);
+    _ = io.test_module("app_test", app_test);
+    let response = engine.compile_please();
+    assert!(response.result.is_ok());
+
+    let compiler = &engine.compiler;
+    let describe = |references| describe(&[compiler], references);
+    let at = |code: &str, text: &str| code.find(text).expect("reference") as u32;
+
+    // Only the name is given, whether it is qualified, aliased or a pattern
+    // with arguments.
+    assert_eq!(
+        describe(compiler.references("shared", "wubble", ReferenceScope::default())),
+        vec![
+            ("app".into(), "wub".into(), at(app, "wub()")),
+            ("app".into(), "wubble".into(), at(app, ".wubble") + 1),
+            (
+                "app_test".into(),
+                "wubble".into(),
+                at(app_test, ".wubble") + 1
+            ),
+        ]
+    );
+    assert_eq!(
+        describe(compiler.references("shared", "wobble", ReferenceScope::RootPackage)),
+        vec![
+            ("app".into(), "wobble".into(), at(app, ".wobble") + 1),
+            ("shared".into(), "wobble".into(), at(SHARED, "{ wobble") + 2),
+        ]
+    );
+    assert_eq!(
+        describe(compiler.references("shared", "Wibble", ReferenceScope::RootPackage)),
+        vec![
+            ("app".into(), "Wibble".into(), at(app, "Wibble(_)")),
+            ("app".into(), "Wibble".into(), at(app, "Wibble(shared")),
+        ]
+    );
+    assert_eq!(
+        compiler.references("shared", "unknown", ReferenceScope::RootPackage),
+        vec![]
+    );
+}
+
+#[test]
+fn references_by_name() {
+    let io = LanguageServerTestIO::new();
+    let mut engine = setup_engine(&io);
+
+    _ = io.src_module(
+        "wobble",
+        "pub type Wibble { Wibble(Int) }\npub fn wobble() { 1 }\n",
+    );
+    // An alias that starts with the original name, a module and a value with
+    // the same name, and an aliased constructor in a pattern.
+    let app = "import wobble.{wobble as wobblex, Wibble as Wib}
+pub const w = wobble.wobble
+pub fn main() {
+  let Wib(_) = Wib(wobblex())
+  let wobble.Wibble(_) = wobble.Wibble(wobble.wobble())
+}
+";
+    _ = io.src_module("app", app);
+    assert!(engine.compile_please().result.is_ok());
+
+    let compiler = &engine.compiler;
+    let describe = |references| describe(&[compiler], references);
+    let nth = |text: &str, n: usize| app.match_indices(text).nth(n).expect("reference").0 as u32;
+
+    assert_eq!(
+        describe(compiler.references("wobble", "wobble", ReferenceScope::default())),
+        vec![
+            ("app".into(), "wobble".into(), nth(".wobble", 0) + 1),
+            ("app".into(), "wobblex".into(), nth("wobblex()", 0)),
+            ("app".into(), "wobble".into(), nth(".wobble()", 0) + 1),
+        ]
+    );
+    assert_eq!(
+        describe(compiler.references("wobble", "Wibble", ReferenceScope::default())),
+        vec![
+            ("app".into(), "Wib".into(), nth("Wib(", 0)),
+            ("app".into(), "Wib".into(), nth("Wib(", 1)),
+            ("app".into(), "Wibble".into(), nth(".Wibble", 0) + 1),
+            ("app".into(), "Wibble".into(), nth(".Wibble", 1) + 1),
+        ]
+    );
+}
+
+#[test]
+fn references_streaming() {
+    let io = LanguageServerTestIO::new();
+    let mut engine = setup_engine(&io);
+
+    _ = io.src_module("shared", SHARED);
+    _ = io.src_module(
+        "app",
+        "import shared\npub fn main() {\n  shared.wobble + shared.wobble\n}\n",
+    );
+    _ = io.src_module("other", "pub fn other() { 1 }");
+    _ = io.test_module("app_test", "import shared\npub const w = shared.wobble\n");
+    assert!(engine.compile_please().result.is_ok());
+
+    let compiler = &engine.compiler;
+    let mut batches = vec![];
+    compiler.references_streaming("shared", "wobble", ReferenceScope::RootPackage, |batch| {
+        batches.push(batch)
+    });
+
+    // One batch for each module that uses the value.
+    let modules = batches
+        .iter()
+        .map(|batch| {
+            batch
+                .iter()
+                .map(|reference| reference.module.as_str())
+                .collect_vec()
+        })
+        .collect_vec();
+    assert_eq!(
+        modules,
+        vec![vec!["app", "app"], vec!["shared"], vec!["app_test"]]
+    );
+    assert_eq!(
+        batches.concat(),
+        compiler.references("shared", "wobble", ReferenceScope::RootPackage)
+    );
+}
+
+#[test]
+fn references_in_workspace() {
+    // The `shared` package and a sibling package that depends on it.
+    let shared_io = LanguageServerTestIO::new();
+    let mut shared = setup_engine(&shared_io);
+    _ = shared_io.src_module("shared", SHARED);
+    assert!(shared.compile_please().result.is_ok());
+
+    let sibling_io = LanguageServerTestIO::new();
+    let mut sibling = setup_engine(&sibling_io);
+    add_path_dep(&mut sibling, "shared");
+    _ = sibling_io.path_dep_module("shared", "shared", SHARED);
+    let sibling_code = "import shared\npub fn main() { shared.wubble() }\n";
+    _ = sibling_io.src_module("sibling", sibling_code);
+    assert!(sibling.compile_please().result.is_ok());
+
+    let compilers = [&shared.compiler, &sibling.compiler];
+    let workspace = Workspace::new(compilers.to_vec());
+
+    assert_eq!(
+        describe(
+            &compilers,
+            shared
+                .compiler
+                .references("shared", "wobble", ReferenceScope::RootPackage)
+        ),
+        vec![(
+            "shared".into(),
+            "wobble".into(),
+            SHARED.find("{ wobble").expect("usage") as u32 + 2
+        )]
+    );
+    assert_eq!(
+        describe(
+            &compilers,
+            shared
+                .compiler
+                .references("shared", "wubble", ReferenceScope::Workspace(&workspace))
+        ),
+        vec![(
+            "sibling".into(),
+            "wubble".into(),
+            sibling_code.find(".wubble").expect("usage") as u32 + 1
+        )]
+    );
+}
+
+#[test]
+fn workspace_project_for_file() {
+    let app_io = LanguageServerTestIO::new();
+    let app = setup_engine(&app_io);
+    let other_io = LanguageServerTestIO::at("/other");
+    let other = setup_engine(&other_io);
+
+    let app_src = app_io.paths.src_directory().join("app.gleam");
+    let app_test = app_io.paths.test_directory().join("app_test.gleam");
+    let other_src = other_io.paths.src_directory().join("other.gleam");
+    let build = app_io.paths.build_directory().join("app.gleam");
+    assert!(app.compiler.owns_file(&app_src));
+    assert!(app.compiler.owns_file(&app_test));
+    assert!(!app.compiler.owns_file(&other_src));
+    assert!(!app.compiler.owns_file(&build));
+    assert!(other.compiler.owns_file(&other_src));
+
+    let workspace = Workspace::new(vec![&app.compiler, &other.compiler]);
+    let app_project = workspace.project_for(&app_src).unwrap();
+    assert!(std::ptr::eq(app_project, &app.compiler));
+    let other_project = workspace.project_for(&other_src).unwrap();
+    assert!(std::ptr::eq(other_project, &other.compiler));
+    assert!(workspace.project_for(&build).is_none());
+}
+
+#[test]
+fn type_references() {
+    let io = LanguageServerTestIO::new();
+    let mut engine = setup_engine(&io);
+
+    let shapes = "pub type Shape { Circle(Float) Square(Float) }
+pub type Shapes = List(Shape)
+pub fn area(shape: Shape) -> Float {
+  case shape {
+    Circle(r) -> r *. r
+    Square(s) -> s *. s
+  }
+}
+";
+    let app = "import shapes.{type Shape as S}
+import shapes as sh
+pub type Drawing { Drawing(shapes: List(sh.Shape)) }
+pub fn first(drawing: Drawing) -> Result(S, Nil) {
+  let f = fn(s: sh.Shape) -> S { s }
+  let x: Result(S, Nil) = Error(Nil)
+  case drawing {
+    Drawing([sh.Circle(_) as c, ..]) -> Ok(f(c))
+    _ -> x
+  }
+}
+";
+    _ = io.src_module("shapes", shapes);
+    _ = io.src_module("app", app);
+    assert!(engine.compile_please().result.is_ok());
+
+    let references = engine
+        .compiler
+        .type_references("shapes", "Shape")
+        .into_iter()
+        .map(|(module, location)| {
+            let code = if module == "app" { app } else { shapes };
+            let text = code
+                .get(location.start as usize..location.end as usize)
+                .unwrap_or_default();
+            (module.to_string(), text.to_string(), location.start)
+        })
+        .collect::<Vec<_>>();
+    let at = |code: &str, text: &str, nth: usize| {
+        code.match_indices(text).nth(nth).expect("reference").0 as u32
+    };
+    assert_eq!(
+        references,
+        vec![
+            ("app".into(), "Shape".into(), at(app, "Shape", 0)),
+            ("app".into(), "Shape".into(), at(app, "sh.Shape", 0) + 3),
+            ("app".into(), "S".into(), at(app, "Result(S", 0) + 7),
+            ("app".into(), "Shape".into(), at(app, "sh.Shape", 1) + 3),
+            ("app".into(), "S".into(), at(app, "-> S", 0) + 3),
+            ("app".into(), "S".into(), at(app, "Result(S", 1) + 7),
+            ("app".into(), "Circle".into(), at(app, "Circle", 0)),
+            (
+                "shapes".into(),
+                "Shape".into(),
+                at(shapes, "(Shape)", 0) + 1
+            ),
+            (
+                "shapes".into(),
+                "Shape".into(),
+                at(shapes, ": Shape", 0) + 2
+            ),
+            ("shapes".into(), "Circle".into(), at(shapes, "Circle(r)", 0)),
+            ("shapes".into(), "Square".into(), at(shapes, "Square(s)", 0)),
+        ]
+    );
+}
+
+#[test]
+fn reference_count_lenses() {
+    let io = LanguageServerTestIO::new();
+    let mut engine = setup_engine(&io);
+
+    _ = io.src_module("shared", SHARED);
+    _ = io.src_module(
+        "app",
+        "import shared
+pub fn main() {
+  #(shared.wubble(), shared.wobble, shared.wobble)
+}
+",
+    );
+    assert!(engine.compile_please().result.is_ok());
+
+    let lenses = engine
+        .compiler
+        .reference_count_lenses("shared")
+        .into_iter()
+        .map(|lens| {
+            let command = lens.command.expect("command");
+            (lens.range.start.line, command.title, command.arguments)
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        lenses,
+        vec![
+            (
+                1,
+                "3 references".into(),
+                Some(vec!["shared".into(), "wobble".into()])
+            ),
+            (
+                2,
+                "1 reference".into(),
+                Some(vec!["shared".into(), "wubble".into()])
+            ),
+        ]
+    );
+
+    let lenses = engine.compiler.reference_count_lenses("app");
+    assert_eq!(
+        lenses
+            .iter()
+            .filter_map(|lens| lens.command.as_ref())
+            .map(|command| command.title.as_str())
+            .collect::<Vec<_>>(),
+        vec!["0 references"]
+    );
+
+    // The counts are found again once the project is compiled again, even
+    // if the module with the lenses has not changed.
+    _ = io.src_module("app", "import shared\npub fn main() { shared.wobble }\n");
+    assert!(engine.compile_please().result.is_ok());
+    let titles = engine
+        .compiler
+        .reference_count_lenses("shared")
+        .into_iter()
+        .filter_map(|lens| lens.command)
+        .map(|command| command.title)
+        .collect::<Vec<_>>();
+    assert_eq!(titles, vec!["2 references", "0 references"]);
+}
+
+#[test]
+fn resolve_reference_count_lens() {
+    let io = LanguageServerTestIO::new();
+    let mut engine = setup_engine(&io);
+
+    _ = io.src_module("shared", SHARED);
+    _ = io.src_module("app", "import shared\npub fn main() { shared.wubble() }\n");
+    assert!(engine.compile_please().result.is_ok());
+    let lens = engine
+        .compiler
+        .reference_count_lenses("shared")
+        .into_iter()
+        .find(|lens| lens.range.start.line == 2)
+        .expect("wubble lens");
+
+    // The lens follows the value when code is added before it.
+    _ = io.src_module(
+        "shared",
+        &format!("pub fn extra() {{ wubble() }}\n{SHARED}"),
+    );
+    assert!(engine.compile_please().result.is_ok());
+    let resolved = engine.code_lens_resolve(lens.clone()).result.unwrap();
+    assert_eq!(resolved.data, lens.data);
+    assert_eq!(resolved.range.start.line, 3);
+    assert_eq!(
+        resolved.command.map(|command| command.title),
+        Some("2 references".into())
+    );
+
+    // A lens for a value that has been removed is left as it is.
+    _ = io.src_module("shared", "pub const wobble = 1\n");
+    _ = io.src_module("app", "pub fn main() { 1 }\n");
+    assert!(engine.compile_please().result.is_ok());
+    assert_eq!(engine.code_lens_resolve(lens.clone()).result.unwrap(), lens);
+}
+
+#[test]
+fn unused_public_functions() {
+    let io = LanguageServerTestIO::new();
+    let mut engine = setup_engine(&io);
+
+    _ = io.src_module(
+        "shared",
+        "pub fn used() { helper() }
+pub fn helper() { 1 }
+fn private() { 2 }
+pub fn main() { private() }
+",
+    );
+    _ = io.src_module(
+        "app",
+        "import shared.{used}\npub fn main() { used() + local() }\npub fn local() { 1 }\n",
+    );
+    _ = io.test_module(
+        "app_test",
+        "import shared
+pub fn helper_test() { shared.helper() }
+pub fn setup(x) { x }
+",
+    );
+    assert!(engine.compile_please().result.is_ok());
+
+    let unused = engine
+        .compiler
+        .unused_public_functions()
+        .into_iter()
+        .map(|(module, name, _)| (module.to_string(), name.to_string()))
+        .collect_vec();
+    assert_eq!(
+        unused,
+        vec![
+            ("app".to_string(), "local".to_string()),
+            ("app_test".to_string(), "setup".to_string()),
+        ]
+    );
+}
@@ -0,0 +1,90 @@
+use lsp_types::{Position, Range, TextEdit};
+
+use crate::language_server::path_to_uri;
+
+use super::*;
+
+#[test]
+fn rename_field() {
+    let io = LanguageServerTestIO::new();
+    let mut engine = setup_engine(&io);
+
+    let people = io.src_module(
+        "people",
+        "pub type Person {
+  Person(name: String, age: Int)
+}
+
+pub type Pet {
+  Pet(name: String)
+}
+
+pub fn greet(person: Person, pet: Pet) -> String {
+  case person {
+    Person(name: n, ..) if person.name == n -> n
+    Person(_, _) -> pet.name
+  }
+}
+",
+    );
+    let app = io.src_module(
+        "app",
+        "import people.{Person}
+
+const nobody = Person(name: \"\", age: 0)
+
+pub fn main() {
+  let alice = Person(\"Alice\", age: 30)
+  let bob = people.Person(..alice, name: \"Bob\")
+  #(nobody, bob.name, people.Pet(name: \"Rex\"))
+}
+",
+    );
+    assert!(engine.compile_please().result.is_ok());
+
+    // The positional use of the label in `app` and the label of `Pet` are not
+    // renamed.
+    let edit = |line, start, end| {
+        TextEdit::new(
+            Range::new(Position::new(line, start), Position::new(line, end)),
+            "full_name".into(),
+        )
+    };
+    let mut changes = edit_changes(engine.compiler.rename_field(
+        "people",
+        "Person",
+        "name",
+        "full_name",
+    ));
+    changes.sort_by(|a, b| a.0.cmp(&b.0));
+
+    assert_eq!(
+        changes,
+        vec![
+            (
+                path_to_uri(app).to_string(),
+                vec![edit(2, 22, 26), edit(6, 35, 39), edit(7, 16, 20)]
+            ),
+            (
+                path_to_uri(people).to_string(),
+                vec![edit(1, 9, 13), edit(10, 11, 15), edit(10, 34, 38)]
+            ),
+        ]
+    );
+
+    // A label only belongs to the types that define it.
+    let changes = edit_changes(
+        engine
+            .compiler
+            .rename_field("people", "Pet", "age", "years"),
+    );
+    assert_eq!(changes, vec![]);
+}
+
+fn edit_changes(edit: lsp_types::WorkspaceEdit) -> Vec<(String, Vec<TextEdit>)> {
+    edit.changes
+        .unwrap_or_default()
+        .into_iter()
+        .map(|(uri, edits)| (uri.to_string(), edits))
+        .collect()
+}
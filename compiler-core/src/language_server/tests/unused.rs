@@ -0,0 +1,196 @@
+use crate::{line_numbers::LineNumbers, type_, Warning};
+
+use super::*;
+
+/// The code after applying each fix for the unused variable warning of the
+/// code, with the title of the fix.
+fn fixes(code: &str) -> Vec<(String, String)> {
+    let io = LanguageServerTestIO::new();
+    let mut engine = setup_engine(&io);
+    _ = io.src_module("app", code);
+    let response = engine.compile_please();
+    assert!(response.result.is_ok());
+
+    let span = response
+        .warnings
+        .iter()
+        .find_map(|warning| match warning {
+            Warning::Type {
+                warning: type_::Warning::UnusedVariable { location, .. },
+                ..
+            } => Some(*location),
+            _ => None,
+        })
+        .expect("unused variable");
+
+    let line_numbers = LineNumbers::new(code);
+    engine
+        .compiler
+        .unused_variable_fixes("app", span)
+        .into_iter()
+        .map(|(title, edit)| {
+            let start = edit.range.start;
+            let end = edit.range.end;
+            let start = line_numbers.byte_index(start.line, start.character) as usize;
+            let end = line_numbers.byte_index(end.line, end.character) as usize;
+            let mut fixed = code.to_string();
+            fixed.replace_range(start..end, &edit.new_text);
+            (title, fixed)
+        })
+        .collect()
+}
+
+#[test]
+fn unused_let_variable() {
+    let code = "pub fn main() {
+  let x = 1
+  2
+}
+";
+    assert_eq!(
+        fixes(code),
+        vec![
+            (
+                "Prefix `x` with an underscore".into(),
+                "pub fn main() {
+  let _x = 1
+  2
+}
+"
+                .into()
+            ),
+            (
+                "Remove `x`".into(),
+                "pub fn main() {
+  1
+  2
+}
+"
+                .into()
+            ),
+        ]
+    );
+}
+
+#[test]
+fn unused_variable_in_pattern() {
+    let code = "pub fn main() {
+  let #(x, y) = #(1, 2)
+  y
+}
+";
+    assert_eq!(
+        fixes(code),
+        vec![
+            (
+                "Prefix `x` with an underscore".into(),
+                "pub fn main() {
+  let #(_x, y) = #(1, 2)
+  y
+}
+"
+                .into()
+            ),
+            (
+                "Remove `x`".into(),
+                "pub fn main() {
+  let #(_, y) = #(1, 2)
+  y
+}
+"
+                .into()
+            ),
+        ]
+    );
+}
+
+#[test]
+fn unused_pattern_assignment() {
+    let code = "pub fn main(list) {
+  case list {
+    [first, ..] as rest -> first
+    [] -> 0
+  }
+}
+";
+    assert_eq!(
+        fixes(code).pop(),
+        Some((
+            "Remove `rest`".into(),
+            "pub fn main(list) {
+  case list {
+    [first, ..] -> first
+    [] -> 0
+  }
+}
+"
+            .into()
+        ))
+    );
+}
+
+#[test]
+fn unused_string_prefix_assignment() {
+    let code = "pub fn main(name) {
+  case name {
+    \"Mr \" as title <> name -> name
+    _ -> \"\"
+  }
+}
+";
+    assert_eq!(
+        fixes(code).pop(),
+        Some((
+            "Remove `title`".into(),
+            "pub fn main(name) {
+  case name {
+    \"Mr \" <> name -> name
+    _ -> \"\"
+  }
+}
+"
+            .into()
+        ))
+    );
+}
+
+#[test]
+fn unused_labelled_argument() {
+    let code = "pub fn main(label x: Int) {
+  1
+}
+";
+    assert_eq!(
+        fixes(code),
+        vec![
+            (
+                "Prefix `x` with an underscore".into(),
+                "pub fn main(label _x: Int) {
+  1
+}
+"
+                .into()
+            ),
+            (
+                "Remove `x`".into(),
+                "pub fn main(label _: Int) {
+  1
+}
+"
+                .into()
+            ),
+        ]
+    );
+}
+
+#[test]
+fn no_fixes_for_alternative_patterns() {
+    let code = "pub fn main(pair) {
+  case pair {
+    #(1, x) | #(x, 1) -> 1
+    _ -> 0
+  }
+}
+";
+    assert_eq!(fixes(code), vec![]);
+}
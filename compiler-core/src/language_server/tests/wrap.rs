@@ -0,0 +1,124 @@
+use crate::{ast::SrcSpan, language_server::TypeMismatch};
+
+use super::*;
+
+fn fixes(code: &str, cursor: &str) -> Vec<String> {
+    let io = LanguageServerTestIO::new();
+    let mut engine = setup_engine(&io);
+    _ = io.src_module("gleam/option", "pub type Option(a) { Some(a) None }");
+    _ = io.src_module("app", code);
+    assert!(engine.compile_please().result.is_err());
+
+    let byte_index = code.find(cursor).expect("cursor") as u32;
+    engine
+        .compiler
+        .wrap_expression_fix("app", byte_index)
+        .into_iter()
+        .map(|edit| edit.new_text)
+        .collect()
+}
+
+#[test]
+fn wrap_in_result() {
+    let code = "pub fn main() -> Result(Int, String) {
+  1
+}
+";
+    assert_eq!(fixes(code, "1"), vec!["Ok(1)"]);
+
+    let code = "pub fn main() -> Result(Int, String) {
+  \"wibble\"
+}
+";
+    assert_eq!(fixes(code, "\"wibble\""), vec!["Error(\"wibble\")"]);
+
+    let code = "pub fn main() -> Result(Int, Int) {
+  1 + 2
+}
+";
+    assert_eq!(fixes(code, "1 + 2"), vec!["Ok(1 + 2)", "Error(1 + 2)"]);
+}
+
+#[test]
+fn wrap_argument() {
+    let code = "fn wibble(x: Result(List(a), Nil)) { x }
+pub fn main() {
+  wibble([1])
+}
+";
+    assert_eq!(fixes(code, "[1]"), vec!["Ok([1])"]);
+}
+
+#[test]
+fn wrap_in_option() {
+    let code = "import gleam/option.{type Option, Some as Just}
+pub fn main() -> Option(Int) {
+  1
+}
+";
+    assert_eq!(fixes(code, "1\n"), vec!["Just(1)"]);
+
+    let code = "import gleam/option.{type Option}
+pub fn main() -> Option(Int) {
+  Nil
+}
+";
+    assert_eq!(fixes(code, "Nil"), vec!["option.None"]);
+}
+
+#[test]
+fn no_fix_for_incompatible_types() {
+    let code = "pub fn main() -> Result(Int, Nil) {
+  \"wibble\"
+}
+";
+    assert!(fixes(code, "\"wibble\"").is_empty());
+
+    // Only the expression with the error is fixed.
+    let code = "pub fn main() -> Result(Int, Nil) {
+  1
+}
+";
+    assert!(fixes(code, "main").is_empty());
+}
+
+#[test]
+fn type_error_details() {
+    let io = LanguageServerTestIO::new();
+    let mut engine = setup_engine(&io);
+    let code = "pub fn main(x: element) -> Result(element, Nil) {
+  [x]
+}
+";
+    _ = io.src_module("app", code);
+    assert!(engine.compile_please().result.is_err());
+
+    let start = code.find("[x]").expect("list") as u32;
+    let location = SrcSpan::new(start, start + 3);
+    assert_eq!(
+        engine
+            .compiler
+            .type_error_details("app", SrcSpan::new(start + 1, start + 1)),
+        Some(TypeMismatch {
+            expected: "Result(element, Nil)".into(),
+            given: "List(element)".into(),
+            location,
+        })
+    );
+    assert_eq!(
+        engine
+            .compiler
+            .type_error_details("app", SrcSpan::new(0, 3)),
+        None
+    );
+
+    // Other errors have no types to compare.
+    _ = io.src_module("app", "pub fn main() { wibble }");
+    assert!(engine.compile_please().result.is_err());
+    assert_eq!(
+        engine
+            .compiler
+            .type_error_details("app", SrcSpan::new(16, 16)),
+        None
+    );
+}
@@ -0,0 +1,23 @@
+/// How much the language server logs about its own work, such as the modules
+/// it compiles. The client can change this at any time with `$/setTrace`, so
+/// a user's project can be debugged without restarting the server.
+///
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub enum TraceLevel {
+    #[default]
+    Off,
+    /// The outcome of each compilation.
+    Messages,
+    /// The outcome of each compilation and every module type checked by it.
+    Verbose,
+}
+
+impl From<lsp_types::TraceValue> for TraceLevel {
+    fn from(value: lsp_types::TraceValue) -> Self {
+        match value {
+            lsp_types::TraceValue::Off => TraceLevel::Off,
+            lsp_types::TraceValue::Messages => TraceLevel::Messages,
+            lsp_types::TraceValue::Verbose => TraceLevel::Verbose,
+        }
+    }
+}
@@ -0,0 +1,152 @@
+use crate::{
+    ast::{BinOp, Definition, SrcSpan, Statement, TypedExpr, TypedStatement},
+    diagnostic::{Diagnostic, Label, Level, Location, Tag},
+    language_server::{
+        compiler::LspProjectCompiler,
+        visit::{walk_module, Visit},
+    },
+};
+
+impl<IO> LspProjectCompiler<IO> {
+    /// A warning for the statements of each block that come after a statement
+    /// that always panics, such as one that is or contains `panic` or `todo`,
+    /// as they can never be run. Each warning is for the code from the first
+    /// unreachable statement to the end of the block, and has the code
+    /// `unreachable_code`.
+    ///
+    /// Unreachable code within code that is already unreachable is not
+    /// reported again. The module is searched as it was in the most recent
+    /// compilation in which it compiled.
+    pub fn unreachable_code(&self, module: &str) -> Vec<Diagnostic> {
+        let Some(module) = self.modules.get(module) else {
+            return vec![];
+        };
+        let mut finder = UnreachableFinder { found: vec![] };
+        for definition in &module.ast.definitions {
+            if let Definition::Function(function) = definition {
+                finder.check_block(&function.body);
+            }
+        }
+        walk_module(&module.ast, &mut finder);
+
+        let mut found = finder.found;
+        found.sort_by_key(|location| location.start);
+        found.dedup();
+        let outermost = found.iter().filter(|location| {
+            !found.iter().any(|outer| {
+                outer != *location && outer.start <= location.start && location.end <= outer.end
+            })
+        });
+        outermost
+            .map(|location| Diagnostic {
+                title: "Unreachable code".into(),
+                text: "This code comes after code that always panics, so it is never run.".into(),
+                level: Level::Warning,
+                tags: vec![Tag::Unnecessary],
+                code: Some("unreachable_code"),
+                location: Some(Location {
+                    src: module.code.clone(),
+                    path: module.input_path.clone(),
+                    label: Label {
+                        text: Some("This code is unreachable".into()),
+                        span: *location,
+                    },
+                    extra_labels: vec![],
+                }),
+                hint: None,
+            })
+            .collect()
+    }
+}
+
+struct UnreachableFinder {
+    found: Vec<SrcSpan>,
+}
+
+impl UnreachableFinder {
+    fn check_block(&mut self, statements: &[TypedStatement]) {
+        let Some(index) = statements.iter().position(statement_diverges) else {
+            return;
+        };
+        if let (Some(first), Some(last)) = (statements.get(index + 1), statements.last()) {
+            self.found
+                .push(SrcSpan::new(first.location().start, last.location().end));
+        }
+    }
+}
+
+impl<'ast> Visit<'ast> for UnreachableFinder {
+    fn visit_expr(&mut self, expr: &'ast TypedExpr) {
+        match expr {
+            TypedExpr::Block { statements, .. }
+            | TypedExpr::Fn {
+                body: statements, ..
+            } => self.check_block(statements),
+            _ => (),
+        }
+    }
+}
+
+fn statement_diverges(statement: &TypedStatement) -> bool {
+    match statement {
+        Statement::Expression(expression) => diverges(expression),
+        Statement::Assignment(assignment) => diverges(&assignment.value),
+        Statement::Use(_) => false,
+    }
+}
+
+/// Whether evaluating the expression always panics. A function body is not
+/// evaluated where the function is written, and the right side of `&&` and
+/// `||` is not always evaluated, so they are not considered.
+fn diverges(expr: &TypedExpr) -> bool {
+    match expr {
+        TypedExpr::Panic { .. } | TypedExpr::Todo { .. } => true,
+
+        TypedExpr::Block { statements, .. } => statements.iter().any(statement_diverges),
+
+        TypedExpr::Pipeline {
+            assignments,
+            finally,
+            ..
+        } => {
+            assignments
+                .iter()
+                .any(|assignment| diverges(&assignment.value))
+                || diverges(finally)
+        }
+
+        TypedExpr::Call { fun, args, .. } => {
+            diverges(fun) || args.iter().any(|arg| diverges(&arg.value))
+        }
+
+        TypedExpr::BinOp {
+            name, left, right, ..
+        } => diverges(left) || (!matches!(name, BinOp::And | BinOp::Or) && diverges(right)),
+
+        TypedExpr::Case {
+            subjects, clauses, ..
+        } => {
+            subjects.iter().any(diverges)
+                || (!clauses.is_empty() && clauses.iter().all(|clause| diverges(&clause.then)))
+        }
+
+        TypedExpr::List { elements, tail, .. } => {
+            elements.iter().any(diverges) || tail.as_deref().is_some_and(diverges)
+        }
+
+        TypedExpr::Tuple { elems, .. } => elems.iter().any(diverges),
+
+        TypedExpr::RecordAccess { record, .. } => diverges(record),
+        TypedExpr::TupleIndex { tuple, .. } => diverges(tuple),
+        TypedExpr::NegateBool { value, .. } | TypedExpr::NegateInt { value, .. } => diverges(value),
+
+        TypedExpr::Int { .. }
+        | TypedExpr::Float { .. }
+        | TypedExpr::String { .. }
+        | TypedExpr::Var { .. }
+        | TypedExpr::Fn { .. }
+        | TypedExpr::ModuleSelect { .. }
+        | TypedExpr::RecordUpdate { .. }
+        | TypedExpr::BitArray { .. } => false,
+    }
+}
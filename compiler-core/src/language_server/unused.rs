@@ -0,0 +1,247 @@
+use crate::{
+    ast::{
+        AssignName, Definition, Pattern, SrcSpan, Statement, TypedArg, TypedExpr, TypedPattern,
+        TypedStatement,
+    },
+    language_server::{
+        compiler::LspProjectCompiler,
+        src_span_to_lsp_range,
+        visit::{walk_module, walk_pattern, Visit},
+    },
+    line_numbers::LineNumbers,
+};
+use ecow::EcoString;
+use lsp_types::TextEdit;
+
+impl<IO> LspProjectCompiler<IO> {
+    /// Fixes for the unused variable warning at the span, which has the code
+    /// `unused_variable`: prefixing the variable with an underscore, or
+    /// removing it. Each edit is labelled with a title for the fix.
+    ///
+    /// A variable bound by a `let` assignment is removed along with the
+    /// assignment, keeping its value, and one bound by `as` is removed along
+    /// with the `as`. Any other variable in a pattern, and an argument, is
+    /// replaced with `_` so that the pattern is still well formed. There are
+    /// no fixes for a variable bound by alternative patterns of a case
+    /// clause, as each of the alternatives would need to be fixed.
+    ///
+    /// Only modules that compiled in the most recent compilation are fixed.
+    pub fn unused_variable_fixes(&self, module: &str, span: SrcSpan) -> Vec<(String, TextEdit)> {
+        let Some(module) = self.current_module(module) else {
+            return vec![];
+        };
+        let mut finder = UnusedVariableFinder {
+            code: &module.code,
+            span,
+            found: None,
+            in_alternatives: false,
+        };
+        for definition in &module.ast.definitions {
+            if let Definition::Function(function) = definition {
+                finder.find_in_arguments(&function.arguments);
+            }
+        }
+        walk_module(&module.ast, &mut finder);
+
+        let Some(variable) = finder.found else {
+            return vec![];
+        };
+        if finder.in_alternatives {
+            return vec![];
+        }
+
+        let line_numbers = LineNumbers::new(&module.code);
+        let edit = |location, new_text: &str| TextEdit {
+            range: src_span_to_lsp_range(location, &line_numbers),
+            new_text: new_text.into(),
+        };
+        let name = &variable.name;
+        let removal = match variable.removal {
+            Removal::Delete(location) => edit(location, ""),
+            Removal::Discard => edit(variable.name_location, "_"),
+        };
+        vec![
+            (
+                format!("Prefix `{name}` with an underscore"),
+                edit(
+                    SrcSpan::new(variable.name_location.start, variable.name_location.start),
+                    "_",
+                ),
+            ),
+            (format!("Remove `{name}`"), removal),
+        ]
+    }
+}
+
+/// An unused variable, and how to remove it.
+struct UnusedVariable {
+    name: EcoString,
+    name_location: SrcSpan,
+    removal: Removal,
+}
+
+enum Removal {
+    /// Delete the code, which includes the name of the variable.
+    Delete(SrcSpan),
+    /// Replace the name of the variable with `_`.
+    Discard,
+}
+
+struct UnusedVariableFinder<'a> {
+    code: &'a str,
+    /// The location of the warning, which is where the variable is defined.
+    span: SrcSpan,
+    found: Option<UnusedVariable>,
+    in_alternatives: bool,
+}
+
+impl UnusedVariableFinder<'_> {
+    fn find(&mut self, name: &EcoString, name_start: u32, removal: Removal) {
+        let name_location = SrcSpan::new(name_start, name_start + name.len() as u32);
+        // Variables added by the compiler, such as for pipelines and `use`
+        // patterns, have a location where their name is not written.
+        let text = self
+            .code
+            .get(name_location.start as usize..name_location.end as usize);
+        if self.found.is_none() && text == Some(name.as_str()) {
+            self.found = Some(UnusedVariable {
+                name: name.clone(),
+                name_location,
+                removal,
+            });
+        }
+    }
+
+    fn find_in_arguments(&mut self, arguments: &[TypedArg]) {
+        for argument in arguments {
+            if argument.location != self.span {
+                continue;
+            }
+            if let Some((name, start)) = argument.variable_name_start(self.code) {
+                self.find(name, start, Removal::Discard);
+            }
+        }
+    }
+
+    /// The end of the pattern that starts at `start` and is assigned a name
+    /// with `as`. The location of some patterns, such as lists, does not
+    /// include all of their code, so it is found from the code instead.
+    fn end_before_as(&self, start: u32, name_start: u32) -> Option<u32> {
+        let text = self.code.get(start as usize..name_start as usize)?;
+        let pattern = text.trim_end().strip_suffix("as")?.trim_end();
+        Some(start + pattern.len() as u32)
+    }
+}
+
+impl<'ast> Visit<'ast> for UnusedVariableFinder<'_> {
+    fn visit_statement(&mut self, statement: &'ast TypedStatement) {
+        if let Statement::Assignment(assignment) = statement {
+            if let Pattern::Variable { name, location, .. } = &assignment.pattern {
+                if *location == self.span {
+                    let assignment_location =
+                        SrcSpan::new(assignment.location.start, assignment.value.location().start);
+                    self.find(name, location.start, Removal::Delete(assignment_location));
+                }
+            }
+        }
+    }
+
+    fn visit_expr(&mut self, expr: &'ast TypedExpr) {
+        match expr {
+            TypedExpr::Fn { args, .. } => self.find_in_arguments(args),
+
+            TypedExpr::Case { clauses, .. } => {
+                for clause in clauses {
+                    if clause.alternative_patterns.is_empty() {
+                        continue;
+                    }
+                    let mut defines = Defines {
+                        span: self.span,
+                        found: false,
+                    };
+                    for pattern in clause
+                        .pattern
+                        .iter()
+                        .chain(clause.alternative_patterns.iter().flatten())
+                    {
+                        walk_pattern(pattern, &mut defines);
+                    }
+                    self.in_alternatives = self.in_alternatives || defines.found;
+                }
+            }
+
+            _ => (),
+        }
+    }
+
+    fn visit_pattern(&mut self, pattern: &'ast TypedPattern) {
+        match pattern {
+            Pattern::Variable { name, location, .. } if *location == self.span => {
+                self.find(name, location.start, Removal::Discard)
+            }
+
+            Pattern::Assign {
+                name,
+                location,
+                pattern,
+            } if *location == self.span => {
+                if let Some(end) = self.end_before_as(pattern.location().start, location.start) {
+                    let as_location = SrcSpan::new(end, location.end);
+                    self.find(name, location.start, Removal::Delete(as_location));
+                }
+            }
+
+            Pattern::StringPrefix {
+                left_location,
+                left_side_assignment,
+                right_side_assignment,
+                right_location,
+                ..
+            } => {
+                if let Some((name, location)) = left_side_assignment {
+                    if *location == self.span {
+                        if let Some(end) = self.end_before_as(left_location.start, location.start) {
+                            let as_location = SrcSpan::new(end, location.end);
+                            self.find(name, location.start, Removal::Delete(as_location));
+                        }
+                    }
+                }
+                if let AssignName::Variable(name) = right_side_assignment {
+                    if *right_location == self.span {
+                        self.find(name, right_location.start, Removal::Discard);
+                    }
+                }
+            }
+
+            _ => (),
+        }
+    }
+}
+
+/// Whether a pattern defines a variable at the span.
+struct Defines {
+    span: SrcSpan,
+    found: bool,
+}
+
+impl<'ast> Visit<'ast> for Defines {
+    fn visit_pattern(&mut self, pattern: &'ast TypedPattern) {
+        let defines = match pattern {
+            Pattern::Variable { location, .. } | Pattern::Assign { location, .. } => {
+                *location == self.span
+            }
+            Pattern::StringPrefix {
+                left_side_assignment,
+                right_location,
+                ..
+            } => {
+                left_side_assignment
+                    .as_ref()
+                    .is_some_and(|(_, location)| *location == self.span)
+                    || *right_location == self.span
+            }
+            _ => false,
+        };
+        self.found = self.found || defines;
+    }
+}
@@ -0,0 +1,248 @@
+use crate::ast::{
+    ClauseGuard, Definition, Pattern, Statement, TypedClauseGuard, TypedConstant, TypedExpr,
+    TypedModule, TypedPattern, TypedStatement,
+};
+
+/// A read-only walk over a typed module, used by the language server features
+/// that need to look at every node rather than only the one under the cursor.
+///
+/// Each method is called for a node before any of its children.
+///
+pub trait Visit<'ast> {
+    fn visit_statement(&mut self, _statement: &'ast TypedStatement) {}
+    fn visit_expr(&mut self, _expr: &'ast TypedExpr) {}
+    fn visit_pattern(&mut self, _pattern: &'ast TypedPattern) {}
+    fn visit_constant(&mut self, _constant: &'ast TypedConstant) {}
+    fn visit_clause_guard(&mut self, _guard: &'ast TypedClauseGuard) {}
+}
+
+pub fn walk_module<'ast>(module: &'ast TypedModule, visitor: &mut impl Visit<'ast>) {
+    for definition in &module.definitions {
+        match definition {
+            Definition::Function(function) => {
+                for statement in &function.body {
+                    walk_statement(statement, visitor);
+                }
+            }
+            Definition::ModuleConstant(constant) => walk_constant(&constant.value, visitor),
+            Definition::TypeAlias(_) | Definition::CustomType(_) | Definition::Import(_) => (),
+        }
+    }
+}
+
+pub fn walk_statement<'ast>(statement: &'ast TypedStatement, visitor: &mut impl Visit<'ast>) {
+    visitor.visit_statement(statement);
+    match statement {
+        Statement::Expression(expression) => walk_expr(expression, visitor),
+        Statement::Assignment(assignment) => {
+            walk_expr(&assignment.value, visitor);
+            walk_pattern(&assignment.pattern, visitor);
+        }
+        Statement::Use(_) => (),
+    }
+}
+
+pub fn walk_expr<'ast>(expr: &'ast TypedExpr, visitor: &mut impl Visit<'ast>) {
+    visitor.visit_expr(expr);
+    match expr {
+        TypedExpr::Int { .. }
+        | TypedExpr::Float { .. }
+        | TypedExpr::String { .. }
+        | TypedExpr::Var { .. }
+        | TypedExpr::ModuleSelect { .. } => (),
+
+        TypedExpr::Block { statements, .. } => {
+            for statement in statements {
+                walk_statement(statement, visitor);
+            }
+        }
+
+        TypedExpr::Pipeline {
+            assignments,
+            finally,
+            ..
+        } => {
+            for assignment in assignments {
+                walk_expr(&assignment.value, visitor);
+                walk_pattern(&assignment.pattern, visitor);
+            }
+            walk_expr(finally, visitor);
+        }
+
+        TypedExpr::Fn { body, .. } => {
+            for statement in body {
+                walk_statement(statement, visitor);
+            }
+        }
+
+        TypedExpr::List { elements, tail, .. } => {
+            for element in elements {
+                walk_expr(element, visitor);
+            }
+            if let Some(tail) = tail {
+                walk_expr(tail, visitor);
+            }
+        }
+
+        TypedExpr::Call { fun, args, .. } => {
+            walk_expr(fun, visitor);
+            for arg in args {
+                walk_expr(&arg.value, visitor);
+            }
+        }
+
+        TypedExpr::BinOp { left, right, .. } => {
+            walk_expr(left, visitor);
+            walk_expr(right, visitor);
+        }
+
+        TypedExpr::Case {
+            subjects, clauses, ..
+        } => {
+            for subject in subjects {
+                walk_expr(subject, visitor);
+            }
+            for clause in clauses {
+                let patterns = std::iter::once(&clause.pattern)
+                    .chain(&clause.alternative_patterns)
+                    .flatten();
+                for pattern in patterns {
+                    walk_pattern(pattern, visitor);
+                }
+                if let Some(guard) = &clause.guard {
+                    walk_clause_guard(guard, visitor);
+                }
+                walk_expr(&clause.then, visitor);
+            }
+        }
+
+        TypedExpr::RecordAccess { record, .. } => walk_expr(record, visitor),
+        TypedExpr::TupleIndex { tuple, .. } => walk_expr(tuple, visitor),
+
+        TypedExpr::Tuple { elems, .. } => {
+            for elem in elems {
+                walk_expr(elem, visitor);
+            }
+        }
+
+        TypedExpr::Todo { message, .. } | TypedExpr::Panic { message, .. } => {
+            if let Some(message) = message {
+                walk_expr(message, visitor);
+            }
+        }
+
+        TypedExpr::BitArray { segments, .. } => {
+            for segment in segments {
+                walk_expr(&segment.value, visitor);
+            }
+        }
+
+        TypedExpr::RecordUpdate { spread, args, .. } => {
+            walk_expr(spread, visitor);
+            for arg in args {
+                walk_expr(&arg.value, visitor);
+            }
+        }
+
+        TypedExpr::NegateBool { value, .. } | TypedExpr::NegateInt { value, .. } => {
+            walk_expr(value, visitor)
+        }
+    }
+}
+
+pub fn walk_pattern<'ast>(pattern: &'ast TypedPattern, visitor: &mut impl Visit<'ast>) {
+    visitor.visit_pattern(pattern);
+    match pattern {
+        Pattern::Int { .. }
+        | Pattern::Float { .. }
+        | Pattern::String { .. }
+        | Pattern::Variable { .. }
+        | Pattern::VarUsage { .. }
+        | Pattern::Discard { .. }
+        | Pattern::StringPrefix { .. } => (),
+
+        Pattern::Assign { pattern, .. } => walk_pattern(pattern, visitor),
+
+        Pattern::List { elements, tail, .. } => {
+            for element in elements {
+                walk_pattern(element, visitor);
+            }
+            if let Some(tail) = tail {
+                walk_pattern(tail, visitor);
+            }
+        }
+
+        Pattern::Constructor { arguments, .. } => {
+            for argument in arguments {
+                walk_pattern(&argument.value, visitor);
+            }
+        }
+
+        Pattern::Tuple { elems, .. } => {
+            for elem in elems {
+                walk_pattern(elem, visitor);
+            }
+        }
+
+        Pattern::BitArray { segments, .. } => {
+            for segment in segments {
+                walk_pattern(&segment.value, visitor);
+            }
+        }
+    }
+}
+
+pub fn walk_clause_guard<'ast>(guard: &'ast TypedClauseGuard, visitor: &mut impl Visit<'ast>) {
+    visitor.visit_clause_guard(guard);
+    match guard {
+        ClauseGuard::Equals { left, right, .. }
+        | ClauseGuard::NotEquals { left, right, .. }
+        | ClauseGuard::GtInt { left, right, .. }
+        | ClauseGuard::GtEqInt { left, right, .. }
+        | ClauseGuard::LtInt { left, right, .. }
+        | ClauseGuard::LtEqInt { left, right, .. }
+        | ClauseGuard::GtFloat { left, right, .. }
+        | ClauseGuard::GtEqFloat { left, right, .. }
+        | ClauseGuard::LtFloat { left, right, .. }
+        | ClauseGuard::LtEqFloat { left, right, .. }
+        | ClauseGuard::Or { left, right, .. }
+        | ClauseGuard::And { left, right, .. } => {
+            walk_clause_guard(left, visitor);
+            walk_clause_guard(right, visitor);
+        }
+
+        ClauseGuard::Not { expression, .. } => walk_clause_guard(expression, visitor),
+        ClauseGuard::TupleIndex { tuple, .. } => walk_clause_guard(tuple, visitor),
+        ClauseGuard::FieldAccess { container, .. } => walk_clause_guard(container, visitor),
+        ClauseGuard::Var { .. } | ClauseGuard::ModuleSelect { .. } => (),
+        ClauseGuard::Constant(constant) => walk_constant(constant, visitor),
+    }
+}
+
+pub fn walk_constant<'ast>(constant: &'ast TypedConstant, visitor: &mut impl Visit<'ast>) {
+    visitor.visit_constant(constant);
+    match constant {
+        TypedConstant::Int { .. }
+        | TypedConstant::Float { .. }
+        | TypedConstant::String { .. }
+        | TypedConstant::Var { .. } => (),
+
+        TypedConstant::Tuple { elements, .. } | TypedConstant::List { elements, .. } => {
+            for element in elements {
+                walk_constant(element, visitor);
+            }
+        }
+
+        TypedConstant::Record { args, .. } => {
+            for arg in args {
+                walk_constant(&arg.value, visitor);
+            }
+        }
+
+        TypedConstant::BitArray { segments, .. } => {
+            for segment in segments {
+                walk_constant(&segment.value, visitor);
+            }
+        }
+    }
+}
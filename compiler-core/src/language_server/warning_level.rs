@@ -0,0 +1,78 @@
+use std::collections::HashSet;
+
+use serde::Deserialize;
+
+use crate::{type_, Warning};
+
+/// Which warnings are reported to the client, as set with the `warningLevel`
+/// setting: `"all"`, `"errorsOnly"` or `{ "custom": ["unused", "unfinished"] }`.
+///
+/// The level only changes what is reported. The type checker still finds
+/// every warning as it checks each module, and those of the kinds that are
+/// left out are removed once the compilation has finished, so compiling
+/// takes as long at every level. Only the passes that the language server
+/// runs itself to find warnings, such as for uses of `panic`, are skipped for
+/// the kinds that are left out. Features that are not driven by warnings,
+/// such as the code action for removing unused imports, are not affected.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum WarningLevel {
+    #[default]
+    All,
+    ErrorsOnly,
+    Custom(HashSet<WarningKind>),
+}
+
+impl WarningLevel {
+    pub fn includes(&self, kind: WarningKind) -> bool {
+        match self {
+            WarningLevel::All => true,
+            WarningLevel::ErrorsOnly => false,
+            WarningLevel::Custom(kinds) => kinds.contains(&kind),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum WarningKind {
+    /// Unused imports, variables, definitions and values.
+    Unused,
+    /// Uses of deprecated values and of packages that are not dependencies.
+    Deprecated,
+    /// Uses of `todo` and `panic`.
+    Unfinished,
+    Other,
+}
+
+impl WarningKind {
+    pub fn of(warning: &Warning) -> Self {
+        let Warning::Type { warning, .. } = warning else {
+            return WarningKind::Other;
+        };
+        match warning {
+            type_::Warning::UnusedType { .. }
+            | type_::Warning::UnusedConstructor { .. }
+            | type_::Warning::UnusedImportedValue { .. }
+            | type_::Warning::UnusedImportedModule { .. }
+            | type_::Warning::UnusedImportedModuleAlias { .. }
+            | type_::Warning::UnusedPrivateModuleConstant { .. }
+            | type_::Warning::UnusedPrivateFunction { .. }
+            | type_::Warning::UnusedVariable { .. }
+            | type_::Warning::UnusedLiteral { .. }
+            | type_::Warning::ImplicitlyDiscardedResult { .. } => WarningKind::Unused,
+
+            type_::Warning::DeprecatedItem { .. }
+            | type_::Warning::TransitiveDependencyImported { .. } => WarningKind::Deprecated,
+
+            type_::Warning::Todo { .. } => WarningKind::Unfinished,
+
+            type_::Warning::NoFieldsRecordUpdate { .. }
+            | type_::Warning::AllFieldsRecordUpdate { .. }
+            | type_::Warning::UnnecessaryDoubleIntNegation { .. }
+            | type_::Warning::UnnecessaryDoubleBoolNegation { .. }
+            | type_::Warning::InefficientEmptyListCheck { .. }
+            | type_::Warning::UnreachableCaseClause { .. } => WarningKind::Other,
+        }
+    }
+}
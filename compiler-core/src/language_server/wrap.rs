@@ -0,0 +1,166 @@
+use std::sync::Arc;
+
+use crate::{
+    ast::Definition,
+    language_server::{
+        compiler::{LspProjectCompiler, ModuleTypeError},
+        src_span_to_lsp_range,
+    },
+    line_numbers::LineNumbers,
+    type_::{self, Type, TypeVar},
+};
+use ecow::EcoString;
+use lsp_types::TextEdit;
+
+impl<IO> LspProjectCompiler<IO> {
+    /// Fixes for a type error where an expression was given where a `Result`
+    /// or `Option` of its type was expected, by wrapping it in `Ok`, `Error`
+    /// or `Some`. A literal `Nil` where an `Option` was expected is replaced
+    /// with `None`. Each edit is an alternative fix for the error.
+    ///
+    /// Only the type error at the byte index from the most recent compilation
+    /// is fixed, and a constructor is only suggested if its type is compatible
+    /// with the expected type. The `Option` constructors are only suggested
+    /// if the module imports `gleam/option`, using the name they are imported
+    /// with.
+    pub fn wrap_expression_fix(&self, module: &str, byte_index: u32) -> Vec<TextEdit> {
+        let Some(ModuleTypeError {
+            src,
+            error:
+                type_::Error::CouldNotUnify {
+                    location,
+                    expected,
+                    given,
+                    ..
+                },
+            ..
+        }) = self.type_error(module)
+        else {
+            return vec![];
+        };
+        if !(location.start..=location.end).contains(&byte_index) {
+            return vec![];
+        }
+        let Some(expression) = src.get(location.start as usize..location.end as usize) else {
+            return vec![];
+        };
+
+        let mut fixes = vec![];
+        if let Some([ok, error]) = named_args(expected, "gleam", "Result").as_deref() {
+            if compatible(ok, given) {
+                fixes.push(format!("Ok({expression})"));
+            }
+            if compatible(error, given) {
+                fixes.push(format!("Error({expression})"));
+            }
+        }
+        if let Some([value]) = named_args(expected, "gleam/option", "Option").as_deref() {
+            let constructor = |name| self.option_constructor(module, name);
+            if compatible(value, given) {
+                fixes.extend(constructor("Some").map(|some| format!("{some}({expression})")));
+            }
+            if given.is_nil() && expression == "Nil" {
+                fixes.extend(constructor("None").map(EcoString::into));
+            }
+        }
+
+        let line_numbers = LineNumbers::new(src);
+        fixes
+            .into_iter()
+            .map(|new_text| TextEdit {
+                range: src_span_to_lsp_range(*location, &line_numbers),
+                new_text,
+            })
+            .collect()
+    }
+
+    /// How a constructor of `gleam/option` is referred to in a module, or
+    /// `None` if the module does not import `gleam/option`.
+    fn option_constructor(&self, module: &str, name: &str) -> Option<EcoString> {
+        self.untyped_module(module)?
+            .definitions
+            .iter()
+            .find_map(|definition| match &definition.definition {
+                Definition::Import(import) if import.module == "gleam/option" => {
+                    let unqualified = import
+                        .unqualified_values
+                        .iter()
+                        .find(|value| value.name == name);
+                    match unqualified {
+                        Some(value) => Some(value.used_name().clone()),
+                        None => Some(format!("{}.{name}", import.used_name()?).into()),
+                    }
+                }
+                _ => None,
+            })
+    }
+}
+
+/// The arguments of a type if it is the named type.
+fn named_args(type_: &Type, module: &str, name: &str) -> Option<Vec<Arc<Type>>> {
+    match type_ {
+        Type::Named {
+            module: m,
+            name: n,
+            args,
+            ..
+        } if m == module && n == name => Some(args.clone()),
+        Type::Var { type_ } => match &*type_.borrow() {
+            TypeVar::Link { type_ } => named_args(type_, module, name),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Whether the types could be unified, without unifying them. Unbound type
+/// variables are compatible with any type.
+fn compatible(one: &Type, other: &Type) -> bool {
+    if let Type::Var { type_ } = one {
+        match &*type_.borrow() {
+            TypeVar::Link { type_ } => return compatible(type_, other),
+            TypeVar::Unbound { .. } => return true,
+            TypeVar::Generic { .. } => (),
+        }
+    }
+    if let Type::Var { type_ } = other {
+        match &*type_.borrow() {
+            TypeVar::Link { type_ } => return compatible(one, type_),
+            TypeVar::Unbound { .. } => return true,
+            TypeVar::Generic { .. } => (),
+        }
+    }
+    match (one, other) {
+        (Type::Var { type_: one }, Type::Var { type_: other }) => one == other,
+        (
+            Type::Named {
+                module, name, args, ..
+            },
+            Type::Named {
+                module: other_module,
+                name: other_name,
+                args: other_args,
+                ..
+            },
+        ) => module == other_module && name == other_name && all_compatible(args, other_args),
+        (
+            Type::Fn { args, retrn },
+            Type::Fn {
+                args: other_args,
+                retrn: other_retrn,
+            },
+        ) => all_compatible(args, other_args) && compatible(retrn, other_retrn),
+        (Type::Tuple { elems }, Type::Tuple { elems: other_elems }) => {
+            all_compatible(elems, other_elems)
+        }
+        _ => false,
+    }
+}
+
+fn all_compatible(types: &[Arc<Type>], others: &[Arc<Type>]) -> bool {
+    types.len() == others.len()
+        && types
+            .iter()
+            .zip(others)
+            .all(|(one, other)| compatible(one, other))
+}
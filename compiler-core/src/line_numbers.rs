@@ -1,4 +1,4 @@
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct LineNumbers {
     line_starts: Vec<u32>,
     length: u32,
@@ -14,6 +14,26 @@ impl LineNumbers {
         }
     }
 
+    /// Update the line numbers for the bytes from `start` to `end` of the
+    /// source being replaced with the new text. Only the new text is scanned
+    /// for line breaks, so this is quicker than making the line numbers of the
+    /// edited source again when the source is large.
+    pub fn apply_edit(&mut self, start: u32, end: u32, new_text: &str) {
+        let removed = end - start;
+        let inserted = new_text.len() as u32;
+        // Lines that begin within the replaced bytes no longer exist.
+        let first = self.line_starts.partition_point(|&line| line <= start);
+        let last = self.line_starts.partition_point(|&line| line <= end);
+        for line_start in self.line_starts.iter_mut().skip(last) {
+            *line_start = *line_start - removed + inserted;
+        }
+        let new_lines = new_text
+            .match_indices('\n')
+            .map(|(i, _)| start + i as u32 + 1);
+        _ = self.line_starts.splice(first..last, new_lines);
+        self.length = self.length - removed + inserted;
+    }
+
     /// Get the line number for a byte index
     pub fn line_number(&self, byte_index: u32) -> u32 {
         self.line_starts
@@ -61,6 +81,32 @@ pub fn main() {
     assert_eq!(line_numbers.byte_index(2, 1), 18);
 }
 
+#[test]
+fn apply_edit() {
+    let src = "import gleam/io\n\npub fn main() {\n  io.println(\"Hello\")\n}\n";
+    let edits = [
+        (0, 0, "// A comment\n"),
+        (17, 33, "pub fn main() {\n  let x = 1\n"),
+        (16, 18, ""),
+        (34, 34, "x"),
+        (src.len() as u32, src.len() as u32, "\n\n"),
+        (0, src.len() as u32, "one line"),
+    ];
+    for (start, end, new_text) in edits {
+        let mut edited = src.to_string();
+        edited.replace_range(start as usize..end as usize, new_text);
+        let mut line_numbers = LineNumbers::new(src);
+        line_numbers.apply_edit(start, end, new_text);
+
+        let expected = LineNumbers::new(&edited);
+        assert_eq!(
+            line_numbers.line_starts, expected.line_starts,
+            "{new_text:?}"
+        );
+        assert_eq!(line_numbers.length, expected.length);
+    }
+}
+
 #[derive(Debug, Clone, Copy)]
 pub struct LineColumn {
     pub line: u32,
@@ -52,7 +52,7 @@
 pub mod error;
 pub mod extra;
 pub mod lexer;
-mod token;
+pub(crate) mod token;
 
 use crate::analyse::Inferred;
 use crate::ast::{
@@ -7,11 +7,24 @@ pub const ARTEFACT_DIRECTORY_NAME: &str = "_gleam_artefacts";
 #[derive(Debug, Clone)]
 pub struct ProjectPaths {
     root: Utf8PathBuf,
+    /// Where the language server builds the project, in place of the `lsp`
+    /// directory within the project's build directory.
+    lsp_build_directory: Option<Utf8PathBuf>,
 }
 
 impl ProjectPaths {
     pub fn new(root: Utf8PathBuf) -> Self {
-        Self { root }
+        Self {
+            root,
+            lsp_build_directory: None,
+        }
+    }
+
+    /// Build in the given directory when in `Lsp` mode. The packages
+    /// directory is still shared with the other modes.
+    pub fn with_lsp_build_directory(mut self, directory: Utf8PathBuf) -> Self {
+        self.lsp_build_directory = Some(directory);
+        self
     }
 
     pub fn at_filesystem_root() -> Self {
@@ -75,7 +88,10 @@ impl ProjectPaths {
     }
 
     pub fn build_directory_for_mode(&self, mode: Mode) -> Utf8PathBuf {
-        self.build_directory().join(mode.to_string())
+        match (mode, &self.lsp_build_directory) {
+            (Mode::Lsp, Some(directory)) => directory.clone(),
+            _ => self.build_directory().join(mode.to_string()),
+        }
     }
 
     pub fn erlang_shipment_directory(&self) -> Utf8PathBuf {
@@ -158,6 +158,8 @@ impl Warning {
  only lowercase alphanumeric characters or underscores."
                     .into(),
                 level: diagnostic::Level::Warning,
+                tags: vec![],
+                code: None,
                 location: None,
                 hint: Some(format!(
                     "Rename `{path}` to be valid, or remove this file from the project source."
@@ -199,6 +201,8 @@ expression.",
                         title,
                         text,
                         level: diagnostic::Level::Warning,
+                        tags: vec![],
+                        code: Some("todo"),
                         location: Some(Location {
                             path: path.to_path_buf(),
                             src: src.clone(),
@@ -219,6 +223,8 @@ expression.",
                         "If you are sure you don't need it you can assign it to `_`.".into(),
                     ),
                     level: diagnostic::Level::Warning,
+                    tags: vec![],
+                    code: None,
                     location: Some(Location {
                         path: path.to_path_buf(),
                         src: src.clone(),
@@ -235,6 +241,8 @@ expression.",
                     text: "".into(),
                     hint: Some("You can safely remove it.".into()),
                     level: diagnostic::Level::Warning,
+                    tags: vec![diagnostic::Tag::Unnecessary],
+                    code: None,
                     location: Some(Location {
                         path: path.to_path_buf(),
                         src: src.clone(),
@@ -253,6 +261,8 @@ expression.",
                         "Add some fields to change or replace it with the record itself.".into(),
                     ),
                     level: diagnostic::Level::Warning,
+                    tags: vec![],
+                    code: None,
                     location: Some(Location {
                         path: path.to_path_buf(),
                         src: src.clone(),
@@ -269,6 +279,8 @@ expression.",
                     text: "".into(),
                     hint: Some("It is better style to use the record creation syntax.".into()),
                     level: diagnostic::Level::Warning,
+                    tags: vec![],
+                    code: None,
                     location: Some(Location {
                         src: src.clone(),
                         path: path.to_path_buf(),
@@ -298,6 +310,8 @@ expression.",
                         text: "".into(),
                         hint: Some("You can safely remove it.".into()),
                         level: diagnostic::Level::Warning,
+                        tags: vec![diagnostic::Tag::Unnecessary],
+                        code: None,
                         location: Some(Location {
                             src: src.clone(),
                             path: path.to_path_buf(),
@@ -328,6 +342,8 @@ expression.",
                         text: "".into(),
                         hint: Some("You can safely remove it.".into()),
                         level: diagnostic::Level::Warning,
+                        tags: vec![diagnostic::Tag::Unnecessary],
+                        code: None,
                         location: Some(Location {
                             src: src.clone(),
                             path: path.to_path_buf(),
@@ -345,6 +361,8 @@ expression.",
                     text: "".into(),
                     hint: Some("You can safely remove it.".into()),
                     level: diagnostic::Level::Warning,
+                    tags: vec![diagnostic::Tag::Unnecessary],
+                    code: Some("unused_import"),
                     location: Some(Location {
                         src: src.clone(),
                         path: path.to_path_buf(),
@@ -373,6 +391,8 @@ Hint: You can safely remove it.
                         text,
                         hint: None,
                         level: diagnostic::Level::Warning,
+                        tags: vec![diagnostic::Tag::Unnecessary],
+                        code: None,
                         location: Some(Location {
                             src: src.clone(),
                             path: path.to_path_buf(),
@@ -390,6 +410,8 @@ Hint: You can safely remove it.
                     text: "".into(),
                     hint: Some("You can safely remove it.".into()),
                     level: diagnostic::Level::Warning,
+                    tags: vec![diagnostic::Tag::Unnecessary],
+                    code: Some("unused_import"),
                     location: Some(Location {
                         src: src.clone(),
                         path: path.to_path_buf(),
@@ -406,6 +428,8 @@ Hint: You can safely remove it.
                     text: "".into(),
                     hint: Some("You can safely remove it.".into()),
                     level: diagnostic::Level::Warning,
+                    tags: vec![diagnostic::Tag::Unnecessary],
+                    code: None,
                     location: Some(Location {
                         src: src.clone(),
                         path: path.to_path_buf(),
@@ -422,6 +446,8 @@ Hint: You can safely remove it.
                     text: "".into(),
                     hint: Some("You can safely remove it.".into()),
                     level: diagnostic::Level::Warning,
+                    tags: vec![diagnostic::Tag::Unnecessary],
+                    code: None,
                     location: Some(Location {
                         src: src.clone(),
                         path: path.to_path_buf(),
@@ -438,6 +464,8 @@ Hint: You can safely remove it.
                     text: "".into(),
                     hint: Some(format!("You can ignore it with an underscore: `_{name}`.")),
                     level: diagnostic::Level::Warning,
+                    tags: vec![diagnostic::Tag::Unnecessary],
+                    code: Some("unused_variable"),
                     location: Some(Location {
                         src: src.clone(),
                         path: path.to_path_buf(),
@@ -453,6 +481,8 @@ Hint: You can safely remove it.
                     text: "".into(),
                     hint: Some("You can safely remove this.".into()),
                     level: diagnostic::Level::Warning,
+                    tags: vec![],
+                    code: None,
                     location: Some(Location {
                         src: src.clone(),
                         path: path.to_path_buf(),
@@ -468,6 +498,8 @@ Hint: You can safely remove it.
                     text: "".into(),
                     hint: Some("You can safely remove this.".into()),
                     level: diagnostic::Level::Warning,
+                    tags: vec![],
+                    code: None,
                     location: Some(Location {
                         src: src.clone(),
                         path: path.to_path_buf(),
@@ -497,6 +529,8 @@ need to know if the list is empty or not.
                         text,
                         hint,
                         level: diagnostic::Level::Warning,
+                        tags: vec![],
+                        code: None,
                         location: Some(Location {
                             src: src.clone(),
                             path: path.to_path_buf(),
@@ -530,6 +564,8 @@ Run this command to add it to your dependencies:
                         text,
                         hint: None,
                         level: diagnostic::Level::Warning,
+                        tags: vec![],
+                        code: None,
                         location: Some(Location {
                             src: src.clone(),
                             path: path.to_path_buf(),
@@ -565,6 +601,8 @@ Run this command to add it to your dependencies:
                         text,
                         hint: None,
                         level: diagnostic::Level::Warning,
+                        tags: vec![diagnostic::Tag::Deprecated],
+                        code: None,
                         location: Some(Location {
                             src: src.clone(),
                             path: path.to_path_buf(),
@@ -587,6 +625,8 @@ the same values.\n"
                         text,
                         hint: Some("It can be safely removed.".into()),
                         level: diagnostic::Level::Warning,
+                        tags: vec![diagnostic::Tag::Unnecessary],
+                        code: Some("unreachable_case_clause"),
                         location: Some(Location {
                             src: src.clone(),
                             path: path.to_path_buf(),